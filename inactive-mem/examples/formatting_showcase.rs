@@ -100,6 +100,17 @@ fn main() -> Result<()> {
         format_percentage(pressure.available_ratio)
     );
 
+    println!("\n♻️  Reclaim Balance (vm.swappiness):");
+    println!(
+        "  Inactive(file) / Inactive(anon): {:.2}",
+        stats.reclaim_balance()
+    );
+    println!(
+        "  {:?}: {}",
+        stats.reclaim_bias(),
+        stats.reclaim_bias().description()
+    );
+
     // Demonstrate change formatting
     println!("\n🔄 Simulated Memory Changes:");
     println!(
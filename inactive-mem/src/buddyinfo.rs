@@ -0,0 +1,154 @@
+use crate::{MemoryError, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+/// Orders the buddy allocator tracks (0..=10 with the default MAX_ORDER)
+pub const BUDDY_ORDERS: usize = 11;
+
+/// Free blocks at each order for one zone of /proc/buddyinfo
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuddyZone {
+    pub node: u32,
+    pub zone: String,
+    /// Free blocks of 2^order contiguous pages, indexed by order
+    pub free_pages: [u32; BUDDY_ORDERS],
+}
+
+impl BuddyZone {
+    /// Free memory in the zone, in pages
+    pub fn total_free_pages(&self) -> u64 {
+        self.free_pages
+            .iter()
+            .enumerate()
+            .map(|(order, &blocks)| (blocks as u64) << order)
+            .sum()
+    }
+
+    /// Free pages in blocks of at least 2^order pages, i.e. usable for an
+    /// allocation of that order without compaction
+    pub fn free_pages_at_or_above(&self, order: usize) -> u64 {
+        self.free_pages
+            .iter()
+            .enumerate()
+            .skip(order)
+            .map(|(order, &blocks)| (blocks as u64) << order)
+            .sum()
+    }
+}
+
+/// Free block counts for every zone of every NUMA node
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BuddyInfo {
+    pub zones: Vec<BuddyZone>,
+}
+
+impl BuddyInfo {
+    /// Read current free block counts from /proc/buddyinfo
+    pub fn current() -> Result<Self> {
+        let content = fs::read_to_string("/proc/buddyinfo")?;
+        Self::parse(&content)
+    }
+
+    /// Parse /proc/buddyinfo content
+    ///
+    /// Each line is `Node N, zone NAME` followed by one count per order.
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut zones = Vec::new();
+
+        for line in content.lines() {
+            let Some(header) = line.strip_prefix("Node ") else {
+                continue;
+            };
+            let (node, rest) = header.split_once(", zone").ok_or_else(|| {
+                MemoryError::ParseError(format!("Invalid buddyinfo line: {}", line))
+            })?;
+            let node = node
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| MemoryError::ParseError(format!("Invalid node number: {}", node)))?;
+
+            let mut parts = rest.split_whitespace();
+            let zone = parts
+                .next()
+                .ok_or_else(|| MemoryError::ParseError(format!("Missing zone name: {}", line)))?;
+
+            let mut free_pages = [0u32; BUDDY_ORDERS];
+            for (order, count) in parts.enumerate() {
+                let slot = free_pages.get_mut(order).ok_or_else(|| {
+                    MemoryError::ParseError(format!(
+                        "More than {} orders in buddyinfo: {}",
+                        BUDDY_ORDERS, line
+                    ))
+                })?;
+                *slot = count
+                    .parse::<u32>()
+                    .map_err(|_| MemoryError::ParseError(format!("Invalid number: {}", count)))?;
+            }
+
+            zones.push(BuddyZone {
+                node,
+                zone: zone.to_string(),
+                free_pages,
+            });
+        }
+
+        Ok(BuddyInfo { zones })
+    }
+
+    /// How fragmented a zone's free memory is, from 0 (all free memory in
+    /// max-order blocks) to 1 (all of it in single pages)
+    ///
+    /// Each free page is weighted by how far its block's order falls short
+    /// of the maximum. A zone with no free memory scores 0.
+    pub fn fragmentation_index(zone: &BuddyZone) -> f64 {
+        let total = zone.total_free_pages();
+        if total == 0 {
+            return 0.0;
+        }
+
+        let max_order = (BUDDY_ORDERS - 1) as f64;
+        let shortfall: f64 = zone
+            .free_pages
+            .iter()
+            .enumerate()
+            .map(|(order, &blocks)| ((blocks as u64) << order) as f64 * (max_order - order as f64))
+            .sum();
+        shortfall / (total as f64 * max_order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_BUDDYINFO: &str = "\
+Node 0, zone      DMA      1      1      1      0      2      1      1      0      1      1      3
+Node 0, zone    DMA32   2048      0      0      0      0      0      0      0      0      0      0
+Node 0, zone   Normal      0      0      0      0      0      0      0      0      0      0      8
+";
+
+    #[test]
+    fn test_parse_buddyinfo() {
+        let info = BuddyInfo::parse(SAMPLE_BUDDYINFO).unwrap();
+        assert_eq!(info.zones.len(), 3);
+        assert_eq!(info.zones[0].zone, "DMA");
+        assert_eq!(info.zones[0].free_pages[10], 3);
+        assert_eq!(info.zones[0].total_free_pages(), 3975);
+        assert_eq!(info.zones[2].free_pages_at_or_above(9), 8 * 1024);
+
+        assert!(BuddyInfo::parse("Node 0, zone DMA 1 x\n").is_err());
+        assert!(BuddyInfo::parse(&format!("Node 0, zone DMA{}\n", " 1".repeat(12))).is_err());
+    }
+
+    #[test]
+    fn test_fragmentation_index() {
+        let info = BuddyInfo::parse(SAMPLE_BUDDYINFO).unwrap();
+        // All order 0, all max order
+        assert_eq!(BuddyInfo::fragmentation_index(&info.zones[1]), 1.0);
+        assert_eq!(BuddyInfo::fragmentation_index(&info.zones[2]), 0.0);
+
+        let mixed = BuddyInfo::fragmentation_index(&info.zones[0]);
+        assert!(mixed > 0.0 && mixed < 0.1);
+        assert_eq!(BuddyInfo::fragmentation_index(&BuddyZone::default()), 0.0);
+    }
+}
@@ -0,0 +1,249 @@
+use crate::{MemoryError, MemoryStats, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Memory usage and limits of one cgroup v2 group, in bytes except for
+/// `host_mem_total_kb`
+///
+/// Inside a container these are the numbers that matter: /proc/meminfo
+/// still describes the whole host.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CgroupMemoryStats {
+    /// memory.current
+    pub current: u64,
+    /// memory.high, the throttling threshold; `None` when set to "max"
+    pub high: Option<u64>,
+    /// memory.max, the OOM limit; `None` when set to "max"
+    pub max: Option<u64>,
+    /// memory.swap.current; `None` without swap accounting
+    pub swap_current: Option<u64>,
+    /// memory.swap.max; `None` when unlimited or without swap accounting
+    pub swap_max: Option<u64>,
+    /// memory.stat breakdown (anon, file, active_file, ...)
+    pub stat: HashMap<String, u64>,
+    /// MemTotal of the host in KB, standing in for the limit of an
+    /// unlimited group when converting to `MemoryStats`
+    pub host_mem_total_kb: u64,
+}
+
+/// A memory.* value: a byte count or "max"
+fn parse_limit(name: &str, content: &str) -> Result<Option<u64>> {
+    match content.trim() {
+        "max" => Ok(None),
+        value => value
+            .parse::<u64>()
+            .map(Some)
+            .map_err(|_| MemoryError::ParseError(format!("Invalid number in {}: {}", name, value))),
+    }
+}
+
+impl CgroupMemoryStats {
+    /// Read the memory controller files of a cgroup v2 directory, e.g.
+    /// /sys/fs/cgroup/system.slice/nginx.service
+    ///
+    /// `host_mem_total_kb` is the host's MemTotal, read once by the caller
+    /// rather than on every sample.
+    pub fn for_cgroup(path: &Path, host_mem_total_kb: u64) -> Result<Self> {
+        let read = |name: &str| fs::read_to_string(path.join(name));
+        // Swap files are missing when swap accounting is off
+        let read_optional = |name: &str| match read(name) {
+            Ok(content) => parse_limit(name, &content),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        };
+
+        let current =
+            parse_limit("memory.current", &read("memory.current")?)?.ok_or_else(|| {
+                MemoryError::ParseError("memory.current can't be \"max\"".to_string())
+            })?;
+        let stats = CgroupMemoryStats {
+            current,
+            high: parse_limit("memory.high", &read("memory.high")?)?,
+            max: parse_limit("memory.max", &read("memory.max")?)?,
+            swap_current: read_optional("memory.swap.current")?,
+            swap_max: read_optional("memory.swap.max")?,
+            stat: Self::parse_stat(&read("memory.stat")?)?,
+            host_mem_total_kb,
+        };
+        Ok(stats)
+    }
+
+    /// Parse memory.stat: one `key value` pair per line
+    pub fn parse_stat(content: &str) -> Result<HashMap<String, u64>> {
+        let mut stat = HashMap::new();
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once(' ') else {
+                continue;
+            };
+            let value = value
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| MemoryError::ParseError(format!("Invalid number: {}", value)))?;
+            stat.insert(key.to_string(), value);
+        }
+        Ok(stat)
+    }
+
+    /// The lower of memory.high and memory.max, if either is set
+    pub fn limit(&self) -> Option<u64> {
+        match (self.high, self.max) {
+            (Some(high), Some(max)) => Some(high.min(max)),
+            (limit, None) | (None, limit) => limit,
+        }
+    }
+
+    /// memory.current as a fraction of the limit (`limit`); 0 when unlimited
+    pub fn utilization(&self) -> f64 {
+        match self.limit() {
+            Some(limit) if limit > 0 => self.current as f64 / limit as f64,
+            _ => 0.0,
+        }
+    }
+
+    /// Bytes left before the group reaches its limit (`limit`), negative
+    /// once it has gone past memory.high; `None` when unlimited
+    pub fn headroom_bytes(&self) -> Option<i64> {
+        self.limit().map(|limit| limit as i64 - self.current as i64)
+    }
+
+    /// A memory.stat counter, 0 if the kernel doesn't report it
+    pub fn stat_value(&self, key: &str) -> u64 {
+        self.stat.get(key).copied().unwrap_or(0)
+    }
+
+    /// The group's usage in /proc/meminfo terms (KB), with its limit as
+    /// MemTotal so ratios and pressure levels are relative to the group
+    ///
+    /// MemAvailable counts free memory below the limit plus inactive file
+    /// pages and reclaimable slab, which the group can reclaim itself.
+    pub fn to_memory_stats(&self) -> MemoryStats {
+        let kb = |key: &str| self.stat_value(key) / 1024;
+        let mem_total = self
+            .limit()
+            .map_or(self.host_mem_total_kb, |limit| limit / 1024);
+        let mem_free = mem_total.saturating_sub(self.current / 1024);
+        let reclaimable = kb("inactive_file") + kb("slab_reclaimable");
+
+        MemoryStats {
+            mem_total,
+            mem_free,
+            mem_available: (mem_free + reclaimable).min(mem_total),
+            cached: kb("file"),
+            active: kb("active_anon") + kb("active_file"),
+            inactive: kb("inactive_anon") + kb("inactive_file"),
+            active_file: kb("active_file"),
+            inactive_file: kb("inactive_file"),
+            active_anon: kb("active_anon"),
+            inactive_anon: kb("inactive_anon"),
+            dirty: kb("file_dirty"),
+            writeback: kb("file_writeback"),
+            mapped: kb("file_mapped"),
+            shmem: kb("shmem"),
+            slab: kb("slab"),
+            s_reclaimable: kb("slab_reclaimable"),
+            s_unreclaimable: kb("slab_unreclaimable"),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventMonitor, MemoryPressure, PressureLevel};
+    use tempfile::TempDir;
+
+    const MIB: u64 = 1024 * 1024;
+    /// MemTotal of the pretend host, 16 GiB
+    const HOST_KB: u64 = 16 * 1024 * 1024;
+
+    fn cgroup_dir(files: &[(&str, String)]) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        for (name, value) in files {
+            fs::write(dir.path().join(name), value).unwrap();
+        }
+        dir
+    }
+
+    fn sample_cgroup() -> TempDir {
+        cgroup_dir(&[
+            ("memory.current", format!("{}\n", 900 * MIB)),
+            ("memory.high", format!("{}\n", 800 * MIB)),
+            ("memory.max", format!("{}\n", 1024 * MIB)),
+            ("memory.swap.current", "0\n".to_string()),
+            ("memory.swap.max", "max\n".to_string()),
+            (
+                "memory.stat",
+                format!(
+                    "anon {}\nfile {}\nactive_file {}\ninactive_file {}\nslab_reclaimable {}\n",
+                    600 * MIB,
+                    300 * MIB,
+                    200 * MIB,
+                    100 * MIB,
+                    20 * MIB
+                ),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_read_cgroup() {
+        let dir = sample_cgroup();
+        let stats = CgroupMemoryStats::for_cgroup(dir.path(), HOST_KB).unwrap();
+        assert_eq!(stats.current, 900 * MIB);
+        assert_eq!(stats.high, Some(800 * MIB));
+        assert_eq!(stats.max, Some(1024 * MIB));
+        assert_eq!(stats.swap_current, Some(0));
+        assert_eq!(stats.swap_max, None);
+        assert_eq!(stats.stat_value("anon"), 600 * MIB);
+        assert_eq!(stats.stat_value("missing"), 0);
+
+        // Relative to memory.high, the lower limit
+        assert!((stats.utilization() - 900.0 / 800.0).abs() < 1e-9);
+        // Above memory.high, so negative
+        assert_eq!(stats.headroom_bytes(), Some(-100 * MIB as i64));
+    }
+
+    #[test]
+    fn test_unlimited_cgroup_without_swap_accounting() {
+        let dir = cgroup_dir(&[
+            ("memory.current", "4096\n".to_string()),
+            ("memory.high", "max\n".to_string()),
+            ("memory.max", "max\n".to_string()),
+            ("memory.stat", "anon 4096\n".to_string()),
+        ]);
+        let stats = CgroupMemoryStats::for_cgroup(dir.path(), HOST_KB).unwrap();
+        assert_eq!(stats.limit(), None);
+        assert_eq!(stats.swap_current, None);
+        assert_eq!(stats.utilization(), 0.0);
+        assert_eq!(stats.headroom_bytes(), None);
+        assert_eq!(stats.to_memory_stats().mem_total, HOST_KB);
+
+        assert!(CgroupMemoryStats::for_cgroup(Path::new("/nonexistent/cgroup"), HOST_KB).is_err());
+    }
+
+    #[test]
+    fn test_pressure_from_cgroup() {
+        let dir = sample_cgroup();
+        let stats = CgroupMemoryStats::for_cgroup(dir.path(), HOST_KB).unwrap();
+
+        let meminfo = stats.to_memory_stats();
+        assert_eq!(meminfo.mem_total, 800 * 1024);
+        assert_eq!(meminfo.mem_free, 0);
+        assert_eq!(meminfo.mem_available, 120 * 1024);
+        assert_eq!(meminfo.cached, 300 * 1024);
+
+        // 120 of 800 MiB available
+        let pressure = MemoryPressure::from_cgroup(&stats);
+        assert_eq!(pressure.pressure_level, PressureLevel::High);
+
+        let mut monitor = EventMonitor::for_cgroup(dir.path());
+        monitor.add_condition("under 20% available".to_string(), |stats, _| {
+            stats.mem_available < stats.mem_total / 5
+        });
+        assert_eq!(monitor.check_conditions().unwrap(), ["under 20% available"]);
+    }
+}
@@ -0,0 +1,224 @@
+use crate::{MemoryStats, ParseOptions, Result, parse_meminfo_fields};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+
+macro_rules! extended_fields {
+    ($($(#[$doc:meta])* $field:ident => $key:literal,)*) => {
+        /// Everything in /proc/meminfo: the core `MemoryStats` plus swap,
+        /// hugepage, vmalloc, kernel and direct-map fields
+        ///
+        /// Fields are `None` when the running kernel doesn't report them
+        /// (e.g. `CmaTotal` without CONFIG_CMA, `DirectMap1G` on most VMs).
+        /// Lines not covered by a named field are kept in `other`.
+        #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+        pub struct ExtendedMemoryStats {
+            pub core: MemoryStats,
+            $($(#[$doc])* pub $field: Option<u64>,)*
+            /// Remaining meminfo lines by their kernel name
+            pub other: BTreeMap<String, u64>,
+        }
+
+        /// /proc/meminfo keys of the named `ExtendedMemoryStats` fields
+        pub const EXTENDED_FIELD_NAMES: &[&str] = &[$($key,)*];
+
+        impl ExtendedMemoryStats {
+            fn from_fields(core: MemoryStats, mut fields: HashMap<String, u64>) -> Self {
+                for &name in crate::FIELD_NAMES {
+                    fields.remove(name);
+                }
+                ExtendedMemoryStats {
+                    core,
+                    $($field: fields.remove($key),)*
+                    other: fields.into_iter().collect(),
+                }
+            }
+
+            /// Look up a field by its /proc/meminfo name, including core
+            /// fields and those kept in `other`
+            pub fn get(&self, field: &str) -> Option<u64> {
+                match field {
+                    $($key => self.$field,)*
+                    _ => self
+                        .core
+                        .get(field)
+                        .or_else(|| self.other.get(field).copied()),
+                }
+            }
+        }
+    };
+}
+
+extended_fields! {
+    /// Total swap space (KB)
+    swap_total => "SwapTotal",
+    /// Unused swap space (KB)
+    swap_free => "SwapFree",
+    /// Pages on the unevictable LRU, e.g. mlocked or ramfs (KB)
+    unevictable => "Unevictable",
+    /// Memory locked with mlock() (KB)
+    mlocked => "Mlocked",
+    /// Anonymous pages mapped into page tables (KB)
+    anon_pages => "AnonPages",
+    /// Memory used by kernel stacks (KB)
+    kernel_stack => "KernelStack",
+    /// Memory used by page tables (KB)
+    page_tables => "PageTables",
+    /// Memory allocatable under the current overcommit policy (KB)
+    commit_limit => "CommitLimit",
+    /// Memory promised to processes, whether or not touched yet (KB)
+    committed_as => "Committed_AS",
+    /// Size of the vmalloc address space (KB)
+    vmalloc_total => "VmallocTotal",
+    /// vmalloc space in use (KB)
+    vmalloc_used => "VmallocUsed",
+    /// Per-CPU allocator memory (KB)
+    percpu => "Percpu",
+    /// Memory lost to hardware errors (KB)
+    hardware_corrupted => "HardwareCorrupted",
+    /// Anonymous memory backed by transparent hugepages (KB)
+    anon_huge_pages => "AnonHugePages",
+    /// Shared memory and tmpfs backed by hugepages (KB)
+    shmem_huge_pages => "ShmemHugePages",
+    /// Shared memory mapped into user space with hugepages (KB)
+    shmem_pmd_mapped => "ShmemPmdMapped",
+    /// Contiguous Memory Allocator reserve (KB)
+    cma_total => "CmaTotal",
+    /// Free memory in the CMA reserve (KB)
+    cma_free => "CmaFree",
+    /// Size of the hugetlb pool (pages)
+    huge_pages_total => "HugePages_Total",
+    /// Hugetlb pages not yet allocated (pages)
+    huge_pages_free => "HugePages_Free",
+    /// Hugetlb pages reserved but not yet faulted in (pages)
+    huge_pages_rsvd => "HugePages_Rsvd",
+    /// Hugetlb pages above the persistent pool size (pages)
+    huge_pages_surp => "HugePages_Surp",
+    /// Default hugepage size (KB)
+    hugepagesize => "Hugepagesize",
+    /// Memory consumed by hugetlb pages of all sizes (KB)
+    hugetlb => "Hugetlb",
+    /// Kernel direct map covered by 4 KB pages (KB)
+    direct_map_4k => "DirectMap4k",
+    /// Kernel direct map covered by 2 MB pages (KB)
+    direct_map_2m => "DirectMap2M",
+    /// Kernel direct map covered by 1 GB pages (KB)
+    direct_map_1g => "DirectMap1G",
+}
+
+impl ExtendedMemoryStats {
+    /// Read every field of /proc/meminfo
+    pub fn current() -> Result<Self> {
+        let content = fs::read_to_string("/proc/meminfo")?;
+        Self::parse(&content)
+    }
+
+    /// Parse /proc/meminfo content, requiring only `REQUIRED_FIELDS`
+    pub fn parse(content: &str) -> Result<Self> {
+        let (core, _) = MemoryStats::parse_meminfo_with(content, &ParseOptions::default())?;
+        Ok(Self::from_fields(core, parse_meminfo_fields(content)?))
+    }
+
+    /// Swap in use (KB), if the kernel reports swap
+    pub fn swap_used(&self) -> Option<u64> {
+        Some(self.swap_total?.saturating_sub(self.swap_free?))
+    }
+
+    /// Memory held by the hugetlb pool, allocated or not (KB)
+    pub fn huge_pages_total_kb(&self) -> Option<u64> {
+        Some(self.huge_pages_total?.saturating_mul(self.hugepagesize?))
+    }
+
+    /// Hugetlb pool memory handed out to users (KB)
+    pub fn huge_pages_used_kb(&self) -> Option<u64> {
+        let used = self.huge_pages_total?.saturating_sub(self.huge_pages_free?);
+        Some(used.saturating_mul(self.hugepagesize?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FULL_MEMINFO: &str = r#"MemTotal:       16384000 kB
+MemFree:         8192000 kB
+MemAvailable:   12288000 kB
+Buffers:          512000 kB
+Cached:          2048000 kB
+SwapCached:         1024 kB
+Active:          4096000 kB
+Inactive:        2048000 kB
+Active(anon):    3072000 kB
+Inactive(anon):   512000 kB
+Active(file):    1024000 kB
+Inactive(file):  1536000 kB
+Unevictable:       16384 kB
+Mlocked:           16384 kB
+SwapTotal:       8388604 kB
+SwapFree:        8126460 kB
+Zswap:                 0 kB
+Dirty:             64000 kB
+Writeback:             0 kB
+AnonPages:       3500000 kB
+Mapped:           256000 kB
+Shmem:            128000 kB
+KReclaimable:     256000 kB
+Slab:             384000 kB
+SReclaimable:     256000 kB
+SUnreclaim:       128000 kB
+KernelStack:       18432 kB
+PageTables:        45056 kB
+CommitLimit:    16580604 kB
+Committed_AS:   10485760 kB
+VmallocTotal:   34359738367 kB
+VmallocUsed:       81920 kB
+VmallocChunk:          0 kB
+Percpu:            12288 kB
+HardwareCorrupted:     0 kB
+AnonHugePages:    204800 kB
+ShmemHugePages:        0 kB
+ShmemPmdMapped:        0 kB
+HugePages_Total:      64
+HugePages_Free:       48
+HugePages_Rsvd:        0
+HugePages_Surp:        0
+Hugepagesize:       2048 kB
+Hugetlb:          131072 kB
+DirectMap4k:      524288 kB
+DirectMap2M:    16252928 kB"#;
+
+    #[test]
+    fn test_parse_extended_meminfo() {
+        let stats = ExtendedMemoryStats::parse(FULL_MEMINFO).unwrap();
+        assert_eq!(
+            stats.core,
+            MemoryStats::parse_meminfo(FULL_MEMINFO).unwrap()
+        );
+        assert_eq!(stats.swap_total, Some(8388604));
+        assert_eq!(stats.swap_used(), Some(262144));
+        assert_eq!(stats.vmalloc_total, Some(34359738367));
+        assert_eq!(stats.huge_pages_total_kb(), Some(64 * 2048));
+        assert_eq!(stats.huge_pages_used_kb(), Some(16 * 2048));
+        assert_eq!(stats.direct_map_1g, None);
+        assert_eq!(stats.cma_total, None);
+
+        // Unnamed lines are kept, and everything is reachable by name
+        assert_eq!(stats.other.get("Zswap"), Some(&0));
+        assert!(!stats.other.contains_key("MemTotal"));
+        assert_eq!(stats.get("VmallocChunk"), Some(0));
+        assert_eq!(stats.get("Inactive(file)"), Some(1536000));
+        assert_eq!(stats.get("DirectMap2M"), Some(16252928));
+        assert_eq!(stats.get("DirectMap1G"), None);
+    }
+
+    #[test]
+    fn test_extended_without_swap() {
+        let stats = ExtendedMemoryStats::parse(
+            "MemTotal: 1024 kB\nMemFree: 512 kB\nMemAvailable: 768 kB\n",
+        )
+        .unwrap();
+        assert_eq!(stats.core.mem_total, 1024);
+        assert_eq!(stats.swap_used(), None);
+        assert!(stats.other.is_empty());
+    }
+}
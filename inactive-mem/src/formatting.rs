@@ -1,4 +1,6 @@
-/// Formatting utilities for displaying memory values with better readability
+//! Formatting utilities for displaying memory values with better readability
+
+use crate::{MemField, MemoryStats, MemoryStatsDiff, kb_to_pages};
 
 /// Format a number with comma separators (e.g., 1234567 -> "1,234,567")
 pub fn format_number(n: u64) -> String {
@@ -7,7 +9,7 @@ pub fn format_number(n: u64) -> String {
     let chars: Vec<char> = s.chars().collect();
 
     for (i, &ch) in chars.iter().enumerate() {
-        if i > 0 && (chars.len() - i) % 3 == 0 {
+        if i > 0 && (chars.len() - i).is_multiple_of(3) {
             result.push(',');
         }
         result.push(ch);
@@ -29,64 +31,28 @@ pub fn format_signed_number(n: i64) -> String {
 pub fn format_memory_kb(kb: u64) -> String {
     let formatted_kb = format_number(kb);
 
-    if kb >= 1024 * 1024 * 1024 {
-        // TB
-        format!(
-            "{} KB ({:.1} TB)",
-            formatted_kb,
-            kb as f64 / (1024.0 * 1024.0 * 1024.0)
-        )
-    } else if kb >= 1024 * 1024 {
-        // GB
-        format!(
-            "{} KB ({:.1} GB)",
-            formatted_kb,
-            kb as f64 / (1024.0 * 1024.0)
-        )
-    } else if kb >= 1024 {
-        // MB
-        format!("{} KB ({:.1} MB)", formatted_kb, kb as f64 / 1024.0)
+    if kb >= 1024 {
+        format!("{} KB ({})", formatted_kb, format_size_kb(kb))
     } else {
-        // Just KB
         format!("{} KB", formatted_kb)
     }
 }
 
 /// Format memory change with sign, comma separators, and appropriate unit conversion
 pub fn format_memory_change_kb(kb: i64) -> String {
-    let abs_kb = kb.abs() as u64;
+    let abs_kb = kb.unsigned_abs();
     let sign = if kb >= 0 { "+" } else { "-" };
     let formatted_kb = format_number(abs_kb);
 
-    if abs_kb >= 1024 * 1024 * 1024 {
-        // TB
+    if abs_kb >= 1024 {
         format!(
-            "{}{} KB ({}{:.1} TB)",
+            "{}{} KB ({}{})",
             sign,
             formatted_kb,
             sign,
-            abs_kb as f64 / (1024.0 * 1024.0 * 1024.0)
-        )
-    } else if abs_kb >= 1024 * 1024 {
-        // GB
-        format!(
-            "{}{} KB ({}{:.1} GB)",
-            sign,
-            formatted_kb,
-            sign,
-            abs_kb as f64 / (1024.0 * 1024.0)
-        )
-    } else if abs_kb >= 1024 {
-        // MB
-        format!(
-            "{}{} KB ({}{:.1} MB)",
-            sign,
-            formatted_kb,
-            sign,
-            abs_kb as f64 / 1024.0
+            format_size_kb(abs_kb)
         )
     } else {
-        // Just KB
         format!("{}{} KB", sign, formatted_kb)
     }
 }
@@ -102,6 +68,220 @@ pub fn format_percentage(ratio: f64) -> String {
     }
 }
 
+/// Render values as a unicode sparkline scaled between their min and max
+pub fn format_sparkline(values: &[u64]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    let min = values.iter().copied().min().unwrap_or(0);
+    let max = values.iter().copied().max().unwrap_or(0);
+    let range = max - min;
+
+    values
+        .iter()
+        .map(|&v| {
+            if range == 0 {
+                BARS[0]
+            } else {
+                BARS[((v - min) as f64 / range as f64 * (BARS.len() - 1) as f64).round() as usize]
+            }
+        })
+        .collect()
+}
+
+/// Render a ratio (0.0-1.0) as a fixed-width gauge, e.g. "[#####-----]"
+pub fn format_gauge(ratio: f64, width: usize) -> String {
+    let filled = (ratio.clamp(0.0, 1.0) * width as f64).round() as usize;
+    format!("[{}{}]", "#".repeat(filled), "-".repeat(width - filled))
+}
+
+/// Size in the largest unit it reaches, without the KB count (e.g. "1.5 GB")
+fn format_size_kb(kb: u64) -> String {
+    if kb >= 1024 * 1024 * 1024 {
+        format!("{:.1} TB", kb as f64 / (1024.0 * 1024.0 * 1024.0))
+    } else if kb >= 1024 * 1024 {
+        format!("{:.1} GB", kb as f64 / (1024.0 * 1024.0))
+    } else if kb >= 1024 {
+        format!("{:.1} MB", kb as f64 / 1024.0)
+    } else {
+        format!("{} KB", kb)
+    }
+}
+
+/// Value column of `format_memory_table_with`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TableUnit {
+    /// meminfo's native KB
+    #[default]
+    Kb,
+    /// Pages of the given size in bytes
+    Pages(u64),
+}
+
+/// Layout of `format_memory_table_with`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MemoryTableOptions {
+    pub unit: TableUnit,
+    /// Field marked with 🎯, e.g. the one a run is generating
+    pub highlight: Option<MemField>,
+    /// Columns available, e.g. the terminal width; the Size and then the
+    /// % Total column are dropped to fit, but values are never cut
+    pub max_width: Option<usize>,
+}
+
+/// Every `MemoryStats` field as a box-drawn table: meminfo name, KB, size in
+/// MB/GB and share of MemTotal
+///
+/// Returns the table without a trailing newline, for printing or for
+/// splitting into lines in a TUI.
+pub fn format_memory_table(stats: &MemoryStats) -> String {
+    format_memory_table_with(stats, &MemoryTableOptions::default())
+}
+
+/// `format_memory_table` with a choice of value unit, a highlighted row and
+/// a width limit
+pub fn format_memory_table_with(stats: &MemoryStats, options: &MemoryTableOptions) -> String {
+    let value_header = match options.unit {
+        TableUnit::Kb => "KB",
+        TableUnit::Pages(_) => "Pages",
+    };
+    let rows: Vec<Vec<String>> = MemField::ALL
+        .into_iter()
+        .map(|field| {
+            let kb = field.value_from(stats);
+            let ratio = if stats.mem_total == 0 {
+                0.0
+            } else {
+                kb as f64 / stats.mem_total as f64
+            };
+            let value = match options.unit {
+                TableUnit::Kb => kb,
+                TableUnit::Pages(page_size) => kb_to_pages(kb, page_size),
+            };
+            let name = if options.highlight == Some(field) {
+                format!("🎯 {}", field.meminfo_name())
+            } else {
+                field.meminfo_name().to_string()
+            };
+            vec![
+                name,
+                format_number(value),
+                format_size_kb(kb),
+                format!("{:.1}%", ratio * 100.0),
+            ]
+        })
+        .collect();
+    let header = ["Field", value_header, "Size", "% Total"];
+
+    // Drop the least essential columns until the table fits
+    let layouts: [&[usize]; 3] = [&[0, 1, 2, 3], &[0, 1, 3], &[0, 1]];
+    let mut table = String::new();
+    for columns in layouts {
+        let header: Vec<&str> = columns.iter().map(|&i| header[i]).collect();
+        let rows: Vec<Vec<String>> = rows
+            .iter()
+            .map(|row| columns.iter().map(|&i| row[i].clone()).collect())
+            .collect();
+        let right_align: Vec<bool> = columns.iter().map(|&i| i > 0).collect();
+        table = format_box_table(&header, &rows, &right_align);
+        let width = table.lines().next().map_or(0, display_width);
+        if options.max_width.is_none_or(|max| width <= max) {
+            break;
+        }
+    }
+    table
+}
+
+/// The fields that changed as a box-drawn table, each marked with ▲ or ▼
+/// and showing the signed change in KB and MB/GB; "no change" if nothing
+/// changed
+///
+/// Plain text without color escapes, so it renders in a TUI too.
+pub fn format_diff_table(diff: &MemoryStatsDiff) -> String {
+    let rows: Vec<Vec<String>> = diff
+        .fields()
+        .into_iter()
+        .filter(|&(_, change)| change != 0)
+        .map(|(name, change)| {
+            let (arrow, sign) = if change > 0 {
+                ("▲", "+")
+            } else {
+                ("▼", "-")
+            };
+            vec![
+                arrow.to_string(),
+                name.to_string(),
+                format_signed_number(change),
+                format!("{}{}", sign, format_size_kb(change.unsigned_abs())),
+            ]
+        })
+        .collect();
+    if rows.is_empty() {
+        return "no change".to_string();
+    }
+    format_box_table(
+        &["", "Field", "Change (KB)", "Size"],
+        &rows,
+        &[false, false, true, true],
+    )
+}
+
+/// Terminal columns `text` takes up; pictographs such as 🎯 take two
+fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|ch| {
+            if ('\u{1F300}'..='\u{1FAFF}').contains(&ch) {
+                2
+            } else {
+                1
+            }
+        })
+        .sum()
+}
+
+/// Table with box-drawing borders and each column as wide as its widest
+/// cell; `right_align[i]` right-aligns column `i`
+fn format_box_table(header: &[&str], rows: &[Vec<String>], right_align: &[bool]) -> String {
+    let widths: Vec<usize> = (0..header.len())
+        .map(|i| {
+            rows.iter()
+                .map(|row| display_width(&row[i]))
+                .fold(display_width(header[i]), usize::max)
+        })
+        .collect();
+    let rule = |left: &str, middle: &str, right: &str| {
+        let segments: Vec<String> = widths.iter().map(|w| "─".repeat(w + 2)).collect();
+        format!("{}{}{}", left, segments.join(middle), right)
+    };
+    let line = |cells: Vec<&str>| {
+        let cells: Vec<String> = cells
+            .into_iter()
+            .zip(&widths)
+            .zip(right_align)
+            .map(|((cell, &width), &right)| {
+                let padding = " ".repeat(width - display_width(cell));
+                if right {
+                    format!(" {}{} ", padding, cell)
+                } else {
+                    format!(" {}{} ", cell, padding)
+                }
+            })
+            .collect();
+        format!("│{}│", cells.join("│"))
+    };
+
+    let mut lines = vec![
+        rule("┌", "┬", "┐"),
+        line(header.to_vec()),
+        rule("├", "┼", "┤"),
+    ];
+    lines.extend(
+        rows.iter()
+            .map(|row| line(row.iter().map(String::as_str).collect())),
+    );
+    lines.push(rule("└", "┴", "┘"));
+    lines.join("\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -146,4 +326,111 @@ mod tests {
         assert_eq!(format_percentage(0.5), "50.0%");
         assert_eq!(format_percentage(0.999), "99.9%");
     }
+
+    #[test]
+    fn test_format_sparkline() {
+        assert_eq!(format_sparkline(&[]), "");
+        assert_eq!(format_sparkline(&[5, 5, 5]), "▁▁▁");
+        assert_eq!(format_sparkline(&[0, 7, 14]), "▁▅█");
+    }
+
+    #[test]
+    fn test_format_gauge() {
+        assert_eq!(format_gauge(0.0, 4), "[----]");
+        assert_eq!(format_gauge(0.5, 4), "[##--]");
+        assert_eq!(format_gauge(1.5, 4), "[####]");
+    }
+
+    #[test]
+    fn test_format_memory_table() {
+        let stats = MemoryStats::builder()
+            .mem_total(16 * 1024 * 1024)
+            .mem_free(4 * 1024 * 1024)
+            .inactive_file(1536)
+            .build();
+        let table = format_memory_table(&stats);
+        let lines: Vec<&str> = table.lines().collect();
+
+        // Borders, header and one row per field
+        assert_eq!(lines.len(), MemField::ALL.len() + 4);
+        assert!(lines[0].starts_with('┌') && lines[0].ends_with('┐'));
+        assert!(lines[2].starts_with('├'));
+        assert!(lines.last().unwrap().starts_with('└'));
+        let width = lines[0].chars().count();
+        assert!(lines.iter().all(|line| line.chars().count() == width));
+
+        assert!(lines[1].contains("Field") && lines[1].contains("KB"));
+        assert_eq!(
+            lines[3],
+            "│ MemTotal       │ 16,777,216 │ 16.0 GB │  100.0% │"
+        );
+        assert!(lines[4].contains("4,194,304") && lines[4].contains("25.0%"));
+        let inactive = lines.iter().find(|l| l.contains("Inactive(file)")).unwrap();
+        assert!(inactive.contains("1,536") && inactive.contains("1.5 MB"));
+
+        let pages = format_memory_table_with(
+            &stats,
+            &MemoryTableOptions {
+                unit: TableUnit::Pages(4096),
+                ..Default::default()
+            },
+        );
+        assert!(pages.contains("Pages") && pages.contains("4,194,304"));
+    }
+
+    #[test]
+    fn test_format_memory_table_highlight_and_fit() {
+        let stats = MemoryStats::builder()
+            .mem_total(16 * 1024 * 1024)
+            .inactive_file(1536)
+            .build();
+        let table_with = |max_width| {
+            format_memory_table_with(
+                &stats,
+                &MemoryTableOptions {
+                    highlight: Some(MemField::InactiveFile),
+                    max_width,
+                    ..Default::default()
+                },
+            )
+        };
+        let width = |table: &str| display_width(table.lines().next().unwrap());
+
+        // The 🎯 row stays aligned with the rest
+        let full = table_with(None);
+        assert!(full.contains("│ 🎯 Inactive(file) │"));
+        assert!(full.lines().all(|line| display_width(line) == width(&full)));
+
+        // Narrow terminals lose Size, then % Total, but never values
+        let no_size = table_with(Some(width(&full) - 1));
+        assert!(!no_size.contains("Size") && no_size.contains("% Total"));
+        assert!(width(&no_size) < width(&full));
+        let minimal = table_with(Some(20));
+        assert!(!minimal.contains("% Total") && minimal.contains("16,777,216"));
+        assert!(width(&minimal) > 20);
+    }
+
+    #[test]
+    fn test_format_diff_table() {
+        let before = MemoryStats::builder().mem_free(4096).cached(1000).build();
+        let after = MemoryStats::builder().mem_free(1024).cached(3048).build();
+        let table = format_diff_table(&before.diff(&after));
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert!(
+            lines
+                .iter()
+                .all(|l| display_width(l) == display_width(lines[0]))
+        );
+
+        let free = lines.iter().find(|l| l.contains("MemFree")).unwrap();
+        assert!(free.starts_with("│ ▼ │"));
+        assert!(free.contains("-3,072") && free.contains("-3.0 MB"));
+        let cached = lines.iter().find(|l| l.contains("Cached")).unwrap();
+        assert!(cached.starts_with("│ ▲ │"));
+        assert!(cached.contains("+2,048") && cached.contains("+2.0 MB"));
+
+        assert!(!table.contains('\x1b'));
+        assert_eq!(format_diff_table(&MemoryStatsDiff::default()), "no change");
+    }
 }
@@ -0,0 +1,120 @@
+use crate::{MemoryError, MemoryUtils, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Kernel Same-page Merging counters from /sys/kernel/mm/ksm
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KsmStats {
+    /// 1 while ksmd is merging, 0 when stopped (or KSM is not built in)
+    pub run: u64,
+    /// Shared pages in use (one per set of merged pages)
+    pub pages_shared: u64,
+    /// Additional sites mapping a shared page, i.e. pages saved
+    pub pages_sharing: u64,
+    /// Pages ksmd checked that are unique but may merge later
+    pub pages_unshared: u64,
+    /// Pages changing too fast for ksmd to merge
+    pub pages_volatile: u64,
+    /// Passes ksmd has completed over all mergeable areas
+    pub full_scans: u64,
+}
+
+impl KsmStats {
+    /// Read current counters from /sys/kernel/mm/ksm
+    ///
+    /// Kernels without KSM have no such directory; they read as all zero.
+    pub fn current() -> Result<Self> {
+        Self::read_from(Path::new("/sys/kernel/mm/ksm"))
+    }
+
+    /// Read counters from a directory laid out like /sys/kernel/mm/ksm
+    pub fn read_from(dir: &Path) -> Result<Self> {
+        if !dir.is_dir() {
+            return Ok(KsmStats::default());
+        }
+
+        let read = |name: &str| -> Result<u64> {
+            let content = fs::read_to_string(dir.join(name))?;
+            content.trim().parse::<u64>().map_err(|_| {
+                MemoryError::ParseError(format!("Invalid number in {}: {}", name, content))
+            })
+        };
+
+        Ok(KsmStats {
+            run: read("run")?,
+            pages_shared: read("pages_shared")?,
+            pages_sharing: read("pages_sharing")?,
+            pages_unshared: read("pages_unshared")?,
+            pages_volatile: read("pages_volatile")?,
+            full_scans: read("full_scans")?,
+        })
+    }
+
+    /// `memory_saved_kb` in bytes
+    pub fn saved_bytes(&self) -> u64 {
+        self.memory_saved_kb().saturating_mul(1024)
+    }
+
+    /// Whether ksmd is merging pages
+    pub fn is_enabled(&self) -> bool {
+        self.run == 1
+    }
+
+    /// Sites mapping each shared page, on average; 0 when nothing is shared
+    pub fn sharing_ratio(&self) -> f64 {
+        if self.pages_shared == 0 {
+            return 0.0;
+        }
+        self.pages_sharing as f64 / self.pages_shared as f64
+    }
+
+    /// Memory KSM is currently saving in KB: `pages_sharing - pages_shared`
+    /// pages, since each shared page still takes one page of its own
+    pub fn memory_saved_kb(&self) -> u64 {
+        MemoryUtils::pages_to_kb(self.pages_sharing.saturating_sub(self.pages_shared))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_read_ksm_counters() {
+        let dir = TempDir::new().unwrap();
+        for (name, value) in [
+            ("run", "1\n"),
+            ("pages_shared", "250\n"),
+            ("pages_sharing", "1000\n"),
+            ("pages_unshared", "5000\n"),
+            ("pages_volatile", "40\n"),
+            ("full_scans", "12\n"),
+        ] {
+            fs::write(dir.path().join(name), value).unwrap();
+        }
+
+        let stats = KsmStats::read_from(dir.path()).unwrap();
+        assert_eq!(stats.run, 1);
+        assert_eq!(stats.pages_shared, 250);
+        assert_eq!(stats.pages_sharing, 1000);
+        assert_eq!(stats.pages_unshared, 5000);
+        assert_eq!(stats.pages_volatile, 40);
+        assert_eq!(stats.full_scans, 12);
+        assert_eq!(stats.saved_bytes(), 750 * MemoryUtils::get_page_size());
+        assert!(stats.is_enabled());
+        assert_eq!(stats.sharing_ratio(), 4.0);
+        assert_eq!(stats.memory_saved_kb(), MemoryUtils::pages_to_kb(750));
+    }
+
+    #[test]
+    fn test_missing_ksm_reads_as_zero() {
+        let stats = KsmStats::read_from(Path::new("/nonexistent/ksm")).unwrap();
+        assert_eq!(stats, KsmStats::default());
+        assert_eq!(stats.saved_bytes(), 0);
+        assert!(!stats.is_enabled());
+        assert_eq!(stats.sharing_ratio(), 0.0);
+        assert_eq!(stats.memory_saved_kb(), 0);
+    }
+}
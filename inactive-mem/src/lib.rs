@@ -2,17 +2,46 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::io;
+use std::path::Path;
 use thiserror::Error;
 
+pub mod buddyinfo;
+pub mod cgroup;
+pub mod extended;
 pub mod formatting;
+pub mod ksm;
+pub mod logger;
 pub mod memory;
 pub mod monitor;
 pub mod page_cache;
+pub mod prometheus;
+pub mod psi;
+pub mod slabinfo;
+pub mod smaps;
+pub mod source;
+pub mod tunables;
+pub mod validation;
+pub mod vmstat;
+pub mod zoneinfo;
 
+pub use buddyinfo::*;
+pub use cgroup::*;
+pub use extended::*;
 pub use formatting::*;
+pub use ksm::*;
+pub use logger::*;
 pub use memory::*;
 pub use monitor::*;
 pub use page_cache::*;
+pub use prometheus::*;
+pub use psi::*;
+pub use slabinfo::*;
+pub use smaps::*;
+pub use source::*;
+pub use tunables::*;
+pub use validation::*;
+pub use vmstat::*;
+pub use zoneinfo::*;
 
 #[derive(Error, Debug)]
 pub enum MemoryError {
@@ -22,18 +51,22 @@ pub enum MemoryError {
     ParseError(String),
     #[error("Memory field not found: {0}")]
     FieldNotFound(String),
+    #[error("Failed to serialize: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
 }
 
 pub type Result<T> = std::result::Result<T, MemoryError>;
 
 /// Core memory statistics from /proc/meminfo
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct MemoryStats {
     /// Total usable RAM (physical RAM minus reserved bits and kernel binary code)
     pub mem_total: u64,
     /// Amount of free memory
     pub mem_free: u64,
-    /// Memory currently in use by the system
+    /// Estimate of memory available for new allocations without swapping
     pub mem_available: u64,
     /// Memory used by buffers
     pub buffers: u64,
@@ -69,15 +102,298 @@ pub struct MemoryStats {
     pub s_unreclaimable: u64,
 }
 
-impl MemoryStats {
-    /// Read current memory statistics from /proc/meminfo
-    pub fn current() -> Result<Self> {
-        let content = fs::read_to_string("/proc/meminfo")?;
-        Self::parse_meminfo(&content)
+/// `MemField::$name()` of every field, in `MemField::ALL` order
+macro_rules! field_table {
+    ($name:ident) => {{
+        let mut table = [""; MemField::ALL.len()];
+        let mut i = 0;
+        while i < table.len() {
+            table[i] = MemField::ALL[i].$name();
+            i += 1;
+        }
+        table
+    }};
+}
+
+/// /proc/meminfo keys for every MemoryStats field, in struct order
+pub const FIELD_NAMES: &[&str] = &field_table!(meminfo_name);
+
+/// What each field in `FIELD_NAMES` measures, as in the MemoryStats field
+/// docs; used as exporter help text
+pub const FIELD_DESCRIPTIONS: &[&str] = &field_table!(description);
+
+/// Rust names of the MemoryStats fields in declaration order, matching
+/// `FIELD_NAMES` index for index
+const STATS_FIELD_NAMES: &[&str] = &field_table!(rust_name);
+
+/// A `MemoryStats` field, for choosing one at run time (e.g.
+/// `EventMonitor::add_rate_condition`); variants are in `FIELD_NAMES` order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MemField {
+    MemTotal,
+    MemFree,
+    MemAvailable,
+    Buffers,
+    Cached,
+    SwapCached,
+    Active,
+    Inactive,
+    ActiveFile,
+    InactiveFile,
+    ActiveAnon,
+    InactiveAnon,
+    Dirty,
+    Writeback,
+    Mapped,
+    Shmem,
+    Slab,
+    SReclaimable,
+    SUnreclaimable,
+}
+
+impl MemField {
+    pub const ALL: [MemField; 19] = [
+        MemField::MemTotal,
+        MemField::MemFree,
+        MemField::MemAvailable,
+        MemField::Buffers,
+        MemField::Cached,
+        MemField::SwapCached,
+        MemField::Active,
+        MemField::Inactive,
+        MemField::ActiveFile,
+        MemField::InactiveFile,
+        MemField::ActiveAnon,
+        MemField::InactiveAnon,
+        MemField::Dirty,
+        MemField::Writeback,
+        MemField::Mapped,
+        MemField::Shmem,
+        MemField::Slab,
+        MemField::SReclaimable,
+        MemField::SUnreclaimable,
+    ];
+
+    /// The field with /proc/meminfo key `name`, e.g. "Inactive(file)"
+    pub fn from_meminfo_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.meminfo_name() == name)
+    }
+
+    /// The field with MemoryStats field name `name`, e.g. "inactive_file"
+    pub fn from_rust_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.rust_name() == name)
+    }
+
+    /// The field's /proc/meminfo key, e.g. "Inactive(file)"
+    pub const fn meminfo_name(self) -> &'static str {
+        match self {
+            MemField::MemTotal => "MemTotal",
+            MemField::MemFree => "MemFree",
+            MemField::MemAvailable => "MemAvailable",
+            MemField::Buffers => "Buffers",
+            MemField::Cached => "Cached",
+            MemField::SwapCached => "SwapCached",
+            MemField::Active => "Active",
+            MemField::Inactive => "Inactive",
+            MemField::ActiveFile => "Active(file)",
+            MemField::InactiveFile => "Inactive(file)",
+            MemField::ActiveAnon => "Active(anon)",
+            MemField::InactiveAnon => "Inactive(anon)",
+            MemField::Dirty => "Dirty",
+            MemField::Writeback => "Writeback",
+            MemField::Mapped => "Mapped",
+            MemField::Shmem => "Shmem",
+            MemField::Slab => "Slab",
+            MemField::SReclaimable => "SReclaimable",
+            MemField::SUnreclaimable => "SUnreclaim",
+        }
+    }
+
+    /// The field's name in `MemoryStats`, e.g. "inactive_file"
+    pub const fn rust_name(self) -> &'static str {
+        match self {
+            MemField::MemTotal => "mem_total",
+            MemField::MemFree => "mem_free",
+            MemField::MemAvailable => "mem_available",
+            MemField::Buffers => "buffers",
+            MemField::Cached => "cached",
+            MemField::SwapCached => "swap_cached",
+            MemField::Active => "active",
+            MemField::Inactive => "inactive",
+            MemField::ActiveFile => "active_file",
+            MemField::InactiveFile => "inactive_file",
+            MemField::ActiveAnon => "active_anon",
+            MemField::InactiveAnon => "inactive_anon",
+            MemField::Dirty => "dirty",
+            MemField::Writeback => "writeback",
+            MemField::Mapped => "mapped",
+            MemField::Shmem => "shmem",
+            MemField::Slab => "slab",
+            MemField::SReclaimable => "s_reclaimable",
+            MemField::SUnreclaimable => "s_unreclaimable",
+        }
+    }
+
+    /// What the field measures; see `FIELD_DESCRIPTIONS`
+    pub const fn description(self) -> &'static str {
+        match self {
+            MemField::MemTotal => {
+                "Total usable RAM (physical RAM minus reserved bits and kernel binary code)"
+            }
+            MemField::MemFree => "Amount of free memory",
+            MemField::MemAvailable => {
+                "Estimate of memory available for new allocations without swapping"
+            }
+            MemField::Buffers => "Memory used by buffers",
+            MemField::Cached => "Memory used by page cache and slabs",
+            MemField::SwapCached => "Swap cache memory",
+            MemField::Active => {
+                "Memory that has been used more recently and usually not reclaimed unless absolutely necessary"
+            }
+            MemField::Inactive => {
+                "Memory which has been less recently used and is more eligible to be reclaimed"
+            }
+            MemField::ActiveFile => "Active memory for file-backed pages",
+            MemField::InactiveFile => {
+                "Inactive memory for file-backed pages (page cache that can be reclaimed)"
+            }
+            MemField::ActiveAnon => "Active memory for anonymous pages",
+            MemField::InactiveAnon => "Inactive memory for anonymous pages",
+            MemField::Dirty => "Memory that is waiting to be written back to disk",
+            MemField::Writeback => "Memory that is actively being written back to disk",
+            MemField::Mapped => "Memory mapped by mmap()",
+            MemField::Shmem => "Shared memory",
+            MemField::Slab => "Kernel slab memory",
+            MemField::SReclaimable => "Reclaimable slab memory",
+            MemField::SUnreclaimable => "Unreclaimable slab memory",
+        }
+    }
+
+    /// The field's value in `stats` (KB)
+    pub fn value_from(&self, stats: &MemoryStats) -> u64 {
+        match self {
+            MemField::MemTotal => stats.mem_total,
+            MemField::MemFree => stats.mem_free,
+            MemField::MemAvailable => stats.mem_available,
+            MemField::Buffers => stats.buffers,
+            MemField::Cached => stats.cached,
+            MemField::SwapCached => stats.swap_cached,
+            MemField::Active => stats.active,
+            MemField::Inactive => stats.inactive,
+            MemField::ActiveFile => stats.active_file,
+            MemField::InactiveFile => stats.inactive_file,
+            MemField::ActiveAnon => stats.active_anon,
+            MemField::InactiveAnon => stats.inactive_anon,
+            MemField::Dirty => stats.dirty,
+            MemField::Writeback => stats.writeback,
+            MemField::Mapped => stats.mapped,
+            MemField::Shmem => stats.shmem,
+            MemField::Slab => stats.slab,
+            MemField::SReclaimable => stats.s_reclaimable,
+            MemField::SUnreclaimable => stats.s_unreclaimable,
+        }
+    }
+
+    /// The field's slot in `stats`, for filling stats in field by field
+    fn value_mut(self, stats: &mut MemoryStats) -> &mut u64 {
+        match self {
+            MemField::MemTotal => &mut stats.mem_total,
+            MemField::MemFree => &mut stats.mem_free,
+            MemField::MemAvailable => &mut stats.mem_available,
+            MemField::Buffers => &mut stats.buffers,
+            MemField::Cached => &mut stats.cached,
+            MemField::SwapCached => &mut stats.swap_cached,
+            MemField::Active => &mut stats.active,
+            MemField::Inactive => &mut stats.inactive,
+            MemField::ActiveFile => &mut stats.active_file,
+            MemField::InactiveFile => &mut stats.inactive_file,
+            MemField::ActiveAnon => &mut stats.active_anon,
+            MemField::InactiveAnon => &mut stats.inactive_anon,
+            MemField::Dirty => &mut stats.dirty,
+            MemField::Writeback => &mut stats.writeback,
+            MemField::Mapped => &mut stats.mapped,
+            MemField::Shmem => &mut stats.shmem,
+            MemField::Slab => &mut stats.slab,
+            MemField::SReclaimable => &mut stats.s_reclaimable,
+            MemField::SUnreclaimable => &mut stats.s_unreclaimable,
+        }
+    }
+}
+
+/// Fields without which the stats are meaningless: every ratio and
+/// pressure level is computed from them
+pub const REQUIRED_FIELDS: &[&str] = &["MemTotal", "MemFree", "MemAvailable"];
+
+/// Which meminfo fields must be present when parsing
+///
+/// The default requires only `REQUIRED_FIELDS`, so stats can still be read
+/// where containers or sandboxes expose a reduced /proc/meminfo.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOptions {
+    required: Vec<String>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self::with_required(REQUIRED_FIELDS)
+    }
+}
+
+impl ParseOptions {
+    /// Require every field in `FIELD_NAMES`
+    pub fn strict() -> Self {
+        Self::with_required(FIELD_NAMES)
+    }
+
+    /// Require exactly the given meminfo fields; all others are optional
+    pub fn with_required(fields: &[&str]) -> Self {
+        ParseOptions {
+            required: fields.iter().map(|f| f.to_string()).collect(),
+        }
+    }
+
+    /// Additionally require `field`
+    pub fn require(mut self, field: &str) -> Self {
+        if !self.is_required(field) {
+            self.required.push(field.to_string());
+        }
+        self
+    }
+
+    pub fn is_required(&self, field: &str) -> bool {
+        self.required.iter().any(|f| f == field)
+    }
+}
+
+/// Extracts every field of /proc/meminfo by name, including those
+/// `MemoryStats` doesn't have (`Percpu`, `DirectMap1G`, fields of newer
+/// kernels, ...)
+///
+/// Values are as the kernel prints them: KB for most fields, but page counts
+/// for `HugePages_*`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProcMemInfoParser;
+
+impl ProcMemInfoParser {
+    pub fn new() -> Self {
+        ProcMemInfoParser
     }
 
-    /// Parse /proc/meminfo content into MemoryStats
-    fn parse_meminfo(content: &str) -> Result<Self> {
+    /// Every `Key: value [kB]` line, by key; lines without a numeric value
+    /// are skipped
+    pub fn parse(&self, content: &str) -> HashMap<String, u64> {
+        content
+            .lines()
+            .filter_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                let value = value.split_whitespace().next()?.parse().ok()?;
+                Some((key.trim().to_string(), value))
+            })
+            .collect()
+    }
+
+    /// Like `parse`, but a line without a numeric value is an error
+    pub fn try_parse(&self, content: &str) -> Result<HashMap<String, u64>> {
         let mut fields = HashMap::new();
 
         for line in content.lines() {
@@ -101,46 +417,164 @@ impl MemoryStats {
             }
         }
 
-        // Helper function to get field value
-        let get_field = |name: &str| -> Result<u64> {
-            fields
-                .get(name)
-                .copied()
-                .ok_or_else(|| MemoryError::FieldNotFound(name.to_string()))
-        };
+        Ok(fields)
+    }
+
+    /// Read and `try_parse` a meminfo file, e.g. /proc/meminfo or a saved
+    /// copy
+    pub fn parse_file(&self, path: &Path) -> Result<HashMap<String, u64>> {
+        self.try_parse(&fs::read_to_string(path)?)
+    }
+}
 
-        Ok(MemoryStats {
-            mem_total: get_field("MemTotal")?,
-            mem_free: get_field("MemFree")?,
-            mem_available: get_field("MemAvailable")?,
-            buffers: get_field("Buffers")?,
-            cached: get_field("Cached")?,
-            swap_cached: get_field("SwapCached")?,
-            active: get_field("Active")?,
-            inactive: get_field("Inactive")?,
-            active_file: get_field("Active(file)")?,
-            inactive_file: get_field("Inactive(file)")?,
-            active_anon: get_field("Active(anon)")?,
-            inactive_anon: get_field("Inactive(anon)")?,
-            dirty: get_field("Dirty")?,
-            writeback: get_field("Writeback")?,
-            mapped: get_field("Mapped")?,
-            shmem: get_field("Shmem")?,
-            slab: get_field("Slab")?,
-            s_reclaimable: get_field("SReclaimable")?,
-            s_unreclaimable: get_field("SUnreclaim")?,
-        })
+/// Every `Key: value [kB]` line of /proc/meminfo content, by key (see
+/// `ProcMemInfoParser::try_parse`)
+pub fn parse_meminfo_fields(content: &str) -> Result<HashMap<String, u64>> {
+    ProcMemInfoParser::new().try_parse(content)
+}
+
+impl MemoryStats {
+    /// Start building stats with every field zero (see `MemoryStatsBuilder`)
+    pub fn builder() -> MemoryStatsBuilder {
+        MemoryStatsBuilder::default()
+    }
+
+    /// Read current memory statistics from /proc/meminfo
+    ///
+    /// Fields beyond `REQUIRED_FIELDS` that the kernel (or a sandbox such as
+    /// gVisor) doesn't report read as 0; use `current_with` to find out which.
+    pub fn current() -> Result<Self> {
+        Self::current_with(&ParseOptions::default()).map(|(stats, _)| stats)
+    }
+
+    /// Read current memory statistics without blocking an async runtime
+    ///
+    /// Same parsing and missing-field handling as `current()`; the read goes
+    /// through `tokio::fs`, so this must be awaited inside a tokio runtime.
+    #[cfg(feature = "async")]
+    pub async fn current_async() -> Result<Self> {
+        let content = tokio::fs::read_to_string("/proc/meminfo").await?;
+        Self::parse_meminfo_with(&content, &ParseOptions::default()).map(|(stats, _)| stats)
+    }
+
+    /// Read /proc/meminfo with the given strictness, returning the stats and
+    /// the optional fields that were missing and set to 0
+    pub fn current_with(options: &ParseOptions) -> Result<(Self, Vec<String>)> {
+        let content = fs::read_to_string("/proc/meminfo")?;
+        Self::parse_meminfo_with(&content, options)
+    }
+
+    /// Parse /proc/meminfo content into MemoryStats, requiring every field
+    pub fn parse_meminfo(content: &str) -> Result<Self> {
+        Self::parse_meminfo_with(content, &ParseOptions::strict()).map(|(stats, _)| stats)
+    }
+
+    /// Parse /proc/meminfo content, failing only on missing required fields
+    ///
+    /// Missing optional fields are set to 0 and returned by name, in
+    /// `FIELD_NAMES` order, so callers can warn about them.
+    pub fn parse_meminfo_with(
+        content: &str,
+        options: &ParseOptions,
+    ) -> Result<(Self, Vec<String>)> {
+        let (stats, missing) = Self::from_fields(&parse_meminfo_fields(content)?);
+        if let Some(name) = missing.iter().find(|name| options.is_required(name)) {
+            return Err(MemoryError::FieldNotFound(name.clone()));
+        }
+        Ok((stats, missing))
+    }
+
+    /// Parse /proc/meminfo content without ever failing
+    ///
+    /// Every field that is absent or has an unreadable value is set to 0 and
+    /// returned by name, in `FIELD_NAMES` order. For dumps from old or
+    /// embedded kernels where even MemAvailable may be missing; check the
+    /// list before trusting ratios computed from the stats.
+    pub fn parse_meminfo_lenient(content: &str) -> (Self, Vec<String>) {
+        Self::from_fields(&ProcMemInfoParser::new().parse(content))
+    }
+
+    /// Read /proc/meminfo with `parse_meminfo_lenient`; only failing to read
+    /// the file is an error
+    pub fn current_lenient() -> Result<(Self, Vec<String>)> {
+        let content = fs::read_to_string("/proc/meminfo")?;
+        Ok(Self::parse_meminfo_lenient(&content))
+    }
+
+    /// Stats from parsed meminfo fields, with the `FIELD_NAMES` that were
+    /// missing and read as 0
+    fn from_fields(fields: &HashMap<String, u64>) -> (Self, Vec<String>) {
+        let missing = FIELD_NAMES
+            .iter()
+            .filter(|&&name| !fields.contains_key(name))
+            .map(|name| name.to_string())
+            .collect();
+        let mut stats = MemoryStats::default();
+        for field in MemField::ALL {
+            *field.value_mut(&mut stats) = fields.get(field.meminfo_name()).copied().unwrap_or(0);
+        }
+        (stats, missing)
+    }
+
+    /// Look up a field by its /proc/meminfo name (e.g. "Inactive(file)")
+    pub fn get(&self, field: &str) -> Option<u64> {
+        MemField::from_meminfo_name(field).map(|f| f.value_from(self))
+    }
+
+    /// Look up a field by its Rust name (e.g. "inactive_file"); see `get`
+    /// for /proc/meminfo names
+    pub fn field_value(&self, name: &str) -> Option<u64> {
+        MemField::from_rust_name(name).map(|f| f.value_from(self))
+    }
+
+    /// Rust names of every field in declaration order, as taken by
+    /// `field_value`; index `i` is the field of `FIELD_NAMES[i]`
+    pub fn field_names() -> &'static [&'static str] {
+        STATS_FIELD_NAMES
+    }
+
+    /// Every field as `(name, KB)` in declaration order, for formatters and
+    /// exporters that treat all fields alike
+    pub fn iter_fields(&self) -> impl Iterator<Item = (&'static str, u64)> + '_ {
+        MemField::ALL
+            .into_iter()
+            .map(|field| (field.rust_name(), field.value_from(self)))
+    }
+
+    /// Field-wise change from these stats to `other` (`other - self`)
+    pub fn diff(&self, other: &MemoryStats) -> MemoryStatsDiff {
+        MemoryStatsDiff::between(self, other)
+    }
+
+    /// Render the stats in /proc/meminfo format, one `Name:  value kB` line
+    /// per field in `FIELD_NAMES` order; `parse_meminfo` reads it back
+    pub fn to_meminfo_string(&self) -> String {
+        FIELD_NAMES
+            .iter()
+            .map(|&name| {
+                format!(
+                    "{:<16}{:>8} kB\n",
+                    format!("{}:", name),
+                    self.get(name).unwrap_or_default()
+                )
+            })
+            .collect()
     }
 
     /// Calculate used memory (Total - Free - Buffers - Cached)
     pub fn used_memory(&self) -> u64 {
         self.mem_total
-            .saturating_sub(self.mem_free + self.buffers + self.cached)
+            .saturating_sub(self.mem_free.saturating_add(self.page_cache_size()))
     }
 
     /// Calculate page cache size (Cached + Buffers)
     pub fn page_cache_size(&self) -> u64 {
-        self.cached + self.buffers
+        self.cached.saturating_add(self.buffers)
+    }
+
+    /// Anonymous (process) memory, Active(anon) + Inactive(anon)
+    pub fn anon_memory(&self) -> u64 {
+        self.active_anon.saturating_add(self.inactive_anon)
     }
 
     /// Calculate memory utilization percentage
@@ -161,39 +595,184 @@ impl MemoryStats {
         }
     }
 
-    /// Convert all values from KB to bytes
-    pub fn to_bytes(&self) -> MemoryStats {
+    /// Ratio of reclaimable file pages to reclaimable anon pages
+    ///
+    /// Above 1.0 the inactive LRU is dominated by page cache, so reclaim will
+    /// mostly drop file pages; below 1.0 it leans on swapping anon pages.
+    pub fn reclaim_balance(&self) -> f64 {
+        self.inactive_file as f64 / self.inactive_anon.saturating_add(1) as f64
+    }
+
+    /// Interpret `reclaim_balance()` for vm.swappiness tuning
+    pub fn reclaim_bias(&self) -> ReclaimBias {
+        match self.reclaim_balance() {
+            r if r > 2.0 => ReclaimBias::File,
+            r if r < 0.5 => ReclaimBias::Anon,
+            _ => ReclaimBias::Balanced,
+        }
+    }
+
+    /// Average a series of stats field-wise (e.g. to smooth over snapshots)
+    pub fn mean(stats: &[MemoryStats]) -> MemoryStats {
+        if stats.is_empty() {
+            return MemoryStats::default();
+        }
+
+        let count = stats.len() as u64;
+        let total: MemoryStats = stats.iter().cloned().sum();
+        total.map_fields(|value| value / count)
+    }
+
+    /// Apply `op` to every field
+    fn map_fields(&self, op: impl Fn(u64) -> u64) -> MemoryStats {
         MemoryStats {
-            mem_total: self.mem_total * 1024,
-            mem_free: self.mem_free * 1024,
-            mem_available: self.mem_available * 1024,
-            buffers: self.buffers * 1024,
-            cached: self.cached * 1024,
-            swap_cached: self.swap_cached * 1024,
-            active: self.active * 1024,
-            inactive: self.inactive * 1024,
-            active_file: self.active_file * 1024,
-            inactive_file: self.inactive_file * 1024,
-            active_anon: self.active_anon * 1024,
-            inactive_anon: self.inactive_anon * 1024,
-            dirty: self.dirty * 1024,
-            writeback: self.writeback * 1024,
-            mapped: self.mapped * 1024,
-            shmem: self.shmem * 1024,
-            slab: self.slab * 1024,
-            s_reclaimable: self.s_reclaimable * 1024,
-            s_unreclaimable: self.s_unreclaimable * 1024,
+            mem_total: op(self.mem_total),
+            mem_free: op(self.mem_free),
+            mem_available: op(self.mem_available),
+            buffers: op(self.buffers),
+            cached: op(self.cached),
+            swap_cached: op(self.swap_cached),
+            active: op(self.active),
+            inactive: op(self.inactive),
+            active_file: op(self.active_file),
+            inactive_file: op(self.inactive_file),
+            active_anon: op(self.active_anon),
+            inactive_anon: op(self.inactive_anon),
+            dirty: op(self.dirty),
+            writeback: op(self.writeback),
+            mapped: op(self.mapped),
+            shmem: op(self.shmem),
+            slab: op(self.slab),
+            s_reclaimable: op(self.s_reclaimable),
+            s_unreclaimable: op(self.s_unreclaimable),
         }
     }
+
+    /// Combine every field of `self` and `other` with `op`
+    fn zip_fields(&self, other: &MemoryStats, op: impl Fn(u64, u64) -> u64) -> MemoryStats {
+        MemoryStats {
+            mem_total: op(self.mem_total, other.mem_total),
+            mem_free: op(self.mem_free, other.mem_free),
+            mem_available: op(self.mem_available, other.mem_available),
+            buffers: op(self.buffers, other.buffers),
+            cached: op(self.cached, other.cached),
+            swap_cached: op(self.swap_cached, other.swap_cached),
+            active: op(self.active, other.active),
+            inactive: op(self.inactive, other.inactive),
+            active_file: op(self.active_file, other.active_file),
+            inactive_file: op(self.inactive_file, other.inactive_file),
+            active_anon: op(self.active_anon, other.active_anon),
+            inactive_anon: op(self.inactive_anon, other.inactive_anon),
+            dirty: op(self.dirty, other.dirty),
+            writeback: op(self.writeback, other.writeback),
+            mapped: op(self.mapped, other.mapped),
+            shmem: op(self.shmem, other.shmem),
+            slab: op(self.slab, other.slab),
+            s_reclaimable: op(self.s_reclaimable, other.s_reclaimable),
+            s_unreclaimable: op(self.s_unreclaimable, other.s_unreclaimable),
+        }
+    }
+
+    /// Convert all values from KB to bytes
+    ///
+    /// Values are already in kB, so anything above `u64::MAX / 1024` (16 EiB)
+    /// saturates at `u64::MAX` instead of overflowing.
+    pub fn to_bytes(&self) -> MemoryStats {
+        self.map_fields(|kb| kb.saturating_mul(1024))
+    }
+
+    /// Convert all values from KB to counts of `page_size`-byte pages, so
+    /// they line up with kpageflags PFN counts
+    ///
+    /// meminfo always reports kB, so the result depends on the page size:
+    /// pass the system's real page size (e.g. 16384 or 65536 on some arm64
+    /// and ppc64 kernels) rather than assuming 4096. Partial pages round down.
+    pub fn in_pages(&self, page_size: u64) -> MemoryStats {
+        self.map_fields(|kb| kb_to_pages(kb, page_size))
+    }
+}
+
+/// Page size of x86-64 and most other Linux systems
+pub const DEFAULT_PAGE_SIZE: u64 = 4096;
+
+/// Convert a meminfo kB value to whole pages of `page_size` bytes
+pub fn kb_to_pages(kb: u64, page_size: u64) -> u64 {
+    kb.saturating_mul(1024) / page_size.max(1)
+}
+
+/// Fluent construction of `MemoryStats` for fixtures and synthetic inputs
+///
+/// ```
+/// use linux_memory_monitor::MemoryStats;
+///
+/// let stats = MemoryStats::builder().mem_total(1000).mem_free(250).build();
+/// assert_eq!(stats.used_memory(), 750);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStatsBuilder {
+    stats: MemoryStats,
+}
+
+macro_rules! builder_setters {
+    ($($field:ident),* $(,)?) => {
+        $(
+            #[doc = concat!("Set `", stringify!($field), "` (in KB)")]
+            pub fn $field(mut self, value: u64) -> Self {
+                self.stats.$field = value;
+                self
+            }
+        )*
+    };
+}
+
+impl MemoryStatsBuilder {
+    builder_setters!(
+        mem_total,
+        mem_free,
+        mem_available,
+        buffers,
+        cached,
+        swap_cached,
+        active,
+        inactive,
+        active_file,
+        inactive_file,
+        active_anon,
+        inactive_anon,
+        dirty,
+        writeback,
+        mapped,
+        shmem,
+        slab,
+        s_reclaimable,
+        s_unreclaimable,
+    );
+
+    pub fn build(self) -> MemoryStats {
+        self.stats
+    }
+}
+
+/// Field-wise sum, e.g. to reconstruct system totals from per-NUMA-node stats
+impl std::ops::Add for MemoryStats {
+    type Output = MemoryStats;
+
+    fn add(self, other: MemoryStats) -> MemoryStats {
+        self.zip_fields(&other, |a, b| a.saturating_add(b))
+    }
+}
+
+impl std::iter::Sum for MemoryStats {
+    fn sum<I: Iterator<Item = MemoryStats>>(iter: I) -> MemoryStats {
+        iter.fold(MemoryStats::default(), |acc, stats| acc + stats)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_parse_meminfo() {
-        let sample_meminfo = r#"MemTotal:       16384000 kB
+    const SAMPLE_MEMINFO: &str = r#"MemTotal:       16384000 kB
 MemFree:         8192000 kB
 MemAvailable:   12288000 kB
 Buffers:          512000 kB
@@ -213,52 +792,321 @@ Slab:             384000 kB
 SReclaimable:     256000 kB
 SUnreclaim:       128000 kB"#;
 
-        let stats = MemoryStats::parse_meminfo(sample_meminfo).unwrap();
+    #[test]
+    fn test_parse_meminfo() {
+        let stats = MemoryStats::parse_meminfo(SAMPLE_MEMINFO).unwrap();
         assert_eq!(stats.mem_total, 16384000);
         assert_eq!(stats.mem_free, 8192000);
         assert_eq!(stats.cached, 2048000);
         assert_eq!(stats.inactive_file, 1536000);
     }
 
+    #[test]
+    fn test_meminfo_string_round_trip() {
+        let stats = MemoryStats::parse_meminfo(SAMPLE_MEMINFO).unwrap();
+        let rendered = stats.to_meminfo_string();
+        assert!(rendered.starts_with("MemTotal:       16384000 kB\n"));
+        assert_eq!(rendered.lines().count(), FIELD_NAMES.len());
+        assert_eq!(MemoryStats::parse_meminfo(&rendered).unwrap(), stats);
+
+        // Values wider than the kernel's 8-digit column still parse
+        let huge = MemoryStats::builder().mem_total(u64::MAX).build();
+        assert_eq!(
+            MemoryStats::parse_meminfo(&huge.to_meminfo_string()).unwrap(),
+            huge
+        );
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_current_async_matches_current() {
+        let stats = MemoryStats::current_async().await.unwrap();
+        // MemTotal doesn't change between the two reads
+        assert_eq!(stats.mem_total, MemoryStats::current().unwrap().mem_total);
+        assert!(stats.mem_total > 0);
+    }
+
+    /// Reduced meminfo as reported by gVisor: no slab or swap cache lines
+    const GVISOR_MEMINFO: &str = r#"MemTotal:        2097152 kB
+MemFree:         1843200 kB
+MemAvailable:    1968128 kB
+Buffers:               0 kB
+Cached:           102400 kB
+Active:           153600 kB
+Inactive:          40960 kB
+Active(anon):     122880 kB
+Inactive(anon):        0 kB
+Active(file):      30720 kB
+Inactive(file):    40960 kB
+Unevictable:           0 kB
+Mlocked:               0 kB
+SwapTotal:             0 kB
+SwapFree:              0 kB
+Dirty:                 0 kB
+Writeback:             0 kB
+AnonPages:        122880 kB
+Mapped:            51200 kB
+Shmem:              4096 kB
+CommitLimit:     1048576 kB
+Committed_AS:     204800 kB"#;
+
+    #[test]
+    fn test_parse_reduced_meminfo() {
+        assert!(matches!(
+            MemoryStats::parse_meminfo(GVISOR_MEMINFO),
+            Err(MemoryError::FieldNotFound(field)) if field == "SwapCached"
+        ));
+
+        let (stats, missing) =
+            MemoryStats::parse_meminfo_with(GVISOR_MEMINFO, &ParseOptions::default()).unwrap();
+        assert_eq!(
+            missing,
+            ["SwapCached", "Slab", "SReclaimable", "SUnreclaim"]
+        );
+        assert_eq!(stats.mem_available, 1968128);
+        assert_eq!(stats.inactive_file, 40960);
+        assert_eq!(stats.slab, 0);
+
+        // Requiring a field the fixture lacks makes it an error again
+        let options = ParseOptions::default().require("Slab");
+        assert!(MemoryStats::parse_meminfo_with(GVISOR_MEMINFO, &options).is_err());
+
+        let no_total = SAMPLE_MEMINFO.replace("MemTotal", "MemTotalX");
+        assert!(MemoryStats::parse_meminfo_with(&no_total, &ParseOptions::default()).is_err());
+
+        // Lenient parsing takes anything, including a garbled value
+        let (stats, missing) = MemoryStats::parse_meminfo_lenient(&no_total);
+        assert_eq!(missing, ["MemTotal"]);
+        assert_eq!(stats.mem_total, 0);
+        assert_eq!(
+            stats.mem_free,
+            MemoryStats::parse_meminfo(SAMPLE_MEMINFO).unwrap().mem_free
+        );
+        let garbled = GVISOR_MEMINFO.replacen("MemAvailable:", "MemAvailable: ??", 1);
+        let (stats, missing) = MemoryStats::parse_meminfo_lenient(&garbled);
+        assert_eq!(
+            missing,
+            [
+                "MemAvailable",
+                "SwapCached",
+                "Slab",
+                "SReclaimable",
+                "SUnreclaim"
+            ]
+        );
+        assert_eq!(stats.inactive_file, 40960);
+        assert_eq!(
+            MemoryStats::parse_meminfo_lenient(SAMPLE_MEMINFO),
+            (
+                MemoryStats::parse_meminfo(SAMPLE_MEMINFO).unwrap(),
+                Vec::new()
+            )
+        );
+    }
+
     #[test]
     fn test_memory_calculations() {
+        let stats = MemoryStats::builder()
+            .mem_total(16384000)
+            .mem_free(8192000)
+            .buffers(512000)
+            .cached(2048000)
+            .build();
+
+        assert_eq!(stats.used_memory(), 5632000); // 16384000 - 8192000 - 512000 - 2048000
+        assert_eq!(stats.page_cache_size(), 2560000); // 2048000 + 512000
+    }
+
+    #[test]
+    fn test_sum_of_nodes_matches_global() {
+        let node0 = MemoryStats::builder()
+            .mem_total(8192000)
+            .mem_free(4096000)
+            .cached(1024000)
+            .inactive_file(768000)
+            .dirty(32000)
+            .build();
+        let node1 = node0.clone();
+
+        let global = MemoryStats::parse_meminfo(SAMPLE_MEMINFO).unwrap();
+        let total = node0 + node1;
+        assert_eq!(total.mem_total, global.mem_total);
+        assert_eq!(total.mem_free, global.mem_free);
+        assert_eq!(total.cached, global.cached);
+        assert_eq!(total.inactive_file, global.inactive_file);
+        assert_eq!(total.dirty, global.dirty);
+    }
+
+    #[test]
+    fn test_mean() {
+        let samples = vec![
+            MemoryStats::builder().mem_free(1000).cached(300).build(),
+            MemoryStats::builder().mem_free(3000).cached(500).build(),
+        ];
+
+        let mean = MemoryStats::mean(&samples);
+        assert_eq!(mean.mem_free, 2000);
+        assert_eq!(mean.cached, 400);
+        assert_eq!(MemoryStats::mean(&[]).mem_free, 0);
+    }
+
+    #[test]
+    fn test_to_bytes_saturates_at_boundary() {
         let stats = MemoryStats {
-            mem_total: 16384000,
-            mem_free: 8192000,
-            buffers: 512000,
-            cached: 2048000,
-            // ... other fields with default values for test
+            mem_total: u64::MAX / 1024,
+            mem_free: u64::MAX / 1024 + 1,
+            cached: u64::MAX,
+            buffers: 1,
             ..Default::default()
         };
 
-        assert_eq!(stats.used_memory(), 5632000); // 16384000 - 8192000 - 512000 - 2048000
-        assert_eq!(stats.page_cache_size(), 2560000); // 2048000 + 512000
+        let bytes = stats.to_bytes();
+        assert_eq!(bytes.mem_total, (u64::MAX / 1024) * 1024);
+        assert_eq!(bytes.mem_free, u64::MAX);
+        assert_eq!(bytes.cached, u64::MAX);
+        assert_eq!(bytes.buffers, 1024);
     }
-}
 
-// Implement Default for MemoryStats for testing
-impl Default for MemoryStats {
-    fn default() -> Self {
-        Self {
-            mem_total: 0,
-            mem_free: 0,
-            mem_available: 0,
-            buffers: 0,
-            cached: 0,
-            swap_cached: 0,
-            active: 0,
-            inactive: 0,
-            active_file: 0,
-            inactive_file: 0,
-            active_anon: 0,
-            inactive_anon: 0,
-            dirty: 0,
-            writeback: 0,
-            mapped: 0,
-            shmem: 0,
-            slab: 0,
-            s_reclaimable: 0,
-            s_unreclaimable: 0,
+    #[test]
+    fn test_anon_memory_saturates() {
+        let stats = MemoryStats {
+            active_anon: u64::MAX,
+            inactive_anon: 1,
+            ..Default::default()
+        };
+        assert_eq!(stats.anon_memory(), u64::MAX);
+    }
+
+    #[test]
+    fn test_reclaim_balance() {
+        let stats = MemoryStats::builder()
+            .inactive_file(3000)
+            .inactive_anon(999)
+            .build();
+        assert_eq!(stats.reclaim_balance(), 3.0);
+        assert_eq!(stats.reclaim_bias(), ReclaimBias::File);
+
+        let anon_heavy = MemoryStats::builder()
+            .inactive_file(100)
+            .inactive_anon(1000)
+            .build();
+        assert_eq!(anon_heavy.reclaim_bias(), ReclaimBias::Anon);
+
+        // No inactive anon pages must not divide by zero
+        let no_anon = MemoryStats::builder().inactive_file(10).build();
+        assert_eq!(no_anon.reclaim_balance(), 10.0);
+        assert_eq!(MemoryStats::default().reclaim_bias(), ReclaimBias::Anon);
+    }
+
+    #[test]
+    fn test_get_by_meminfo_name() {
+        let stats = MemoryStats::parse_meminfo(SAMPLE_MEMINFO).unwrap();
+
+        // Every parsed field is retrievable under the key it was parsed from
+        for line in SAMPLE_MEMINFO.lines() {
+            let (key, value) = line.split_once(':').unwrap();
+            let value: u64 = value.split_whitespace().next().unwrap().parse().unwrap();
+            assert!(
+                FIELD_NAMES.contains(&key),
+                "{} missing from FIELD_NAMES",
+                key
+            );
+            assert_eq!(stats.get(key), Some(value), "{}", key);
+        }
+        assert_eq!(FIELD_NAMES.len(), SAMPLE_MEMINFO.lines().count());
+        assert_eq!(FIELD_DESCRIPTIONS.len(), FIELD_NAMES.len());
+        assert_eq!(stats.get("HugePages_Total"), None);
+
+        for field in MemField::ALL {
+            assert_eq!(
+                Some(field.value_from(&stats)),
+                stats.get(field.meminfo_name()),
+                "{:?}",
+                field
+            );
+        }
+        assert_eq!(MemField::InactiveFile.meminfo_name(), "Inactive(file)");
+        for (i, field) in MemField::ALL.into_iter().enumerate() {
+            assert_eq!(field as usize, i);
+            assert_eq!(MemField::from_meminfo_name(FIELD_NAMES[i]), Some(field));
+            assert_eq!(MemField::from_rust_name(field.rust_name()), Some(field));
+            let mut filled = MemoryStats::default();
+            *field.value_mut(&mut filled) = 7;
+            assert_eq!(field.value_from(&filled), 7, "{:?}", field);
+            assert_eq!(filled.field_value(field.rust_name()), Some(7));
+        }
+
+        // Rust field names line up with the meminfo names
+        assert_eq!(MemoryStats::field_names().len(), FIELD_NAMES.len());
+        for ((name, value), meminfo_name) in stats.iter_fields().zip(FIELD_NAMES) {
+            assert_eq!(Some(value), stats.get(meminfo_name), "{}", name);
+        }
+        assert_eq!(
+            stats.field_value("inactive_file"),
+            Some(stats.inactive_file)
+        );
+        assert_eq!(stats.field_value("Inactive(file)"), None);
+        // ... and with the serialized keys
+        let json = serde_json::to_value(&stats).unwrap();
+        for (name, value) in stats.iter_fields() {
+            assert_eq!(json[name], value, "{}", name);
         }
     }
+
+    #[test]
+    fn test_proc_meminfo_parser() {
+        let parser = ProcMemInfoParser::new();
+        let content = format!(
+            "{}\nPercpu:            3584 kB\nDirectMap1G:    4194304 kB\nHugePages_Total:       2\nBogus: n/a\n",
+            SAMPLE_MEMINFO
+        );
+        let fields = parser.parse(&content);
+        assert_eq!(fields["Percpu"], 3584);
+        assert_eq!(fields["DirectMap1G"], 4194304);
+        assert_eq!(fields["HugePages_Total"], 2);
+        assert!(!fields.contains_key("Bogus"));
+        assert_eq!(fields.len(), FIELD_NAMES.len() + 3);
+        assert!(parser.try_parse(&content).is_err());
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("meminfo");
+        fs::write(&path, SAMPLE_MEMINFO).unwrap();
+        let fields = parser.parse_file(&path).unwrap();
+        let stats = MemoryStats::parse_meminfo(SAMPLE_MEMINFO).unwrap();
+        assert_eq!(fields["Inactive(file)"], stats.inactive_file);
+        assert!(parser.parse_file(&dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_in_pages() {
+        let stats = MemoryStats::builder()
+            .mem_total(16384000)
+            .inactive_file(6)
+            .build();
+
+        let pages = stats.in_pages(DEFAULT_PAGE_SIZE);
+        assert_eq!(pages.mem_total, 4096000);
+        // 6 kB is one full 4K page and a partial one
+        assert_eq!(pages.inactive_file, 1);
+        assert_eq!(stats.in_pages(65536).mem_total, 256000);
+    }
+
+    #[test]
+    fn test_builder_matches_struct_literal() {
+        let built = MemoryStats::builder()
+            .mem_total(1000)
+            .mem_available(600)
+            .s_unreclaimable(7)
+            .build();
+        let literal = MemoryStats {
+            mem_total: 1000,
+            mem_available: 600,
+            s_unreclaimable: 7,
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(&built).unwrap(),
+            serde_json::to_value(&literal).unwrap()
+        );
+    }
 }
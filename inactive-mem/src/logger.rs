@@ -0,0 +1,203 @@
+use crate::{MemorySnapshot, MemoryStats, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Line format written by `RotatingLogger`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// One JSON-serialized `MemorySnapshot` per line
+    Json,
+    /// `timestamp` followed by every `MemoryStats` field, headed by
+    /// `MemoryStats::field_names`
+    Csv,
+}
+
+impl LogFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "json" | "jsonl" => Some(LogFormat::Json),
+            "csv" => Some(LogFormat::Csv),
+            _ => None,
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            LogFormat::Json => "jsonl",
+            LogFormat::Csv => "csv",
+        }
+    }
+}
+
+/// Appends one line per snapshot to files in a directory, starting a new
+/// file once the current one exceeds `max_bytes` and deleting the oldest
+/// files beyond `max_files`
+#[derive(Debug)]
+pub struct RotatingLogger {
+    dir: PathBuf,
+    format: LogFormat,
+    max_bytes: u64,
+    max_files: usize,
+    current: Option<(File, u64)>,
+}
+
+impl RotatingLogger {
+    pub fn new<P: AsRef<Path>>(
+        dir: P,
+        format: LogFormat,
+        max_bytes: u64,
+        max_files: usize,
+    ) -> Result<Self> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(RotatingLogger {
+            dir: dir.as_ref().to_path_buf(),
+            format,
+            max_bytes,
+            max_files: max_files.max(1),
+            current: None,
+        })
+    }
+
+    /// Append a snapshot, rotating first if the current file is full
+    pub fn log(&mut self, snapshot: &MemorySnapshot) -> Result<()> {
+        if matches!(self.current, Some((_, written)) if written >= self.max_bytes) {
+            self.current = None;
+        }
+        if self.current.is_none() {
+            self.open_new_file(snapshot.timestamp)?;
+        }
+
+        let line = self.format_line(snapshot)?;
+        let (file, written) = self.current.as_mut().expect("log file was just opened");
+        file.write_all(line.as_bytes())?;
+        *written += line.len() as u64;
+        Ok(())
+    }
+
+    /// Log files in the directory written by this format, oldest first
+    pub fn log_files(&self) -> Result<Vec<PathBuf>> {
+        let mut files: Vec<PathBuf> = fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                path.extension().and_then(|e| e.to_str()) == Some(self.format.extension())
+                    && path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|n| n.starts_with("memory-"))
+            })
+            .collect();
+        // Names embed a fixed-width timestamp and suffix, so lexical order is chronological
+        files.sort();
+        Ok(files)
+    }
+
+    fn open_new_file(&mut self, timestamp: u64) -> Result<()> {
+        // Several rotations within the same millisecond get increasing suffixes
+        let mut suffix = 0;
+        let mut path;
+        loop {
+            path = self.dir.join(format!(
+                "memory-{:016}-{:03}.{}",
+                timestamp,
+                suffix,
+                self.format.extension()
+            ));
+            if !path.exists() {
+                break;
+            }
+            suffix += 1;
+        }
+
+        let mut file = OpenOptions::new()
+            .create_new(true)
+            .append(true)
+            .open(&path)?;
+        let mut written = 0;
+        if self.format == LogFormat::Csv {
+            let header = format!("timestamp,{}\n", MemoryStats::field_names().join(","));
+            file.write_all(header.as_bytes())?;
+            written = header.len() as u64;
+        }
+        self.current = Some((file, written));
+        self.prune()
+    }
+
+    fn prune(&self) -> Result<()> {
+        let files = self.log_files()?;
+        if files.len() > self.max_files {
+            for old in &files[..files.len() - self.max_files] {
+                fs::remove_file(old)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn format_line(&self, snapshot: &MemorySnapshot) -> Result<String> {
+        match self.format {
+            LogFormat::Json => Ok(serde_json::to_string(snapshot)? + "\n"),
+            LogFormat::Csv => {
+                let values: Vec<String> = snapshot
+                    .stats
+                    .iter_fields()
+                    .map(|(_, kb)| kb.to_string())
+                    .collect();
+                Ok(format!("{},{}\n", snapshot.timestamp, values.join(",")))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn snapshot(timestamp: u64) -> MemorySnapshot {
+        MemorySnapshot {
+            timestamp,
+            stats: MemoryStats {
+                mem_total: 1000,
+                ..Default::default()
+            },
+            label: None,
+            tags: Vec::new(),
+            vmstat: None,
+        }
+    }
+
+    #[test]
+    fn test_rotates_and_prunes() {
+        let dir = TempDir::new().unwrap();
+        // Every JSON line is larger than 10 bytes, so each log call rotates
+        let mut logger = RotatingLogger::new(dir.path(), LogFormat::Json, 10, 3).unwrap();
+        for timestamp in 1..=5 {
+            logger.log(&snapshot(timestamp)).unwrap();
+        }
+
+        let files = logger.log_files().unwrap();
+        assert_eq!(files.len(), 3);
+        assert!(files[0].to_str().unwrap().contains("0000000000000003"));
+
+        let content = fs::read_to_string(&files[2]).unwrap();
+        let parsed: MemorySnapshot = serde_json::from_str(content.trim()).unwrap();
+        assert_eq!(parsed.timestamp, 5);
+    }
+
+    #[test]
+    fn test_csv_lines_share_one_file_until_full() {
+        let dir = TempDir::new().unwrap();
+        let mut logger = RotatingLogger::new(dir.path(), LogFormat::Csv, 1024 * 1024, 3).unwrap();
+        logger.log(&snapshot(1)).unwrap();
+        logger.log(&snapshot(2)).unwrap();
+
+        let files = logger.log_files().unwrap();
+        assert_eq!(files.len(), 1);
+
+        let content = fs::read_to_string(&files[0]).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("timestamp,mem_total,mem_free,"));
+        assert!(lines[2].starts_with("2,1000,0"));
+    }
+}
@@ -1,7 +1,9 @@
+use crossterm::{cursor, execute, terminal};
 use linux_memory_monitor::*;
+use std::collections::VecDeque;
 use std::env;
 use std::fs::File;
-use std::io::Write;
+use std::io::{self, Write};
 use std::thread;
 use std::time::{Duration, Instant};
 
@@ -11,7 +13,55 @@ fn main() -> Result<()> {
 
     // Parse command line arguments
     let args: Vec<String> = env::args().collect();
-    let (file_size_gb, max_files, target_inactive_gb) = parse_args(&args);
+    let config = parse_args(&args);
+
+    // Sandboxes like gVisor expose a reduced meminfo; say what reads as 0
+    let (stats, missing) = MemoryStats::current_with(&ParseOptions::default())?;
+    if !missing.is_empty() {
+        println!(
+            "⚠️  /proc/meminfo lacks {}; these read as 0\n",
+            missing.join(", ")
+        );
+    }
+    // Warning-severity findings are normal on real kernels (shmem counts as
+    // both anon and Cached), so only errors are flagged as wrong
+    for warning in stats.validate() {
+        match warning.severity {
+            ValidationSeverity::Error => {
+                println!("⚠️  /proc/meminfo looks wrong: {}", warning.message)
+            }
+            ValidationSeverity::Warning => {
+                println!("ℹ️  /proc/meminfo note (advisory): {}", warning.message)
+            }
+        }
+    }
+    let mut logger = match &config.log_dir {
+        Some(dir) => Some(RotatingLogger::new(
+            dir,
+            config.log_format,
+            config.log_rotate_mb.saturating_mul(1024 * 1024),
+            LOG_KEEP_FILES,
+        )?),
+        None => None,
+    };
+    if config.dashboard {
+        return run_dashboard(
+            config.interval,
+            config.interval_jitter,
+            config.unit,
+            logger.as_mut(),
+        );
+    }
+    let Config {
+        file_size_gb,
+        max_files,
+        target_inactive_gb,
+        chunk_mb,
+        sync_every,
+        unit,
+        drop_caches_after,
+        ..
+    } = config;
 
     let mut file_counter = 0;
     let mut created_files = Vec::new();
@@ -20,14 +70,32 @@ fn main() -> Result<()> {
     println!("  File size: {} GB per file", file_size_gb);
     println!("  Max files before cleanup: {}", max_files);
     println!("  Target inactive memory: {} GB", target_inactive_gb);
+    println!(
+        "  Write chunk: {} MB, sync every {} chunks",
+        chunk_mb, sync_every
+    );
     println!("  No pause between files - running at maximum speed!\n");
 
+    // Without drop_caches the only way the cache shrinks is real memory
+    // pressure, which may never come on a large machine
+    let can_drop_caches = MemoryUtils::can_drop_caches();
+    if !can_drop_caches {
+        println!("ℹ️  Not running as root: /proc/sys/vm/drop_caches is not writable.");
+        println!("   Cached file data will only be reclaimed if memory pressure forces it.");
+        if drop_caches_after {
+            println!("   --drop-caches-after will be skipped; rerun with sudo to use it.");
+        }
+        println!();
+    }
+
     // Show initial state
     let initial_stats = MemoryStats::current()?;
     let initial_inactive_gb = initial_stats.inactive_file as f64 / (1024.0 * 1024.0);
-    print_memory_stats("INITIAL STATE", &initial_stats);
+    print_memory_stats("INITIAL STATE", &initial_stats, unit);
 
     let start_time = Instant::now();
+    // Readings taken during the run, for the reclaim efficiency at the end
+    let mut samples = vec![initial_stats.clone()];
 
     loop {
         // Create a large file to generate inactive memory
@@ -35,7 +103,7 @@ fn main() -> Result<()> {
         println!("\n🔄 Creating file: {} ({} GB)", file_path, file_size_gb);
 
         let create_start = Instant::now();
-        match create_large_file(&file_path, file_size_gb) {
+        match create_large_file(&file_path, file_size_gb, chunk_mb, sync_every) {
             Ok(_) => {
                 let create_duration = create_start.elapsed();
                 println!(
@@ -53,7 +121,15 @@ fn main() -> Result<()> {
 
         // Print current memory stats
         let current_stats = MemoryStats::current()?;
-        print_memory_stats(&format!("AFTER FILE #{}", file_counter), &current_stats);
+        if let Some(logger) = logger.as_mut() {
+            logger.log(&MemorySnapshot::from_stats(current_stats.clone()))?;
+        }
+        samples.push(current_stats.clone());
+        print_memory_stats(
+            &format!("AFTER FILE #{}", file_counter),
+            &current_stats,
+            unit,
+        );
 
         // Calculate progress
         let current_inactive_gb = current_stats.inactive_file as f64 / (1024.0 * 1024.0);
@@ -107,7 +183,7 @@ fn main() -> Result<()> {
             // Show memory stats after cleanup
             thread::sleep(Duration::from_millis(500)); // Let kernel react
             let after_cleanup = MemoryStats::current()?;
-            print_memory_stats("AFTER CLEANUP", &after_cleanup);
+            print_memory_stats("AFTER CLEANUP", &after_cleanup, unit);
         }
 
         // Check for memory pressure
@@ -156,6 +232,13 @@ fn main() -> Result<()> {
         "Average file creation time: {:.2} seconds",
         total_runtime.as_secs_f64() / file_counter as f64
     );
+    if let Some(efficiency) = ReclaimEfficiency::from_samples(&samples, &final_stats) {
+        println!("♻️  Kernel {}", efficiency.summary());
+        println!(
+            "   Free memory recovered since the peak: {}",
+            format_memory_change_kb(efficiency.free_recovered)
+        );
+    }
 
     // Cleanup on exit
     println!("\n🧹 Cleaning up all test files...");
@@ -166,10 +249,81 @@ fn main() -> Result<()> {
     }
     println!("✅ Cleanup complete!");
 
+    if drop_caches_after && can_drop_caches {
+        println!("\n🧽 Dropping page cache...");
+        let diff = MemoryUtils::drop_caches_measured(1)?;
+        println!(
+            "  Page cache released: {}",
+            unit.format(diff.cached.min(0).unsigned_abs())
+        );
+        println!(
+            "  Inactive(file) released: {}",
+            unit.format(diff.inactive_file.min(0).unsigned_abs())
+        );
+        println!(
+            "  Free memory gained: {}",
+            unit.format(diff.mem_free.max(0) as u64)
+        );
+        for line in format_diff_table(&diff).lines() {
+            println!("  {}", line);
+        }
+    }
+
     Ok(())
 }
 
-fn parse_args(args: &[String]) -> (usize, usize, usize) {
+/// Command line configuration
+#[derive(Debug)]
+struct Config {
+    file_size_gb: usize,
+    max_files: usize,
+    target_inactive_gb: usize,
+    chunk_mb: usize,
+    sync_every: usize,
+    dashboard: bool,
+    interval: Duration,
+    /// Random ± percent applied to each dashboard interval
+    interval_jitter: f64,
+    log_dir: Option<String>,
+    log_rotate_mb: u64,
+    log_format: LogFormat,
+    unit: Unit,
+    drop_caches_after: bool,
+}
+
+/// Unit used when printing memory amounts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    /// Human-readable sizes (meminfo's native kB, scaled)
+    Kb,
+    /// Pages of this machine's page size, comparable with kpageflags PFN counts
+    Pages,
+}
+
+impl Unit {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "kb" => Some(Unit::Kb),
+            "pages" => Some(Unit::Pages),
+            _ => None,
+        }
+    }
+
+    fn format(self, kb: u64) -> String {
+        match self {
+            Unit::Kb => format_memory_kb(kb),
+            Unit::Pages => format!("{} pages", format_number(MemoryUtils::kb_to_pages(kb))),
+        }
+    }
+}
+
+/// Number of rotated log files kept in --log-dir
+const LOG_KEEP_FILES: usize = 10;
+
+/// Largest --log-rotate accepted, 1 TiB
+const MAX_LOG_ROTATE_MB: u64 = 1024 * 1024;
+
+fn parse_args(args: &[String]) -> Config {
     if args.len() == 1 {
         // No arguments provided, show usage
         print_usage(&args[0]);
@@ -179,6 +333,16 @@ fn parse_args(args: &[String]) -> (usize, usize, usize) {
     let mut file_size_gb = 1;
     let mut max_files = 20;
     let mut target_inactive_gb = 50;
+    let mut chunk_mb = 64; // 64MB chunks for better performance
+    let mut sync_every = 8; // 512MB of dirty data with the default chunk size
+    let mut dashboard = false;
+    let mut interval = Duration::from_secs(1);
+    let mut interval_jitter = 0.0;
+    let mut log_dir = None;
+    let mut log_rotate_mb = 10;
+    let mut log_format = LogFormat::Json;
+    let mut unit = Unit::Kb;
+    let mut drop_caches_after = false;
 
     let mut i = 1;
     while i < args.len() {
@@ -228,6 +392,135 @@ fn parse_args(args: &[String]) -> (usize, usize, usize) {
                     std::process::exit(1);
                 }
             }
+            "--chunk-mb" => {
+                if i + 1 < args.len() {
+                    match args[i + 1].parse::<usize>() {
+                        Ok(mb) if mb > 0 => chunk_mb = mb,
+                        _ => {
+                            eprintln!("Error: Invalid chunk size. Must be a positive integer.");
+                            std::process::exit(1);
+                        }
+                    }
+                    i += 2;
+                } else {
+                    eprintln!("Error: --chunk-mb requires a value");
+                    std::process::exit(1);
+                }
+            }
+            "--sync-every" => {
+                if i + 1 < args.len() {
+                    match args[i + 1].parse::<usize>() {
+                        Ok(chunks) if chunks > 0 => sync_every = chunks,
+                        _ => {
+                            eprintln!("Error: Invalid sync interval. Must be a positive integer.");
+                            std::process::exit(1);
+                        }
+                    }
+                    i += 2;
+                } else {
+                    eprintln!("Error: --sync-every requires a value");
+                    std::process::exit(1);
+                }
+            }
+            "-d" | "--dashboard" => {
+                dashboard = true;
+                i += 1;
+            }
+            "-i" | "--interval" => {
+                if i + 1 < args.len() {
+                    match args[i + 1].parse::<u64>() {
+                        Ok(secs) if secs > 0 => interval = Duration::from_secs(secs),
+                        _ => {
+                            eprintln!("Error: Invalid interval. Must be a positive integer.");
+                            std::process::exit(1);
+                        }
+                    }
+                    i += 2;
+                } else {
+                    eprintln!("Error: --interval requires a value");
+                    std::process::exit(1);
+                }
+            }
+            "--interval-jitter" => {
+                if i + 1 < args.len() {
+                    match args[i + 1].parse::<f64>() {
+                        Ok(percent) if (0.0..=100.0).contains(&percent) => {
+                            interval_jitter = percent
+                        }
+                        _ => {
+                            eprintln!(
+                                "Error: Invalid interval jitter. Must be a percentage from 0 to 100."
+                            );
+                            std::process::exit(1);
+                        }
+                    }
+                    i += 2;
+                } else {
+                    eprintln!("Error: --interval-jitter requires a value");
+                    std::process::exit(1);
+                }
+            }
+            "--log-dir" => {
+                if i + 1 < args.len() {
+                    log_dir = Some(args[i + 1].clone());
+                    i += 2;
+                } else {
+                    eprintln!("Error: --log-dir requires a value");
+                    std::process::exit(1);
+                }
+            }
+            "--log-rotate" => {
+                if i + 1 < args.len() {
+                    match args[i + 1].parse::<u64>() {
+                        Ok(mb) if (1..=MAX_LOG_ROTATE_MB).contains(&mb) => log_rotate_mb = mb,
+                        _ => {
+                            eprintln!(
+                                "Error: Invalid log rotate size. Must be from 1 to {} MB.",
+                                MAX_LOG_ROTATE_MB
+                            );
+                            std::process::exit(1);
+                        }
+                    }
+                    i += 2;
+                } else {
+                    eprintln!("Error: --log-rotate requires a value");
+                    std::process::exit(1);
+                }
+            }
+            "--log-format" => {
+                if i + 1 < args.len() {
+                    match LogFormat::from_name(&args[i + 1]) {
+                        Some(format) => log_format = format,
+                        None => {
+                            eprintln!("Error: Invalid log format. Must be 'json' or 'csv'.");
+                            std::process::exit(1);
+                        }
+                    }
+                    i += 2;
+                } else {
+                    eprintln!("Error: --log-format requires a value");
+                    std::process::exit(1);
+                }
+            }
+            "--unit" => {
+                if i + 1 < args.len() {
+                    match Unit::from_name(&args[i + 1]) {
+                        Some(value) => unit = value,
+                        None => {
+                            eprintln!("Error: Invalid unit. Must be 'kb' or 'pages'.");
+                            std::process::exit(1);
+                        }
+                    }
+                    i += 2;
+                } else {
+                    eprintln!("Error: --unit requires a value");
+                    std::process::exit(1);
+                }
+            }
+            "--drop-caches-after" => {
+                drop_caches_after = true;
+                i += 1;
+            }
             "-h" | "--help" => {
                 print_usage(&args[0]);
                 std::process::exit(0);
@@ -240,7 +533,29 @@ fn parse_args(args: &[String]) -> (usize, usize, usize) {
         }
     }
 
-    (file_size_gb, max_files, target_inactive_gb)
+    if file_and_chunk_bytes(file_size_gb, chunk_mb).is_none() {
+        eprintln!(
+            "Error: Invalid chunk size. Must be at most the file size ({} GB).",
+            file_size_gb
+        );
+        std::process::exit(1);
+    }
+
+    Config {
+        file_size_gb,
+        max_files,
+        target_inactive_gb,
+        chunk_mb,
+        sync_every,
+        dashboard,
+        interval,
+        interval_jitter,
+        log_dir,
+        log_rotate_mb,
+        log_format,
+        unit,
+        drop_caches_after,
+    }
 }
 
 fn print_usage(program_name: &str) {
@@ -255,6 +570,25 @@ fn print_usage(program_name: &str) {
     println!(
         "    -t, --target <GB>    Target amount of new inactive memory to generate in GB (default: 50)"
     );
+    println!(
+        "        --chunk-mb <MB>  Size of each write while creating files, at most the file size (default: 64)"
+    );
+    println!("        --sync-every <N> Sync file data every N chunks (default: 8)");
+    println!("    -d, --dashboard      Show a live in-place dashboard instead of generating files");
+    println!("    -i, --interval <SEC> Dashboard refresh interval in seconds (default: 1)");
+    println!(
+        "        --interval-jitter <PCT> Vary each interval randomly by up to ±PCT percent (default: 0)"
+    );
+    println!("        --log-dir <DIR>  Append a line per interval to rotating log files in DIR");
+    println!("        --log-rotate <MB> Start a new log file after this many MB (default: 10)");
+    println!("        --log-format <FMT> Log line format: json or csv (default: json)");
+    println!(
+        "        --unit <UNIT>    Show amounts in kb or pages ({} KiB pages; default: kb)",
+        MemoryUtils::get_page_size() / 1024
+    );
+    println!(
+        "        --drop-caches-after Drop the page cache at the end and report what it freed (root)"
+    );
     println!("    -h, --help           Show this help message");
     println!();
     println!("EXAMPLES:");
@@ -267,6 +601,9 @@ fn print_usage(program_name: &str) {
     println!("    {} --size 1 --files 50 --target 25", program_name);
     println!("        Create 1GB files, keep max 50 files, target 25GB inactive memory");
     println!();
+    println!("    {} --dashboard --interval 2", program_name);
+    println!("        Watch memory gauges and inactive(file) history, refreshing every 2s");
+    println!();
     println!("DESCRIPTION:");
     println!("    This tool creates large files to generate inactive file memory in Linux,");
     println!("    demonstrating how the kernel manages page cache and memory pressure.");
@@ -274,18 +611,39 @@ fn print_usage(program_name: &str) {
     println!("    file I/O operations on system memory usage.");
 }
 
-fn create_large_file(path: &str, size_gb: usize) -> std::io::Result<()> {
+/// Sizes of a test file and of each write to it, in bytes
+///
+/// None when either overflows `usize` or the chunk is empty or larger than
+/// the file.
+fn file_and_chunk_bytes(size_gb: usize, chunk_mb: usize) -> Option<(usize, usize)> {
+    let total_bytes = size_gb.checked_mul(1024 * 1024 * 1024)?;
+    let chunk_size = chunk_mb.checked_mul(1024 * 1024)?;
+    (chunk_size > 0 && chunk_size <= total_bytes).then_some((total_bytes, chunk_size))
+}
+
+fn create_large_file(
+    path: &str,
+    size_gb: usize,
+    chunk_mb: usize,
+    sync_every: usize,
+) -> std::io::Result<()> {
+    let (total_bytes, chunk_size) = file_and_chunk_bytes(size_gb, chunk_mb).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "chunk size must be nonzero and at most the file size",
+        )
+    })?;
     let mut file = File::create(path)?;
-    let chunk_size = 64 * 1024 * 1024; // 64MB chunks for better performance
     let chunk = vec![0u8; chunk_size];
-    let chunks_per_gb = (1024 * 1024 * 1024) / chunk_size;
-    let total_chunks = size_gb * chunks_per_gb;
+    let total_chunks = total_bytes.div_ceil(chunk_size);
 
     for i in 0..total_chunks {
-        file.write_all(&chunk)?;
+        // The last chunk may be partial when chunk size doesn't divide 1GB
+        let len = chunk_size.min(total_bytes - i * chunk_size);
+        file.write_all(&chunk[..len])?;
 
-        // Sync every 8 chunks (512MB) to avoid too much dirty memory
-        if i % 8 == 7 {
+        // Sync every N chunks to avoid too much dirty memory
+        if (i + 1) % sync_every == 0 {
             file.sync_data()?;
         }
     }
@@ -294,44 +652,118 @@ fn create_large_file(path: &str, size_gb: usize) -> std::io::Result<()> {
     Ok(())
 }
 
-fn print_memory_stats(label: &str, stats: &MemoryStats) {
+/// Number of samples kept for the dashboard sparkline
+const DASHBOARD_HISTORY: usize = 60;
+const GAUGE_WIDTH: usize = 40;
+
+/// Redraw a compact dashboard in place every interval until interrupted
+fn run_dashboard(
+    interval: Duration,
+    jitter_percent: f64,
+    unit: Unit,
+    mut logger: Option<&mut RotatingLogger>,
+) -> Result<()> {
+    let mut history = VecDeque::with_capacity(DASHBOARD_HISTORY);
+    let mut stdout = io::stdout();
+
+    loop {
+        let stats = MemoryStats::current()?;
+        if let Some(logger) = logger.as_mut() {
+            logger.log(&MemorySnapshot::from_stats(stats.clone()))?;
+        }
+        if history.len() == DASHBOARD_HISTORY {
+            history.pop_front();
+        }
+        history.push_back(stats.inactive_file);
+
+        execute!(
+            stdout,
+            cursor::MoveTo(0, 0),
+            terminal::Clear(terminal::ClearType::All)
+        )?;
+        render_dashboard(
+            &mut stdout,
+            &stats,
+            history.make_contiguous(),
+            interval,
+            unit,
+        )?;
+        stdout.flush()?;
+
+        thread::sleep(jittered_interval(interval, jitter_percent));
+    }
+}
+
+fn render_dashboard(
+    out: &mut impl Write,
+    stats: &MemoryStats,
+    inactive_history: &[u64],
+    interval: Duration,
+    unit: Unit,
+) -> io::Result<()> {
+    let pressure = MemoryPressure::from_stats(stats);
+    let used_ratio = stats.memory_utilization() / 100.0;
+
+    writeln!(
+        out,
+        "Linux Memory Monitor - Dashboard (every {}s, Ctrl+C to quit)",
+        interval.as_secs()
+    )?;
+    writeln!(
+        out,
+        "Total: {}   Pressure: {:?}",
+        unit.format(stats.mem_total),
+        pressure.pressure_level
+    )?;
+    writeln!(out)?;
+
+    for (label, ratio, kb) in [
+        ("Used", used_ratio, stats.used_memory()),
+        ("Free", pressure.free_ratio, stats.mem_free),
+        ("Cache", pressure.cache_ratio, stats.page_cache_size()),
+        (
+            "Inactive(file)",
+            pressure.inactive_file_ratio,
+            stats.inactive_file,
+        ),
+    ] {
+        writeln!(
+            out,
+            "  {:<15} {} {:>7}  {}",
+            label,
+            format_gauge(ratio, GAUGE_WIDTH),
+            format_percentage(ratio),
+            unit.format(kb)
+        )?;
+    }
+
+    writeln!(out)?;
+    writeln!(
+        out,
+        "  Inactive(file) history ({} samples):",
+        inactive_history.len()
+    )?;
+    writeln!(out, "  {}", format_sparkline(inactive_history))?;
+    Ok(())
+}
+
+fn print_memory_stats(label: &str, stats: &MemoryStats, unit: Unit) {
+    let options = MemoryTableOptions {
+        unit: match unit {
+            Unit::Kb => TableUnit::Kb,
+            Unit::Pages => TableUnit::Pages(MemoryUtils::get_page_size()),
+        },
+        highlight: Some(MemField::InactiveFile),
+        // Fit the terminal after the two-column indent
+        max_width: terminal::size()
+            .ok()
+            .map(|(columns, _)| (columns as usize).saturating_sub(2)),
+    };
+    let table = format_memory_table_with(stats, &options);
     println!("\n📊 {} - Memory Statistics:", label);
-    println!("  ┌─────────────────────────────────────────────────────────────┐");
-    println!(
-        "  │ Total Memory:      {} │",
-        format!("{:>35}", format_memory_kb(stats.mem_total))
-    );
-    println!(
-        "  │ Free Memory:       {} │",
-        format!("{:>35}", format_memory_kb(stats.mem_free))
-    );
-    println!(
-        "  │ Available Memory:  {} │",
-        format!("{:>35}", format_memory_kb(stats.mem_available))
-    );
-    println!(
-        "  │ Page Cache:        {} │",
-        format!("{:>35}", format_memory_kb(stats.page_cache_size()))
-    );
-    println!("  │ ──────────────────────────────────────────────────────────── │");
-    println!(
-        "  │ 🎯 Inactive(file): {} │",
-        format!("{:>35}", format_memory_kb(stats.inactive_file))
-    );
-    println!(
-        "  │ Active(file):      {} │",
-        format!("{:>35}", format_memory_kb(stats.active_file))
-    );
-    println!("  │ ──────────────────────────────────────────────────────────── │");
-    println!(
-        "  │ Dirty Pages:       {} │",
-        format!("{:>35}", format_memory_kb(stats.dirty))
-    );
-    println!(
-        "  │ Writeback:         {} │",
-        format!("{:>35}", format_memory_kb(stats.writeback))
-    );
-    println!("  └─────────────────────────────────────────────────────────────┘");
+    for line in table.lines() {
+        println!("  {}", line);
+    }
 
     // Calculate and show key ratios
     let inactive_ratio = stats.inactive_file as f64 / stats.mem_total as f64 * 100.0;
@@ -400,9 +832,96 @@ mod tests {
             "100".to_string(),
         ];
 
-        let (size, files, target) = parse_args(&args);
-        assert_eq!(size, 5);
-        assert_eq!(files, 30);
-        assert_eq!(target, 100);
+        let config = parse_args(&args);
+        assert_eq!(config.file_size_gb, 5);
+        assert_eq!(config.max_files, 30);
+        assert_eq!(config.target_inactive_gb, 100);
+        assert_eq!(config.chunk_mb, 64);
+        assert_eq!(config.sync_every, 8);
+        assert!(!config.dashboard);
+        assert_eq!(config.unit, Unit::Kb);
+        assert!(!config.drop_caches_after);
+    }
+
+    #[test]
+    fn test_parse_args_chunking() {
+        let args = vec![
+            "program".to_string(),
+            "--chunk-mb".to_string(),
+            "16".to_string(),
+            "--sync-every".to_string(),
+            "4".to_string(),
+            "--drop-caches-after".to_string(),
+        ];
+
+        let config = parse_args(&args);
+        assert_eq!(config.chunk_mb, 16);
+        assert_eq!(config.sync_every, 4);
+        assert!(config.drop_caches_after);
+    }
+
+    #[test]
+    fn test_file_and_chunk_bytes() {
+        assert_eq!(
+            file_and_chunk_bytes(1, 64),
+            Some((1024 * 1024 * 1024, 64 * 1024 * 1024))
+        );
+        assert_eq!(
+            file_and_chunk_bytes(1, 1024),
+            Some((1024 * 1024 * 1024, 1024 * 1024 * 1024))
+        );
+        assert_eq!(file_and_chunk_bytes(1, 1025), None);
+        assert_eq!(file_and_chunk_bytes(1, 0), None);
+        assert_eq!(file_and_chunk_bytes(1, usize::MAX), None);
+        assert_eq!(file_and_chunk_bytes(usize::MAX, 64), None);
+    }
+
+    #[test]
+    fn test_parse_args_logging() {
+        let args = vec![
+            "program".to_string(),
+            "--log-dir".to_string(),
+            "/tmp/logs".to_string(),
+            "--log-rotate".to_string(),
+            MAX_LOG_ROTATE_MB.to_string(),
+        ];
+
+        let config = parse_args(&args);
+        assert_eq!(config.log_dir.as_deref(), Some("/tmp/logs"));
+        assert_eq!(config.log_rotate_mb, MAX_LOG_ROTATE_MB);
+    }
+
+    #[test]
+    fn test_parse_args_dashboard() {
+        let args = vec![
+            "program".to_string(),
+            "--dashboard".to_string(),
+            "--interval".to_string(),
+            "3".to_string(),
+            "--interval-jitter".to_string(),
+            "10".to_string(),
+        ];
+
+        let config = parse_args(&args);
+        assert!(config.dashboard);
+        assert_eq!(config.interval, Duration::from_secs(3));
+        assert_eq!(config.interval_jitter, 10.0);
+    }
+
+    #[test]
+    fn test_parse_args_unit() {
+        let args = vec![
+            "program".to_string(),
+            "--unit".to_string(),
+            "pages".to_string(),
+        ];
+
+        let config = parse_args(&args);
+        assert_eq!(config.unit, Unit::Pages);
+        let pages = 8192 * 1024 / MemoryUtils::get_page_size();
+        assert_eq!(
+            config.unit.format(8192),
+            format!("{} pages", format_number(pages))
+        );
     }
 }
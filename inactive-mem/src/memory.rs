@@ -1,29 +1,151 @@
-use crate::{MemoryStats, Result};
+use crate::{
+    CgroupMemoryStats, DEFAULT_PAGE_SIZE, MemField, MemoryError, MemoryStats, PsiResource, Result,
+    VmStats, ZoneInfo,
+};
 use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
 
 /// Memory snapshot with timestamp
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemorySnapshot {
     pub timestamp: u64, // Unix timestamp in milliseconds
     pub stats: MemoryStats,
+    /// Free-form description, e.g. "before deploy"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// /proc/vmstat counters at the same moment, when available
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vmstat: Option<SnapshotVmCounters>,
+}
+
+/// The /proc/vmstat counters a `MemorySnapshot` keeps: page I/O for
+/// `CacheChurn` and OOM kills for `EventMonitor`
+///
+/// A full `VmStats` carries every other counter in a map, too much to
+/// store with each of a long history of snapshots.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotVmCounters {
+    pub pgpgin: u64,
+    pub pgpgout: u64,
+    pub oom_kill: u64,
+}
+
+impl From<&VmStats> for SnapshotVmCounters {
+    fn from(vmstat: &VmStats) -> Self {
+        SnapshotVmCounters {
+            pgpgin: vmstat.pgpgin,
+            pgpgout: vmstat.pgpgout,
+            oom_kill: vmstat.oom_kill,
+        }
+    }
 }
 
 impl MemorySnapshot {
     /// Create a new memory snapshot with current time and memory stats
     pub fn new() -> Result<Self> {
-        let stats = MemoryStats::current()?;
+        let mut snapshot = Self::from_stats(MemoryStats::current()?);
+        snapshot.vmstat = VmStats::current()
+            .ok()
+            .as_ref()
+            .map(SnapshotVmCounters::from);
+        Ok(snapshot)
+    }
+
+    /// Wrap already-read stats in a snapshot stamped with the current time
+    pub fn from_stats(stats: MemoryStats) -> Self {
         let timestamp = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_millis() as u64;
 
-        Ok(MemorySnapshot { timestamp, stats })
+        MemorySnapshot {
+            timestamp,
+            stats,
+            label: None,
+            tags: Vec::new(),
+            vmstat: None,
+        }
     }
 
     /// Create a snapshot with a specific timestamp (useful for testing)
     pub fn with_timestamp(timestamp: u64) -> Result<Self> {
         let stats = MemoryStats::current()?;
-        Ok(MemorySnapshot { timestamp, stats })
+        Ok(MemorySnapshot {
+            timestamp,
+            stats,
+            label: None,
+            tags: Vec::new(),
+            vmstat: None,
+        })
+    }
+
+    /// Create a snapshot of the current memory stats with a label attached
+    pub fn labeled(label: impl Into<String>) -> Result<Self> {
+        let mut snapshot = Self::new()?;
+        snapshot.label = Some(label.into());
+        Ok(snapshot)
+    }
+
+    /// Add a tag to the snapshot
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+}
+
+/// meminfo, vmstat and memory PSI read together under one timestamp
+///
+/// The three files are read on parallel threads so a loaded system doesn't
+/// skew one source against another, keeping rates that combine them (e.g.
+/// pgpgin per KB of page cache growth) consistent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemSnapshot {
+    pub timestamp: u64, // Unix timestamp in milliseconds
+    pub stats: MemoryStats,
+    pub vmstat: VmStats,
+    /// /proc/pressure/memory, absent on kernels without PSI
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub psi: Option<PsiResource>,
+}
+
+impl SystemSnapshot {
+    /// Read /proc/meminfo, /proc/vmstat and /proc/pressure/memory in parallel
+    pub fn current() -> Result<Self> {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+
+        let (stats, vmstat, psi) = std::thread::scope(|scope| {
+            let vmstat = scope.spawn(VmStats::current);
+            let psi = scope.spawn(PsiResource::memory);
+            let stats = MemoryStats::current();
+            (
+                stats,
+                vmstat.join().expect("vmstat reader panicked"),
+                psi.join().expect("PSI reader panicked"),
+            )
+        });
+
+        Ok(SystemSnapshot {
+            timestamp,
+            stats: stats?,
+            vmstat: vmstat?,
+            psi: psi.ok(),
+        })
+    }
+
+    /// The meminfo and vmstat parts as a `MemorySnapshot`
+    pub fn to_memory_snapshot(&self) -> MemorySnapshot {
+        MemorySnapshot {
+            timestamp: self.timestamp,
+            stats: self.stats.clone(),
+            label: None,
+            tags: Vec::new(),
+            vmstat: Some(SnapshotVmCounters::from(&self.vmstat)),
+        }
     }
 }
 
@@ -89,6 +211,165 @@ impl MemoryDiff {
     }
 }
 
+/// Signed change of every `MemoryStats` field between two readings (in KB)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryStatsDiff {
+    pub mem_total: i64,
+    pub mem_free: i64,
+    pub mem_available: i64,
+    pub buffers: i64,
+    pub cached: i64,
+    pub swap_cached: i64,
+    pub active: i64,
+    pub inactive: i64,
+    pub active_file: i64,
+    pub inactive_file: i64,
+    pub active_anon: i64,
+    pub inactive_anon: i64,
+    pub dirty: i64,
+    pub writeback: i64,
+    pub mapped: i64,
+    pub shmem: i64,
+    pub slab: i64,
+    pub s_reclaimable: i64,
+    pub s_unreclaimable: i64,
+}
+
+impl MemoryStatsDiff {
+    /// Field-wise `after - before`, clamped to the range of `i64`
+    pub fn between(before: &MemoryStats, after: &MemoryStats) -> Self {
+        let delta = |b: u64, a: u64| {
+            (a as i128 - b as i128).clamp(i64::MIN as i128, i64::MAX as i128) as i64
+        };
+        MemoryStatsDiff {
+            mem_total: delta(before.mem_total, after.mem_total),
+            mem_free: delta(before.mem_free, after.mem_free),
+            mem_available: delta(before.mem_available, after.mem_available),
+            buffers: delta(before.buffers, after.buffers),
+            cached: delta(before.cached, after.cached),
+            swap_cached: delta(before.swap_cached, after.swap_cached),
+            active: delta(before.active, after.active),
+            inactive: delta(before.inactive, after.inactive),
+            active_file: delta(before.active_file, after.active_file),
+            inactive_file: delta(before.inactive_file, after.inactive_file),
+            active_anon: delta(before.active_anon, after.active_anon),
+            inactive_anon: delta(before.inactive_anon, after.inactive_anon),
+            dirty: delta(before.dirty, after.dirty),
+            writeback: delta(before.writeback, after.writeback),
+            mapped: delta(before.mapped, after.mapped),
+            shmem: delta(before.shmem, after.shmem),
+            slab: delta(before.slab, after.slab),
+            s_reclaimable: delta(before.s_reclaimable, after.s_reclaimable),
+            s_unreclaimable: delta(before.s_unreclaimable, after.s_unreclaimable),
+        }
+    }
+
+    /// The change of `field`
+    pub fn get(&self, field: MemField) -> i64 {
+        match field {
+            MemField::MemTotal => self.mem_total,
+            MemField::MemFree => self.mem_free,
+            MemField::MemAvailable => self.mem_available,
+            MemField::Buffers => self.buffers,
+            MemField::Cached => self.cached,
+            MemField::SwapCached => self.swap_cached,
+            MemField::Active => self.active,
+            MemField::Inactive => self.inactive,
+            MemField::ActiveFile => self.active_file,
+            MemField::InactiveFile => self.inactive_file,
+            MemField::ActiveAnon => self.active_anon,
+            MemField::InactiveAnon => self.inactive_anon,
+            MemField::Dirty => self.dirty,
+            MemField::Writeback => self.writeback,
+            MemField::Mapped => self.mapped,
+            MemField::Shmem => self.shmem,
+            MemField::Slab => self.slab,
+            MemField::SReclaimable => self.s_reclaimable,
+            MemField::SUnreclaimable => self.s_unreclaimable,
+        }
+    }
+
+    /// Every change paired with its /proc/meminfo name, in `FIELD_NAMES` order
+    pub fn fields(&self) -> [(&'static str, i64); 19] {
+        MemField::ALL.map(|field| (field.meminfo_name(), self.get(field)))
+    }
+
+    /// The field that moved most in either direction and its change; the
+    /// first in `FIELD_NAMES` order on a tie, `(MemTotal, 0)` if none did
+    pub fn largest_absolute_change(&self) -> (MemField, i64) {
+        MemField::ALL
+            .into_iter()
+            .map(|field| (field, self.get(field)))
+            .rev()
+            .max_by_key(|(_, change)| change.unsigned_abs())
+            .unwrap()
+    }
+}
+
+/// One `Name  +change kB` row per field that changed, or "no change"
+impl std::fmt::Display for MemoryStatsDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rows: Vec<String> = self
+            .fields()
+            .into_iter()
+            .filter(|&(_, change)| change != 0)
+            .map(|(name, change)| {
+                format!(
+                    "{:<16}{:>14} kB",
+                    name,
+                    crate::formatting::format_signed_number(change)
+                )
+            })
+            .collect();
+        if rows.is_empty() {
+            write!(f, "no change")
+        } else {
+            write!(f, "{}", rows.join("\n"))
+        }
+    }
+}
+
+/// Memory state at a reference point, e.g. program start, for asking how
+/// much has changed since
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryBaseline {
+    pub snapshot: MemorySnapshot,
+}
+
+impl MemoryBaseline {
+    /// Take the current state as the baseline
+    pub fn capture() -> Result<Self> {
+        Ok(MemoryBaseline {
+            snapshot: MemorySnapshot::new()?,
+        })
+    }
+
+    /// Change of every field from the baseline to now
+    pub fn delta_now(&self) -> Result<MemoryStatsDiff> {
+        Ok(self.delta_to(&MemoryStats::current()?))
+    }
+
+    /// Change of every field from the baseline to `stats`
+    pub fn delta_to(&self, stats: &MemoryStats) -> MemoryStatsDiff {
+        self.snapshot.stats.diff(stats)
+    }
+
+    /// Milliseconds since the baseline was taken
+    pub fn elapsed_ms(&self) -> u64 {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        now.saturating_sub(self.snapshot.timestamp)
+    }
+
+    /// Take the current state as the new baseline
+    pub fn reset(&mut self) -> Result<()> {
+        self.snapshot = MemorySnapshot::new()?;
+        Ok(())
+    }
+}
+
 /// Memory pressure indicators
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryPressure {
@@ -100,7 +381,7 @@ pub struct MemoryPressure {
     pub pressure_level: PressureLevel,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum PressureLevel {
     Low,      // > 50% available
     Medium,   // 20-50% available
@@ -108,6 +389,37 @@ pub enum PressureLevel {
     Critical, // < 10% available
 }
 
+impl PressureLevel {
+    /// Classify by MemAvailable / MemTotal
+    pub fn from_available_ratio(available_ratio: f64) -> Self {
+        match available_ratio {
+            r if r > 0.5 => PressureLevel::Low,
+            r if r > 0.2 => PressureLevel::Medium,
+            r if r > 0.1 => PressureLevel::High,
+            _ => PressureLevel::Critical,
+        }
+    }
+}
+
+/// Which LRU list reclaim is likely to favor, see `MemoryStats::reclaim_balance`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReclaimBias {
+    File,     // inactive(file) > 2x inactive(anon)
+    Balanced, // within 2x either way
+    Anon,     // inactive(anon) > 2x inactive(file)
+}
+
+impl ReclaimBias {
+    /// Short explanation of what this means for vm.swappiness
+    pub fn description(&self) -> &'static str {
+        match self {
+            ReclaimBias::File => "reclaim favors page cache; swappiness has little effect",
+            ReclaimBias::Balanced => "file and anon reclaim are balanced; swappiness decides",
+            ReclaimBias::Anon => "reclaim must swap anon pages; consider lowering swappiness",
+        }
+    }
+}
+
 impl MemoryPressure {
     /// Calculate memory pressure from current stats
     pub fn from_stats(stats: &MemoryStats) -> Self {
@@ -117,12 +429,7 @@ impl MemoryPressure {
         let dirty_ratio = stats.dirty as f64 / stats.mem_total as f64;
         let inactive_file_ratio = stats.inactive_file as f64 / stats.mem_total as f64;
 
-        let pressure_level = match available_ratio {
-            r if r > 0.5 => PressureLevel::Low,
-            r if r > 0.2 => PressureLevel::Medium,
-            r if r > 0.1 => PressureLevel::High,
-            _ => PressureLevel::Critical,
-        };
+        let pressure_level = PressureLevel::from_available_ratio(available_ratio);
 
         MemoryPressure {
             available_ratio,
@@ -134,17 +441,155 @@ impl MemoryPressure {
         }
     }
 
+    /// Calculate memory pressure, escalating to Critical when any zone's free
+    /// pages are near its min watermark. MemAvailable is a global figure and
+    /// can look healthy while a single zone (e.g. Normal on one node) is
+    /// about to enter direct reclaim or OOM.
+    pub fn with_zones(stats: &MemoryStats, zones: &ZoneInfo) -> Self {
+        let mut pressure = Self::from_stats(stats);
+        if !zones.zones_near_min().is_empty() {
+            pressure.pressure_level = PressureLevel::Critical;
+        }
+        pressure
+    }
+
     /// Get current memory pressure
     pub fn current() -> Result<Self> {
         let stats = MemoryStats::current()?;
         Ok(Self::from_stats(&stats))
     }
+
+    /// Calculate memory pressure of a cgroup relative to its own limit
+    pub fn from_cgroup(cgroup: &CgroupMemoryStats) -> Self {
+        Self::from_stats(&cgroup.to_memory_stats())
+    }
+}
+
+/// Exponentially weighted moving average of MemAvailable / MemTotal
+///
+/// Each update moves the average `alpha` of the way toward the new sample,
+/// so it follows sustained changes faster than a flat moving average while
+/// damping one-off spikes.
+#[derive(Debug, Clone)]
+pub struct PressureEwma {
+    alpha: f64,
+    value: Option<f64>,
+}
+
+impl PressureEwma {
+    /// `alpha` in (0, 1]: higher reacts faster, lower smooths more
+    ///
+    /// Anything outside that range, including NaN, is rejected.
+    pub fn new(alpha: f64) -> Result<Self> {
+        if !(alpha > 0.0 && alpha <= 1.0) {
+            return Err(MemoryError::InvalidArgument(format!(
+                "EWMA alpha must be in (0, 1], got {}",
+                alpha
+            )));
+        }
+        Ok(PressureEwma { alpha, value: None })
+    }
+
+    /// Fold in a new sample and return the smoothed available ratio
+    ///
+    /// The first sample seeds the average. Stats without MemTotal are ignored.
+    pub fn update(&mut self, stats: &MemoryStats) -> f64 {
+        if stats.mem_total > 0 {
+            let sample = stats.mem_available as f64 / stats.mem_total as f64;
+            self.value = Some(match self.value {
+                Some(current) => current + self.alpha * (sample - current),
+                None => sample,
+            });
+        }
+        self.value.unwrap_or(0.0)
+    }
+
+    /// Smoothed available ratio, if any sample has been seen
+    pub fn value(&self) -> Option<f64> {
+        self.value
+    }
+
+    /// Pressure level of the smoothed ratio
+    pub fn level(&self) -> Option<PressureLevel> {
+        self.value.map(PressureLevel::from_available_ratio)
+    }
+}
+
+/// How much of the page cache built up during a pressure run the kernel
+/// gave back by the end of it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReclaimEfficiency {
+    /// Highest Inactive(file) seen during the run (KB)
+    pub peak_inactive_file: u64,
+    pub final_inactive_file: u64,
+    /// Inactive(file) no longer cached at the end, `peak - final` (KB)
+    pub reclaimed: u64,
+    /// MemFree at the end minus MemFree at the peak (KB)
+    pub free_recovered: i64,
+}
+
+impl ReclaimEfficiency {
+    /// Compare the sample with the most Inactive(file) against a final
+    /// post-pressure reading; `None` without samples
+    pub fn from_samples(samples: &[MemoryStats], final_stats: &MemoryStats) -> Option<Self> {
+        let peak = samples.iter().max_by_key(|s| s.inactive_file)?;
+        Some(ReclaimEfficiency {
+            peak_inactive_file: peak.inactive_file,
+            final_inactive_file: final_stats.inactive_file,
+            reclaimed: peak.inactive_file.saturating_sub(final_stats.inactive_file),
+            free_recovered: final_stats.mem_free as i64 - peak.mem_free as i64,
+        })
+    }
+
+    /// Share of the peak Inactive(file) that was reclaimed, in percent
+    pub fn efficiency_percent(&self) -> f64 {
+        if self.peak_inactive_file == 0 {
+            0.0
+        } else {
+            self.reclaimed as f64 / self.peak_inactive_file as f64 * 100.0
+        }
+    }
+
+    /// e.g. "reclaimed 1.5 GB of 6.0 GB cache under pressure (25.0% efficiency)"
+    pub fn summary(&self) -> String {
+        let gb = |kb: u64| kb as f64 / (1024.0 * 1024.0);
+        format!(
+            "reclaimed {:.1} GB of {:.1} GB cache under pressure ({:.1}% efficiency)",
+            gb(self.reclaimed),
+            gb(self.peak_inactive_file),
+            self.efficiency_percent()
+        )
+    }
 }
 
 /// Utility functions for memory operations
 pub struct MemoryUtils;
 
 impl MemoryUtils {
+    /// This machine's page size from sysconf, read once and cached;
+    /// `DEFAULT_PAGE_SIZE` if sysconf fails
+    pub fn get_page_size() -> u64 {
+        static PAGE_SIZE: OnceLock<u64> = OnceLock::new();
+        *PAGE_SIZE.get_or_init(|| {
+            // SAFETY: sysconf has no preconditions
+            let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+            u64::try_from(page_size)
+                .ok()
+                .filter(|&size| size > 0)
+                .unwrap_or(DEFAULT_PAGE_SIZE)
+        })
+    }
+
+    /// Size in KB of `pages` pages of this machine's page size
+    pub fn pages_to_kb(pages: u64) -> u64 {
+        pages.saturating_mul(Self::get_page_size()) / 1024
+    }
+
+    /// Whole pages of this machine's page size in `kb` KB
+    pub fn kb_to_pages(kb: u64) -> u64 {
+        crate::kb_to_pages(kb, Self::get_page_size())
+    }
+
     /// Force a sync to flush dirty pages to disk
     pub fn sync_filesystem() -> std::io::Result<()> {
         std::process::Command::new("sync").status()?;
@@ -166,6 +611,34 @@ impl MemoryUtils {
         std::fs::write("/proc/sys/vm/drop_caches", cache_type.to_string())
     }
 
+    /// Ask the kernel to compact all zones so free memory forms larger
+    /// contiguous blocks, e.g. before allocating huge pages (requires root
+    /// privileges)
+    pub fn compact_memory() -> std::io::Result<()> {
+        std::fs::write("/proc/sys/vm/compact_memory", "1")
+    }
+
+    /// Whether this process may write /proc/sys/vm/drop_caches (normally root only)
+    pub fn can_drop_caches() -> bool {
+        std::fs::OpenOptions::new()
+            .write(true)
+            .open("/proc/sys/vm/drop_caches")
+            .is_ok()
+    }
+
+    /// Sync, drop caches and return how meminfo changed across the drop
+    ///
+    /// Syncing first lets dirty pages be dropped too, so the diff shows
+    /// everything that was reclaimable. `-diff.cached` is the page cache
+    /// released and `diff.mem_free` the memory it returned.
+    pub fn drop_caches_measured(cache_type: u8) -> Result<MemoryStatsDiff> {
+        Self::sync_filesystem()?;
+        let before = MemoryStats::current()?;
+        Self::drop_caches(cache_type)?;
+        let after = MemoryStats::current()?;
+        Ok(MemoryStatsDiff::between(&before, &after))
+    }
+
     /// Get memory info for a specific process
     pub fn process_memory_info(pid: u32) -> std::io::Result<ProcessMemoryInfo> {
         let status_path = format!("/proc/{}/status", pid);
@@ -176,13 +649,13 @@ impl MemoryUtils {
 
         for line in content.lines() {
             if let Some(value_str) = line.strip_prefix("VmRSS:") {
-                if let Some(num_str) = value_str.trim().split_whitespace().next() {
+                if let Some(num_str) = value_str.split_whitespace().next() {
                     vm_rss = num_str.parse().unwrap_or(0);
                 }
-            } else if let Some(value_str) = line.strip_prefix("VmSize:") {
-                if let Some(num_str) = value_str.trim().split_whitespace().next() {
-                    vm_size = num_str.parse().unwrap_or(0);
-                }
+            } else if let Some(value_str) = line.strip_prefix("VmSize:")
+                && let Some(num_str) = value_str.split_whitespace().next()
+            {
+                vm_size = num_str.parse().unwrap_or(0);
             }
         }
 
@@ -199,6 +672,7 @@ pub struct ProcessMemoryInfo {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ZoneStats;
 
     #[test]
     fn test_memory_diff_calculation() {
@@ -210,6 +684,9 @@ mod tests {
                 inactive_file: 300000,
                 ..Default::default()
             },
+            label: None,
+            tags: Vec::new(),
+            vmstat: None,
         };
 
         let after = MemorySnapshot {
@@ -220,6 +697,9 @@ mod tests {
                 inactive_file: 400000,
                 ..Default::default()
             },
+            label: None,
+            tags: Vec::new(),
+            vmstat: None,
         };
 
         let diff = MemoryDiff::between(&before, &after);
@@ -231,16 +711,217 @@ mod tests {
         assert!(!diff.memory_was_freed());
     }
 
+    #[test]
+    fn test_memory_stats_diff() {
+        let before = MemoryStats::builder().mem_free(1000).dirty(300).build();
+        let after = MemoryStats::builder().mem_free(400).dirty(500).build();
+
+        let diff = MemoryStatsDiff::between(&before, &after);
+        assert_eq!(diff.mem_free, -600);
+        assert_eq!(diff.dirty, 200);
+        assert_eq!(diff.cached, 0);
+
+        let names: Vec<&str> = diff.fields().iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, crate::FIELD_NAMES);
+
+        assert_eq!(before.diff(&after), diff);
+        assert_eq!(diff.largest_absolute_change(), (MemField::MemFree, -600));
+        assert_eq!(diff.get(MemField::Dirty), 200);
+        for field in MemField::ALL {
+            let change = field.value_from(&after) as i64 - field.value_from(&before) as i64;
+            assert_eq!(diff.get(field), change, "{:?}", field);
+        }
+        assert_eq!(
+            diff.to_string(),
+            "MemFree                   -600 kB\nDirty                     +200 kB"
+        );
+        let unchanged = before.diff(&before);
+        assert_eq!(unchanged.largest_absolute_change(), (MemField::MemTotal, 0));
+        assert_eq!(unchanged.to_string(), "no change");
+    }
+
+    #[test]
+    fn test_stats_diff_clamps_huge_values() {
+        let small = MemoryStats::builder().mem_free(1).build();
+        let huge = MemoryStats::builder().mem_free(u64::MAX).build();
+
+        assert_eq!(small.diff(&huge).mem_free, i64::MAX);
+        assert_eq!(huge.diff(&small).mem_free, i64::MIN);
+        assert_eq!(huge.diff(&small).largest_absolute_change().1, i64::MIN);
+        assert_eq!(huge.diff(&huge).mem_free, 0);
+    }
+
     #[test]
     fn test_pressure_level_calculation() {
-        let stats = MemoryStats {
-            mem_total: 1000000,
-            mem_available: 600000,
-            ..Default::default()
-        };
+        let stats = MemoryStats::builder()
+            .mem_total(1000000)
+            .mem_available(600000)
+            .build();
 
         let pressure = MemoryPressure::from_stats(&stats);
         assert!(matches!(pressure.pressure_level, PressureLevel::Low));
         assert_eq!(pressure.available_ratio, 0.6);
     }
+
+    #[test]
+    fn test_pressure_ewma_converges_on_step() {
+        let healthy = MemoryStats::builder()
+            .mem_total(1000)
+            .mem_available(800)
+            .build();
+        let squeezed = MemoryStats::builder()
+            .mem_total(1000)
+            .mem_available(0)
+            .build();
+
+        for alpha in [0.0, -0.5, 1.5, f64::NAN] {
+            assert!(matches!(
+                PressureEwma::new(alpha),
+                Err(MemoryError::InvalidArgument(_))
+            ));
+        }
+
+        let mut ewma = PressureEwma::new(0.5).unwrap();
+        assert_eq!(ewma.level(), None);
+        assert_eq!(ewma.update(&healthy), 0.8);
+        assert_eq!(ewma.level(), Some(PressureLevel::Low));
+
+        // Each step closes half of the remaining distance to 0.0
+        let mut expected = 0.8;
+        for _ in 0..4 {
+            expected *= 0.5;
+            assert!((ewma.update(&squeezed) - expected).abs() < 1e-12);
+        }
+        assert_eq!(ewma.level(), Some(PressureLevel::Critical));
+    }
+
+    #[test]
+    fn test_labeled_snapshot_serialization() {
+        let snapshot = MemorySnapshot::labeled("during incident")
+            .unwrap()
+            .with_tag("ticket-42");
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert!(json.contains("\"label\":\"during incident\""));
+        assert!(json.contains("\"tags\":[\"ticket-42\"]"));
+
+        // Unlabeled snapshots keep the original format
+        let plain = serde_json::to_string(&MemorySnapshot::with_timestamp(1).unwrap()).unwrap();
+        assert!(!plain.contains("label"));
+        assert!(!plain.contains("tags"));
+
+        // Captures saved before labels existed still load
+        let old = format!(
+            "{{\"timestamp\":1,\"stats\":{}}}",
+            serde_json::to_string(&MemoryStats::default()).unwrap()
+        );
+        let parsed: MemorySnapshot = serde_json::from_str(&old).unwrap();
+        assert_eq!(parsed.label, None);
+        assert!(parsed.tags.is_empty());
+
+        // As do captures that embedded the full vmstat
+        let vmstat = VmStats {
+            pgpgin: 7,
+            oom_kill: 2,
+            other: [("nr_free_pages".to_string(), 100)].into(),
+            ..Default::default()
+        };
+        let full = format!(
+            "{{\"timestamp\":1,\"stats\":{},\"vmstat\":{}}}",
+            serde_json::to_string(&MemoryStats::default()).unwrap(),
+            serde_json::to_string(&vmstat).unwrap()
+        );
+        let parsed: MemorySnapshot = serde_json::from_str(&full).unwrap();
+        assert_eq!(parsed.vmstat, Some(SnapshotVmCounters::from(&vmstat)));
+    }
+
+    #[test]
+    fn test_system_snapshot_round_trip() {
+        let snapshot = SystemSnapshot::current().unwrap();
+        assert!(snapshot.stats.mem_total > 0);
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let parsed: SystemSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.timestamp, snapshot.timestamp);
+        assert_eq!(parsed.vmstat, snapshot.vmstat);
+        assert_eq!(parsed.psi, snapshot.psi);
+
+        let memory = snapshot.to_memory_snapshot();
+        assert_eq!(
+            memory.vmstat,
+            Some(SnapshotVmCounters::from(&snapshot.vmstat))
+        );
+    }
+
+    #[test]
+    fn test_reclaim_efficiency() {
+        let sample = |inactive_file, mem_free| {
+            MemoryStats::builder()
+                .inactive_file(inactive_file)
+                .mem_free(mem_free)
+                .build()
+        };
+        let samples = [
+            sample(1_048_576, 4_000_000),
+            sample(6_291_456, 500_000),
+            sample(5_242_880, 600_000),
+        ];
+
+        let efficiency =
+            ReclaimEfficiency::from_samples(&samples, &sample(4_718_592, 2_000_000)).unwrap();
+        assert_eq!(efficiency.peak_inactive_file, 6_291_456);
+        assert_eq!(efficiency.reclaimed, 1_572_864);
+        assert_eq!(efficiency.free_recovered, 1_500_000);
+        assert_eq!(efficiency.efficiency_percent(), 25.0);
+        assert_eq!(
+            efficiency.summary(),
+            "reclaimed 1.5 GB of 6.0 GB cache under pressure (25.0% efficiency)"
+        );
+
+        // Cache still growing at the end reclaimed nothing
+        let grown = ReclaimEfficiency::from_samples(&samples, &sample(7_000_000, 0)).unwrap();
+        assert_eq!(grown.reclaimed, 0);
+        assert!(ReclaimEfficiency::from_samples(&[], &samples[0]).is_none());
+    }
+
+    #[test]
+    fn test_pressure_with_zones_escalates() {
+        let stats = MemoryStats::builder()
+            .mem_total(1000)
+            .mem_available(800)
+            .build();
+        let healthy_zone = ZoneStats {
+            free: 5000,
+            min: 100,
+            low: 200,
+            high: 300,
+            managed: 10000,
+            ..Default::default()
+        };
+        let mut zones = ZoneInfo {
+            zones: vec![healthy_zone.clone()],
+        };
+
+        let pressure = MemoryPressure::with_zones(&stats, &zones);
+        assert!(matches!(pressure.pressure_level, PressureLevel::Low));
+
+        zones.zones.push(ZoneStats {
+            free: 120,
+            ..healthy_zone
+        });
+        let pressure = MemoryPressure::with_zones(&stats, &zones);
+        assert!(matches!(pressure.pressure_level, PressureLevel::Critical));
+    }
+
+    #[test]
+    fn test_page_size_conversions() {
+        let page_size = MemoryUtils::get_page_size();
+        assert!(page_size.is_power_of_two() && page_size >= 4096);
+        assert_eq!(MemoryUtils::get_page_size(), page_size);
+
+        let page_kb = page_size / 1024;
+        assert_eq!(MemoryUtils::pages_to_kb(10), 10 * page_kb);
+        assert_eq!(MemoryUtils::kb_to_pages(10 * page_kb), 10);
+        // Partial pages round down
+        assert_eq!(MemoryUtils::kb_to_pages(10 * page_kb + 1), 10);
+    }
 }
@@ -1,26 +1,246 @@
-use crate::{MemorySnapshot, MemoryStats, Result};
+use crate::{
+    CgroupMemSource, FIELD_NAMES, MemField, MemoryError, MemoryPressure, MemorySnapshot,
+    MemorySource, MemoryStats, PressureLevel, ProcMemSource, Result,
+};
+#[cfg(feature = "jitter")]
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
+#[cfg(feature = "async")]
+use tokio::{sync::watch, task::JoinHandle};
+
+/// Snapshots a subscriber's channel holds before new ones are dropped for
+/// it, so a slow consumer never stalls sampling
+pub const SUBSCRIBER_BUFFER: usize = 64;
+
+/// Predicate choosing which snapshots a subscriber receives
+type SnapshotFilter = Arc<dyn Fn(&MemoryStats) -> bool + Send + Sync>;
+
+/// Source of `Subscriber::id`s
+static NEXT_SUBSCRIBER_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A receiver from `ContinuousMonitor::subscribe` or `subscribe_filtered`
+#[derive(Clone)]
+struct Subscriber {
+    /// Identifies the subscriber when removing it after a failed send
+    id: u64,
+    sender: SyncSender<MemorySnapshot>,
+    filter: Option<SnapshotFilter>,
+}
 
 /// Continuous memory monitor with configurable sampling
 pub struct ContinuousMonitor {
     snapshots: Arc<Mutex<VecDeque<MemorySnapshot>>>,
+    subscribers: Arc<Mutex<Vec<Subscriber>>>,
     max_snapshots: usize,
     running: Arc<Mutex<bool>>,
     handle: Option<thread::JoinHandle<()>>,
+    source: Arc<dyn MemorySource>,
+    jitter_percent: f64,
+    /// Nice value for the sampling thread, `None` to inherit
+    nice: Option<i32>,
+    /// CPUs the sampling thread may run on, `None` to inherit
+    cpu_affinity: Option<Vec<usize>>,
+}
+
+/// Set the nice value (-20..=19, higher is lower priority) of the calling
+/// thread only; raising priority above the current one needs CAP_SYS_NICE
+pub fn set_current_thread_nice(nice: i32) -> std::io::Result<()> {
+    // SAFETY: gettid has no preconditions; setpriority only reads its
+    // arguments, and on Linux PRIO_PROCESS with a thread id affects just
+    // that thread
+    let ret = unsafe { libc::setpriority(libc::PRIO_PROCESS, libc::gettid() as libc::id_t, nice) };
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+/// Restrict the calling thread to the given CPUs
+pub fn set_current_thread_affinity(cpus: &[usize]) -> std::io::Result<()> {
+    // SAFETY: cpu_set_t is plain data for which all zeroes is the empty set
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    for &cpu in cpus {
+        if cpu >= libc::CPU_SETSIZE as usize {
+            return Err(std::io::Error::from_raw_os_error(libc::EINVAL));
+        }
+        // SAFETY: cpu is below CPU_SETSIZE, checked above
+        unsafe { libc::CPU_SET(cpu, &mut set) };
+    }
+    // SAFETY: set is a valid cpu_set_t of the size passed; pid 0 means the
+    // calling thread
+    let ret = unsafe { libc::sched_setaffinity(0, std::mem::size_of_val(&set), &set) };
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+/// Randomly lengthen or shorten `interval` by up to `percent` percent
+///
+/// Sampling on a fixed period can alias with a periodic workload and see the
+/// same phase of it every time; jitter decorrelates the two.
+///
+/// A NaN `percent` means no jitter.
+#[cfg(feature = "jitter")]
+pub fn jittered_interval(interval: Duration, percent: f64) -> Duration {
+    let fraction = (percent / 100.0).clamp(0.0, 1.0);
+    if fraction.is_nan() || fraction == 0.0 {
+        return interval;
+    }
+    let factor = 1.0 + rand::thread_rng().gen_range(-fraction..=fraction);
+    interval.mul_f64(factor)
+}
+
+/// Sleep between background samples
+#[cfg(feature = "jitter")]
+fn sample_interval(interval: Duration, jitter_percent: f64) -> Duration {
+    jittered_interval(interval, jitter_percent)
+}
+
+/// Sleep between background samples; without the `jitter` feature there is
+/// no way to set a jitter, so it is always the plain interval
+#[cfg(not(feature = "jitter"))]
+fn sample_interval(interval: Duration, _jitter_percent: f64) -> Duration {
+    interval
 }
 
 impl ContinuousMonitor {
-    /// Create a new continuous monitor
+    /// Create a new continuous monitor reading /proc
     pub fn new(max_snapshots: usize) -> Self {
+        Self::with_source(max_snapshots, Arc::new(ProcMemSource))
+    }
+
+    /// Create a monitor sampling the given source
+    pub fn with_source(max_snapshots: usize, source: Arc<dyn MemorySource>) -> Self {
         ContinuousMonitor {
             snapshots: Arc::new(Mutex::new(VecDeque::with_capacity(max_snapshots))),
+            subscribers: Arc::new(Mutex::new(Vec::new())),
             max_snapshots,
             running: Arc::new(Mutex::new(false)),
             handle: None,
+            source,
+            jitter_percent: 0.0,
+            nice: None,
+            cpu_affinity: None,
+        }
+    }
+
+    /// Run the sampling thread at this nice value (e.g. 19 so the monitor
+    /// never competes with the workload it watches)
+    pub fn with_nice(mut self, nice: i32) -> Self {
+        self.nice = Some(nice);
+        self
+    }
+
+    /// Pin the sampling thread to these CPUs, e.g. a housekeeping core
+    pub fn with_cpu_affinity(mut self, cpus: &[usize]) -> Self {
+        self.cpu_affinity = Some(cpus.to_vec());
+        self
+    }
+
+    /// Vary each sleep between samples randomly by up to ±`percent` percent
+    /// (capped at 100). The default of 0 keeps sampling periodic; negative
+    /// and non-finite values are rejected.
+    #[cfg(feature = "jitter")]
+    pub fn with_jitter(mut self, percent: f64) -> Result<Self> {
+        if !percent.is_finite() || percent < 0.0 {
+            return Err(MemoryError::InvalidArgument(format!(
+                "jitter must be a finite, non-negative percentage, got {}",
+                percent
+            )));
+        }
+        self.jitter_percent = percent.min(100.0);
+        Ok(self)
+    }
+
+    /// Take one snapshot now, outside of the background sampling
+    pub fn sample(&self) -> Result<()> {
+        let snapshot = self.source.snapshot()?;
+        Self::publish(&self.subscribers, &snapshot);
+        Self::push_snapshot(&self.snapshots, self.max_snapshots, snapshot);
+        Ok(())
+    }
+
+    /// Receive every snapshot collected from now on
+    ///
+    /// Each call returns an independent receiver. Snapshots are dropped for
+    /// a receiver while it has `SUBSCRIBER_BUFFER` unread, and dropping the
+    /// receiver unsubscribes it.
+    pub fn subscribe(&self) -> Receiver<MemorySnapshot> {
+        self.add_subscriber(None)
+    }
+
+    /// Like `subscribe`, but only receive snapshots whose stats pass `filter`
+    pub fn subscribe_filtered<F>(&self, filter: F) -> Receiver<MemorySnapshot>
+    where
+        F: Fn(&MemoryStats) -> bool + Send + Sync + 'static,
+    {
+        self.add_subscriber(Some(Arc::new(filter)))
+    }
+
+    fn add_subscriber(&self, filter: Option<SnapshotFilter>) -> Receiver<MemorySnapshot> {
+        let (sender, receiver) = mpsc::sync_channel(SUBSCRIBER_BUFFER);
+        self.subscribers.lock().unwrap().push(Subscriber {
+            id: NEXT_SUBSCRIBER_ID.fetch_add(1, Ordering::Relaxed),
+            sender,
+            filter,
+        });
+        receiver
+    }
+
+    /// Send a snapshot to each subscriber whose filter it passes, dropping
+    /// subscribers whose receiver is gone
+    ///
+    /// Filters run on a copy of the list, outside the lock, so a filter may
+    /// itself subscribe.
+    fn publish(subscribers: &Mutex<Vec<Subscriber>>, snapshot: &MemorySnapshot) {
+        let current = subscribers.lock().unwrap().clone();
+        let disconnected: Vec<u64> = current
+            .into_iter()
+            .filter(|subscriber| {
+                subscriber
+                    .filter
+                    .as_ref()
+                    .is_none_or(|filter| filter(&snapshot.stats))
+            })
+            .filter(|subscriber| {
+                matches!(
+                    subscriber.sender.try_send(snapshot.clone()),
+                    Err(TrySendError::Disconnected(_))
+                )
+            })
+            .map(|subscriber| subscriber.id)
+            .collect();
+        if !disconnected.is_empty() {
+            subscribers
+                .lock()
+                .unwrap()
+                .retain(|subscriber| !disconnected.contains(&subscriber.id));
+        }
+    }
+
+    fn push_snapshot(
+        snapshots: &Mutex<VecDeque<MemorySnapshot>>,
+        max_snapshots: usize,
+        snapshot: MemorySnapshot,
+    ) {
+        let mut snapshots_guard = snapshots.lock().unwrap();
+
+        // Add new snapshot
+        snapshots_guard.push_back(snapshot);
+
+        // Remove old snapshots if we exceed the limit
+        while snapshots_guard.len() > max_snapshots {
+            snapshots_guard.pop_front();
         }
     }
 
@@ -33,24 +253,40 @@ impl ContinuousMonitor {
         *running = true;
 
         let snapshots = Arc::clone(&self.snapshots);
+        let subscribers = Arc::clone(&self.subscribers);
         let running_flag = Arc::clone(&self.running);
         let max_snapshots = self.max_snapshots;
+        let source = Arc::clone(&self.source);
+        let jitter_percent = self.jitter_percent;
+        let nice = self.nice;
+        let cpu_affinity = self.cpu_affinity.clone();
 
         let handle = thread::spawn(move || {
-            while *running_flag.lock().unwrap() {
-                if let Ok(snapshot) = MemorySnapshot::new() {
-                    let mut snapshots_guard = snapshots.lock().unwrap();
-
-                    // Add new snapshot
-                    snapshots_guard.push_back(snapshot);
+            // Monitoring still works at default scheduling, so only warn
+            if let Some(nice) = nice
+                && let Err(e) = set_current_thread_nice(nice)
+            {
+                eprintln!(
+                    "Warning: could not set monitor thread nice to {}: {}",
+                    nice, e
+                );
+            }
+            if let Some(cpus) = &cpu_affinity
+                && let Err(e) = set_current_thread_affinity(cpus)
+            {
+                eprintln!(
+                    "Warning: could not pin monitor thread to CPUs {:?}: {}",
+                    cpus, e
+                );
+            }
 
-                    // Remove old snapshots if we exceed the limit
-                    while snapshots_guard.len() > max_snapshots {
-                        snapshots_guard.pop_front();
-                    }
+            while *running_flag.lock().unwrap() {
+                if let Ok(snapshot) = source.snapshot() {
+                    Self::publish(&subscribers, &snapshot);
+                    Self::push_snapshot(&snapshots, max_snapshots, snapshot);
                 }
 
-                thread::sleep(interval);
+                thread::sleep(sample_interval(interval, jitter_percent));
             }
         });
 
@@ -58,6 +294,41 @@ impl ContinuousMonitor {
         Ok(())
     }
 
+    /// Sample memory on a tokio interval for async consumers
+    ///
+    /// Must be called from within a tokio runtime. The receiver always holds
+    /// the latest snapshot; await `changed()` for the next one. The task ends
+    /// once every receiver has been dropped.
+    #[cfg(feature = "async")]
+    pub fn spawn_async(
+        interval: Duration,
+    ) -> Result<(JoinHandle<()>, watch::Receiver<MemorySnapshot>)> {
+        let (tx, rx) = watch::channel(MemorySnapshot::new()?);
+
+        let handle = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            // The first tick completes immediately and the channel already
+            // holds the initial snapshot
+            ticker.tick().await;
+
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {}
+                    _ = tx.closed() => break,
+                }
+
+                if let Ok(snapshot) = MemorySnapshot::new()
+                    && tx.send(snapshot).is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        Ok((handle, rx))
+    }
+
     /// Stop monitoring
     pub fn stop(&mut self) {
         {
@@ -87,10 +358,20 @@ impl ContinuousMonitor {
             return None;
         }
 
-        let recent: Vec<_> = snapshots.iter().rev().take(window_size).cloned().collect();
+        // Oldest first, so changes run forward in time
+        let skip = snapshots.len() - window_size;
+        let recent: Vec<_> = snapshots.iter().skip(skip).cloned().collect();
         Some(TrendAnalysis::from_snapshots(&recent))
     }
 
+    /// Page cache churn over the most recent `window_size` snapshots
+    pub fn get_cache_churn(&self, window_size: usize) -> Option<CacheChurn> {
+        let snapshots = self.snapshots.lock().unwrap();
+        let skip = snapshots.len().saturating_sub(window_size);
+        let recent: Vec<_> = snapshots.iter().skip(skip).cloned().collect();
+        CacheChurn::from_snapshots(&recent)
+    }
+
     /// Clear all stored snapshots
     pub fn clear(&self) {
         self.snapshots.lock().unwrap().clear();
@@ -103,6 +384,53 @@ impl Drop for ContinuousMonitor {
     }
 }
 
+/// Gross page cache turnover, which a flat net cache size can hide
+///
+/// Insertions are estimated from vmstat pgpgin (reads) plus pgpgout (writes
+/// that passed through the cache); evictions are whatever was inserted but
+/// didn't show up as net cache growth. Rates are in KB per second.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheChurn {
+    pub duration_ms: u64,
+    pub read_in_kb_per_sec: f64,
+    pub written_out_kb_per_sec: f64,
+    pub insertion_kb_per_sec: f64,
+    pub eviction_kb_per_sec: f64,
+    /// Net change in Cached + Buffers over the window
+    pub net_cache_change_kb: i64,
+}
+
+impl CacheChurn {
+    /// Estimate churn between the first and last snapshots (oldest first)
+    /// Returns None unless both carry vmstat counters and time has passed
+    pub fn from_snapshots(snapshots: &[MemorySnapshot]) -> Option<Self> {
+        let first = snapshots.first()?;
+        let last = snapshots.last()?;
+        let (before, after) = (first.vmstat.as_ref()?, last.vmstat.as_ref()?);
+        let duration_ms = last.timestamp.saturating_sub(first.timestamp);
+        if duration_ms == 0 {
+            return None;
+        }
+
+        let read_in = after.pgpgin.saturating_sub(before.pgpgin);
+        let written_out = after.pgpgout.saturating_sub(before.pgpgout);
+        let inserted = read_in + written_out;
+        let net_cache_change_kb =
+            last.stats.page_cache_size() as i64 - first.stats.page_cache_size() as i64;
+        let evicted = (inserted as i64 - net_cache_change_kb).max(0) as u64;
+
+        let secs = duration_ms as f64 / 1000.0;
+        Some(CacheChurn {
+            duration_ms,
+            read_in_kb_per_sec: read_in as f64 / secs,
+            written_out_kb_per_sec: written_out as f64 / secs,
+            insertion_kb_per_sec: inserted as f64 / secs,
+            eviction_kb_per_sec: evicted as f64 / secs,
+            net_cache_change_kb,
+        })
+    }
+}
+
 /// Memory trend analysis over a time window
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrendAnalysis {
@@ -111,6 +439,35 @@ pub struct TrendAnalysis {
     pub memory_trends: MemoryTrends,
     pub cache_trends: CacheTrends,
     pub pressure_changes: Vec<f64>, // Available memory ratio over time
+    /// When each `pressure_changes` sample was taken, in ms since the first
+    #[serde(default)]
+    pub pressure_timestamps: Vec<u64>,
+}
+
+/// Available memory ratio at which `TrendForecast` reports a critical
+/// crossing, the `PressureLevel::Critical` threshold
+const FORECAST_CRITICAL_RATIO: f64 = 0.1;
+
+/// Two-sided 95% Student's t critical values for 1 to 30 degrees of freedom
+const T_95: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+    2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+    2.052, 2.048, 2.045, 2.042,
+];
+
+/// Where the available memory ratio is heading, from a straight line fit
+/// through `TrendAnalysis::pressure_changes`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrendForecast {
+    /// Available memory ratio expected at the horizon, within 0..=1
+    pub predicted_available_ratio: f64,
+    /// 95% confidence interval of the fitted line at the horizon
+    pub confidence_interval: (f64, f64),
+    /// How well the samples fit the line; 0 without enough samples to fit
+    pub r_squared: f64,
+    /// Time from the last sample until the line falls below 10% available,
+    /// 0 if it already has; `None` if it never does
+    pub will_reach_critical_in_ms: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -138,14 +495,65 @@ pub struct Trend {
     pub volatility: f64, // Standard deviation of changes
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TrendDirection {
     Increasing,
     Decreasing,
     Stable,
 }
 
+/// Fewest samples `TrendAnalysis::is_memory_leak_likely` will judge
+const LEAK_MIN_SAMPLES: usize = 10;
+
+/// Largest used-memory volatility, as a fraction of its total change, for a
+/// rise to count as steady
+const LEAK_MAX_VOLATILITY: f64 = 0.05;
+
+/// The conditions `TrendAnalysis::is_memory_leak_likely` checks, to explain
+/// its answer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryLeakIndicators {
+    pub used_trend: TrendDirection,
+    pub available_trend: TrendDirection,
+    pub cache_trend: TrendDirection,
+    /// Used memory rose steadily: volatility under 5% of its change
+    pub volatility_low: bool,
+    /// At least 10 samples
+    pub sufficient_samples: bool,
+}
+
+impl MemoryLeakIndicators {
+    /// Used memory rising steadily while available memory falls, and not
+    /// because the page cache is filling
+    pub fn leak_likely(&self) -> bool {
+        self.sufficient_samples
+            && self.used_trend == TrendDirection::Increasing
+            && self.volatility_low
+            && self.available_trend == TrendDirection::Decreasing
+            && self.cache_trend != TrendDirection::Increasing
+    }
+}
+
 impl TrendAnalysis {
+    /// Quick yes/no leak check from the trends alone; see
+    /// `memory_leak_indicators` for why, and `LeakHeuristic` for a
+    /// regression-based check with growth rates
+    pub fn is_memory_leak_likely(&self) -> bool {
+        self.memory_leak_indicators().leak_likely()
+    }
+
+    pub fn memory_leak_indicators(&self) -> MemoryLeakIndicators {
+        let used = &self.memory_trends.used_memory_trend;
+        MemoryLeakIndicators {
+            used_trend: used.direction,
+            available_trend: self.memory_trends.available_memory_trend.direction,
+            cache_trend: self.cache_trends.page_cache_trend.direction,
+            volatility_low: used.volatility
+                < used.change.unsigned_abs() as f64 * LEAK_MAX_VOLATILITY,
+            sufficient_samples: self.sample_count >= LEAK_MIN_SAMPLES,
+        }
+    }
+
     fn from_snapshots(snapshots: &[MemorySnapshot]) -> Self {
         if snapshots.is_empty() {
             return Self::default();
@@ -185,6 +593,10 @@ impl TrendAnalysis {
             .iter()
             .map(|s| s.stats.mem_available as f64 / s.stats.mem_total as f64)
             .collect();
+        let pressure_timestamps: Vec<u64> = snapshots
+            .iter()
+            .map(|s| s.timestamp.saturating_sub(first.timestamp))
+            .collect();
 
         TrendAnalysis {
             duration_ms,
@@ -192,6 +604,75 @@ impl TrendAnalysis {
             memory_trends,
             cache_trends,
             pressure_changes,
+            pressure_timestamps,
+        }
+    }
+
+    /// Extrapolate the available memory ratio `horizon_ms` past the last
+    /// sample with a least-squares line through `pressure_changes`
+    ///
+    /// The confidence interval comes from the residual variance, so it is
+    /// zero-width for a perfect fit and for two samples. With fewer than two
+    /// samples (or all at the same time) the last ratio is carried forward.
+    pub fn forecast(&self, horizon_ms: u64) -> TrendForecast {
+        let ratios = &self.pressure_changes;
+        // Analyses saved before timestamps were kept: assume even spacing
+        let times: Vec<f64> = if self.pressure_timestamps.len() == ratios.len() {
+            self.pressure_timestamps.iter().map(|&t| t as f64).collect()
+        } else {
+            let step = self.duration_ms as f64 / ratios.len().saturating_sub(1).max(1) as f64;
+            (0..ratios.len()).map(|i| i as f64 * step).collect()
+        };
+        let points: Vec<(f64, f64)> = times.iter().copied().zip(ratios.iter().copied()).collect();
+
+        let last_ratio = ratios.last().copied().unwrap_or(1.0).clamp(0.0, 1.0);
+        let Some(fit) = LinearFit::fit(&points) else {
+            return TrendForecast {
+                predicted_available_ratio: last_ratio,
+                confidence_interval: (last_ratio, last_ratio),
+                r_squared: 0.0,
+                will_reach_critical_in_ms: (last_ratio < FORECAST_CRITICAL_RATIO).then_some(0),
+            };
+        };
+
+        let last_x = times.iter().copied().fold(f64::MIN, f64::max);
+        let x = last_x + horizon_ms as f64;
+        let predicted = fit.intercept + fit.slope * x;
+
+        // Standard error of the line at x, scaled by the t quantile
+        let n = points.len();
+        let mean_x = times.iter().sum::<f64>() / n as f64;
+        let sxx: f64 = times.iter().map(|t| (t - mean_x).powi(2)).sum();
+        let sse: f64 = points
+            .iter()
+            .map(|&(t, y)| (y - (fit.intercept + fit.slope * t)).powi(2))
+            .sum();
+        let margin = if n > 2 {
+            let residual_sd = (sse / (n - 2) as f64).sqrt();
+            let t = T_95.get(n - 3).copied().unwrap_or(1.96);
+            t * residual_sd * (1.0 / n as f64 + (x - mean_x).powi(2) / sxx).sqrt()
+        } else {
+            0.0
+        };
+
+        let now = fit.intercept + fit.slope * last_x;
+        let will_reach_critical_in_ms = if now < FORECAST_CRITICAL_RATIO {
+            Some(0)
+        } else if fit.slope < 0.0 {
+            let crossing = (FORECAST_CRITICAL_RATIO - fit.intercept) / fit.slope;
+            Some((crossing - last_x).max(0.0).round() as u64)
+        } else {
+            None
+        };
+
+        TrendForecast {
+            predicted_available_ratio: predicted.clamp(0.0, 1.0),
+            confidence_interval: (
+                (predicted - margin).clamp(0.0, 1.0),
+                (predicted + margin).clamp(0.0, 1.0),
+            ),
+            r_squared: fit.r_squared,
+            will_reach_critical_in_ms,
         }
     }
 
@@ -260,6 +741,7 @@ impl Default for TrendAnalysis {
                 dirty_pages_trend: Trend::default(),
             },
             pressure_changes: Vec::new(),
+            pressure_timestamps: Vec::new(),
         }
     }
 }
@@ -277,15 +759,330 @@ impl Default for Trend {
     }
 }
 
+/// Descriptive statistics of one meminfo field over a snapshot series (KB)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FieldSummary {
+    /// /proc/meminfo name, e.g. "Inactive(file)"
+    pub field: String,
+    pub min: u64,
+    pub max: u64,
+    pub mean: f64,
+    pub first: u64,
+    pub last: u64,
+    /// `last - first`
+    pub delta: i64,
+}
+
+/// Min/max/mean/first/last/delta of every meminfo field over a series
+///
+/// The end-of-run counterpart to `TrendAnalysis`, which looks at direction
+/// and volatility instead.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SeriesSummary {
+    pub sample_count: usize,
+    pub duration_ms: u64,
+    /// One entry per field, in `FIELD_NAMES` order
+    pub fields: Vec<FieldSummary>,
+}
+
+impl SeriesSummary {
+    /// Summarize snapshots given oldest first; empty input has no fields
+    pub fn from_snapshots(snapshots: &[MemorySnapshot]) -> Self {
+        let (Some(first), Some(last)) = (snapshots.first(), snapshots.last()) else {
+            return Self::default();
+        };
+
+        let fields = FIELD_NAMES
+            .iter()
+            .map(|&name| {
+                let values: Vec<u64> = snapshots
+                    .iter()
+                    .map(|s| s.stats.get(name).unwrap_or_default())
+                    .collect();
+                let first = values[0];
+                let last = values[values.len() - 1];
+                FieldSummary {
+                    field: name.to_string(),
+                    min: values.iter().copied().min().unwrap_or_default(),
+                    max: values.iter().copied().max().unwrap_or_default(),
+                    mean: values.iter().map(|&v| v as f64).sum::<f64>() / values.len() as f64,
+                    first,
+                    last,
+                    delta: last as i64 - first as i64,
+                }
+            })
+            .collect();
+
+        SeriesSummary {
+            sample_count: snapshots.len(),
+            duration_ms: last.timestamp.saturating_sub(first.timestamp),
+            fields,
+        }
+    }
+
+    /// Summary of a field by its /proc/meminfo name
+    pub fn field(&self, name: &str) -> Option<&FieldSummary> {
+        self.fields.iter().find(|f| f.field == name)
+    }
+}
+
+impl std::fmt::Display for SeriesSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} samples over {:.1}s (values in KB)",
+            self.sample_count,
+            self.duration_ms as f64 / 1000.0
+        )?;
+        writeln!(
+            f,
+            "{:<16} {:>12} {:>12} {:>14} {:>12} {:>12} {:>12}",
+            "Field", "Min", "Max", "Mean", "First", "Last", "Delta"
+        )?;
+        for field in &self.fields {
+            writeln!(
+                f,
+                "{:<16} {:>12} {:>12} {:>14.1} {:>12} {:>12} {:>+12}",
+                field.field, field.min, field.max, field.mean, field.first, field.last, field.delta
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Least-squares line through `(x, y)` points
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LinearFit {
+    pub slope: f64,
+    pub intercept: f64,
+    /// Coefficient of determination, 1.0 for a perfect fit; a flat series
+    /// is fit perfectly
+    pub r_squared: f64,
+}
+
+impl LinearFit {
+    /// Fit a line; `None` with fewer than two points or all x equal
+    pub fn fit(points: &[(f64, f64)]) -> Option<Self> {
+        if points.len() < 2 {
+            return None;
+        }
+
+        let n = points.len() as f64;
+        let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let sxx: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+        let sxy: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+        let syy: f64 = points.iter().map(|p| (p.1 - mean_y).powi(2)).sum();
+        if sxx == 0.0 {
+            return None;
+        }
+
+        let slope = sxy / sxx;
+        let r_squared = if syy == 0.0 {
+            1.0
+        } else {
+            (sxy * sxy) / (sxx * syy)
+        };
+        Some(LinearFit {
+            slope,
+            intercept: mean_y - slope * mean_x,
+            r_squared,
+        })
+    }
+
+    /// Fit a meminfo value (KB) against snapshot time (ms since the
+    /// earliest); the snapshots may be in any order
+    pub fn over_time(
+        snapshots: &[MemorySnapshot],
+        value: impl Fn(&MemoryStats) -> u64,
+    ) -> Option<Self> {
+        let start = snapshots.iter().map(|s| s.timestamp).min()?;
+        let points: Vec<(f64, f64)> = snapshots
+            .iter()
+            .map(|s| ((s.timestamp - start) as f64, value(&s.stats) as f64))
+            .collect();
+        Self::fit(&points)
+    }
+}
+
+/// KB per millisecond to MB per hour
+fn kb_per_ms_to_mb_per_hour(slope: f64) -> f64 {
+    slope * 3_600_000.0 / 1024.0
+}
+
+/// Flags a suspected leak: anonymous memory growing steadily while the page
+/// cache stays flat and available memory shrinks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeakHeuristic {
+    /// Fewer samples than this are never flagged
+    pub min_samples: usize,
+    /// Nor are series shorter than this
+    pub min_duration_ms: u64,
+    /// How well anon growth must fit a straight line
+    pub min_r_squared: f64,
+    /// Cache growth faster than this fraction of anon growth means the
+    /// workload is growing as a whole rather than leaking
+    pub max_cache_growth_ratio: f64,
+}
+
+impl Default for LeakHeuristic {
+    fn default() -> Self {
+        Self {
+            min_samples: 10,
+            min_duration_ms: 60_000,
+            min_r_squared: 0.8,
+            max_cache_growth_ratio: 0.5,
+        }
+    }
+}
+
+/// Outcome of `LeakHeuristic::analyze`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LeakReport {
+    pub suspected: bool,
+    /// Growth of Active(anon) + Inactive(anon)
+    pub anon_growth_mb_per_hour: f64,
+    /// R² of the anon fit
+    pub r_squared: f64,
+    pub cache_growth_mb_per_hour: f64,
+    pub available_change_mb_per_hour: f64,
+    /// Time until MemAvailable runs out at the anon growth rate, if it grows
+    pub hours_to_exhaustion: Option<f64>,
+}
+
+impl LeakHeuristic {
+    /// Analyze snapshots given oldest first; `None` when there are too few
+    /// samples or too short a window to judge
+    pub fn analyze(&self, snapshots: &[MemorySnapshot]) -> Option<LeakReport> {
+        let (first, last) = (snapshots.first()?, snapshots.last()?);
+        if snapshots.len() < self.min_samples.max(2)
+            || last.timestamp.saturating_sub(first.timestamp) < self.min_duration_ms
+        {
+            return None;
+        }
+
+        let anon = LinearFit::over_time(snapshots, MemoryStats::anon_memory)?;
+        let cache = LinearFit::over_time(snapshots, MemoryStats::page_cache_size)?;
+        let available = LinearFit::over_time(snapshots, |s| s.mem_available)?;
+
+        let suspected = anon.slope > 0.0
+            && anon.r_squared >= self.min_r_squared
+            && cache.slope <= anon.slope * self.max_cache_growth_ratio
+            && available.slope < 0.0;
+        let hours_to_exhaustion =
+            (anon.slope > 0.0).then(|| last.stats.mem_available as f64 / anon.slope / 3_600_000.0);
+
+        Some(LeakReport {
+            suspected,
+            anon_growth_mb_per_hour: kb_per_ms_to_mb_per_hour(anon.slope),
+            r_squared: anon.r_squared,
+            cache_growth_mb_per_hour: kb_per_ms_to_mb_per_hour(cache.slope),
+            available_change_mb_per_hour: kb_per_ms_to_mb_per_hour(available.slope),
+            hours_to_exhaustion,
+        })
+    }
+}
+
+impl std::fmt::Display for LeakReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: anon {:+.1} MB/h (R² {:.2}), cache {:+.1} MB/h, available {:+.1} MB/h",
+            if self.suspected {
+                "Suspected leak"
+            } else {
+                "No leak"
+            },
+            self.anon_growth_mb_per_hour,
+            self.r_squared,
+            self.cache_growth_mb_per_hour,
+            self.available_change_mb_per_hour
+        )?;
+        if self.suspected
+            && let Some(hours) = self.hours_to_exhaustion
+        {
+            write!(f, ", memory exhausted in ~{:.1}h", hours)?;
+        }
+        Ok(())
+    }
+}
+
+/// Threshold alerts for `EventMonitor::from_config`, each a fraction of
+/// MemTotal (SwapTotal for `min_swap_free_ratio`); `None` disables it
+///
+/// Missing keys deserialize as `None`, so a TOML config only needs the
+/// thresholds it sets:
+///
+/// ```toml
+/// min_available_ratio = 0.1
+/// max_dirty_ratio = 0.05
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThresholdConfig {
+    /// Alert when MemAvailable drops below this ratio
+    pub min_available_ratio: Option<f64>,
+    /// Alert when Dirty rises above this ratio
+    pub max_dirty_ratio: Option<f64>,
+    /// Alert when the page cache (Cached + Buffers) rises above this ratio
+    pub max_cache_ratio: Option<f64>,
+    /// Alert when Inactive(file) rises above this ratio
+    pub max_inactive_file_ratio: Option<f64>,
+    /// Alert when SwapFree drops below this ratio of SwapTotal, as reported
+    /// by the monitor's source; never fires without swap
+    pub min_swap_free_ratio: Option<f64>,
+    /// Alert when Slab rises above this ratio
+    pub max_slab_ratio: Option<f64>,
+}
+
+impl ThresholdConfig {
+    /// Check every set threshold is a ratio between 0 and 1
+    pub fn validate(&self) -> Result<()> {
+        let thresholds = [
+            ("min_available_ratio", self.min_available_ratio),
+            ("max_dirty_ratio", self.max_dirty_ratio),
+            ("max_cache_ratio", self.max_cache_ratio),
+            ("max_inactive_file_ratio", self.max_inactive_file_ratio),
+            ("min_swap_free_ratio", self.min_swap_free_ratio),
+            ("max_slab_ratio", self.max_slab_ratio),
+        ];
+        for (name, value) in thresholds {
+            if let Some(ratio) = value.filter(|ratio| !(0.0..=1.0).contains(ratio)) {
+                return Err(MemoryError::InvalidArgument(format!(
+                    "{} must be between 0 and 1, got {}",
+                    name, ratio
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `value / total`, 0 when `total` is 0
+fn ratio_of(value: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        value as f64 / total as f64
+    }
+}
+
 /// Event-based monitoring for specific memory conditions
 pub struct EventMonitor {
     conditions: Vec<MemoryCondition>,
+    rate_conditions: Vec<RateCondition>,
+    /// Event name reported when the OOM killer ran since the last check
+    oom_kill_event: Option<String>,
     last_snapshot: Option<MemorySnapshot>,
+    source: Arc<dyn MemorySource>,
 }
 
+/// Predicate evaluated against the current and previous memory stats
+pub type ConditionFn = Box<dyn Fn(&MemoryStats, Option<&MemoryStats>) -> bool + Send + Sync>;
+
 pub struct MemoryCondition {
     pub name: String,
-    pub condition: Box<dyn Fn(&MemoryStats, Option<&MemoryStats>) -> bool + Send + Sync>,
+    pub condition: ConditionFn,
     pub triggered: bool,
 }
 
@@ -299,11 +1096,122 @@ impl std::fmt::Debug for MemoryCondition {
     }
 }
 
+/// Fires when a field changes faster than `rate_kb_per_sec` between two
+/// checks: in either direction when positive, falling when negative
+#[derive(Debug, Clone)]
+pub struct RateCondition {
+    pub name: String,
+    pub field: MemField,
+    pub rate_kb_per_sec: f64,
+    pub triggered: bool,
+}
+
+impl RateCondition {
+    /// Rate of change of the field from `previous` to `current` in KB/s;
+    /// `None` unless time moved forward between them
+    pub fn rate(&self, previous: &MemorySnapshot, current: &MemorySnapshot) -> Option<f64> {
+        if current.timestamp <= previous.timestamp {
+            return None;
+        }
+        let change = self.field.value_from(&current.stats) as f64
+            - self.field.value_from(&previous.stats) as f64;
+        Some(change * 1000.0 / (current.timestamp - previous.timestamp) as f64)
+    }
+
+    fn is_exceeded(&self, previous: &MemorySnapshot, current: &MemorySnapshot) -> bool {
+        self.rate(previous, current).is_some_and(|rate| {
+            if self.rate_kb_per_sec < 0.0 {
+                rate < self.rate_kb_per_sec
+            } else {
+                rate.abs() > self.rate_kb_per_sec
+            }
+        })
+    }
+}
+
+impl Default for EventMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl EventMonitor {
     pub fn new() -> Self {
+        Self::with_source(Arc::new(ProcMemSource))
+    }
+
+    /// Create a monitor checking conditions against the given source
+    pub fn with_source(source: Arc<dyn MemorySource>) -> Self {
         EventMonitor {
             conditions: Vec::new(),
+            rate_conditions: Vec::new(),
+            oom_kill_event: None,
             last_snapshot: None,
+            source,
+        }
+    }
+
+    /// Create a monitor checking conditions against a cgroup v2 group's
+    /// memory, with its limit as MemTotal
+    pub fn for_cgroup(path: impl Into<PathBuf>) -> Self {
+        Self::with_source(Arc::new(CgroupMemSource::new(path)))
+    }
+
+    /// Create a monitor alerting on the thresholds set in `config`
+    pub fn from_config(config: ThresholdConfig) -> Result<Self> {
+        let mut monitor = Self::new();
+        monitor.add_thresholds(&config)?;
+        Ok(monitor)
+    }
+
+    /// Add a condition for each threshold set in `config`, named after its
+    /// field, e.g. "min_available_ratio"
+    ///
+    /// Fails without adding any if a threshold isn't a ratio between 0 and
+    /// 1, or if `min_swap_free_ratio` is set but the source doesn't report
+    /// swap.
+    pub fn add_thresholds(&mut self, config: &ThresholdConfig) -> Result<()> {
+        config.validate()?;
+        if config.min_swap_free_ratio.is_some() && self.source.swap().is_none() {
+            return Err(MemoryError::InvalidArgument(
+                "min_swap_free_ratio needs a source that reports swap".to_string(),
+            ));
+        }
+
+        if let Some(min) = config.min_available_ratio {
+            self.add_condition("min_available_ratio".to_string(), move |stats, _| {
+                stats.mem_total > 0 && ratio_of(stats.mem_available, stats.mem_total) < min
+            });
+        }
+        self.add_max_ratio("max_dirty_ratio", config.max_dirty_ratio, |stats| {
+            stats.dirty
+        });
+        self.add_max_ratio("max_cache_ratio", config.max_cache_ratio, |stats| {
+            stats.page_cache_size()
+        });
+        self.add_max_ratio(
+            "max_inactive_file_ratio",
+            config.max_inactive_file_ratio,
+            |stats| stats.inactive_file,
+        );
+        self.add_max_ratio("max_slab_ratio", config.max_slab_ratio, |stats| stats.slab);
+        if let Some(min) = config.min_swap_free_ratio {
+            let source = Arc::clone(&self.source);
+            self.add_condition("min_swap_free_ratio".to_string(), move |_, _| {
+                source
+                    .swap()
+                    .is_some_and(|(total, free)| total > 0 && ratio_of(free, total) < min)
+            });
+        }
+        Ok(())
+    }
+
+    /// Condition `name` firing when `field` is over `max` of MemTotal
+    fn add_max_ratio(&mut self, name: &str, max: Option<f64>, field: fn(&MemoryStats) -> u64) {
+        if let Some(max) = max {
+            self.add_condition(name.to_string(), move |stats, _| {
+                ratio_of(field(stats), stats.mem_total) > max
+            });
         }
     }
 
@@ -319,9 +1227,32 @@ impl EventMonitor {
         });
     }
 
+    /// Add a condition firing when `field` changes faster than
+    /// `rate_kb_per_sec` between two consecutive `check_conditions` calls:
+    /// in either direction when positive, or falling faster when negative
+    /// (e.g. -10240.0 for MemAvailable draining at over 10 MB/s)
+    pub fn add_rate_condition(&mut self, name: String, field: MemField, rate_kb_per_sec: f64) {
+        self.rate_conditions.push(RateCondition {
+            name,
+            field,
+            rate_kb_per_sec,
+            triggered: false,
+        });
+    }
+
+    /// Report `name` whenever the OOM killer ran since the previous check,
+    /// going by the `oom_kill` counter in /proc/vmstat
+    ///
+    /// Unlike other conditions this fires on every check that saw a new
+    /// kill, not only on the first. Needs a source that attaches vmstat to
+    /// its snapshots, as `ProcMemSource` does; otherwise it never fires.
+    pub fn add_oom_kill_condition(&mut self, name: String) {
+        self.oom_kill_event = Some(name);
+    }
+
     /// Check all conditions against current memory state
     pub fn check_conditions(&mut self) -> Result<Vec<String>> {
-        let current = MemorySnapshot::new()?;
+        let current = self.source.snapshot()?;
         let mut triggered_events = Vec::new();
 
         let previous_stats = self.last_snapshot.as_ref().map(|s| &s.stats);
@@ -337,6 +1268,28 @@ impl EventMonitor {
             }
         }
 
+        // Rates need a previous check to compare with
+        if let Some(previous) = &self.last_snapshot {
+            for condition in &mut self.rate_conditions {
+                let is_triggered = condition.is_exceeded(previous, &current);
+                if is_triggered && !condition.triggered {
+                    triggered_events.push(condition.name.clone());
+                }
+                condition.triggered = is_triggered;
+            }
+        }
+
+        if let Some(name) = &self.oom_kill_event {
+            let oom_kills =
+                |snapshot: &MemorySnapshot| snapshot.vmstat.as_ref().map(|v| v.oom_kill);
+            let previous = self.last_snapshot.as_ref().and_then(oom_kills);
+            if let (Some(before), Some(after)) = (previous, oom_kills(&current))
+                && after > before
+            {
+                triggered_events.push(name.clone());
+            }
+        }
+
         self.last_snapshot = Some(current);
         Ok(triggered_events)
     }
@@ -375,6 +1328,143 @@ impl EventMonitor {
     }
 }
 
+/// Callback receiving (previous, new) pressure levels
+pub type PressureCallback = Box<dyn Fn(PressureLevel, PressureLevel) + Send + Sync>;
+
+/// Default margin around the available-ratio thresholds (2% of total memory)
+pub const DEFAULT_PRESSURE_HYSTERESIS: f64 = 0.02;
+
+struct PressureWatcherState {
+    hysteresis: f64,
+    level: Option<PressureLevel>,
+}
+
+impl PressureWatcherState {
+    fn observe(&mut self, available_ratio: f64) -> Option<(PressureLevel, PressureLevel)> {
+        let Some(current) = self.level else {
+            // The first observation sets the baseline without firing
+            self.level = Some(PressureLevel::from_available_ratio(available_ratio));
+            return None;
+        };
+
+        // Only move once the ratio is clearly past a threshold, so a value
+        // hovering at the boundary doesn't flap between two levels
+        let worse = PressureLevel::from_available_ratio(available_ratio + self.hysteresis);
+        let better = PressureLevel::from_available_ratio(available_ratio - self.hysteresis);
+        let next = if worse > current {
+            worse
+        } else if better < current {
+            better
+        } else {
+            return None;
+        };
+
+        self.level = Some(next);
+        Some((current, next))
+    }
+}
+
+/// Feed `available_ratio` to `state` and run `callback` on a change, after
+/// the lock is released so the callback may use the watcher
+fn observe_and_notify(
+    state: &Mutex<PressureWatcherState>,
+    callback: &PressureCallback,
+    available_ratio: f64,
+) -> Option<(PressureLevel, PressureLevel)> {
+    let change = state.lock().unwrap().observe(available_ratio);
+    if let Some((previous, next)) = change {
+        callback(previous, next);
+    }
+    change
+}
+
+/// Fires a callback when the memory pressure level changes
+///
+/// The pressure analogue of `EventMonitor`: instead of polling, register a
+/// callback and let the watcher sample `MemoryPressure::current()`.
+pub struct PressureWatcher {
+    state: Arc<Mutex<PressureWatcherState>>,
+    callback: Arc<PressureCallback>,
+    running: Arc<Mutex<bool>>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl PressureWatcher {
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: Fn(PressureLevel, PressureLevel) + Send + Sync + 'static,
+    {
+        PressureWatcher {
+            state: Arc::new(Mutex::new(PressureWatcherState {
+                hysteresis: DEFAULT_PRESSURE_HYSTERESIS,
+                level: None,
+            })),
+            callback: Arc::new(Box::new(callback)),
+            running: Arc::new(Mutex::new(false)),
+            handle: None,
+        }
+    }
+
+    /// Set the margin (as a fraction of total memory) the available ratio must
+    /// move past a threshold before the level changes
+    pub fn with_hysteresis(self, hysteresis: f64) -> Self {
+        self.state.lock().unwrap().hysteresis = hysteresis.max(0.0);
+        self
+    }
+
+    /// Last level reported, if any sample has been taken
+    pub fn level(&self) -> Option<PressureLevel> {
+        self.state.lock().unwrap().level
+    }
+
+    /// Feed a pressure sample, returning the (previous, new) levels on a change
+    pub fn observe(&self, pressure: &MemoryPressure) -> Option<(PressureLevel, PressureLevel)> {
+        observe_and_notify(&self.state, &self.callback, pressure.available_ratio)
+    }
+
+    /// Sample the current pressure once
+    pub fn check(&self) -> Result<Option<(PressureLevel, PressureLevel)>> {
+        Ok(self.observe(&MemoryPressure::current()?))
+    }
+
+    /// Start sampling on a background thread with the specified interval
+    pub fn start(&mut self, interval: Duration) {
+        let mut running = self.running.lock().unwrap();
+        if *running {
+            return; // Already running
+        }
+        *running = true;
+
+        let state = Arc::clone(&self.state);
+        let callback = Arc::clone(&self.callback);
+        let running_flag = Arc::clone(&self.running);
+
+        self.handle = Some(thread::spawn(move || {
+            while *running_flag.lock().unwrap() {
+                if let Ok(pressure) = MemoryPressure::current() {
+                    observe_and_notify(&state, &callback, pressure.available_ratio);
+                }
+                thread::sleep(interval);
+            }
+        }));
+    }
+
+    /// Stop sampling
+    pub fn stop(&mut self) {
+        *self.running.lock().unwrap() = false;
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for PressureWatcher {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -385,6 +1475,39 @@ mod tests {
         assert_eq!(monitor.max_snapshots, 100);
     }
 
+    #[cfg(feature = "jitter")]
+    #[test]
+    fn test_jittered_interval_stays_in_bounds() {
+        let interval = Duration::from_millis(1000);
+        assert_eq!(jittered_interval(interval, 0.0), interval);
+
+        for _ in 0..100 {
+            let jittered = jittered_interval(interval, 10.0);
+            assert!(jittered >= Duration::from_millis(900));
+            assert!(jittered <= Duration::from_millis(1100));
+        }
+        // More than 100% would mean negative sleeps
+        assert!(jittered_interval(interval, 500.0) <= Duration::from_millis(2000));
+    }
+
+    #[cfg(feature = "jitter")]
+    #[test]
+    fn test_with_jitter_rejects_invalid_percent() {
+        assert_eq!(
+            jittered_interval(Duration::from_secs(1), f64::NAN),
+            Duration::from_secs(1)
+        );
+
+        for percent in [f64::NAN, f64::INFINITY, -1.0] {
+            assert!(matches!(
+                ContinuousMonitor::new(10).with_jitter(percent),
+                Err(MemoryError::InvalidArgument(_))
+            ));
+        }
+        let monitor = ContinuousMonitor::new(10).with_jitter(250.0).unwrap();
+        assert_eq!(monitor.jitter_percent, 100.0);
+    }
+
     #[test]
     fn test_trend_calculation() {
         let values = vec![1000, 1100, 1200, 1150, 1300];
@@ -396,15 +1519,571 @@ mod tests {
         assert!(matches!(trend.direction, TrendDirection::Increasing));
     }
 
+    #[test]
+    fn test_memory_leak_likely() {
+        let series = |count: u64, step: fn(u64) -> (u64, u64)| -> TrendAnalysis {
+            let snapshots: Vec<MemorySnapshot> = (0..count)
+                .map(|i| {
+                    let (available, cached) = step(i);
+                    MemorySnapshot::from_stats(stats(available, cached))
+                })
+                .collect();
+            TrendAnalysis::from_snapshots(&snapshots)
+        };
+
+        // Available memory drains 10 MB per sample into non-cache memory
+        let leak = series(12, |i| (800_000 - i * 10_000, 100_000));
+        assert!(leak.is_memory_leak_likely());
+
+        let short = series(5, |i| (800_000 - i * 10_000, 100_000));
+        assert!(!short.memory_leak_indicators().sufficient_samples);
+        assert!(!short.is_memory_leak_likely());
+
+        // The same drain going into the page cache
+        let cache_fill = series(12, |i| (800_000 - i * 10_000, 100_000 + i * 10_000));
+        assert_eq!(
+            cache_fill.memory_leak_indicators().cache_trend,
+            TrendDirection::Increasing
+        );
+        assert!(!cache_fill.is_memory_leak_likely());
+
+        // A slow climb with two transient spikes
+        let spiky = series(12, |i| {
+            let spike = if i == 3 || i == 7 { 200_000 } else { 0 };
+            (800_000 - i * 5_000 - spike, 100_000)
+        });
+        let indicators = spiky.memory_leak_indicators();
+        assert_eq!(indicators.used_trend, TrendDirection::Increasing);
+        assert!(!indicators.volatility_low);
+        assert!(!spiky.is_memory_leak_likely());
+    }
+
+    #[test]
+    fn test_trend_forecast() {
+        let samples = |ratios: &[u64]| -> Vec<MemorySnapshot> {
+            ratios
+                .iter()
+                .enumerate()
+                .map(|(i, &percent)| MemorySnapshot {
+                    timestamp: 5_000 + i as u64 * 1000,
+                    ..MemorySnapshot::from_stats(stats(percent * 10_000, 0))
+                })
+                .collect()
+        };
+
+        // 50% available, losing 5 points a second
+        let trend = TrendAnalysis::from_snapshots(&samples(&[50, 45, 40, 35]));
+        assert_eq!(trend.pressure_timestamps, [0, 1000, 2000, 3000]);
+        let forecast = trend.forecast(2000);
+        assert!((forecast.predicted_available_ratio - 0.25).abs() < 1e-9);
+        assert!((forecast.r_squared - 1.0).abs() < 1e-9);
+        let (low, high) = forecast.confidence_interval;
+        assert!((high - low).abs() < 1e-9);
+        // Reaches 10% at 8s, 5s after the last sample
+        assert_eq!(forecast.will_reach_critical_in_ms, Some(5000));
+        // Extrapolating far enough clamps at zero
+        assert_eq!(trend.forecast(60_000).predicted_available_ratio, 0.0);
+
+        // Noisy but rising: a real interval around the prediction, no crossing
+        let forecast =
+            TrendAnalysis::from_snapshots(&samples(&[40, 44, 41, 47, 45, 50])).forecast(1000);
+        let (low, high) = forecast.confidence_interval;
+        assert!(low < forecast.predicted_available_ratio);
+        assert!(forecast.predicted_available_ratio < high);
+        assert!(forecast.r_squared < 1.0);
+        assert_eq!(forecast.will_reach_critical_in_ms, None);
+
+        // Already critical, and a single sample carries forward
+        let forecast = TrendAnalysis::from_snapshots(&samples(&[5])).forecast(1000);
+        assert!((forecast.predicted_available_ratio - 0.05).abs() < 1e-9);
+        assert_eq!(forecast.r_squared, 0.0);
+        assert_eq!(forecast.will_reach_critical_in_ms, Some(0));
+    }
+
+    fn stats(mem_available: u64, cached: u64) -> MemoryStats {
+        MemoryStats::builder()
+            .mem_total(1_000_000)
+            .mem_free(mem_available)
+            .mem_available(mem_available)
+            .cached(cached)
+            .build()
+    }
+
     #[test]
     fn test_event_monitor() {
-        let mut monitor = EventMonitor::new();
+        let source = Arc::new(crate::MockMemSource::new([
+            stats(500_000, 100_000),
+            stats(50_000, 100_000),
+            stats(40_000, 300_000),
+            stats(400_000, 300_000),
+        ]));
+        let mut monitor = EventMonitor::with_source(source);
+        monitor.add_common_conditions();
+
+        assert!(monitor.check_conditions().unwrap().is_empty());
+        // Low memory fires once while it persists
+        assert_eq!(monitor.check_conditions().unwrap(), vec!["low_memory"]);
+        assert_eq!(
+            monitor.check_conditions().unwrap(),
+            vec!["high_cache_growth"]
+        );
+        assert_eq!(
+            monitor.check_conditions().unwrap(),
+            vec!["memory_pressure_relief"]
+        );
+    }
+
+    #[test]
+    fn test_threshold_config() {
+        let config: ThresholdConfig =
+            toml::from_str("min_available_ratio = 0.2\nmax_dirty_ratio = 0.05\n").unwrap();
+        assert_eq!(
+            config,
+            ThresholdConfig {
+                min_available_ratio: Some(0.2),
+                max_dirty_ratio: Some(0.05),
+                ..Default::default()
+            }
+        );
+
+        let dirty = |mem_available, dirty| {
+            MemoryStats::builder()
+                .mem_total(1_000_000)
+                .mem_available(mem_available)
+                .dirty(dirty)
+                .build()
+        };
+        let source = Arc::new(crate::MockMemSource::new([
+            dirty(500_000, 10_000),
+            dirty(100_000, 10_000),
+            dirty(100_000, 60_000),
+        ]));
+        let mut monitor = EventMonitor::with_source(source);
+        monitor.add_thresholds(&config).unwrap();
+        assert!(monitor.check_conditions().unwrap().is_empty());
+        assert_eq!(monitor.check_conditions().unwrap(), ["min_available_ratio"]);
+        assert_eq!(monitor.check_conditions().unwrap(), ["max_dirty_ratio"]);
+
+        let monitor = EventMonitor::from_config(ThresholdConfig {
+            max_slab_ratio: Some(0.5),
+            min_swap_free_ratio: Some(0.1),
+            ..Default::default()
+        })
+        .unwrap();
+        let names: Vec<&str> = monitor.conditions.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["max_slab_ratio", "min_swap_free_ratio"]);
+    }
+
+    #[test]
+    fn test_threshold_config_rejects_invalid() {
+        for ratio in [f64::NAN, -0.1, 1.5, f64::INFINITY] {
+            let config = ThresholdConfig {
+                max_dirty_ratio: Some(ratio),
+                ..Default::default()
+            };
+            assert!(matches!(
+                EventMonitor::from_config(config),
+                Err(MemoryError::InvalidArgument(_))
+            ));
+        }
+
+        // A mock source has no swap to compare against
+        let source = Arc::new(crate::MockMemSource::new([MemoryStats::default()]));
+        let mut monitor = EventMonitor::with_source(source);
+        let config = ThresholdConfig {
+            min_available_ratio: Some(0.1),
+            min_swap_free_ratio: Some(0.1),
+            ..Default::default()
+        };
+        assert!(monitor.add_thresholds(&config).is_err());
+        assert!(monitor.conditions.is_empty());
+    }
+
+    #[test]
+    fn test_rate_condition() {
+        let available = |kb| MemoryStats::builder().mem_available(kb).build();
+        let at = |timestamp, kb| MemorySnapshot {
+            timestamp,
+            ..MemorySnapshot::from_stats(available(kb))
+        };
+        let draining = RateCondition {
+            name: "draining".to_string(),
+            field: MemField::MemAvailable,
+            rate_kb_per_sec: -1000.0,
+            triggered: false,
+        };
+        // 3000 KB in 2 s
+        assert_eq!(
+            draining.rate(&at(1000, 9000), &at(3000, 6000)),
+            Some(-1500.0)
+        );
+        assert!(draining.is_exceeded(&at(1000, 9000), &at(3000, 6000)));
+        // Rising fast doesn't count as draining
+        assert!(!draining.is_exceeded(&at(1000, 6000), &at(3000, 9000)));
+        assert_eq!(draining.rate(&at(3000, 9000), &at(3000, 6000)), None);
+
+        let either = RateCondition {
+            rate_kb_per_sec: 1000.0,
+            ..draining
+        };
+        assert!(either.is_exceeded(&at(1000, 6000), &at(3000, 9000)));
+        assert!(either.is_exceeded(&at(1000, 9000), &at(3000, 6000)));
+        assert!(!either.is_exceeded(&at(1000, 9000), &at(3000, 8000)));
+
+        let source = Arc::new(crate::MockMemSource::new([
+            available(500_000),
+            available(100_000),
+            available(100_000),
+        ]));
+        let mut monitor = EventMonitor::with_source(source);
+        monitor.add_rate_condition("draining".to_string(), MemField::MemAvailable, -1000.0);
+        assert!(monitor.check_conditions().unwrap().is_empty());
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(monitor.check_conditions().unwrap(), ["draining"]);
+        thread::sleep(Duration::from_millis(20));
+        assert!(monitor.check_conditions().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_oom_kill_condition() {
+        /// Reports the scripted oom_kill counts, one per snapshot
+        struct OomKills(Mutex<VecDeque<u64>>);
+
+        impl MemorySource for OomKills {
+            fn current(&self) -> Result<MemoryStats> {
+                Ok(MemoryStats::default())
+            }
+
+            fn vmstat(&self) -> Option<crate::VmStats> {
+                let oom_kill = self.0.lock().unwrap().pop_front()?;
+                Some(crate::VmStats {
+                    oom_kill,
+                    ..Default::default()
+                })
+            }
+        }
+
+        let source = OomKills(Mutex::new([3, 3, 4, 6, 6].into()));
+        let mut monitor = EventMonitor::with_source(Arc::new(source));
+        monitor.add_oom_kill_condition("oom_kill".to_string());
+        let checks: Vec<Vec<String>> = (0..6)
+            .map(|_| monitor.check_conditions().unwrap())
+            .collect();
+        // Kills before the first check don't count; each new one does
+        assert_eq!(
+            checks.iter().map(Vec::len).collect::<Vec<_>>(),
+            [0, 0, 1, 1, 0, 0]
+        );
+        assert_eq!(checks[2], ["oom_kill"]);
+    }
+
+    #[test]
+    fn test_series_summary() {
+        let snapshot = |timestamp, mem_free| MemorySnapshot {
+            timestamp,
+            ..MemorySnapshot::from_stats(MemoryStats::builder().mem_free(mem_free).build())
+        };
+        let summary = SeriesSummary::from_snapshots(&[
+            snapshot(1000, 300),
+            snapshot(2000, 100),
+            snapshot(3000, 200),
+        ]);
+
+        assert_eq!(summary.sample_count, 3);
+        assert_eq!(summary.duration_ms, 2000);
+        assert_eq!(summary.fields.len(), FIELD_NAMES.len());
+
+        let free = summary.field("MemFree").unwrap();
+        assert_eq!((free.min, free.max, free.mean), (100, 300, 200.0));
+        assert_eq!((free.first, free.last, free.delta), (300, 200, -100));
+
+        let json = serde_json::to_string(&summary).unwrap();
+        let parsed: SeriesSummary = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.field("MemFree"), Some(free));
+
+        assert!(SeriesSummary::from_snapshots(&[]).fields.is_empty());
+    }
+
+    #[test]
+    fn test_thread_scheduling() {
+        thread::spawn(|| {
+            // Lowering priority is always allowed
+            set_current_thread_nice(19).unwrap();
+            // SAFETY: getpriority only reads its arguments
+            let nice =
+                unsafe { libc::getpriority(libc::PRIO_PROCESS, libc::gettid() as libc::id_t) };
+            assert_eq!(nice, 19);
+
+            // SAFETY: as in set_current_thread_affinity
+            let mut allowed: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+            unsafe { libc::sched_getaffinity(0, std::mem::size_of_val(&allowed), &mut allowed) };
+            let cpu = (0..libc::CPU_SETSIZE as usize)
+                .find(|&cpu| unsafe { libc::CPU_ISSET(cpu, &allowed) })
+                .unwrap();
+            set_current_thread_affinity(&[cpu]).unwrap();
+            assert!(set_current_thread_affinity(&[libc::CPU_SETSIZE as usize]).is_err());
+        })
+        .join()
+        .unwrap();
+
+        // Failures only warn; sampling goes on
+        let mut monitor = ContinuousMonitor::new(10)
+            .with_nice(-20)
+            .with_cpu_affinity(&[libc::CPU_SETSIZE as usize]);
+        monitor.start(Duration::from_millis(10)).unwrap();
+        thread::sleep(Duration::from_millis(50));
+        monitor.stop();
+        assert!(!monitor.get_snapshots().is_empty());
+    }
+
+    #[test]
+    fn test_subscribe() {
+        let free = |kb| MemoryStats::builder().mem_free(kb).build();
+        let source = Arc::new(crate::MockMemSource::new([free(1), free(2), free(3)]));
+        let monitor = ContinuousMonitor::with_source(10, source);
+        let all = monitor.subscribe();
+        let odd = monitor.subscribe_filtered(|stats| stats.mem_free % 2 == 1);
+        let dropped = monitor.subscribe();
+        drop(dropped);
+
+        for _ in 0..3 {
+            monitor.sample().unwrap();
+        }
+        let received = |rx: &Receiver<MemorySnapshot>| -> Vec<u64> {
+            rx.try_iter()
+                .map(|snapshot| snapshot.stats.mem_free)
+                .collect()
+        };
+        assert_eq!(received(&all), [1, 2, 3]);
+        assert_eq!(received(&odd), [1, 3]);
+        // The dead receiver was removed on the first send
+        assert_eq!(monitor.subscribers.lock().unwrap().len(), 2);
+
+        // A full buffer drops snapshots instead of blocking the sampler
+        for _ in 0..SUBSCRIBER_BUFFER + 5 {
+            monitor.sample().unwrap();
+        }
+        assert_eq!(all.try_iter().count(), SUBSCRIBER_BUFFER);
+
+        let mut monitor =
+            ContinuousMonitor::with_source(10, Arc::new(crate::MockMemSource::new([free(7)])));
+        let rx = monitor.subscribe();
+        monitor.start(Duration::from_millis(10)).unwrap();
+        let snapshot = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        monitor.stop();
+        assert_eq!(snapshot.stats.mem_free, 7);
+    }
 
-        monitor.add_condition("test_condition".to_string(), |stats, _| {
-            stats.mem_free < 1000
+    #[test]
+    fn test_filter_may_subscribe() {
+        let source = Arc::new(crate::MockMemSource::new([MemoryStats::default()]));
+        let monitor = Arc::new(ContinuousMonitor::with_source(10, source));
+        let slot: Arc<std::sync::OnceLock<Arc<ContinuousMonitor>>> = Arc::default();
+        let filter_slot = Arc::clone(&slot);
+        let _rx = monitor.subscribe_filtered(move |_| {
+            // Would deadlock if filters ran under the subscriber lock
+            if let Some(monitor) = filter_slot.get() {
+                drop(monitor.subscribe());
+            }
+            true
         });
+        assert!(slot.set(Arc::clone(&monitor)).is_ok());
+
+        monitor.sample().unwrap();
+        monitor.sample().unwrap();
+        // The first sample's subscriber was dropped and removed by the second
+        assert_eq!(monitor.subscribers.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_linear_fit() {
+        let fit = LinearFit::fit(&[(0.0, 1.0), (1.0, 3.0), (2.0, 5.0)]).unwrap();
+        assert!((fit.slope - 2.0).abs() < 1e-12);
+        assert!((fit.intercept - 1.0).abs() < 1e-12);
+        assert!((fit.r_squared - 1.0).abs() < 1e-12);
+
+        let noisy = LinearFit::fit(&[(0.0, 0.0), (1.0, 5.0), (2.0, 0.0), (3.0, 5.0)]).unwrap();
+        assert!(noisy.r_squared < 0.5);
+
+        assert!(LinearFit::fit(&[(0.0, 1.0)]).is_none());
+        assert!(LinearFit::fit(&[(1.0, 1.0), (1.0, 2.0)]).is_none());
+
+        // Out-of-order snapshots fit the same line
+        let at = |timestamp, kb| MemorySnapshot {
+            timestamp,
+            ..MemorySnapshot::from_stats(MemoryStats::builder().mem_free(kb).build())
+        };
+        let fit = LinearFit::over_time(&[at(2000, 30), at(1000, 10), at(3000, 50)], |s| s.mem_free)
+            .unwrap();
+        assert!((fit.slope - 0.02).abs() < 1e-12);
+        assert!((fit.intercept - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_leak_heuristic() {
+        // One sample a minute for an hour; anon grows 1 MB a minute
+        let series = |cache_growth_kb: u64| -> Vec<MemorySnapshot> {
+            (0..60u64)
+                .map(|i| MemorySnapshot {
+                    timestamp: i * 60_000,
+                    ..MemorySnapshot::from_stats(
+                        MemoryStats::builder()
+                            .mem_total(8_000_000)
+                            .mem_available(4_000_000 - i * 1024)
+                            .active_anon(1_000_000 + i * 1024)
+                            .cached(2_000_000 + i * cache_growth_kb)
+                            .build(),
+                    )
+                })
+                .collect()
+        };
+        let heuristic = LeakHeuristic::default();
+
+        let report = heuristic.analyze(&series(0)).unwrap();
+        assert!(report.suspected);
+        assert!((report.anon_growth_mb_per_hour - 60.0).abs() < 1e-6);
+        assert!((report.r_squared - 1.0).abs() < 1e-9);
+        let remaining_hours = (4_000_000.0 - 59.0 * 1024.0) / 1024.0 / 60.0;
+        assert!((report.hours_to_exhaustion.unwrap() - remaining_hours).abs() < 1e-6);
+        assert!(report.to_string().starts_with("Suspected leak"));
+
+        // Cache growing as fast as anon is a growing workload, not a leak
+        assert!(!heuristic.analyze(&series(1024)).unwrap().suspected);
+
+        // Too short to judge
+        assert!(heuristic.analyze(&series(0)[..5]).is_none());
+    }
+
+    #[test]
+    fn test_trend_analysis_from_source() {
+        let source = Arc::new(crate::MockMemSource::new(
+            (0..5).map(|i| stats(500_000 - i * 50_000, 100_000 + i * 50_000)),
+        ));
+        let monitor = ContinuousMonitor::with_source(3, source);
+        for _ in 0..5 {
+            monitor.sample().unwrap();
+        }
+
+        // Only the newest three samples are kept
+        assert_eq!(monitor.get_snapshots().len(), 3);
+        assert!(monitor.get_trend_analysis(4).is_none());
+
+        let trend = monitor.get_trend_analysis(3).unwrap();
+        assert_eq!(trend.sample_count, 3);
+        let cache = &trend.cache_trends.page_cache_trend;
+        assert_eq!(cache.initial_value, 200_000);
+        assert_eq!(cache.change, 100_000);
+        assert!(matches!(cache.direction, TrendDirection::Increasing));
+        assert!(matches!(
+            trend.memory_trends.available_memory_trend.direction,
+            TrendDirection::Decreasing
+        ));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_spawn_async_publishes_snapshots() {
+        let (handle, mut rx) = ContinuousMonitor::spawn_async(Duration::from_millis(10)).unwrap();
+        let first = rx.borrow().timestamp;
+
+        rx.changed().await.unwrap();
+        assert!(rx.borrow_and_update().timestamp >= first);
+
+        // Dropping the last receiver stops the task
+        drop(rx);
+        handle.await.unwrap();
+    }
+
+    #[test]
+    fn test_pressure_watcher_hysteresis() {
+        let transitions = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&transitions);
+        let watcher = PressureWatcher::new(move |from, to| {
+            recorded.lock().unwrap().push((from, to));
+        })
+        .with_hysteresis(0.02);
+
+        let sample = |available_ratio| MemoryPressure {
+            available_ratio,
+            free_ratio: 0.0,
+            cache_ratio: 0.0,
+            dirty_ratio: 0.0,
+            inactive_file_ratio: 0.0,
+            pressure_level: PressureLevel::from_available_ratio(available_ratio),
+        };
+
+        // Baseline, then jitter around the 20% Medium/High boundary
+        assert_eq!(watcher.observe(&sample(0.25)), None);
+        assert_eq!(watcher.observe(&sample(0.19)), None);
+        assert_eq!(watcher.observe(&sample(0.21)), None);
+
+        // Clearly below the boundary escalates, and only once
+        assert_eq!(
+            watcher.observe(&sample(0.17)),
+            Some((PressureLevel::Medium, PressureLevel::High))
+        );
+        assert_eq!(watcher.observe(&sample(0.21)), None);
+        assert_eq!(
+            watcher.observe(&sample(0.23)),
+            Some((PressureLevel::High, PressureLevel::Medium))
+        );
+
+        assert_eq!(transitions.lock().unwrap().len(), 2);
+        assert_eq!(watcher.level(), Some(PressureLevel::Medium));
+    }
+
+    #[test]
+    fn test_pressure_callback_may_use_watcher() {
+        let slot: Arc<std::sync::OnceLock<Arc<PressureWatcher>>> = Arc::default();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let (watcher_slot, recorded) = (Arc::clone(&slot), Arc::clone(&seen));
+        let watcher = Arc::new(PressureWatcher::new(move |_, _| {
+            // Would deadlock if the callback ran under the state lock
+            let level = watcher_slot.get().and_then(|watcher| watcher.level());
+            recorded.lock().unwrap().push(level);
+        }));
+        assert!(slot.set(Arc::clone(&watcher)).is_ok());
+
+        let sample = |available_ratio| MemoryPressure {
+            available_ratio,
+            free_ratio: 0.0,
+            cache_ratio: 0.0,
+            dirty_ratio: 0.0,
+            inactive_file_ratio: 0.0,
+            pressure_level: PressureLevel::from_available_ratio(available_ratio),
+        };
+        watcher.observe(&sample(0.5));
+        watcher.observe(&sample(0.05));
+        assert_eq!(*seen.lock().unwrap(), vec![Some(PressureLevel::Critical)]);
+    }
+
+    #[test]
+    fn test_cache_churn_with_flat_cache() {
+        let snapshot = |timestamp, pgpgin, pgpgout| MemorySnapshot {
+            timestamp,
+            stats: MemoryStats {
+                cached: 500_000,
+                ..Default::default()
+            },
+            label: None,
+            tags: Vec::new(),
+            vmstat: Some(crate::SnapshotVmCounters {
+                pgpgin,
+                pgpgout,
+                ..Default::default()
+            }),
+        };
+
+        // 20MB read and 10MB written in 2s while the cache size stays flat
+        let churn =
+            CacheChurn::from_snapshots(&[snapshot(0, 1000, 500), snapshot(2000, 21480, 10740)])
+                .unwrap();
+        assert_eq!(churn.net_cache_change_kb, 0);
+        assert_eq!(churn.read_in_kb_per_sec, 10240.0);
+        assert_eq!(churn.written_out_kb_per_sec, 5120.0);
+        assert_eq!(churn.insertion_kb_per_sec, 15360.0);
+        assert_eq!(churn.eviction_kb_per_sec, 15360.0);
 
-        // This test would need actual memory stats to be meaningful
-        // In a real scenario, you'd mock the MemorySnapshot::new() function
+        assert!(CacheChurn::from_snapshots(&[snapshot(0, 0, 0)]).is_none());
     }
 }
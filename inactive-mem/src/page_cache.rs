@@ -1,4 +1,4 @@
-use crate::{MemorySnapshot, MemoryStats, Result};
+use crate::{MemoryBaseline, MemorySnapshot, MemoryStats, Result};
 use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::io::{self, Write};
@@ -10,6 +10,10 @@ use std::time::{Duration, Instant};
 pub struct PageCacheMonitor {
     pub initial_snapshot: MemorySnapshot,
     pub snapshots: Vec<MemorySnapshot>,
+    /// Reference for `net_cache_change_kb` when set, e.g. a baseline taken
+    /// at program start; otherwise `initial_snapshot` is
+    #[serde(default)]
+    pub baseline: Option<MemoryBaseline>,
 }
 
 impl PageCacheMonitor {
@@ -19,9 +23,28 @@ impl PageCacheMonitor {
         Ok(PageCacheMonitor {
             initial_snapshot: initial_snapshot.clone(),
             snapshots: vec![initial_snapshot],
+            baseline: None,
         })
     }
 
+    /// Measure `net_cache_change_kb` from `baseline` instead of from when
+    /// the monitor was created
+    pub fn with_baseline(mut self, baseline: MemoryBaseline) -> Self {
+        self.baseline = Some(baseline);
+        self
+    }
+
+    /// Net change in page cache (Cached + Buffers) from the baseline, or
+    /// construction, to the latest snapshot
+    pub fn net_cache_change_kb(&self) -> i64 {
+        let reference = self
+            .baseline
+            .as_ref()
+            .map_or(&self.initial_snapshot, |baseline| &baseline.snapshot);
+        let diff = reference.stats.diff(&self.latest_snapshot().stats);
+        diff.cached + diff.buffers
+    }
+
     /// Take a new snapshot and add it to the monitoring history
     pub fn take_snapshot(&mut self) -> Result<&MemorySnapshot> {
         let snapshot = MemorySnapshot::new()?;
@@ -44,7 +67,7 @@ impl PageCacheMonitor {
 
         // Perform the operation
         let start_time = Instant::now();
-        operation().map_err(|e| crate::MemoryError::ProcMemInfoRead(e))?;
+        operation().map_err(crate::MemoryError::ProcMemInfoRead)?;
         let operation_duration = start_time.elapsed();
 
         // Take snapshot after operation
@@ -196,7 +219,7 @@ impl MemoryImpact {
 }
 
 /// Summary of page cache behavior over time
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PageCacheSummary {
     pub initial_cache_kb: u64,
     pub final_cache_kb: u64,
@@ -207,20 +230,6 @@ pub struct PageCacheSummary {
     pub snapshot_count: usize,
 }
 
-impl Default for PageCacheSummary {
-    fn default() -> Self {
-        Self {
-            initial_cache_kb: 0,
-            final_cache_kb: 0,
-            cache_change_kb: 0,
-            max_cache_kb: 0,
-            min_cache_kb: 0,
-            max_inactive_file_kb: 0,
-            snapshot_count: 0,
-        }
-    }
-}
-
 /// File operation utilities for testing page cache behavior
 pub struct FileOperations;
 
@@ -292,24 +301,62 @@ mod tests {
     }
 
     #[test]
-    fn test_memory_impact_calculation() {
-        let before = MemoryStats {
-            mem_free: 1000000,
-            cached: 500000,
-            buffers: 100000,
-            inactive_file: 300000,
-            dirty: 50000,
-            ..Default::default()
+    fn test_net_cache_change() {
+        let snapshot = |cached| {
+            MemorySnapshot::from_stats(MemoryStats::builder().cached(cached).buffers(100).build())
+        };
+        let mut monitor = PageCacheMonitor {
+            initial_snapshot: snapshot(5_000),
+            snapshots: vec![snapshot(5_000)],
+            baseline: None,
         };
+        monitor.snapshots.push(snapshot(7_000));
+        assert_eq!(monitor.net_cache_change_kb(), 2_000);
 
-        let after = MemoryStats {
-            mem_free: 800000,
-            cached: 700000,
-            buffers: 100000,
-            inactive_file: 400000,
-            dirty: 75000,
-            ..Default::default()
+        let monitor = monitor.with_baseline(MemoryBaseline {
+            snapshot: snapshot(1_000),
+        });
+        assert_eq!(monitor.net_cache_change_kb(), 6_000);
+    }
+
+    #[test]
+    fn test_memory_baseline() {
+        let mut baseline = MemoryBaseline::capture().unwrap();
+        let mem_total = baseline.snapshot.stats.mem_total;
+        assert_eq!(baseline.delta_now().unwrap().mem_total, 0);
+        let grown = MemoryStats {
+            cached: baseline.snapshot.stats.cached + 1024,
+            ..baseline.snapshot.stats.clone()
         };
+        assert_eq!(baseline.delta_to(&grown).cached, 1024);
+
+        // Only lower bounds: an upper bound on elapsed time flakes on a
+        // loaded machine
+        let taken_at = baseline.snapshot.timestamp;
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(baseline.elapsed_ms() >= 20);
+        baseline.reset().unwrap();
+        assert!(baseline.snapshot.timestamp >= taken_at + 20);
+        assert_eq!(baseline.snapshot.stats.mem_total, mem_total);
+    }
+
+    #[test]
+    fn test_memory_impact_calculation() {
+        let before = MemoryStats::builder()
+            .mem_free(1000000)
+            .cached(500000)
+            .buffers(100000)
+            .inactive_file(300000)
+            .dirty(50000)
+            .build();
+
+        let after = MemoryStats::builder()
+            .mem_free(800000)
+            .cached(700000)
+            .buffers(100000)
+            .inactive_file(400000)
+            .dirty(75000)
+            .build();
 
         let impact = MemoryImpact::calculate(&before, &after);
         assert_eq!(impact.free_memory_change_kb, -200000);
@@ -0,0 +1,301 @@
+use crate::{
+    FIELD_DESCRIPTIONS, KsmStats, MemoryStats, PsiResource, PsiStats, VMSTAT_COUNTERS, VmStats,
+};
+use std::fmt::Write;
+
+/// Prefix of every exported metric name
+const METRIC_PREFIX: &str = "linux_memory_";
+
+/// Renders stats in the Prometheus text exposition format
+///
+/// Sizes are exported in bytes and every metric is prefixed `linux_memory_`;
+/// labels set with `with_labels` are attached to every sample.
+#[derive(Debug, Clone, Default)]
+pub struct PrometheusExporter {
+    labels: Vec<(String, String)>,
+}
+
+/// Escape a label value: backslash, double quote and newline
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+impl PrometheusExporter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attach `name="value"` labels to every sample, e.g. the host name
+    pub fn with_labels(mut self, labels: &[(&str, &str)]) -> Self {
+        self.labels.extend(
+            labels
+                .iter()
+                .map(|&(name, value)| (name.to_string(), value.to_string())),
+        );
+        self
+    }
+
+    /// `{a="1",b="2"}` for the exporter's labels plus `extra`, or nothing
+    fn label_set(&self, extra: &[(&str, &str)]) -> String {
+        let labels: Vec<String> = self
+            .labels
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .chain(extra.iter().copied())
+            .map(|(name, value)| format!("{}=\"{}\"", name, escape_label_value(value)))
+            .collect();
+        if labels.is_empty() {
+            String::new()
+        } else {
+            format!("{{{}}}", labels.join(","))
+        }
+    }
+
+    fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(out, "# HELP {}{} {}", METRIC_PREFIX, name, help.trim());
+        let _ = writeln!(out, "# TYPE {}{} {}", METRIC_PREFIX, name, kind);
+    }
+
+    fn sample(&self, out: &mut String, name: &str, extra: &[(&str, &str)], value: impl ToString) {
+        let _ = writeln!(
+            out,
+            "{}{}{} {}",
+            METRIC_PREFIX,
+            name,
+            self.label_set(extra),
+            value.to_string()
+        );
+    }
+
+    /// One gauge per `MemoryStats` field, named after it, e.g.
+    /// `linux_memory_mem_total_bytes`
+    pub fn render(&self, stats: &MemoryStats) -> String {
+        let mut out = String::new();
+        for ((field, kb), &help) in stats.iter_fields().zip(FIELD_DESCRIPTIONS) {
+            let name = format!("{}_bytes", field);
+            Self::header(&mut out, &name, "gauge", help);
+            self.sample(&mut out, &name, &[], kb.saturating_mul(1024));
+        }
+        out
+    }
+
+    /// Pressure stall averages (`linux_memory_psi_avg_percent`) and totals
+    /// (`linux_memory_psi_stall_seconds_total`), labelled by resource, kind
+    /// ("some"/"full") and, for averages, window in seconds
+    pub fn render_psi(&self, psi: &PsiStats) -> String {
+        let resources: [(&str, &PsiResource); 3] =
+            [("memory", &psi.memory), ("cpu", &psi.cpu), ("io", &psi.io)];
+        let mut out = String::new();
+
+        Self::header(
+            &mut out,
+            "psi_avg_percent",
+            "gauge",
+            "Share of time tasks were stalled on the resource, averaged over the window",
+        );
+        for (resource, r) in resources {
+            for (kind, averages) in [
+                ("some", [r.some_avg10, r.some_avg60, r.some_avg300]),
+                ("full", [r.full_avg10, r.full_avg60, r.full_avg300]),
+            ] {
+                for (window, value) in ["10", "60", "300"].into_iter().zip(averages) {
+                    let labels = [("resource", resource), ("kind", kind), ("window", window)];
+                    self.sample(&mut out, "psi_avg_percent", &labels, value);
+                }
+            }
+        }
+
+        Self::header(
+            &mut out,
+            "psi_stall_seconds_total",
+            "counter",
+            "Total time tasks were stalled on the resource",
+        );
+        for (resource, r) in resources {
+            for (kind, total_us) in [("some", r.some_total), ("full", r.full_total)] {
+                let labels = [("resource", resource), ("kind", kind)];
+                self.sample(
+                    &mut out,
+                    "psi_stall_seconds_total",
+                    &labels,
+                    total_us as f64 / 1_000_000.0,
+                );
+            }
+        }
+        out
+    }
+
+    /// One counter per named `VmStats` field, e.g.
+    /// `linux_memory_vmstat_pgmajfault_total`
+    pub fn render_vmstat(&self, vmstat: &VmStats) -> String {
+        let mut out = String::new();
+        for &(field, help) in VMSTAT_COUNTERS {
+            let name = format!("vmstat_{}_total", field);
+            Self::header(&mut out, &name, "counter", help);
+            self.sample(&mut out, &name, &[], vmstat.get(field).unwrap_or_default());
+        }
+        out
+    }
+
+    /// KSM page gauges, full scan counter and saved bytes, e.g.
+    /// `linux_memory_ksm_pages_sharing`
+    pub fn render_ksm(&self, ksm: &KsmStats) -> String {
+        let mut out = String::new();
+        let gauges = [
+            ("ksm_run", "1 while ksmd is merging", ksm.run),
+            (
+                "ksm_pages_shared",
+                "Shared pages in use (one per set of merged pages)",
+                ksm.pages_shared,
+            ),
+            (
+                "ksm_pages_sharing",
+                "Additional sites mapping a shared page",
+                ksm.pages_sharing,
+            ),
+            (
+                "ksm_pages_unshared",
+                "Unique pages ksmd checked that may merge later",
+                ksm.pages_unshared,
+            ),
+            (
+                "ksm_pages_volatile",
+                "Pages changing too fast to merge",
+                ksm.pages_volatile,
+            ),
+            (
+                "ksm_saved_bytes",
+                "Memory KSM is currently saving",
+                ksm.saved_bytes(),
+            ),
+        ];
+        for (name, help, value) in gauges {
+            Self::header(&mut out, name, "gauge", help);
+            self.sample(&mut out, name, &[], value);
+        }
+
+        Self::header(
+            &mut out,
+            "ksm_full_scans_total",
+            "counter",
+            "Passes ksmd has completed over all mergeable areas",
+        );
+        self.sample(&mut out, "ksm_full_scans_total", &[], ksm.full_scans);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemoryUtils;
+
+    /// Every sample's metric has HELP and TYPE lines before it, and names
+    /// are valid Prometheus identifiers
+    fn assert_valid_exposition(text: &str) {
+        let mut declared = Vec::new();
+        for line in text.lines() {
+            if let Some(rest) = line.strip_prefix("# TYPE ") {
+                declared.push(rest.split(' ').next().unwrap().to_string());
+                continue;
+            }
+            if line.starts_with("# HELP ") {
+                continue;
+            }
+            let name_end = line.find(['{', ' ']).unwrap();
+            let name = &line[..name_end];
+            assert!(name.starts_with(METRIC_PREFIX), "{}", line);
+            assert!(
+                name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'),
+                "{}",
+                line
+            );
+            assert!(declared.iter().any(|d| d == name), "undeclared: {}", line);
+            let value = line.rsplit(' ').next().unwrap();
+            assert!(value.parse::<f64>().is_ok(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_render_meminfo() {
+        let stats = MemoryStats::builder()
+            .mem_total(1024)
+            .active_file(2)
+            .s_unreclaimable(3)
+            .build();
+        let text = PrometheusExporter::new().render(&stats);
+        assert_valid_exposition(&text);
+
+        assert!(text.contains("# TYPE linux_memory_mem_total_bytes gauge\n"));
+        assert!(text.contains("linux_memory_mem_total_bytes 1048576\n"));
+        assert!(text.contains("linux_memory_active_file_bytes 2048\n"));
+        assert!(text.contains("linux_memory_s_unreclaimable_bytes 3072\n"));
+        assert!(
+            text.contains("# HELP linux_memory_mem_available_bytes Estimate of memory available")
+        );
+        assert_eq!(
+            text.matches("# TYPE").count(),
+            MemoryStats::field_names().len()
+        );
+    }
+
+    #[test]
+    fn test_labels_are_escaped_and_merged() {
+        let exporter =
+            PrometheusExporter::new().with_labels(&[("host", "db\"1\""), ("dc", "a\\b")]);
+        let text = exporter.render(&MemoryStats::default());
+        assert!(
+            text.contains("linux_memory_mem_free_bytes{host=\"db\\\"1\\\"\",dc=\"a\\\\b\"} 0\n")
+        );
+
+        let psi = PsiStats {
+            memory: PsiResource {
+                some_avg10: 1.5,
+                full_total: 2_500_000,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let text = exporter.render_psi(&psi);
+        assert_valid_exposition(&text);
+        assert!(text.contains(
+            "linux_memory_psi_avg_percent{host=\"db\\\"1\\\"\",dc=\"a\\\\b\",resource=\"memory\",kind=\"some\",window=\"10\"} 1.5\n"
+        ));
+        assert!(text.contains("resource=\"memory\",kind=\"full\"} 2.5\n"));
+    }
+
+    #[test]
+    fn test_render_vmstat_and_ksm() {
+        let vmstat = VmStats {
+            pgmajfault: 42,
+            ..Default::default()
+        };
+        let text = PrometheusExporter::new().render_vmstat(&vmstat);
+        assert_valid_exposition(&text);
+        assert!(
+            text.contains(
+                "# HELP linux_memory_vmstat_pgmajfault_total Page faults that needed I/O\n"
+            )
+        );
+        assert!(text.contains("# TYPE linux_memory_vmstat_pgmajfault_total counter\n"));
+        assert!(text.contains("linux_memory_vmstat_pgmajfault_total 42\n"));
+        assert_eq!(text.matches("# TYPE").count(), VMSTAT_COUNTERS.len());
+
+        let ksm = KsmStats {
+            run: 1,
+            pages_sharing: 10,
+            full_scans: 3,
+            ..Default::default()
+        };
+        let text = PrometheusExporter::new().render_ksm(&ksm);
+        assert_valid_exposition(&text);
+        assert!(text.contains(&format!(
+            "linux_memory_ksm_saved_bytes {}\n",
+            10 * MemoryUtils::get_page_size()
+        )));
+        assert!(text.contains("linux_memory_ksm_full_scans_total 3\n"));
+    }
+}
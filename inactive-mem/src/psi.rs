@@ -0,0 +1,180 @@
+use crate::{MemoryError, PressureLevel, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Pressure stall information for one resource, as in /proc/pressure/*
+///
+/// `some` is the share of time at least one task was stalled on the
+/// resource, `full` the share of time all non-idle tasks were; averages are
+/// percentages over 10s/60s/300s windows and totals are microseconds.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PsiResource {
+    pub some_avg10: f64,
+    pub some_avg60: f64,
+    pub some_avg300: f64,
+    pub some_total: u64,
+    pub full_avg10: f64,
+    pub full_avg60: f64,
+    pub full_avg300: f64,
+    pub full_total: u64,
+}
+
+impl PsiResource {
+    /// Read /proc/pressure/memory (Linux 4.20+ with CONFIG_PSI)
+    pub fn memory() -> Result<Self> {
+        Self::read_from(Path::new("/proc/pressure/memory"))
+    }
+
+    /// Read a file laid out like /proc/pressure/memory
+    pub fn read_from(path: &Path) -> Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Parse `some`/`full` lines of `key=value` pairs
+    ///
+    /// /proc/pressure/cpu has no `full` line on older kernels; its fields
+    /// stay 0.
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut psi = PsiResource::default();
+        let mut seen_some = false;
+
+        for line in content.lines() {
+            let mut parts = line.split_whitespace();
+            let (avg10, avg60, avg300, total) = match parts.next() {
+                Some("some") => {
+                    seen_some = true;
+                    (
+                        &mut psi.some_avg10,
+                        &mut psi.some_avg60,
+                        &mut psi.some_avg300,
+                        &mut psi.some_total,
+                    )
+                }
+                Some("full") => (
+                    &mut psi.full_avg10,
+                    &mut psi.full_avg60,
+                    &mut psi.full_avg300,
+                    &mut psi.full_total,
+                ),
+                _ => continue,
+            };
+
+            for pair in parts {
+                let Some((key, value)) = pair.split_once('=') else {
+                    continue;
+                };
+                let invalid = || MemoryError::ParseError(format!("Invalid PSI value: {}", pair));
+                match key {
+                    "avg10" => *avg10 = value.parse().map_err(|_| invalid())?,
+                    "avg60" => *avg60 = value.parse().map_err(|_| invalid())?,
+                    "avg300" => *avg300 = value.parse().map_err(|_| invalid())?,
+                    "total" => *total = value.parse().map_err(|_| invalid())?,
+                    _ => {}
+                }
+            }
+        }
+
+        if !seen_some {
+            return Err(MemoryError::FieldNotFound("some".to_string()));
+        }
+        Ok(psi)
+    }
+}
+
+/// Pressure stall information for memory, CPU and I/O
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PsiStats {
+    pub memory: PsiResource,
+    pub cpu: PsiResource,
+    pub io: PsiResource,
+}
+
+impl PsiStats {
+    /// Read all three files under /proc/pressure
+    pub fn current() -> Result<Self> {
+        Self::read_from(Path::new("/proc/pressure"))
+    }
+
+    /// Read `memory`, `cpu` and `io` from a directory laid out like
+    /// /proc/pressure
+    pub fn read_from(dir: &Path) -> Result<Self> {
+        Ok(PsiStats {
+            memory: PsiResource::read_from(&dir.join("memory"))?,
+            cpu: PsiResource::read_from(&dir.join("cpu"))?,
+            io: PsiResource::read_from(&dir.join("io"))?,
+        })
+    }
+
+    /// Classify memory `some_avg10`, the share of the last 10s any task
+    /// spent waiting on memory: under 1% Low, under 10% Medium, under 25%
+    /// High, otherwise Critical
+    ///
+    /// Unlike `MemoryPressure::from_stats`, this reflects stalls actually
+    /// suffered rather than how much memory is left.
+    pub fn memory_pressure_level(&self) -> PressureLevel {
+        match self.memory.some_avg10 {
+            p if p < 1.0 => PressureLevel::Low,
+            p if p < 10.0 => PressureLevel::Medium,
+            p if p < 25.0 => PressureLevel::High,
+            _ => PressureLevel::Critical,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_psi() {
+        let psi = PsiResource::parse(
+            "some avg10=1.50 avg60=0.75 avg300=0.10 total=123456\n\
+             full avg10=0.50 avg60=0.25 avg300=0.00 total=65432\n",
+        )
+        .unwrap();
+        assert_eq!(psi.some_avg10, 1.5);
+        assert_eq!(psi.some_avg300, 0.1);
+        assert_eq!(psi.some_total, 123456);
+        assert_eq!(psi.full_avg60, 0.25);
+        assert_eq!(psi.full_total, 65432);
+    }
+
+    #[test]
+    fn test_parse_psi_errors() {
+        assert!(PsiResource::parse("").is_err());
+        assert!(PsiResource::parse("some avg10=abc avg60=0 avg300=0 total=0\n").is_err());
+    }
+
+    #[test]
+    fn test_read_psi_stats() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let write = |name: &str, some_avg10: &str| {
+            fs::write(
+                dir.path().join(name),
+                format!(
+                    "some avg10={} avg60=0.00 avg300=0.00 total=100\n\
+                     full avg10=0.00 avg60=0.00 avg300=0.00 total=50\n",
+                    some_avg10
+                ),
+            )
+            .unwrap();
+        };
+        write("memory", "12.00");
+        write("cpu", "3.00");
+        write("io", "0.50");
+
+        let psi = PsiStats::read_from(dir.path()).unwrap();
+        assert_eq!(psi.memory.some_avg10, 12.0);
+        assert_eq!(psi.cpu.some_avg10, 3.0);
+        assert_eq!(psi.io.full_total, 50);
+        assert_eq!(psi.memory_pressure_level(), PressureLevel::High);
+
+        let calm = PsiStats::default();
+        assert_eq!(calm.memory_pressure_level(), PressureLevel::Low);
+
+        // A missing resource file is an error
+        fs::remove_file(dir.path().join("io")).unwrap();
+        assert!(PsiStats::read_from(dir.path()).is_err());
+    }
+}
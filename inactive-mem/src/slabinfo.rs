@@ -0,0 +1,210 @@
+use crate::{MemoryError, MemoryStats, MemoryUtils, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+/// One cache line of /proc/slabinfo (format version 2.1)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlabEntry {
+    pub name: String,
+    pub active_objs: u64,
+    pub num_objs: u64,
+    /// Object size in bytes
+    pub objsize: u64,
+    pub objperslab: u64,
+    pub pagesperslab: u64,
+    pub limit: u64,
+    pub batchcount: u64,
+    pub sharedfactor: u64,
+    pub active_slabs: u64,
+    pub num_slabs: u64,
+    pub sharedavail: u64,
+}
+
+impl SlabEntry {
+    /// Memory held by the cache's slabs, including free objects
+    pub fn memory_bytes(&self) -> u64 {
+        self.num_slabs
+            .saturating_mul(self.pagesperslab)
+            .saturating_mul(MemoryUtils::get_page_size())
+    }
+
+    /// Memory in objects actually allocated
+    pub fn active_bytes(&self) -> u64 {
+        self.active_objs.saturating_mul(self.objsize)
+    }
+}
+
+/// Slab totals from /proc/slabinfo next to the meminfo figures
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlabCrossCheck {
+    /// Sum of all caches in /proc/slabinfo (KB)
+    pub slabinfo_kb: u64,
+    /// Slab from /proc/meminfo (KB)
+    pub meminfo_slab_kb: u64,
+    /// SReclaimable + SUnreclaim from /proc/meminfo (KB)
+    pub meminfo_split_kb: u64,
+}
+
+impl SlabCrossCheck {
+    /// slabinfo total minus meminfo Slab (KB)
+    pub fn difference_kb(&self) -> i64 {
+        self.slabinfo_kb as i64 - self.meminfo_slab_kb as i64
+    }
+
+    /// Whether the sources agree within `tolerance_percent` of meminfo Slab,
+    /// and meminfo's reclaimable/unreclaimable split adds up
+    pub fn is_consistent(&self, tolerance_percent: f64) -> bool {
+        let tolerance = self.meminfo_slab_kb as f64 * tolerance_percent / 100.0;
+        self.meminfo_split_kb == self.meminfo_slab_kb
+            && (self.difference_kb().unsigned_abs() as f64) <= tolerance
+    }
+}
+
+/// Per-cache slab allocator statistics
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SlabInfo {
+    pub entries: Vec<SlabEntry>,
+}
+
+impl SlabInfo {
+    /// Read current slab caches from /proc/slabinfo (root only)
+    pub fn current() -> Result<Self> {
+        let content = fs::read_to_string("/proc/slabinfo")?;
+        Self::parse(&content)
+    }
+
+    /// Parse /proc/slabinfo content
+    ///
+    /// Lines look like `name active num size perslab pages : tunables l b s
+    /// : slabdata active num avail`; the version and `#` header lines are
+    /// skipped.
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut entries = Vec::new();
+
+        for line in content.lines() {
+            if line.starts_with("slabinfo") || line.starts_with('#') || line.trim().is_empty() {
+                continue;
+            }
+
+            let mut parts = line
+                .split_whitespace()
+                .filter(|part| !matches!(*part, ":" | "tunables" | "slabdata"));
+            let name = parts.next().unwrap_or_default().to_string();
+            let mut values = [0u64; 11];
+            for value in values.iter_mut() {
+                let field = parts.next().ok_or_else(|| {
+                    MemoryError::ParseError(format!("Truncated slabinfo line: {}", line))
+                })?;
+                *value = field
+                    .parse::<u64>()
+                    .map_err(|_| MemoryError::ParseError(format!("Invalid number: {}", field)))?;
+            }
+
+            let [
+                active_objs,
+                num_objs,
+                objsize,
+                objperslab,
+                pagesperslab,
+                limit,
+                batchcount,
+                sharedfactor,
+                active_slabs,
+                num_slabs,
+                sharedavail,
+            ] = values;
+            entries.push(SlabEntry {
+                name,
+                active_objs,
+                num_objs,
+                objsize,
+                objperslab,
+                pagesperslab,
+                limit,
+                batchcount,
+                sharedfactor,
+                active_slabs,
+                num_slabs,
+                sharedavail,
+            });
+        }
+
+        Ok(SlabInfo { entries })
+    }
+
+    /// Memory held by all slab caches
+    pub fn total_bytes(&self) -> u64 {
+        self.entries.iter().map(SlabEntry::memory_bytes).sum()
+    }
+
+    /// The `n` caches holding the most memory, largest first
+    pub fn top_n_by_memory(&self, n: usize) -> Vec<&SlabEntry> {
+        let mut entries: Vec<&SlabEntry> = self.entries.iter().collect();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.memory_bytes()));
+        entries.truncate(n);
+        entries
+    }
+
+    /// Compare the slabinfo total with meminfo's Slab fields
+    pub fn cross_check(&self, stats: &MemoryStats) -> SlabCrossCheck {
+        SlabCrossCheck {
+            slabinfo_kb: self.total_bytes() / 1024,
+            meminfo_slab_kb: stats.slab,
+            meminfo_split_kb: stats.s_reclaimable + stats.s_unreclaimable,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_SLABINFO: &str = "\
+slabinfo - version: 2.1
+# name            <active_objs> <num_objs> <objsize> <objperslab> <pagesperslab> : tunables <limit> <batchcount> <sharedfactor> : slabdata <active_slabs> <num_slabs> <sharedavail>
+dentry            189000 190000    192   21    1 : tunables    0    0    0 : slabdata   9048   9048      0
+kmalloc-8k           234    244   8192    4    8 : tunables    0    0    0 : slabdata     61     61      0
+ext4_inode_cache   50000  50400   1096   29    8 : tunables    0    0    0 : slabdata   1738   1738      0
+";
+
+    #[test]
+    fn test_parse_slabinfo() {
+        let info = SlabInfo::parse(SAMPLE_SLABINFO).unwrap();
+        assert_eq!(info.entries.len(), 3);
+        let dentry = &info.entries[0];
+        assert_eq!(dentry.name, "dentry");
+        assert_eq!(dentry.objsize, 192);
+        assert_eq!(dentry.num_slabs, 9048);
+        assert_eq!(dentry.memory_bytes(), 9048 * MemoryUtils::get_page_size());
+        assert_eq!(info.entries[1].pagesperslab, 8);
+
+        assert!(SlabInfo::parse("dentry 1 2 3\n").is_err());
+    }
+
+    #[test]
+    fn test_top_slabs_and_cross_check() {
+        let info = SlabInfo::parse(SAMPLE_SLABINFO).unwrap();
+        let top: Vec<&str> = info
+            .top_n_by_memory(2)
+            .iter()
+            .map(|e| e.name.as_str())
+            .collect();
+        assert_eq!(top, ["ext4_inode_cache", "dentry"]);
+        assert_eq!(
+            info.total_bytes(),
+            (9048 + 61 * 8 + 1738 * 8) * MemoryUtils::get_page_size()
+        );
+
+        // 9048 + 488 + 13904 pages = 93760 KB
+        let stats = MemoryStats::builder()
+            .slab(94000)
+            .s_reclaimable(80000)
+            .s_unreclaimable(14000)
+            .build();
+        let check = info.cross_check(&stats);
+        assert_eq!(check.slabinfo_kb, 93760);
+        assert_eq!(check.difference_kb(), -240);
+        assert!(check.is_consistent(1.0));
+        assert!(!check.is_consistent(0.1));
+    }
+}
@@ -0,0 +1,248 @@
+use crate::{MemoryError, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+/// One mapping (VMA) of /proc/PID/smaps; sizes are in KB
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SmapsEntry {
+    pub start_addr: u64,
+    pub end_addr: u64,
+    /// e.g. "r-xp"
+    pub permissions: String,
+    pub offset: u64,
+    /// "major:minor" of the backing device, "00:00" for anonymous memory
+    pub device: String,
+    /// 0 for mappings not backed by a file
+    pub inode: u64,
+    /// File path or a pseudo-name like "[heap]"; empty for anonymous mappings
+    pub pathname: String,
+    pub size: u64,
+    pub rss: u64,
+    pub pss: u64,
+    pub shared_clean: u64,
+    pub shared_dirty: u64,
+    pub private_clean: u64,
+    pub private_dirty: u64,
+    pub referenced: u64,
+    pub anonymous: u64,
+    pub swap: u64,
+    pub swap_pss: u64,
+}
+
+/// Next whitespace-separated field of `rest`, advancing past it
+fn next_field<'a>(rest: &mut &'a str) -> Option<&'a str> {
+    let trimmed = rest.trim_start();
+    let end = trimmed.find(char::is_whitespace).unwrap_or(trimmed.len());
+    let (field, remainder) = trimmed.split_at(end);
+    *rest = remainder;
+    (!field.is_empty()).then_some(field)
+}
+
+impl SmapsEntry {
+    /// Parse a mapping header: `start-end perms offset dev inode [pathname]`
+    fn parse_header(line: &str) -> Result<Self> {
+        let invalid = || MemoryError::ParseError(format!("Invalid smaps header: {}", line));
+        let hex = |s: &str| u64::from_str_radix(s, 16).map_err(|_| invalid());
+
+        let mut rest = line;
+        let (start, end) = next_field(&mut rest)
+            .and_then(|range| range.split_once('-'))
+            .ok_or_else(invalid)?;
+        let permissions = next_field(&mut rest).ok_or_else(invalid)?;
+        let offset = next_field(&mut rest).ok_or_else(invalid)?;
+        let device = next_field(&mut rest).ok_or_else(invalid)?;
+        let inode = next_field(&mut rest).ok_or_else(invalid)?;
+
+        Ok(SmapsEntry {
+            start_addr: hex(start)?,
+            end_addr: hex(end)?,
+            permissions: permissions.to_string(),
+            offset: hex(offset)?,
+            device: device.to_string(),
+            inode: inode.parse().map_err(|_| invalid())?,
+            // Paths may contain spaces, so take the rest of the line
+            pathname: rest.trim().to_string(),
+            ..Default::default()
+        })
+    }
+
+    /// Parse every mapping of /proc/PID/smaps content
+    pub fn parse_all(content: &str) -> Result<Vec<Self>> {
+        let mut entries: Vec<SmapsEntry> = Vec::new();
+
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once(':').filter(|(key, _)| {
+                // Header lines start with the address range, never `Key:`
+                !key.contains('-') && !key.contains(' ')
+            }) else {
+                if !line.trim().is_empty() {
+                    entries.push(Self::parse_header(line)?);
+                }
+                continue;
+            };
+
+            let Some(entry) = entries.last_mut() else {
+                continue;
+            };
+            let field = match key {
+                "Size" => &mut entry.size,
+                "Rss" => &mut entry.rss,
+                "Pss" => &mut entry.pss,
+                "Shared_Clean" => &mut entry.shared_clean,
+                "Shared_Dirty" => &mut entry.shared_dirty,
+                "Private_Clean" => &mut entry.private_clean,
+                "Private_Dirty" => &mut entry.private_dirty,
+                "Referenced" => &mut entry.referenced,
+                "Anonymous" => &mut entry.anonymous,
+                "Swap" => &mut entry.swap,
+                "SwapPss" => &mut entry.swap_pss,
+                _ => continue,
+            };
+            let value_str = value.split_whitespace().next().unwrap_or_default();
+            *field = value_str
+                .parse::<u64>()
+                .map_err(|_| MemoryError::ParseError(format!("Invalid number: {}", value_str)))?;
+        }
+
+        Ok(entries)
+    }
+
+    /// Whether the mapping is backed by a file rather than anonymous memory
+    pub fn is_file_backed(&self) -> bool {
+        self.inode != 0
+    }
+}
+
+/// A process's memory by kind, summed over its mappings (in KB)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SmapsSummary {
+    pub mappings: usize,
+    pub rss: u64,
+    pub pss: u64,
+    /// Anonymous pages, including copy-on-write copies of file pages
+    pub anonymous: u64,
+    /// Resident file pages, excluding anonymous copies
+    pub file_backed: u64,
+    /// Resident pages also mapped by other processes
+    pub shared: u64,
+    /// Resident pages of the main thread's `[stack]`
+    pub stack: u64,
+    /// Resident pages of `[heap]`
+    pub heap: u64,
+    pub swap: u64,
+}
+
+impl SmapsSummary {
+    /// Read and summarize /proc/PID/smaps
+    pub fn for_pid(pid: u32) -> Result<Self> {
+        let content = fs::read_to_string(format!("/proc/{}/smaps", pid))?;
+        Ok(Self::from_entries(&SmapsEntry::parse_all(&content)?))
+    }
+
+    pub fn from_entries(entries: &[SmapsEntry]) -> Self {
+        let mut summary = SmapsSummary {
+            mappings: entries.len(),
+            ..Default::default()
+        };
+        for entry in entries {
+            summary.rss += entry.rss;
+            summary.pss += entry.pss;
+            summary.anonymous += entry.anonymous;
+            summary.shared += entry.shared_clean + entry.shared_dirty;
+            summary.swap += entry.swap;
+            if entry.is_file_backed() {
+                summary.file_backed += entry.rss.saturating_sub(entry.anonymous);
+            }
+            match entry.pathname.as_str() {
+                "[stack]" => summary.stack += entry.rss,
+                "[heap]" => summary.heap += entry.rss,
+                _ => {}
+            }
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_SMAPS: &str = "\
+55d0c4a00000-55d0c4a21000 r-xp 00002000 fd:01 1048602                    /usr/bin/my app
+Size:                132 kB
+Rss:                 120 kB
+Pss:                  60 kB
+Shared_Clean:        120 kB
+Shared_Dirty:          0 kB
+Private_Clean:         0 kB
+Private_Dirty:         0 kB
+Referenced:          120 kB
+Anonymous:             0 kB
+Swap:                  0 kB
+SwapPss:               0 kB
+VmFlags: rd ex mr mw me dw sd
+55d0c5e3b000-55d0c5e5c000 rw-p 00000000 00:00 0                          [heap]
+Size:                132 kB
+Rss:                  64 kB
+Pss:                  64 kB
+Private_Dirty:        64 kB
+Anonymous:            64 kB
+Swap:                  8 kB
+SwapPss:               8 kB
+7f1c2a000000-7f1c2a400000 rw-p 00000000 00:00 0
+Size:               4096 kB
+Rss:                2048 kB
+Pss:                2048 kB
+Anonymous:          2048 kB
+Swap:                512 kB
+7ffc1e7d0000-7ffc1e7f1000 rw-p 00000000 00:00 0                          [stack]
+Size:                132 kB
+Rss:                  16 kB
+Pss:                  16 kB
+Anonymous:            16 kB
+";
+
+    #[test]
+    fn test_parse_smaps() {
+        let entries = SmapsEntry::parse_all(SAMPLE_SMAPS).unwrap();
+        assert_eq!(entries.len(), 4);
+
+        let text = &entries[0];
+        assert_eq!(text.start_addr, 0x55d0c4a00000);
+        assert_eq!(text.end_addr, 0x55d0c4a21000);
+        assert_eq!(text.permissions, "r-xp");
+        assert_eq!(text.offset, 0x2000);
+        assert_eq!(text.device, "fd:01");
+        assert_eq!(text.inode, 1048602);
+        assert_eq!(text.pathname, "/usr/bin/my app");
+        assert_eq!(text.shared_clean, 120);
+        assert!(text.is_file_backed());
+
+        assert_eq!(entries[1].pathname, "[heap]");
+        assert_eq!(entries[1].swap_pss, 8);
+        assert_eq!(entries[2].pathname, "");
+        assert!(!entries[2].is_file_backed());
+
+        assert!(SmapsEntry::parse_all("nonsense\n").is_err());
+    }
+
+    #[test]
+    fn test_smaps_summary() {
+        let summary = SmapsSummary::from_entries(&SmapsEntry::parse_all(SAMPLE_SMAPS).unwrap());
+        assert_eq!(summary.mappings, 4);
+        assert_eq!(summary.rss, 120 + 64 + 2048 + 16);
+        assert_eq!(summary.anonymous, 64 + 2048 + 16);
+        assert_eq!(summary.file_backed, 120);
+        assert_eq!(summary.shared, 120);
+        assert_eq!(summary.heap, 64);
+        assert_eq!(summary.stack, 16);
+        assert_eq!(summary.swap, 520);
+    }
+
+    #[test]
+    fn test_own_smaps() {
+        let summary = SmapsSummary::for_pid(std::process::id()).unwrap();
+        assert!(summary.mappings > 0);
+        assert!(summary.rss > 0);
+    }
+}
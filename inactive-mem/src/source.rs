@@ -0,0 +1,141 @@
+use crate::{
+    CgroupMemoryStats, ExtendedMemoryStats, MemoryError, MemorySnapshot, MemoryStats, Result,
+    SnapshotVmCounters, VmStats,
+};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Where the monitors read memory stats from
+///
+/// `ProcMemSource` reads the live system, `CgroupMemSource` one cgroup;
+/// `MockMemSource` replays scripted stats so monitor logic can be tested
+/// deterministically.
+pub trait MemorySource: Send + Sync {
+    /// Current memory stats
+    fn current(&self) -> Result<MemoryStats>;
+
+    /// vmstat counters to attach to snapshots, if the source has any
+    fn vmstat(&self) -> Option<VmStats> {
+        None
+    }
+
+    /// (SwapTotal, SwapFree) in KB, or `None` if the source doesn't report
+    /// swap or it couldn't be read
+    fn swap(&self) -> Option<(u64, u64)> {
+        None
+    }
+
+    /// Snapshot of the current stats, stamped with the current time
+    fn snapshot(&self) -> Result<MemorySnapshot> {
+        let mut snapshot = MemorySnapshot::from_stats(self.current()?);
+        snapshot.vmstat = self.vmstat().as_ref().map(SnapshotVmCounters::from);
+        Ok(snapshot)
+    }
+}
+
+/// Reads /proc/meminfo and /proc/vmstat
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProcMemSource;
+
+impl MemorySource for ProcMemSource {
+    fn current(&self) -> Result<MemoryStats> {
+        MemoryStats::current()
+    }
+
+    fn vmstat(&self) -> Option<VmStats> {
+        VmStats::current().ok()
+    }
+
+    fn swap(&self) -> Option<(u64, u64)> {
+        let extended = ExtendedMemoryStats::current().ok()?;
+        Some((
+            extended.swap_total.unwrap_or(0),
+            extended.swap_free.unwrap_or(0),
+        ))
+    }
+}
+
+/// Reads a cgroup v2 group's memory files, reported relative to its limit
+/// (see `CgroupMemoryStats::to_memory_stats`)
+#[derive(Debug, Clone)]
+pub struct CgroupMemSource {
+    path: PathBuf,
+    /// Host MemTotal in KB, read once when the source is created
+    host_mem_total_kb: u64,
+}
+
+impl CgroupMemSource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        CgroupMemSource {
+            path: path.into(),
+            host_mem_total_kb: MemoryStats::current().map_or(0, |host| host.mem_total),
+        }
+    }
+}
+
+impl MemorySource for CgroupMemSource {
+    fn current(&self) -> Result<MemoryStats> {
+        Ok(CgroupMemoryStats::for_cgroup(&self.path, self.host_mem_total_kb)?.to_memory_stats())
+    }
+}
+
+/// Returns a scripted sequence of stats, one per call
+///
+/// Once the script runs out the last stats are repeated, so a background
+/// monitor keeps seeing a steady state; an empty script is an error.
+#[derive(Debug, Default)]
+pub struct MockMemSource {
+    remaining: Mutex<VecDeque<MemoryStats>>,
+    last: Mutex<Option<MemoryStats>>,
+}
+
+impl MockMemSource {
+    pub fn new(script: impl IntoIterator<Item = MemoryStats>) -> Self {
+        MockMemSource {
+            remaining: Mutex::new(script.into_iter().collect()),
+            last: Mutex::new(None),
+        }
+    }
+
+    /// Append stats to the end of the script
+    pub fn push(&self, stats: MemoryStats) {
+        self.remaining.lock().unwrap().push_back(stats);
+    }
+}
+
+impl MemorySource for MockMemSource {
+    fn current(&self) -> Result<MemoryStats> {
+        let mut last = self.last.lock().unwrap();
+        if let Some(next) = self.remaining.lock().unwrap().pop_front() {
+            *last = Some(next);
+        }
+        last.clone()
+            .ok_or_else(|| MemoryError::FieldNotFound("mock script is empty".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(mem_free: u64) -> MemoryStats {
+        MemoryStats::builder().mem_free(mem_free).build()
+    }
+
+    #[test]
+    fn test_mock_replays_then_repeats_last() {
+        let source = MockMemSource::new([stats(1), stats(2)]);
+        assert_eq!(source.current().unwrap().mem_free, 1);
+        assert_eq!(source.current().unwrap().mem_free, 2);
+        assert_eq!(source.current().unwrap().mem_free, 2);
+
+        source.push(stats(3));
+        assert_eq!(source.snapshot().unwrap().stats.mem_free, 3);
+    }
+
+    #[test]
+    fn test_empty_mock_is_an_error() {
+        assert!(MockMemSource::default().current().is_err());
+    }
+}
@@ -0,0 +1,192 @@
+use crate::{MemoryError, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+const VM_SYSCTL_DIR: &str = "/proc/sys/vm";
+
+/// Writeback, reclaim and overcommit settings from /proc/sys/vm
+///
+/// A field is `None` when the kernel does not expose that sysctl, as happens
+/// in some containers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VmTunables {
+    /// Percent of available memory that may be dirty before writers are
+    /// throttled into doing writeback themselves
+    pub dirty_ratio: Option<u32>,
+    /// Percent of available memory that may be dirty before background
+    /// writeback starts
+    pub dirty_background_ratio: Option<u32>,
+    /// Age in hundredths of a second after which dirty data is written back
+    pub dirty_expire_centisecs: Option<u32>,
+    /// Free memory in KB the kernel keeps in reserve; sets the zone watermarks
+    pub min_free_kbytes: Option<u64>,
+    /// 0 heuristic, 1 always allow, 2 never exceed the commit limit
+    pub overcommit_memory: Option<u8>,
+    /// Preference for swapping anonymous memory over dropping page cache
+    pub swappiness: Option<u8>,
+    /// How readily dentries and inodes are reclaimed relative to page cache;
+    /// 100 is neutral
+    pub vfs_cache_pressure: Option<u32>,
+}
+
+impl VmTunables {
+    /// Read the current settings from /proc/sys/vm
+    pub fn current() -> Result<Self> {
+        Self::read_from(Path::new(VM_SYSCTL_DIR))
+    }
+
+    /// Read settings from a directory laid out like /proc/sys/vm
+    pub fn read_from(dir: &Path) -> Result<Self> {
+        Ok(VmTunables {
+            dirty_ratio: read_optional_tunable(dir, "dirty_ratio")?,
+            dirty_background_ratio: read_optional_tunable(dir, "dirty_background_ratio")?,
+            dirty_expire_centisecs: read_optional_tunable(dir, "dirty_expire_centisecs")?,
+            min_free_kbytes: read_optional_tunable(dir, "min_free_kbytes")?,
+            overcommit_memory: read_optional_tunable(dir, "overcommit_memory")?,
+            swappiness: read_optional_tunable(dir, "swappiness")?,
+            vfs_cache_pressure: read_optional_tunable(dir, "vfs_cache_pressure")?,
+        })
+    }
+
+    /// Set vm.dirty_ratio, 0-100 (requires root privileges)
+    pub fn set_dirty_ratio(value: u32) -> io::Result<()> {
+        check_max("dirty_ratio", value, 100)?;
+        write_tunable(Path::new(VM_SYSCTL_DIR), "dirty_ratio", value)
+    }
+
+    /// Set vm.dirty_background_ratio, 0-100 (requires root privileges)
+    pub fn set_dirty_background_ratio(value: u32) -> io::Result<()> {
+        check_max("dirty_background_ratio", value, 100)?;
+        write_tunable(Path::new(VM_SYSCTL_DIR), "dirty_background_ratio", value)
+    }
+
+    /// Set vm.dirty_expire_centisecs (requires root privileges)
+    pub fn set_dirty_expire_centisecs(value: u32) -> io::Result<()> {
+        write_tunable(Path::new(VM_SYSCTL_DIR), "dirty_expire_centisecs", value)
+    }
+
+    /// Set vm.min_free_kbytes (requires root privileges)
+    pub fn set_min_free_kbytes(value: u64) -> io::Result<()> {
+        write_tunable(Path::new(VM_SYSCTL_DIR), "min_free_kbytes", value)
+    }
+
+    /// Set vm.overcommit_memory, 0-2 (requires root privileges)
+    pub fn set_overcommit_memory(value: u8) -> io::Result<()> {
+        check_max("overcommit_memory", value, 2)?;
+        write_tunable(Path::new(VM_SYSCTL_DIR), "overcommit_memory", value)
+    }
+
+    /// Set vm.swappiness, 0-200 (requires root privileges)
+    pub fn set_swappiness(value: u8) -> io::Result<()> {
+        check_max("swappiness", value, 200)?;
+        write_tunable(Path::new(VM_SYSCTL_DIR), "swappiness", value)
+    }
+
+    /// Set vm.vfs_cache_pressure (requires root privileges)
+    pub fn set_vfs_cache_pressure(value: u32) -> io::Result<()> {
+        write_tunable(Path::new(VM_SYSCTL_DIR), "vfs_cache_pressure", value)
+    }
+}
+
+fn read_tunable<T: FromStr>(dir: &Path, name: &str) -> Result<T> {
+    let content = fs::read_to_string(dir.join(name))?;
+    content.trim().parse::<T>().map_err(|_| {
+        MemoryError::ParseError(format!("Invalid value in vm.{}: {}", name, content.trim()))
+    })
+}
+
+/// Like `read_tunable`, but a missing file reads as `None`
+fn read_optional_tunable<T: FromStr>(dir: &Path, name: &str) -> Result<Option<T>> {
+    match read_tunable(dir, name) {
+        Ok(value) => Ok(Some(value)),
+        Err(MemoryError::ProcMemInfoRead(e)) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn check_max<T: PartialOrd + Display>(name: &str, value: T, max: T) -> io::Result<()> {
+    if value > max {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("vm.{} must be at most {}, got {}", name, max, value),
+        ));
+    }
+    Ok(())
+}
+
+/// Write `value` to `dir/name`, explaining a permission failure
+fn write_tunable(dir: &Path, name: &str, value: impl Display) -> io::Result<()> {
+    let path = dir.join(name);
+    fs::write(&path, value.to_string()).map_err(|e| match e.kind() {
+        io::ErrorKind::PermissionDenied => io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("Writing {} requires root privileges", path.display()),
+        ),
+        _ => e,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_read_vm_tunables() {
+        let dir = TempDir::new().unwrap();
+        for (name, value) in [
+            ("dirty_ratio", "20\n"),
+            ("dirty_background_ratio", "10\n"),
+            ("dirty_expire_centisecs", "3000\n"),
+            ("min_free_kbytes", "67584\n"),
+            ("overcommit_memory", "0\n"),
+            ("swappiness", "60\n"),
+            ("vfs_cache_pressure", "100\n"),
+        ] {
+            fs::write(dir.path().join(name), value).unwrap();
+        }
+
+        let tunables = VmTunables::read_from(dir.path()).unwrap();
+        assert_eq!(
+            tunables,
+            VmTunables {
+                dirty_ratio: Some(20),
+                dirty_background_ratio: Some(10),
+                dirty_expire_centisecs: Some(3000),
+                min_free_kbytes: Some(67584),
+                overcommit_memory: Some(0),
+                swappiness: Some(60),
+                vfs_cache_pressure: Some(100),
+            }
+        );
+
+        fs::write(dir.path().join("swappiness"), "lots\n").unwrap();
+        assert!(matches!(
+            VmTunables::read_from(dir.path()),
+            Err(MemoryError::ParseError(_))
+        ));
+
+        fs::remove_file(dir.path().join("swappiness")).unwrap();
+        fs::remove_file(dir.path().join("vfs_cache_pressure")).unwrap();
+        let tunables = VmTunables::read_from(dir.path()).unwrap();
+        assert_eq!(tunables.swappiness, None);
+        assert_eq!(tunables.vfs_cache_pressure, None);
+        assert_eq!(tunables.dirty_ratio, Some(20));
+    }
+
+    #[test]
+    fn test_write_tunable() {
+        let dir = TempDir::new().unwrap();
+        write_tunable(dir.path(), "swappiness", 10u8).unwrap();
+        assert_eq!(read_tunable::<u8>(dir.path(), "swappiness").unwrap(), 10);
+
+        let err = VmTunables::set_swappiness(201).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let err = VmTunables::set_overcommit_memory(3).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,255 @@
+use crate::{MemoryStats, Result};
+use serde::{Deserialize, Serialize};
+
+/// How wrong a `ValidationWarning` is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ValidationSeverity {
+    /// Fields disagree in a way the kernel can produce briefly or in odd
+    /// setups, e.g. counters read at slightly different moments
+    Warning,
+    /// Physically impossible, so the source is broken or not /proc/meminfo
+    Error,
+}
+
+/// A value or combination of values that can't be right
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationWarning {
+    pub severity: ValidationSeverity,
+    pub message: String,
+}
+
+impl std::fmt::Display for ValidationWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}: {}", self.severity, self.message)
+    }
+}
+
+impl MemoryStats {
+    /// Check for impossible or inconsistent values, e.g. from a hand-edited
+    /// dump or a source that isn't really /proc/meminfo
+    ///
+    /// Empty for any sane reading. Anonymous memory above `used_memory()`
+    /// and a Slab total that doesn't match its parts are only warnings:
+    /// shmem/tmpfs pages count as both anon and Cached, and the slab
+    /// counters aren't read atomically.
+    pub fn validate(&self) -> Vec<ValidationWarning> {
+        use ValidationSeverity::{Error, Warning};
+
+        // Saturate: bogus input is exactly what this is meant to catch
+        let file = self.active_file.saturating_add(self.inactive_file);
+        let anon = self.anon_memory();
+        let lru = self.active.saturating_add(self.inactive);
+        let slab = self.s_reclaimable.saturating_add(self.s_unreclaimable);
+        let cache = self.page_cache_size();
+        let checks = [
+            (
+                file > cache,
+                Error,
+                format!(
+                    "Active(file) + Inactive(file) ({} KB) exceeds Cached + Buffers ({} KB)",
+                    file, cache
+                ),
+            ),
+            (
+                lru > self.mem_total,
+                Error,
+                format!(
+                    "Active + Inactive ({} KB) exceeds MemTotal ({} KB)",
+                    lru, self.mem_total
+                ),
+            ),
+            (
+                self.mem_free > self.mem_total,
+                Error,
+                format!(
+                    "MemFree ({} KB) exceeds MemTotal ({} KB)",
+                    self.mem_free, self.mem_total
+                ),
+            ),
+            (
+                anon > self.used_memory(),
+                Warning,
+                format!(
+                    "Active(anon) + Inactive(anon) ({} KB) exceeds used memory ({} KB)",
+                    anon,
+                    self.used_memory()
+                ),
+            ),
+            (
+                slab != self.slab,
+                Warning,
+                format!(
+                    "SReclaimable + SUnreclaim ({} KB) differs from Slab ({} KB)",
+                    slab, self.slab
+                ),
+            ),
+            (
+                self.dirty > cache,
+                Error,
+                format!(
+                    "Dirty ({} KB) exceeds Cached + Buffers ({} KB)",
+                    self.dirty, cache
+                ),
+            ),
+            (
+                self.writeback > self.mem_total,
+                Error,
+                format!(
+                    "Writeback ({} KB) exceeds MemTotal ({} KB)",
+                    self.writeback, self.mem_total
+                ),
+            ),
+        ];
+
+        checks
+            .into_iter()
+            .filter(|(failed, _, _)| *failed)
+            .map(|(_, severity, message)| ValidationWarning { severity, message })
+            .collect()
+    }
+
+    /// Read /proc/meminfo and `validate` the result
+    pub fn current_validated() -> Result<(Self, Vec<ValidationWarning>)> {
+        let stats = Self::current()?;
+        let warnings = stats.validate();
+        Ok((stats, warnings))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sane() -> MemoryStats {
+        MemoryStats::builder()
+            .mem_total(1_000_000)
+            .mem_free(300_000)
+            .buffers(10_000)
+            .cached(400_000)
+            .active(350_000)
+            .inactive(300_000)
+            .active_file(200_000)
+            .inactive_file(150_000)
+            .active_anon(150_000)
+            .inactive_anon(100_000)
+            .dirty(1_000)
+            .writeback(10)
+            .slab(50_000)
+            .s_reclaimable(30_000)
+            .s_unreclaimable(20_000)
+            .build()
+    }
+
+    #[test]
+    fn test_validate_sane_stats() {
+        assert!(sane().validate().is_empty());
+    }
+
+    #[test]
+    fn test_validate_near_max_values_do_not_overflow() {
+        let stats = MemoryStats {
+            cached: u64::MAX - 1,
+            buffers: u64::MAX - 1,
+            active: u64::MAX,
+            inactive: u64::MAX,
+            active_file: u64::MAX,
+            inactive_file: u64::MAX,
+            active_anon: u64::MAX,
+            inactive_anon: u64::MAX,
+            s_reclaimable: u64::MAX,
+            s_unreclaimable: u64::MAX,
+            mem_free: u64::MAX,
+            ..sane()
+        };
+        let warnings = stats.validate();
+        assert!(
+            warnings
+                .iter()
+                .any(|w| w.message.starts_with("Active + Inactive")),
+            "{:?}",
+            warnings
+        );
+        assert!(
+            warnings
+                .iter()
+                .any(|w| w.message.starts_with("SReclaimable + SUnreclaim")),
+            "{:?}",
+            warnings
+        );
+    }
+
+    #[test]
+    fn test_validate_flags_each_check() {
+        let cases: [(MemoryStats, ValidationSeverity, &str); 7] = [
+            (
+                MemoryStats {
+                    inactive_file: 300_000,
+                    ..sane()
+                },
+                ValidationSeverity::Error,
+                "Active(file) + Inactive(file)",
+            ),
+            (
+                MemoryStats {
+                    inactive: 700_000,
+                    ..sane()
+                },
+                ValidationSeverity::Error,
+                "Active + Inactive",
+            ),
+            (
+                MemoryStats {
+                    mem_free: 1_000_001,
+                    mem_available: 0,
+                    ..sane()
+                },
+                ValidationSeverity::Error,
+                "MemFree",
+            ),
+            (
+                MemoryStats {
+                    inactive_anon: 200_000,
+                    ..sane()
+                },
+                ValidationSeverity::Warning,
+                "Active(anon) + Inactive(anon)",
+            ),
+            (
+                MemoryStats {
+                    slab: 60_000,
+                    ..sane()
+                },
+                ValidationSeverity::Warning,
+                "SReclaimable + SUnreclaim",
+            ),
+            (
+                MemoryStats {
+                    dirty: 500_000,
+                    ..sane()
+                },
+                ValidationSeverity::Error,
+                "Dirty",
+            ),
+            (
+                MemoryStats {
+                    writeback: 2_000_000,
+                    ..sane()
+                },
+                ValidationSeverity::Error,
+                "Writeback",
+            ),
+        ];
+
+        for (stats, severity, prefix) in cases {
+            let warnings = stats.validate();
+            assert!(
+                warnings
+                    .iter()
+                    .any(|w| w.severity == severity && w.message.starts_with(prefix)),
+                "{}: {:?}",
+                prefix,
+                warnings
+            );
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{MemoryError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+
+macro_rules! vmstat_counters {
+    ($($(#[doc = $doc:literal])* $field:ident,)*) => {
+        /// Name and doc comment of every named `VmStats` counter
+        pub const VMSTAT_COUNTERS: &[(&str, &str)] = &[
+            $((stringify!($field), concat!($($doc),*)),)*
+        ];
+
+        /// Kernel event counters from /proc/vmstat
+        ///
+        /// The most useful counters are named fields (0 if the kernel
+        /// doesn't report them); every other line is kept in `other`.
+        #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+        pub struct VmStats {
+            $($(#[doc = $doc])* pub $field: u64,)*
+            /// Remaining counters and `nr_*` gauges by name
+            pub other: HashMap<String, u64>,
+        }
+
+        /// Per-second rates of `VmStats` counters over an interval
+        #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+        pub struct VmStatRates {
+            $(pub $field: f64,)*
+            /// Rates of the event counters in `VmStats::other`
+            pub other: HashMap<String, f64>,
+        }
+
+        impl VmStats {
+            fn field_mut(&mut self, key: &str) -> Option<&mut u64> {
+                match key {
+                    $(stringify!($field) => Some(&mut self.$field),)*
+                    _ => None,
+                }
+            }
+
+            /// A counter by its /proc/vmstat name, named field or `other`
+            pub fn get(&self, key: &str) -> Option<u64> {
+                match key {
+                    $(stringify!($field) => Some(self.$field),)*
+                    _ => self.other.get(key).copied(),
+                }
+            }
+
+            /// Per-second rate of every counter between two readings
+            /// `duration_ms` apart
+            ///
+            /// Counters that went backwards (a reset) read as 0, as do all
+            /// rates over a zero duration. `nr_*` entries are current levels
+            /// rather than event counts, so they get no rate.
+            pub fn diff(before: &VmStats, after: &VmStats, duration_ms: u64) -> VmStatRates {
+                let rate = |before: u64, after: u64| {
+                    if duration_ms == 0 {
+                        0.0
+                    } else {
+                        after.saturating_sub(before) as f64 * 1000.0 / duration_ms as f64
+                    }
+                };
+
+                VmStatRates {
+                    $($field: rate(before.$field, after.$field),)*
+                    other: after
+                        .other
+                        .iter()
+                        .filter(|(name, _)| !name.starts_with("nr_"))
+                        .map(|(name, &value)| {
+                            let previous = before.other.get(name).copied().unwrap_or(value);
+                            (name.clone(), rate(previous, value))
+                        })
+                        .collect(),
+                }
+            }
+        }
+    };
+}
+
+vmstat_counters! {
+    /// KB paged in from block devices since boot
+    pgpgin,
+    /// KB paged out to block devices since boot
+    pgpgout,
+    /// Pages swapped in
+    pswpin,
+    /// Pages swapped out
+    pswpout,
+    /// Page faults, minor and major
+    pgfault,
+    /// Page faults that needed I/O
+    pgmajfault,
+    /// Pages scanned by kswapd
+    pgscan_kswapd,
+    /// Pages scanned by direct reclaim
+    pgscan_direct,
+    /// Pages reclaimed by kswapd
+    pgsteal_kswapd,
+    /// Pages reclaimed by direct reclaim
+    pgsteal_direct,
+    /// Evicted file pages read back in shortly after (cache thrashing)
+    workingset_refault_file,
+    /// Huge pages allocated at fault time
+    thp_fault_alloc,
+    /// Faults that wanted a huge page but fell back to small pages
+    thp_fault_fallback,
+    /// Allocations that stalled on memory compaction
+    compact_stall,
+    /// Compactions that failed to produce a free block
+    compact_fail,
+    /// Processes killed by the OOM killer
+    oom_kill,
+}
+
+impl VmStats {
+    /// Read current counters from /proc/vmstat
+    pub fn current() -> Result<Self> {
+        let content = fs::read_to_string("/proc/vmstat")?;
+        Self::parse(&content)
+    }
+
+    /// Parse /proc/vmstat content ("name value" per line)
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut stats = VmStats::default();
+
+        for line in content.lines() {
+            let Some((key, value_str)) = line.split_once(' ') else {
+                continue;
+            };
+            let value = value_str
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| MemoryError::ParseError(format!("Invalid number: {}", value_str)))?;
+            match stats.field_mut(key) {
+                Some(field) => *field = value,
+                None => {
+                    stats.other.insert(key.to_string(), value);
+                }
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vmstat() {
+        let stats = VmStats::parse("nr_free_pages 1000\npgpgin 4096\npgpgout 2048\n").unwrap();
+        assert_eq!(stats.pgpgin, 4096);
+        assert_eq!(stats.pgpgout, 2048);
+        assert_eq!(stats.oom_kill, 0);
+        assert_eq!(stats.other.get("nr_free_pages"), Some(&1000));
+    }
+
+    #[test]
+    fn test_vmstat_rates() {
+        let before = VmStats::parse(
+            "nr_free_pages 1000\npgfault 1000\npgmajfault 10\noom_kill 0\nballoon_inflate 5\n",
+        )
+        .unwrap();
+        let after = VmStats::parse(
+            "nr_free_pages 500\npgfault 5000\npgmajfault 4\noom_kill 1\nballoon_inflate 25\n",
+        )
+        .unwrap();
+
+        let rates = VmStats::diff(&before, &after, 2000);
+        assert_eq!(rates.pgfault, 2000.0);
+        assert_eq!(rates.oom_kill, 0.5);
+        // Went backwards: treated as a reset
+        assert_eq!(rates.pgmajfault, 0.0);
+        assert_eq!(rates.other.get("balloon_inflate"), Some(&10.0));
+        assert!(!rates.other.contains_key("nr_free_pages"));
+
+        assert_eq!(VmStats::diff(&before, &after, 0).pgfault, 0.0);
+    }
+}
@@ -0,0 +1,201 @@
+use crate::{MemoryError, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+/// Free pages and watermarks of a single zone from /proc/zoneinfo (in pages)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ZoneStats {
+    pub node: u32,
+    pub zone: String,
+    pub free: u64,
+    pub min: u64,
+    pub low: u64,
+    pub high: u64,
+    pub managed: u64,
+    /// Pages between the zone's first and last PFN, including holes
+    pub spanned: u64,
+    /// First PFN of the zone (0 for empty zones)
+    pub start_pfn: u64,
+}
+
+impl ZoneStats {
+    /// Free pages have fallen at least halfway from the low watermark to min.
+    /// Below low kswapd is already reclaiming; at min allocations stall in
+    /// direct reclaim and eventually OOM.
+    pub fn is_near_min(&self) -> bool {
+        if self.managed == 0 {
+            // Empty zones (e.g. Movable/Device) report watermarks but hold no pages
+            return false;
+        }
+        let threshold = self.min + self.low.saturating_sub(self.min) / 2;
+        self.free <= threshold
+    }
+}
+
+/// Per-zone watermark information for all NUMA nodes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ZoneInfo {
+    pub zones: Vec<ZoneStats>,
+}
+
+impl ZoneInfo {
+    /// Read current zone information from /proc/zoneinfo
+    pub fn current() -> Result<Self> {
+        let content = fs::read_to_string("/proc/zoneinfo")?;
+        Self::parse(&content)
+    }
+
+    /// Parse /proc/zoneinfo content
+    ///
+    /// Each zone starts with a `Node N, zone NAME` header; the free count and
+    /// watermarks follow as `pages free N`, `min N`, `low N`, `high N` lines.
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut zones = Vec::new();
+        let mut current: Option<ZoneStats> = None;
+
+        for line in content.lines() {
+            if let Some(header) = line.strip_prefix("Node ") {
+                if let Some(zone) = current.take() {
+                    zones.push(zone);
+                }
+
+                let (node, zone) = header.split_once(", zone").ok_or_else(|| {
+                    MemoryError::ParseError(format!("Invalid zoneinfo header: {}", line))
+                })?;
+                let node = node.trim().parse::<u32>().map_err(|_| {
+                    MemoryError::ParseError(format!("Invalid node number: {}", node))
+                })?;
+
+                current = Some(ZoneStats {
+                    node,
+                    zone: zone.trim().to_string(),
+                    ..Default::default()
+                });
+                continue;
+            }
+
+            let Some(zone) = current.as_mut() else {
+                continue;
+            };
+
+            let mut parts = line.split_whitespace();
+            let field = match (parts.next(), parts.next()) {
+                (Some("pages"), Some("free")) => &mut zone.free,
+                (Some("min"), _) => &mut zone.min,
+                (Some("low"), _) => &mut zone.low,
+                (Some("high"), _) => &mut zone.high,
+                (Some("managed"), _) => &mut zone.managed,
+                (Some("spanned"), _) => &mut zone.spanned,
+                (Some("start_pfn:"), _) => &mut zone.start_pfn,
+                _ => continue,
+            };
+
+            let value_str = line.split_whitespace().last().unwrap_or_default();
+            *field = value_str
+                .parse::<u64>()
+                .map_err(|_| MemoryError::ParseError(format!("Invalid number: {}", value_str)))?;
+        }
+
+        if let Some(zone) = current {
+            zones.push(zone);
+        }
+
+        Ok(ZoneInfo { zones })
+    }
+
+    /// Non-empty zones ordered by their first PFN
+    pub fn zones_by_pfn(&self) -> Vec<&ZoneStats> {
+        let mut zones: Vec<&ZoneStats> = self.zones.iter().filter(|z| z.spanned > 0).collect();
+        zones.sort_by_key(|z| z.start_pfn);
+        zones
+    }
+
+    /// Zones whose free pages are close to the min watermark
+    pub fn zones_near_min(&self) -> Vec<&ZoneStats> {
+        self.zones.iter().filter(|z| z.is_near_min()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_ZONEINFO: &str = r#"Node 0, zone      DMA
+  per-node stats
+      nr_inactive_anon 1234
+  pages free     3840
+        boost    0
+        min      42
+        low      52
+        high     62
+        spanned  4095
+        present  3998
+        managed  3840
+        protection: (0, 2966, 5884, 5884, 5884)
+  start_pfn:           1
+Node 0, zone   Normal
+  pages free     8400
+        boost    0
+        min      8312
+        low      10390
+        high     12468
+        spanned  786432
+        present  786432
+        managed  753664
+  pagesets
+    cpu: 0
+              count: 10
+              high:  378
+              batch: 63
+  node_unreclaimable:  0
+  start_pfn:           1048576
+Node 1, zone  Movable
+  pages free     0
+        min      32
+        low      32
+        high     32
+        managed  0"#;
+
+    #[test]
+    fn test_parse_zoneinfo() {
+        let info = ZoneInfo::parse(SAMPLE_ZONEINFO).unwrap();
+        assert_eq!(info.zones.len(), 3);
+
+        let normal = &info.zones[1];
+        assert_eq!(normal.node, 0);
+        assert_eq!(normal.zone, "Normal");
+        assert_eq!(normal.free, 8400);
+        assert_eq!(normal.min, 8312);
+        assert_eq!(normal.low, 10390);
+        // The per-cpu pageset "high:" must not override the watermark
+        assert_eq!(normal.high, 12468);
+        assert_eq!(normal.spanned, 786432);
+        assert_eq!(normal.start_pfn, 1048576);
+
+        assert_eq!(info.zones[2].node, 1);
+        assert_eq!(info.zones[2].zone, "Movable");
+    }
+
+    #[test]
+    fn test_zones_near_min() {
+        let info = ZoneInfo::parse(SAMPLE_ZONEINFO).unwrap();
+        let near_min = info.zones_near_min();
+
+        // Normal is just above min; empty Movable zone is ignored
+        assert_eq!(near_min.len(), 1);
+        assert_eq!(near_min[0].zone, "Normal");
+    }
+
+    #[test]
+    fn test_zones_by_pfn_skips_empty_zones() {
+        let info = ZoneInfo::parse(SAMPLE_ZONEINFO).unwrap();
+        let starts: Vec<u64> = info.zones_by_pfn().iter().map(|z| z.start_pfn).collect();
+        assert_eq!(starts, vec![1, 1048576]);
+    }
+
+    #[test]
+    fn test_current_zoneinfo() {
+        let info = ZoneInfo::current().unwrap();
+        assert!(!info.zones.is_empty());
+    }
+}
@@ -0,0 +1,325 @@
+//! Compact binary encoding of pages for shipping scans between machines
+//!
+//! Layout: varint page count, then per page a zigzag varint PFN delta
+//! (relative to previous PFN + 1, so contiguous ranges encode as 0) and a
+//! varint of the flags XOR the previous page's flags (repeats encode as 0).
+//! Streams are a sequence of length-prefixed frames; export files start with
+//! `EXPORT_MAGIC` so `--input` can recognize them.
+
+use crate::PageInfo;
+use std::collections::BTreeMap;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+/// Upper bound on a single frame, to reject corrupt length prefixes
+const MAX_FRAME_LEN: u32 = 256 * 1024 * 1024;
+
+/// Pages per frame when exporting a scan
+const EXPORT_FRAME_PAGES: usize = 64 * 1024;
+
+/// Leading bytes of a file written by `write_export`
+pub const EXPORT_MAGIC: &[u8; 4] = b"KPFB";
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(data: &[u8], pos: &mut usize) -> io::Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *data
+            .get(*pos)
+            .ok_or_else(|| invalid_data("truncated varint"))?;
+        *pos += 1;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid_data("varint too long"))
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+/// Encode pages into the compact delta/varint format
+pub fn encode_pages(pages: &[PageInfo]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(pages.len() * 2 + 10);
+    write_varint(&mut out, pages.len() as u64);
+
+    let mut expected_pfn = 0u64;
+    let mut prev_flags = 0u64;
+    for page in pages {
+        write_varint(&mut out, zigzag(page.pfn.wrapping_sub(expected_pfn) as i64));
+        write_varint(&mut out, page.flags.bits() ^ prev_flags);
+        expected_pfn = page.pfn.wrapping_add(1);
+        prev_flags = page.flags.bits();
+    }
+
+    out
+}
+
+/// Decode pages produced by `encode_pages`
+pub fn decode_pages(data: &[u8]) -> io::Result<Vec<PageInfo>> {
+    let mut pos = 0;
+    let count = read_varint(data, &mut pos)?;
+    // Every page takes at least two bytes, so a larger count is corrupt
+    if count > (data.len() / 2) as u64 {
+        return Err(invalid_data("page count exceeds data length"));
+    }
+
+    let mut pages = Vec::with_capacity(count as usize);
+    let mut expected_pfn = 0u64;
+    let mut prev_flags = 0u64;
+    for _ in 0..count {
+        let pfn = expected_pfn.wrapping_add(unzigzag(read_varint(data, &mut pos)?) as u64);
+        let flags = read_varint(data, &mut pos)? ^ prev_flags;
+        pages.push(PageInfo::new(pfn, flags));
+        expected_pfn = pfn.wrapping_add(1);
+        prev_flags = flags;
+    }
+
+    if pos != data.len() {
+        return Err(invalid_data("trailing bytes after pages"));
+    }
+    Ok(pages)
+}
+
+/// Write pages as one frame: a u32 little-endian length then the encoding
+pub fn write_frame<W: Write>(writer: &mut W, pages: &[PageInfo]) -> io::Result<()> {
+    let payload = encode_pages(pages);
+    let len = u32::try_from(payload.len())
+        .ok()
+        .filter(|&len| len <= MAX_FRAME_LEN)
+        .ok_or_else(|| invalid_data("frame too large"))?;
+    writer.write_all(&len.to_le_bytes())?;
+    writer.write_all(&payload)
+}
+
+/// Read one frame, returning None at a clean end of stream
+pub fn read_frame<R: Read>(reader: &mut R) -> io::Result<Option<Vec<PageInfo>>> {
+    let mut len_bytes = [0u8; 4];
+    match reader.read_exact(&mut len_bytes) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+
+    let len = u32::from_le_bytes(len_bytes);
+    if len > MAX_FRAME_LEN {
+        return Err(invalid_data("frame too large"));
+    }
+    let mut payload = vec![0u8; len as usize];
+    reader.read_exact(&mut payload)?;
+    decode_pages(&payload).map(Some)
+}
+
+/// Write pages to a file as `EXPORT_MAGIC` followed by frames
+pub fn write_export<W: Write>(writer: &mut W, pages: &[PageInfo]) -> io::Result<()> {
+    writer.write_all(EXPORT_MAGIC)?;
+    for chunk in pages.chunks(EXPORT_FRAME_PAGES) {
+        write_frame(writer, chunk)?;
+    }
+    writer.flush()
+}
+
+/// Read the frames following `EXPORT_MAGIC` back as a capture that reads
+/// like /proc/kpageflags, so exports can be scanned like any other capture
+pub fn read_export_as_kpageflags<R: Read>(reader: &mut R) -> io::Result<SparseCapture> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != EXPORT_MAGIC {
+        return Err(invalid_data("not a kpageflags export"));
+    }
+
+    let mut capture = SparseCapture::default();
+    while let Some(pages) = read_frame(reader)? {
+        for page in &pages {
+            capture.insert(page)?;
+        }
+    }
+    Ok(capture)
+}
+
+/// Imported pages laid out like /proc/kpageflags (8 little-endian bytes per
+/// PFN from PFN 0), holding only the PFNs that were imported
+///
+/// PFNs missing from the import read as pages with no flags, so memory
+/// grows with the number of pages rather than the highest PFN.
+#[derive(Debug, Default)]
+pub struct SparseCapture {
+    flags: BTreeMap<u64, u64>,
+    /// Read position in bytes
+    pos: u64,
+}
+
+impl SparseCapture {
+    /// Store a page's flags, replacing any earlier entry for its PFN
+    pub fn insert(&mut self, page: &PageInfo) -> io::Result<()> {
+        if page.pfn >= u64::MAX / 8 {
+            return Err(invalid_data("PFN too large for a capture"));
+        }
+        self.flags.insert(page.pfn, page.flags.bits());
+        Ok(())
+    }
+
+    /// Size of the equivalent /proc/kpageflags file: up to the highest PFN
+    pub fn byte_len(&self) -> u64 {
+        self.flags
+            .keys()
+            .next_back()
+            .map_or(0, |&pfn| (pfn + 1) * 8)
+    }
+}
+
+impl Read for SparseCapture {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.byte_len();
+        let mut written = 0;
+        while written < buf.len() && self.pos < len {
+            let pfn = self.pos / 8;
+            let skip = (self.pos % 8) as usize;
+            let entry = self.flags.get(&pfn).copied().unwrap_or(0).to_le_bytes();
+            let n = (8 - skip).min(buf.len() - written);
+            buf[written..written + n].copy_from_slice(&entry[skip..skip + n]);
+            written += n;
+            self.pos += n as u64;
+        }
+        Ok(written)
+    }
+}
+
+impl Seek for SparseCapture {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(delta) => self.byte_len().checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.pos.checked_add_signed(delta),
+        };
+        self.pos = target.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "seek before start of capture")
+        })?;
+        Ok(self.pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn assert_same(a: &[PageInfo], b: &[PageInfo]) {
+        assert_eq!(a.len(), b.len());
+        for (x, y) in a.iter().zip(b) {
+            assert_eq!((x.pfn, x.flags), (y.pfn, y.flags));
+        }
+    }
+
+    fn random_pages(rng: &mut StdRng) -> Vec<PageInfo> {
+        let len = rng.gen_range(0..500);
+        let mut pfn: u64 = rng.gen();
+        let palette: Vec<u64> = (0..4).map(|_| rng.gen()).collect();
+        (0..len)
+            .map(|_| {
+                // Mostly contiguous with occasional jumps in either direction
+                pfn = match rng.gen_range(0..10) {
+                    0 => rng.gen(),
+                    1 => pfn.wrapping_sub(rng.gen_range(0..1000)),
+                    _ => pfn.wrapping_add(1),
+                };
+                PageInfo::new(pfn, palette[rng.gen_range(0..palette.len())])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip_random_pages() {
+        let mut rng = StdRng::seed_from_u64(0x6b70_6167);
+        for _ in 0..200 {
+            let pages = random_pages(&mut rng);
+            assert_same(&pages, &decode_pages(&encode_pages(&pages)).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_contiguous_pages_are_compact() {
+        let pages: Vec<PageInfo> = (0x1000..0x1000 + 1000)
+            .map(|pfn| PageInfo::new(pfn, 0x400))
+            .collect();
+        // First page costs a few bytes, every following page two
+        assert!(encode_pages(&pages).len() < 2 * 1000 + 10);
+    }
+
+    #[test]
+    fn test_framed_stream() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let frames: Vec<Vec<PageInfo>> = (0..5).map(|_| random_pages(&mut rng)).collect();
+
+        let mut stream = Vec::new();
+        for frame in &frames {
+            write_frame(&mut stream, frame).unwrap();
+        }
+
+        let mut reader = stream.as_slice();
+        for frame in &frames {
+            assert_same(frame, &read_frame(&mut reader).unwrap().unwrap());
+        }
+        assert!(read_frame(&mut reader).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_rejects_truncated_data() {
+        let pages = vec![PageInfo::new(1, 2), PageInfo::new(5, 3)];
+        let encoded = encode_pages(&pages);
+        assert!(decode_pages(&encoded[..encoded.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_export_reads_back_as_kpageflags() {
+        let pages = vec![PageInfo::new(2, 0x10), PageInfo::new(3, 0x20)];
+        let mut file = Vec::new();
+        write_export(&mut file, &pages).unwrap();
+
+        let mut capture = read_export_as_kpageflags(&mut file.as_slice()).unwrap();
+        assert_eq!(capture.byte_len(), 4 * 8);
+        let mut flags = Vec::new();
+        capture.read_to_end(&mut flags).unwrap();
+        assert_eq!(flags.len(), 4 * 8);
+        assert_eq!(&flags[..16], &[0u8; 16]);
+        assert_eq!(u64::from_le_bytes(flags[16..24].try_into().unwrap()), 0x10);
+        assert_eq!(u64::from_le_bytes(flags[24..32].try_into().unwrap()), 0x20);
+
+        // Unaligned reads after a seek
+        capture.seek(SeekFrom::Start(20)).unwrap();
+        let mut tail = [0u8; 6];
+        capture.read_exact(&mut tail).unwrap();
+        assert_eq!(tail, [0, 0, 0, 0, 0x20, 0]);
+    }
+
+    #[test]
+    fn test_sparse_capture_grows_with_pages_not_pfns() {
+        let mut capture = SparseCapture::default();
+        capture.insert(&PageInfo::new(1 << 40, 0x10)).unwrap();
+        assert_eq!(capture.byte_len(), ((1 << 40) + 1) * 8);
+        capture.seek(SeekFrom::Start((1 << 40) * 8)).unwrap();
+        let mut entry = [0u8; 8];
+        capture.read_exact(&mut entry).unwrap();
+        assert_eq!(u64::from_le_bytes(entry), 0x10);
+        assert_eq!(capture.read(&mut entry).unwrap(), 0);
+
+        assert!(capture.insert(&PageInfo::new(u64::MAX, 1)).is_err());
+    }
+}
@@ -0,0 +1,188 @@
+//! Runs of physically contiguous pages that all satisfy a flag constraint,
+//! e.g. free (BUDDY) memory large enough for a huge page
+
+use crate::{find_flag, KPageFlagsReader, PageFlags, PageInfo};
+use linux_memory_monitor::MemoryUtils;
+use std::borrow::Borrow;
+
+/// `length` consecutive PFNs from `start_pfn`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PfnRange {
+    pub start_pfn: u64,
+    pub length: u64,
+}
+
+impl PfnRange {
+    /// PFN just past the range
+    pub fn end_pfn(&self) -> u64 {
+        self.start_pfn + self.length
+    }
+
+    /// Size of the range with this machine's page size
+    pub fn physical_size_bytes(&self) -> u64 {
+        self.length.saturating_mul(MemoryUtils::get_page_size())
+    }
+}
+
+impl KPageFlagsReader {
+    /// Runs of at least `min_length` consecutive pages that have every bit of
+    /// `required_flags` and none of `forbidden_flags`
+    ///
+    /// `pages` must be sorted by PFN, and may be a slice or a stream such as
+    /// `stream_pages`. A PFN missing from it (a hole in physical memory, or a
+    /// page that couldn't be read) ends a run.
+    ///
+    /// Only the head page of a free buddy block carries BUDDY; its tail pages
+    /// read 0. So when BUDDY is required, pages without any flags extend a
+    /// run already under way, though they never start one.
+    pub fn find_contiguous_regions(
+        pages: impl IntoIterator<Item = impl Borrow<PageInfo>>,
+        min_length: u64,
+        required_flags: PageFlags,
+        forbidden_flags: PageFlags,
+    ) -> Vec<PfnRange> {
+        let mut ranges = Vec::new();
+        let mut current: Option<PfnRange> = None;
+        let tails_continue = required_flags.contains(PageFlags::BUDDY);
+
+        for page in pages {
+            let page = page.borrow();
+            let flags = page.flags;
+            let wanted = flags.contains(required_flags) && !flags.intersects(forbidden_flags);
+            let continues = wanted || (tails_continue && flags.is_empty());
+
+            match current.as_mut() {
+                Some(run) if continues && page.pfn == run.end_pfn() => run.length += 1,
+                _ => {
+                    if let Some(run) = current.take().filter(|run| run.length >= min_length) {
+                        ranges.push(run);
+                    }
+                    if wanted {
+                        current = Some(PfnRange {
+                            start_pfn: page.pfn,
+                            length: 1,
+                        });
+                    }
+                }
+            }
+        }
+        if let Some(run) = current.filter(|run| run.length >= min_length) {
+            ranges.push(run);
+        }
+
+        ranges
+    }
+}
+
+/// Mask of a comma-separated list of flag names, e.g. `BUDDY,ZERO_PAGE`
+pub fn parse_flag_list(list: &str) -> Result<PageFlags, String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .try_fold(PageFlags::EMPTY, |mask, name| {
+            find_flag(&name.to_uppercase())
+                .map(|def| mask | def.flag())
+                .ok_or_else(|| format!("Unknown flag '{}'", name))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMPTY: PageFlags = PageFlags::EMPTY;
+
+    fn pages(entries: &[(u64, PageFlags)]) -> Vec<PageInfo> {
+        entries
+            .iter()
+            .map(|&(pfn, flags)| PageInfo { pfn, flags })
+            .collect()
+    }
+
+    #[test]
+    fn test_find_contiguous_regions() {
+        let (buddy, lru) = (PageFlags::BUDDY, PageFlags::LRU);
+        let pages = pages(&[
+            (10, buddy),
+            (11, buddy),
+            (12, buddy),
+            // Gap at 13 ends the run
+            (14, buddy),
+            (15, buddy | lru),
+            (16, buddy),
+            (17, buddy),
+            (18, lru),
+        ]);
+
+        let runs = KPageFlagsReader::find_contiguous_regions(&pages, 2, buddy, lru);
+        assert_eq!(
+            runs,
+            [
+                PfnRange {
+                    start_pfn: 10,
+                    length: 3
+                },
+                PfnRange {
+                    start_pfn: 16,
+                    length: 2
+                }
+            ]
+        );
+        assert_eq!(
+            KPageFlagsReader::find_contiguous_regions(&pages, 4, buddy, EMPTY),
+            [PfnRange {
+                start_pfn: 14,
+                length: 4
+            }]
+        );
+        assert!(KPageFlagsReader::find_contiguous_regions(&pages, 5, buddy, EMPTY).is_empty());
+    }
+
+    #[test]
+    fn test_buddy_tails_extend_run() {
+        let (buddy, lru) = (PageFlags::BUDDY, PageFlags::LRU);
+        // An order-2 free block at 8, then a flagless page after an LRU page
+        let pages = pages(&[
+            (7, EMPTY),
+            (8, buddy),
+            (9, EMPTY),
+            (10, EMPTY),
+            (11, EMPTY),
+            (12, lru),
+            (13, EMPTY),
+        ]);
+
+        assert_eq!(
+            KPageFlagsReader::find_contiguous_regions(&pages, 1, buddy, EMPTY),
+            [PfnRange {
+                start_pfn: 8,
+                length: 4
+            }]
+        );
+        // Without BUDDY required, flagless pages are judged like any other
+        assert_eq!(
+            KPageFlagsReader::find_contiguous_regions(&pages, 1, lru, EMPTY),
+            [PfnRange {
+                start_pfn: 12,
+                length: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_flag_list() {
+        let (buddy, lru) = (PageFlags::BUDDY, PageFlags::LRU);
+        assert_eq!(parse_flag_list("buddy, LRU"), Ok(buddy | lru));
+        assert_eq!(parse_flag_list(""), Ok(EMPTY));
+        assert!(parse_flag_list("BUDDY,NOPE").is_err());
+
+        let range = PfnRange {
+            start_pfn: 0,
+            length: 512,
+        };
+        assert_eq!(
+            range.physical_size_bytes(),
+            512 * MemoryUtils::get_page_size()
+        );
+    }
+}
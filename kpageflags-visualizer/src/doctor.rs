@@ -0,0 +1,225 @@
+//! `doctor` subcommand: read-only checks of everything a scan depends on
+
+use crate::get_estimated_total_pages;
+use colored::*;
+use linux_memory_monitor::{KsmStats, MemoryStats, ParseOptions, ZoneInfo};
+use linux_memory_monitor::{MemoryUtils, DEFAULT_PAGE_SIZE};
+use std::fs::File;
+use std::io::Read;
+
+/// Bit of CAP_SYS_ADMIN in the capability sets of /proc/PID/status
+const CAP_SYS_ADMIN: u32 = 21;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Pass,
+    /// Works, but some feature is degraded
+    Warn,
+    Fail,
+}
+
+struct Check {
+    name: &'static str,
+    status: Status,
+    detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, status: Status, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+        }
+    }
+
+    fn print(&self) {
+        let label = match self.status {
+            Status::Pass => "[PASS]".green().bold(),
+            Status::Warn => "[WARN]".yellow().bold(),
+            Status::Fail => "[FAIL]".red().bold(),
+        };
+        println!("{} {:<22} {}", label, self.name, self.detail);
+    }
+}
+
+/// Whether the effective capability set in /proc/PID/status content
+/// includes CAP_SYS_ADMIN
+fn has_cap_sys_admin(status: &str) -> bool {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("CapEff:"))
+        .and_then(|caps| u64::from_str_radix(caps.trim(), 16).ok())
+        .is_some_and(|caps| caps & (1 << CAP_SYS_ADMIN) != 0)
+}
+
+fn check_linux() -> Check {
+    if cfg!(target_os = "linux") && std::path::Path::new("/proc/self").exists() {
+        Check::new("Linux with /proc", Status::Pass, std::env::consts::ARCH)
+    } else {
+        Check::new(
+            "Linux with /proc",
+            Status::Fail,
+            "kpageflags is a Linux procfs interface",
+        )
+    }
+}
+
+fn check_meminfo() -> Check {
+    match MemoryStats::current_with(&ParseOptions::default()) {
+        Ok((stats, missing)) if missing.is_empty() => Check::new(
+            "/proc/meminfo",
+            Status::Pass,
+            format!("MemTotal {} kB", stats.mem_total),
+        ),
+        Ok((_, missing)) => Check::new(
+            "/proc/meminfo",
+            Status::Warn,
+            format!("missing {} (read as 0)", missing.join(", ")),
+        ),
+        Err(e) => Check::new("/proc/meminfo", Status::Fail, e.to_string()),
+    }
+}
+
+fn check_kpageflags() -> Check {
+    let mut entry = [0u8; 8];
+    match File::open("/proc/kpageflags").and_then(|mut file| file.read_exact(&mut entry)) {
+        Ok(()) => Check::new("/proc/kpageflags", Status::Pass, "readable"),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Check::new(
+            "/proc/kpageflags",
+            Status::Fail,
+            "not found (kernel built without CONFIG_PROC_PAGE_MONITOR?)",
+        ),
+        Err(e) => Check::new(
+            "/proc/kpageflags",
+            Status::Fail,
+            format!("{} (run as root)", e),
+        ),
+    }
+}
+
+fn check_privileges() -> Check {
+    // SAFETY: geteuid has no preconditions and cannot fail
+    let euid = unsafe { libc::geteuid() };
+    let cap_sys_admin = std::fs::read_to_string("/proc/self/status")
+        .map(|status| has_cap_sys_admin(&status))
+        .unwrap_or(false);
+
+    match (euid, cap_sys_admin) {
+        (0, true) => Check::new("Privileges", Status::Pass, "root with CAP_SYS_ADMIN"),
+        (_, true) => Check::new("Privileges", Status::Pass, "CAP_SYS_ADMIN"),
+        (0, false) => Check::new(
+            "Privileges",
+            Status::Warn,
+            "root without CAP_SYS_ADMIN (container?); pagemap PFNs are hidden",
+        ),
+        (euid, false) => Check::new(
+            "Privileges",
+            Status::Fail,
+            format!("uid {} without CAP_SYS_ADMIN; run with sudo", euid),
+        ),
+    }
+}
+
+fn check_page_size() -> Check {
+    let page_size = MemoryUtils::get_page_size();
+    if page_size == DEFAULT_PAGE_SIZE {
+        Check::new("Page size", Status::Pass, format!("{} bytes", page_size))
+    } else {
+        Check::new(
+            "Page size",
+            Status::Pass,
+            format!(
+                "{} bytes, not the usual {}; sizes in reports use {}",
+                page_size, DEFAULT_PAGE_SIZE, page_size
+            ),
+        )
+    }
+}
+
+fn check_estimated_pages() -> Check {
+    match get_estimated_total_pages() {
+        Ok(pages) => Check::new(
+            "Estimated pages",
+            Status::Pass,
+            format!("~{} pages of {} bytes", pages, MemoryUtils::get_page_size()),
+        ),
+        Err(e) => Check::new("Estimated pages", Status::Fail, e.to_string()),
+    }
+}
+
+fn check_zoneinfo() -> Check {
+    match ZoneInfo::current() {
+        Ok(info) => Check::new(
+            "/proc/zoneinfo",
+            Status::Pass,
+            format!("{} zones with pages", info.zones_by_pfn().len()),
+        ),
+        Err(e) => Check::new(
+            "/proc/zoneinfo",
+            Status::Warn,
+            format!("{}; --by-region falls back to x86-64 zones", e),
+        ),
+    }
+}
+
+fn check_ksm() -> Check {
+    match KsmStats::current() {
+        Ok(ksm) if ksm.is_enabled() => Check::new("KSM", Status::Pass, "running"),
+        Ok(_) => Check::new("KSM", Status::Pass, "not running (optional)"),
+        Err(e) => Check::new("KSM", Status::Warn, e.to_string()),
+    }
+}
+
+/// Run every check, print a checklist and return whether none failed
+pub fn run_doctor() -> bool {
+    println!(
+        "{}",
+        "KPageFlags Visualizer - environment check".blue().bold()
+    );
+    println!("{}", "=".repeat(50).blue());
+
+    let checks = [
+        check_linux(),
+        check_meminfo(),
+        check_kpageflags(),
+        check_privileges(),
+        check_page_size(),
+        check_estimated_pages(),
+        check_zoneinfo(),
+        check_ksm(),
+    ];
+    for check in &checks {
+        check.print();
+    }
+
+    let failures = checks.iter().filter(|c| c.status == Status::Fail).count();
+    let warnings = checks.iter().filter(|c| c.status == Status::Warn).count();
+    println!();
+    if failures == 0 {
+        println!(
+            "{}",
+            format!("All checks passed ({} warnings)", warnings).green()
+        );
+    } else {
+        println!(
+            "{}",
+            format!("{} checks failed, {} warnings", failures, warnings).red()
+        );
+    }
+    failures == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cap_sys_admin_from_status() {
+        let status = |caps: &str| format!("Name:\tcat\nCapPrm:\t0\nCapEff:\t{}\n", caps);
+        assert!(has_cap_sys_admin(&status("000001ffffffffff")));
+        assert!(has_cap_sys_admin(&status("0000000000200000")));
+        assert!(!has_cap_sys_admin(&status("0000000000000000")));
+        assert!(!has_cap_sys_admin("Name:\tcat\n"));
+    }
+}
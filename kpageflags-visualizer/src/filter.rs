@@ -0,0 +1,260 @@
+//! Boolean predicates over page flags, e.g. `LRU & !ACTIVE & !ANON`
+
+use crate::{category_mask, find_flag, FlagCategory, PageFlags, PageInfo};
+use std::fmt;
+
+/// A predicate a page's flags either match or don't
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PageFilter {
+    /// Any bit of the mask is set
+    HasFlag(PageFlags),
+    /// Any flag of the category is set
+    Category(FlagCategory),
+    /// The flags are exactly the mask, no more and no fewer
+    ExactFlags(PageFlags),
+    Not(Box<PageFilter>),
+    And(Box<PageFilter>, Box<PageFilter>),
+    Or(Box<PageFilter>, Box<PageFilter>),
+}
+
+impl PageFilter {
+    pub fn matches(&self, page: &PageInfo) -> bool {
+        match self {
+            PageFilter::HasFlag(mask) => page.flags.intersects(*mask),
+            PageFilter::Category(category) => page.flags.intersects(category_mask(*category)),
+            PageFilter::ExactFlags(mask) => page.flags == *mask,
+            PageFilter::Not(inner) => !inner.matches(page),
+            PageFilter::And(a, b) => a.matches(page) && b.matches(page),
+            PageFilter::Or(a, b) => a.matches(page) || b.matches(page),
+        }
+    }
+
+    /// Parse an expression of flag names combined with `!`, `&`, `|` and
+    /// parentheses; `&` binds tighter than `|`, and names are case-insensitive
+    pub fn parse(expr: &str) -> Result<PageFilter, String> {
+        let tokens = tokenize(expr)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let filter = parser.or_expr()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(filter),
+            Some(token) => Err(format!("unexpected '{}' in '{}'", token.text(), expr)),
+        }
+    }
+
+    /// Pages having every flag of a comma-separated list, e.g. `LRU,DIRTY`,
+    /// as an `And` chain
+    pub fn all_of(list: &str) -> Result<PageFilter, String> {
+        list.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                find_flag(&name.to_ascii_uppercase())
+                    .map(|def| PageFilter::HasFlag(def.flag()))
+                    .ok_or_else(|| format!("unknown flag '{}'", name))
+            })
+            .reduce(|a, b| Ok(PageFilter::And(Box::new(a?), Box::new(b?))))
+            .unwrap_or_else(|| Err(format!("no flag names in '{}'", list)))
+    }
+}
+
+/// Names of the known flags in `mask`, joined by `sep`
+fn mask_names(mask: PageFlags, sep: &str) -> String {
+    mask.iter_names().collect::<Vec<_>>().join(sep)
+}
+
+impl fmt::Display for PageFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PageFilter::HasFlag(mask) => write!(f, "{}", mask_names(*mask, " | ")),
+            PageFilter::Category(category) => write!(f, "{:?}", category),
+            PageFilter::ExactFlags(mask) => write!(f, "={{{}}}", mask_names(*mask, ", ")),
+            PageFilter::Not(inner) => write!(f, "!{}", inner),
+            PageFilter::And(a, b) => write!(f, "({} & {})", a, b),
+            PageFilter::Or(a, b) => write!(f, "({} | {})", a, b),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Name(String),
+    Not,
+    And,
+    Or,
+    Open,
+    Close,
+}
+
+impl Token {
+    fn text(&self) -> &str {
+        match self {
+            Token::Name(name) => name,
+            Token::Not => "!",
+            Token::And => "&",
+            Token::Or => "|",
+            Token::Open => "(",
+            Token::Close => ")",
+        }
+    }
+}
+
+fn tokenize(expr: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = expr.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        let token = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '!' => Token::Not,
+            '&' => Token::And,
+            '|' => Token::Or,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            c if c.is_ascii_alphanumeric() || c == '_' => {
+                let mut name = String::new();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    name.push(c.to_ascii_uppercase());
+                    chars.next();
+                }
+                tokens.push(Token::Name(name));
+                continue;
+            }
+            c => return Err(format!("unexpected character '{}' in '{}'", c, expr)),
+        };
+        chars.next();
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next_if(&mut self, token: &Token) -> bool {
+        if self.tokens.get(self.pos) == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or_expr(&mut self) -> Result<PageFilter, String> {
+        let mut filter = self.and_expr()?;
+        while self.next_if(&Token::Or) {
+            filter = PageFilter::Or(Box::new(filter), Box::new(self.and_expr()?));
+        }
+        Ok(filter)
+    }
+
+    fn and_expr(&mut self) -> Result<PageFilter, String> {
+        let mut filter = self.unary()?;
+        while self.next_if(&Token::And) {
+            filter = PageFilter::And(Box::new(filter), Box::new(self.unary()?));
+        }
+        Ok(filter)
+    }
+
+    fn unary(&mut self) -> Result<PageFilter, String> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        match token {
+            Some(Token::Not) => Ok(PageFilter::Not(Box::new(self.unary()?))),
+            Some(Token::Open) => {
+                let filter = self.or_expr()?;
+                if !self.next_if(&Token::Close) {
+                    return Err("missing ')'".to_string());
+                }
+                Ok(filter)
+            }
+            Some(Token::Name(name)) => find_flag(&name)
+                .map(|def| PageFilter::HasFlag(def.flag()))
+                .ok_or_else(|| format!("unknown flag '{}'", name)),
+            Some(token) => Err(format!("expected a flag name, found '{}'", token.text())),
+            None => Err("expected a flag name, found end of expression".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mask(name: &str) -> PageFlags {
+        find_flag(name).unwrap().flag()
+    }
+
+    fn page(names: &[&str]) -> PageInfo {
+        let flags = names
+            .iter()
+            .fold(PageFlags::EMPTY, |flags, n| flags | mask(n));
+        PageInfo { pfn: 0, flags }
+    }
+
+    #[test]
+    fn test_parse_and_match() {
+        let filter = PageFilter::parse("lru & !(ACTIVE | anon)").unwrap();
+
+        assert!(filter.matches(&page(&["LRU", "UPTODATE"])));
+        assert!(!filter.matches(&page(&["LRU", "ACTIVE"])));
+        assert!(!filter.matches(&page(&["LRU", "ANON"])));
+        assert!(!filter.matches(&page(&["UPTODATE"])));
+    }
+
+    #[test]
+    fn test_and_binds_tighter_than_or() {
+        assert_eq!(
+            PageFilter::parse("DIRTY | LRU & ACTIVE").unwrap(),
+            PageFilter::Or(
+                Box::new(PageFilter::HasFlag(mask("DIRTY"))),
+                Box::new(PageFilter::And(
+                    Box::new(PageFilter::HasFlag(mask("LRU"))),
+                    Box::new(PageFilter::HasFlag(mask("ACTIVE"))),
+                )),
+            )
+        );
+    }
+
+    #[test]
+    fn test_category_exact_and_lists() {
+        let io = PageFilter::Category(FlagCategory::IO);
+        assert!(io.matches(&page(&["LRU", "WRITEBACK"])));
+        assert!(!io.matches(&page(&["LRU"])));
+
+        let exact = PageFilter::ExactFlags(mask("LRU") | mask("ACTIVE"));
+        assert!(exact.matches(&page(&["LRU", "ACTIVE"])));
+        assert!(!exact.matches(&page(&["LRU", "ACTIVE", "DIRTY"])));
+
+        let list = PageFilter::all_of("lru, DIRTY").unwrap();
+        assert_eq!(
+            list,
+            PageFilter::And(
+                Box::new(PageFilter::HasFlag(mask("LRU"))),
+                Box::new(PageFilter::HasFlag(mask("DIRTY"))),
+            )
+        );
+        assert!(list.matches(&page(&["LRU", "DIRTY", "ACTIVE"])));
+        assert!(!list.matches(&page(&["LRU"])));
+        assert_eq!(list.to_string(), "(LRU & DIRTY)");
+        assert!(PageFilter::all_of("LRU,NOPE").is_err());
+        assert!(PageFilter::all_of(" , ").is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(PageFilter::parse("").is_err());
+        assert!(PageFilter::parse("NOT_A_FLAG").is_err());
+        assert!(PageFilter::parse("LRU &").is_err());
+        assert!(PageFilter::parse("(LRU").is_err());
+        assert!(PageFilter::parse("LRU DIRTY").is_err());
+        assert!(PageFilter::parse("LRU + DIRTY").is_err());
+    }
+}
@@ -0,0 +1,487 @@
+use serde::Serialize;
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum FlagCategory {
+    State,      // Page state flags
+    Memory,     // Memory management flags
+    Usage,      // Usage tracking flags
+    Allocation, // Allocation type flags
+    IO,         // I/O related flags
+    Structure,  // Page structure flags
+    Special,    // Special purpose flags
+    Error,      // Error flags
+}
+
+impl FlagCategory {
+    pub const ALL: [FlagCategory; 8] = [
+        FlagCategory::State,
+        FlagCategory::Memory,
+        FlagCategory::Usage,
+        FlagCategory::Allocation,
+        FlagCategory::IO,
+        FlagCategory::Structure,
+        FlagCategory::Special,
+        FlagCategory::Error,
+    ];
+
+    /// Parse a category name case-insensitively (e.g. "state", "IO")
+    pub fn from_name(name: &str) -> Option<FlagCategory> {
+        Self::ALL
+            .into_iter()
+            .find(|category| format!("{:?}", category).eq_ignore_ascii_case(name))
+    }
+}
+
+/// Coarse owner of a page: process memory, page cache, free, or kernel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum PageClass {
+    Anon,
+    File,
+    Free,
+    Kernel,
+}
+
+impl PageClass {
+    pub const ALL: [PageClass; 4] = [
+        PageClass::Anon,
+        PageClass::File,
+        PageClass::Free,
+        PageClass::Kernel,
+    ];
+
+    /// Classify a page from its flags. Free buddy pages win, then anonymous
+    /// and swap-backed pages, then LRU or mapped pages as page cache; the
+    /// rest (slab, page tables, reserved) is kernel memory.
+    ///
+    /// A page with no flags at all counts as free: only the head page of a
+    /// free buddy block carries BUDDY, and its tail pages read 0. The odd
+    /// flagless kernel allocation lands here too.
+    pub fn from_flags(flags: PageFlags) -> PageClass {
+        if flags.is_empty() || flags.intersects(PageFlags::BUDDY) {
+            PageClass::Free
+        } else if flags.intersects(PageFlags::ANON | PageFlags::SWAPBACKED | PageFlags::KSM) {
+            PageClass::Anon
+        } else if flags.intersects(PageFlags::LRU | PageFlags::MMAP) {
+            PageClass::File
+        } else {
+            PageClass::Kernel
+        }
+    }
+}
+
+/// A single kpageflags bit definition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlagDef {
+    /// Bit mask of the flag (1 << bit)
+    pub mask: u64,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub category: FlagCategory,
+}
+
+impl FlagDef {
+    /// The flag as a single-bit `PageFlags`
+    pub const fn flag(&self) -> PageFlags {
+        PageFlags(self.mask)
+    }
+
+    pub const fn new(
+        bit: u32,
+        name: &'static str,
+        description: &'static str,
+        category: FlagCategory,
+    ) -> Self {
+        Self {
+            mask: 1 << bit,
+            name,
+            description,
+            category,
+        }
+    }
+}
+
+// Page flag definitions with categories (upstream kernel ABI)
+pub const PAGE_FLAGS: &[FlagDef] = &[
+    FlagDef::new(0, "LOCKED", "Page is locked", FlagCategory::State),
+    FlagDef::new(1, "ERROR", "Page has error", FlagCategory::Error),
+    FlagDef::new(
+        2,
+        "REFERENCED",
+        "Page has been referenced",
+        FlagCategory::Usage,
+    ),
+    FlagDef::new(3, "UPTODATE", "Page is up to date", FlagCategory::State),
+    FlagDef::new(4, "DIRTY", "Page is dirty", FlagCategory::State),
+    FlagDef::new(5, "LRU", "Page is on LRU list", FlagCategory::Memory),
+    FlagDef::new(6, "ACTIVE", "Page is on active list", FlagCategory::Memory),
+    FlagDef::new(
+        7,
+        "SLAB",
+        "Page is slab allocated",
+        FlagCategory::Allocation,
+    ),
+    FlagDef::new(8, "WRITEBACK", "Page is under writeback", FlagCategory::IO),
+    FlagDef::new(
+        9,
+        "RECLAIM",
+        "Page is being reclaimed",
+        FlagCategory::Memory,
+    ),
+    FlagDef::new(
+        10,
+        "BUDDY",
+        "Page is free buddy page",
+        FlagCategory::Allocation,
+    ),
+    FlagDef::new(11, "MMAP", "Page is memory mapped", FlagCategory::Usage),
+    FlagDef::new(12, "ANON", "Page is anonymous", FlagCategory::Usage),
+    FlagDef::new(
+        13,
+        "SWAPCACHE",
+        "Page is in swap cache",
+        FlagCategory::Memory,
+    ),
+    FlagDef::new(
+        14,
+        "SWAPBACKED",
+        "Page is swap backed",
+        FlagCategory::Memory,
+    ),
+    FlagDef::new(
+        15,
+        "COMPOUND_HEAD",
+        "Compound page head",
+        FlagCategory::Structure,
+    ),
+    FlagDef::new(
+        16,
+        "COMPOUND_TAIL",
+        "Compound page tail",
+        FlagCategory::Structure,
+    ),
+    FlagDef::new(17, "HUGE", "Huge page", FlagCategory::Structure),
+    FlagDef::new(
+        18,
+        "UNEVICTABLE",
+        "Page is unevictable",
+        FlagCategory::Memory,
+    ),
+    FlagDef::new(
+        19,
+        "HWPOISON",
+        "Hardware poisoned page",
+        FlagCategory::Error,
+    ),
+    FlagDef::new(20, "NOPAGE", "No page frame exists", FlagCategory::State),
+    FlagDef::new(21, "KSM", "KSM page", FlagCategory::Special),
+    FlagDef::new(22, "THP", "Transparent huge page", FlagCategory::Structure),
+    FlagDef::new(23, "OFFLINE", "Page is offline", FlagCategory::State),
+    FlagDef::new(24, "ZERO_PAGE", "Zero page", FlagCategory::Special),
+    FlagDef::new(25, "IDLE", "Page is idle", FlagCategory::Usage),
+    FlagDef::new(26, "PGTABLE", "Page table page", FlagCategory::Special),
+    // Additional flags that might be present
+    FlagDef::new(
+        32,
+        "RESERVED",
+        "Reserved page (common in early memory)",
+        FlagCategory::State,
+    ),
+];
+
+/// The kpageflags word of one page
+///
+/// Named constants cover the upstream bits in `PAGE_FLAGS`; names are
+/// resolved through `flag_table()`, so flags registered at runtime show up
+/// in `iter_names` too. Serializes as the raw u64.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(transparent)]
+pub struct PageFlags(u64);
+
+impl PageFlags {
+    pub const EMPTY: PageFlags = PageFlags(0);
+    pub const LOCKED: PageFlags = PageFlags(1 << 0);
+    pub const ERROR: PageFlags = PageFlags(1 << 1);
+    pub const REFERENCED: PageFlags = PageFlags(1 << 2);
+    pub const UPTODATE: PageFlags = PageFlags(1 << 3);
+    pub const DIRTY: PageFlags = PageFlags(1 << 4);
+    pub const LRU: PageFlags = PageFlags(1 << 5);
+    pub const ACTIVE: PageFlags = PageFlags(1 << 6);
+    pub const SLAB: PageFlags = PageFlags(1 << 7);
+    pub const WRITEBACK: PageFlags = PageFlags(1 << 8);
+    pub const RECLAIM: PageFlags = PageFlags(1 << 9);
+    pub const BUDDY: PageFlags = PageFlags(1 << 10);
+    pub const MMAP: PageFlags = PageFlags(1 << 11);
+    pub const ANON: PageFlags = PageFlags(1 << 12);
+    pub const SWAPCACHE: PageFlags = PageFlags(1 << 13);
+    pub const SWAPBACKED: PageFlags = PageFlags(1 << 14);
+    pub const COMPOUND_HEAD: PageFlags = PageFlags(1 << 15);
+    pub const COMPOUND_TAIL: PageFlags = PageFlags(1 << 16);
+    pub const HUGE: PageFlags = PageFlags(1 << 17);
+    pub const UNEVICTABLE: PageFlags = PageFlags(1 << 18);
+    pub const HWPOISON: PageFlags = PageFlags(1 << 19);
+    pub const NOPAGE: PageFlags = PageFlags(1 << 20);
+    pub const KSM: PageFlags = PageFlags(1 << 21);
+    pub const THP: PageFlags = PageFlags(1 << 22);
+    pub const OFFLINE: PageFlags = PageFlags(1 << 23);
+    pub const ZERO_PAGE: PageFlags = PageFlags(1 << 24);
+    pub const IDLE: PageFlags = PageFlags(1 << 25);
+    pub const PGTABLE: PageFlags = PageFlags(1 << 26);
+    pub const RESERVED: PageFlags = PageFlags(1 << 32);
+    /// Kernel-internal KPF_MLOCKED, reported only to root and not in the
+    /// flag table
+    pub const MLOCKED: PageFlags = PageFlags(1 << 33);
+
+    pub const fn from_bits(bits: u64) -> Self {
+        PageFlags(bits)
+    }
+
+    pub const fn bits(self) -> u64 {
+        self.0
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether every flag of `other` is set
+    pub const fn contains(self, other: PageFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Whether any flag of `other` is set
+    pub const fn intersects(self, other: PageFlags) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// Definitions of the known flags that are set, in flag table order
+    pub fn iter_defs(self) -> impl Iterator<Item = &'static FlagDef> {
+        flag_table()
+            .iter()
+            .filter(move |def| self.intersects(def.flag()))
+    }
+
+    /// Names of the known flags that are set, in flag table order
+    pub fn iter_names(self) -> impl Iterator<Item = &'static str> {
+        self.iter_defs().map(|def| def.name)
+    }
+
+    /// Set bits that no entry in the flag table describes
+    pub fn unknown(self) -> PageFlags {
+        PageFlags(self.0 & !known_flags_mask())
+    }
+}
+
+impl From<u64> for PageFlags {
+    fn from(bits: u64) -> Self {
+        PageFlags(bits)
+    }
+}
+
+impl std::ops::BitOr for PageFlags {
+    type Output = PageFlags;
+
+    fn bitor(self, rhs: PageFlags) -> PageFlags {
+        PageFlags(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitAnd for PageFlags {
+    type Output = PageFlags;
+
+    fn bitand(self, rhs: PageFlags) -> PageFlags {
+        PageFlags(self.0 & rhs.0)
+    }
+}
+
+impl std::ops::BitXor for PageFlags {
+    type Output = PageFlags;
+
+    fn bitxor(self, rhs: PageFlags) -> PageFlags {
+        PageFlags(self.0 ^ rhs.0)
+    }
+}
+
+impl std::ops::Not for PageFlags {
+    type Output = PageFlags;
+
+    fn not(self) -> PageFlags {
+        PageFlags(!self.0)
+    }
+}
+
+impl fmt::LowerHex for PageFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FlagRegistryError {
+    /// The flag table was already used, so it can no longer be extended
+    TableFrozen,
+    InvalidBit(u64),
+    BitInUse(u64, &'static str),
+    NameInUse(&'static str),
+}
+
+impl fmt::Display for FlagRegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FlagRegistryError::TableFrozen => {
+                write!(f, "flags must be registered before the flag table is used")
+            }
+            FlagRegistryError::InvalidBit(bit) => write!(f, "flag bit {} is out of range", bit),
+            FlagRegistryError::BitInUse(bit, name) => {
+                write!(f, "flag bit {} is already defined as {}", bit, name)
+            }
+            FlagRegistryError::NameInUse(name) => write!(f, "flag {} is already defined", name),
+        }
+    }
+}
+
+impl std::error::Error for FlagRegistryError {}
+
+/// Custom flags registered at runtime, e.g. for vendor kernel bits
+struct FlagRegistry {
+    custom: Vec<FlagDef>,
+    /// Set once the combined table is built; later registrations would be
+    /// missing from it
+    frozen: bool,
+}
+
+impl FlagRegistry {
+    const fn new() -> Self {
+        FlagRegistry {
+            custom: Vec::new(),
+            frozen: false,
+        }
+    }
+
+    fn register(&mut self, def: FlagDef) -> Result<(), FlagRegistryError> {
+        if self.frozen {
+            return Err(FlagRegistryError::TableFrozen);
+        }
+        let mut known = PAGE_FLAGS.iter().chain(self.custom.iter());
+        if let Some(existing) = known.clone().find(|existing| existing.mask == def.mask) {
+            return Err(FlagRegistryError::BitInUse(
+                def.mask.trailing_zeros() as u64,
+                existing.name,
+            ));
+        }
+        if known.any(|existing| existing.name == def.name) {
+            return Err(FlagRegistryError::NameInUse(def.name));
+        }
+        self.custom.push(def);
+        Ok(())
+    }
+
+    /// PAGE_FLAGS followed by the custom flags; no flags can be added after
+    fn freeze(&mut self) -> Vec<FlagDef> {
+        self.frozen = true;
+        PAGE_FLAGS
+            .iter()
+            .chain(self.custom.iter())
+            .copied()
+            .collect()
+    }
+}
+
+static REGISTRY: Mutex<FlagRegistry> = Mutex::new(FlagRegistry::new());
+// Combined table, built on first use
+static FLAG_TABLE: OnceLock<Vec<FlagDef>> = OnceLock::new();
+
+/// Register an extra flag definition supplementing PAGE_FLAGS.
+/// Must be called before the flag table is first used (i.e. at startup).
+pub fn register_flag(
+    bit: u64,
+    name: &'static str,
+    desc: &'static str,
+    category: FlagCategory,
+) -> Result<(), FlagRegistryError> {
+    if bit >= 64 {
+        return Err(FlagRegistryError::InvalidBit(bit));
+    }
+    // The frozen check and the insert share the lock `flag_table` freezes
+    // under, so a flag is either in the table or rejected
+    REGISTRY
+        .lock()
+        .unwrap()
+        .register(FlagDef::new(bit as u32, name, desc, category))
+}
+
+/// All known flags: PAGE_FLAGS followed by any registered custom flags
+pub fn flag_table() -> &'static [FlagDef] {
+    FLAG_TABLE.get_or_init(|| REGISTRY.lock().unwrap().freeze())
+}
+
+/// Look up a flag definition by name
+pub fn find_flag(name: &str) -> Option<&'static FlagDef> {
+    flag_table().iter().find(|def| def.name == name)
+}
+
+/// Combined mask of every flag in the table
+pub fn known_flags_mask() -> u64 {
+    flag_table().iter().fold(0, |mask, def| mask | def.mask)
+}
+
+/// Combined mask of every flag in a category
+pub fn category_mask(category: FlagCategory) -> PageFlags {
+    flag_table()
+        .iter()
+        .filter(|def| def.category == category)
+        .fold(PageFlags::EMPTY, |mask, def| mask | def.flag())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flag_registry_rejects_duplicates() {
+        let mut registry = FlagRegistry::new();
+        registry
+            .register(FlagDef::new(60, "VENDOR_A", "", FlagCategory::Special))
+            .unwrap();
+
+        assert_eq!(
+            registry.register(FlagDef::new(60, "VENDOR_B", "", FlagCategory::Special)),
+            Err(FlagRegistryError::BitInUse(60, "VENDOR_A"))
+        );
+        assert_eq!(
+            registry.register(FlagDef::new(5, "LRU_TOO", "", FlagCategory::Special)),
+            Err(FlagRegistryError::BitInUse(5, "LRU"))
+        );
+        assert_eq!(
+            registry.register(FlagDef::new(61, "VENDOR_A", "", FlagCategory::Special)),
+            Err(FlagRegistryError::NameInUse("VENDOR_A"))
+        );
+        assert_eq!(
+            registry.register(FlagDef::new(62, "DIRTY", "", FlagCategory::Special)),
+            Err(FlagRegistryError::NameInUse("DIRTY"))
+        );
+
+        let table = registry.freeze();
+        assert_eq!(table.len(), PAGE_FLAGS.len() + 1);
+        assert_eq!(table.last().unwrap().name, "VENDOR_A");
+        assert_eq!(
+            registry.register(FlagDef::new(61, "VENDOR_C", "", FlagCategory::Special)),
+            Err(FlagRegistryError::TableFrozen)
+        );
+    }
+
+    #[test]
+    fn test_register_flag_after_table_use() {
+        assert!(find_flag("LRU").is_some());
+        assert_eq!(
+            register_flag(63, "LATE", "", FlagCategory::Special),
+            Err(FlagRegistryError::TableFrozen)
+        );
+        assert_eq!(
+            register_flag(64, "WIDE", "", FlagCategory::Special),
+            Err(FlagRegistryError::InvalidBit(64))
+        );
+        assert!(find_flag("LATE").is_none());
+    }
+}
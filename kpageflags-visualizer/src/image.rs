@@ -0,0 +1,165 @@
+use crate::{category_mask, FlagCategory, PageFlags, PageInfo};
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+const PIXEL_ON: u8 = 255;
+const PIXEL_OFF: u8 = 0;
+
+/// Tallest image written; larger scans put several pages in each pixel
+const MAX_IMAGE_HEIGHT: usize = 4096;
+
+/// Row-major layout shared with the --grid view: page `i` of the range is
+/// drawn at column `i % width`, row `i / width`
+fn grid_position(index: usize, width: usize) -> (usize, usize) {
+    (index % width, index / width)
+}
+
+fn grid_height(pages: usize, width: usize) -> usize {
+    pages.div_ceil(width)
+}
+
+/// Size of the images for a scan, and how many consecutive pages share a
+/// pixel so the height stays within `MAX_IMAGE_HEIGHT`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageLayout {
+    pub width: usize,
+    pub height: usize,
+    pub pages_per_pixel: usize,
+}
+
+impl ImageLayout {
+    pub fn new(pages: usize, width: usize) -> Self {
+        let width = width.max(1);
+        let pages_per_pixel = pages.div_ceil(width * MAX_IMAGE_HEIGHT).max(1);
+        let height = grid_height(pages.div_ceil(pages_per_pixel), width).max(1);
+        Self {
+            width,
+            height,
+            pages_per_pixel,
+        }
+    }
+
+    /// Pixels of one category: on when any page of the pixel has a flag in
+    /// `mask`
+    fn pixels(&self, pages: &[PageInfo], mask: PageFlags) -> Vec<u8> {
+        let mut pixels = vec![PIXEL_OFF; self.width * self.height];
+        for (i, page) in pages.iter().enumerate() {
+            if page.flags.intersects(mask) {
+                let (x, y) = grid_position(i / self.pages_per_pixel, self.width);
+                pixels[y * self.width + x] = PIXEL_ON;
+            }
+        }
+        pixels
+    }
+}
+
+/// Write one monochrome PNG per flag category into `dir` (e.g. `state.png`)
+/// A pixel is on when a page it covers has any flag of the category set
+pub fn write_category_images(
+    pages: &[PageInfo],
+    width: usize,
+    dir: &Path,
+) -> Result<(Vec<PathBuf>, ImageLayout), Box<dyn std::error::Error>> {
+    let layout = ImageLayout::new(pages.len(), width);
+    fs::create_dir_all(dir)?;
+
+    let mut written = Vec::new();
+    for category in FlagCategory::ALL {
+        let pixels = layout.pixels(pages, category_mask(category));
+        let path = dir.join(format!("{:?}.png", category).to_lowercase());
+        write_grayscale_png(&path, layout.width as u32, layout.height as u32, &pixels)?;
+        written.push(path);
+    }
+
+    Ok((written, layout))
+}
+
+fn write_grayscale_png(
+    path: &Path,
+    width: u32,
+    height: u32,
+    pixels: &[u8],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), width, height);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(pixels)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(pfn: u64, flags: PageFlags) -> PageInfo {
+        PageInfo { pfn, flags }
+    }
+
+    /// A page with only LRU set, or with no flags
+    fn lru_page(pfn: u64, lru: bool) -> PageInfo {
+        page(
+            pfn,
+            if lru {
+                PageFlags::LRU
+            } else {
+                PageFlags::EMPTY
+            },
+        )
+    }
+
+    #[test]
+    fn test_pixels_follow_grid_layout() {
+        let pages: Vec<PageInfo> = (0..7).map(|i| lru_page(i, i % 3 == 0)).collect();
+        let layout = ImageLayout::new(pages.len(), 3);
+        assert_eq!(
+            layout,
+            ImageLayout {
+                width: 3,
+                height: 3,
+                pages_per_pixel: 1
+            }
+        );
+        // Pages 0, 3 and 6 start each row; the last row is padded
+        assert_eq!(
+            layout.pixels(&pages, PageFlags::LRU),
+            [255, 0, 0, 255, 0, 0, 255, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_categories_get_separate_images() {
+        let pages = [
+            page(0, PageFlags::LRU),
+            page(1, PageFlags::DIRTY),
+            page(2, PageFlags::LRU | PageFlags::DIRTY),
+        ];
+        let layout = ImageLayout::new(pages.len(), 3);
+        let memory = layout.pixels(&pages, category_mask(FlagCategory::Memory));
+        let state = layout.pixels(&pages, category_mask(FlagCategory::State));
+        assert_eq!(memory, [255, 0, 255]);
+        assert_eq!(state, [0, 255, 255]);
+
+        let dir = tempfile::tempdir().unwrap();
+        let (written, _) = write_category_images(&pages, 3, dir.path()).unwrap();
+        assert_eq!(written.len(), FlagCategory::ALL.len());
+        assert!(dir.path().join("memory.png").exists());
+    }
+
+    #[test]
+    fn test_large_scans_are_downsampled() {
+        let pages = 1000 * MAX_IMAGE_HEIGHT * 3 + 1;
+        let layout = ImageLayout::new(pages, 1000);
+        assert_eq!(layout.pages_per_pixel, 4);
+        assert!(layout.height <= MAX_IMAGE_HEIGHT);
+
+        // A flagged page anywhere in a pixel's run lights it
+        let pages: Vec<PageInfo> = (0..(2 * MAX_IMAGE_HEIGHT + 2) as u64)
+            .map(|pfn| lru_page(pfn, pfn == 3))
+            .collect();
+        let layout = ImageLayout::new(pages.len(), 1);
+        assert_eq!(layout.pages_per_pixel, 3);
+        let pixels = layout.pixels(&pages, PageFlags::LRU);
+        assert_eq!(&pixels[..3], [0, 255, 0]);
+    }
+}
@@ -0,0 +1,266 @@
+//! /proc/kpagecount: how many times each physical page is mapped, laid out
+//! like /proc/kpageflags (one little-endian u64 per PFN)
+
+use crate::flags::PageFlags;
+use crate::report::MapCountHistogram;
+use crate::{FlagSource, InterruptCheck, KPageFlagsReader, PageInfo, MAX_CONSECUTIVE_FAILURES};
+use byteorder::{LittleEndian, ReadBytesExt};
+use colored::*;
+use std::fs::File;
+use std::io::{BufReader, Seek, SeekFrom};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+pub struct KPageCountReader {
+    file: Box<dyn FlagSource>,
+}
+
+impl KPageCountReader {
+    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
+        let file = File::open("/proc/kpagecount")?;
+        Ok(Self {
+            file: Box::new(BufReader::new(file)),
+        })
+    }
+
+    /// Map count of a page, or `None` past the end of the data
+    pub fn read_count(&mut self, pfn: u64) -> Result<Option<u64>, Box<dyn std::error::Error>> {
+        let Some(offset) = pfn.checked_mul(8) else {
+            return Ok(None);
+        };
+        self.file.seek(SeekFrom::Start(offset))?;
+
+        match self.file.read_u64::<LittleEndian>() {
+            Ok(count) => Ok(Some(count)),
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(Box::new(e)),
+        }
+    }
+
+    /// Call `visit(pfn, count)` for every readable page from `start_pfn`
+    /// until `end_pfn`, the end of memory, or Ctrl-C
+    fn for_each_count(
+        &mut self,
+        start_pfn: u64,
+        end_pfn: u64,
+        interrupt_flag: Arc<AtomicBool>,
+        mut visit: impl FnMut(u64, u64),
+    ) {
+        let mut consecutive_failures = 0;
+        let mut interrupt_check = InterruptCheck::new(interrupt_flag);
+        for pfn in start_pfn..end_pfn {
+            if interrupt_check.triggered() {
+                break;
+            }
+
+            match self.read_count(pfn) {
+                Ok(Some(count)) => {
+                    visit(pfn, count);
+                    consecutive_failures = 0;
+                }
+                Ok(None) | Err(_) => {
+                    consecutive_failures += 1;
+                    if consecutive_failures > MAX_CONSECUTIVE_FAILURES {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// `(pfn, map count)` of every readable page in a range
+    pub fn read_range(
+        &mut self,
+        start_pfn: u64,
+        count: u64,
+        interrupt_flag: Arc<AtomicBool>,
+    ) -> Vec<(u64, u64)> {
+        let mut pages = Vec::new();
+        self.for_each_count(
+            start_pfn,
+            start_pfn.saturating_add(count),
+            interrupt_flag,
+            |pfn, count| pages.push((pfn, count)),
+        );
+        pages
+    }
+
+    /// `(pfn, map count)` of every page from `start_pfn` to the end of memory
+    pub fn read_all_pages(
+        &mut self,
+        start_pfn: u64,
+        interrupt_flag: Arc<AtomicBool>,
+    ) -> Vec<(u64, u64)> {
+        let mut pages = Vec::new();
+        self.for_each_count(start_pfn, u64::MAX, interrupt_flag, |pfn, count| {
+            pages.push((pfn, count))
+        });
+        pages
+    }
+
+    /// Bucket map counts without keeping per-page data, then print the
+    /// histogram
+    pub fn scan_for_summary_only(
+        &mut self,
+        start_pfn: u64,
+        count: Option<u64>,
+        interrupt_flag: Arc<AtomicBool>,
+    ) -> MapCountHistogram {
+        let mut histogram = MapCountHistogram::default();
+        let end_pfn = count
+            .map(|c| start_pfn.saturating_add(c))
+            .unwrap_or(u64::MAX);
+        self.for_each_count(start_pfn, end_pfn, interrupt_flag, |_, count| {
+            histogram.record(count)
+        });
+
+        println!("\n{}", "=== MAP COUNTS ===".blue().bold());
+        histogram.print();
+        histogram
+    }
+}
+
+/// A page's flags together with its map count from /proc/kpagecount
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageInfoFull {
+    pub pfn: u64,
+    pub flags: PageFlags,
+    pub ref_count: u64,
+}
+
+impl PageInfoFull {
+    pub fn page_info(&self) -> PageInfo {
+        PageInfo::new(self.pfn, self.flags.bits())
+    }
+}
+
+/// Reads /proc/kpageflags and /proc/kpagecount side by side
+pub struct KPageReader {
+    flags: KPageFlagsReader,
+    counts: KPageCountReader,
+}
+
+impl KPageReader {
+    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self {
+            flags: KPageFlagsReader::new()?,
+            counts: KPageCountReader::new()?,
+        })
+    }
+
+    /// Flags and map count of every page in a range that both files have
+    pub fn read_range_combined(
+        &mut self,
+        start_pfn: u64,
+        count: u64,
+        interrupt_flag: Arc<AtomicBool>,
+    ) -> Result<Vec<PageInfoFull>, Box<dyn std::error::Error>> {
+        let counts = self.counts.read_range(start_pfn, count, interrupt_flag);
+        self.combine(counts)
+    }
+
+    /// Flags and map count of every page from `start_pfn` to the end of
+    /// memory
+    pub fn read_all_pages_combined(
+        &mut self,
+        start_pfn: u64,
+        interrupt_flag: Arc<AtomicBool>,
+    ) -> Result<Vec<PageInfoFull>, Box<dyn std::error::Error>> {
+        let counts = self.counts.read_all_pages(start_pfn, interrupt_flag);
+        self.combine(counts)
+    }
+
+    /// Look up the flags of PFN-ordered map counts, dropping PFNs that
+    /// kpageflags doesn't have
+    fn combine(
+        &mut self,
+        counts: Vec<(u64, u64)>,
+    ) -> Result<Vec<PageInfoFull>, Box<dyn std::error::Error>> {
+        let pfns: Vec<u64> = counts.iter().map(|&(pfn, _)| pfn).collect();
+        let mut flags = self.flags.read_pfns(&pfns)?.into_iter().peekable();
+
+        let mut pages = Vec::with_capacity(counts.len());
+        for (pfn, ref_count) in counts {
+            while flags.next_if(|&(flags_pfn, _)| flags_pfn < pfn).is_some() {}
+            if let Some((_, page_flags)) = flags.next_if(|&(flags_pfn, _)| flags_pfn == pfn) {
+                pages.push(PageInfoFull {
+                    pfn,
+                    flags: page_flags,
+                    ref_count,
+                });
+            }
+        }
+        Ok(pages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn entries(values: &[u64]) -> Box<dyn FlagSource> {
+        let data: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        Box::new(Cursor::new(data))
+    }
+
+    #[test]
+    fn test_read_count() {
+        let mut reader = KPageCountReader {
+            file: entries(&[0, 1, 42]),
+        };
+
+        assert_eq!(reader.read_count(2).unwrap(), Some(42));
+        assert_eq!(reader.read_count(0).unwrap(), Some(0));
+        assert_eq!(reader.read_count(3).unwrap(), None);
+    }
+
+    #[test]
+    fn test_read_count_ranges() {
+        let interrupt = Arc::new(AtomicBool::new(false));
+        let mut reader = KPageCountReader {
+            file: entries(&[0, 1, 2, 300]),
+        };
+
+        assert_eq!(reader.read_range(1, 2, interrupt.clone()), [(1, 1), (2, 2)]);
+        assert_eq!(
+            reader.read_all_pages(2, interrupt.clone()),
+            [(2, 2), (3, 300)]
+        );
+
+        let histogram = reader.scan_for_summary_only(0, None, interrupt.clone());
+        assert_eq!(histogram.buckets.iter().sum::<u32>(), 4);
+        assert_eq!(histogram.shared_pages, 2);
+
+        // A range ending past u64::MAX saturates instead of overflowing
+        let histogram = reader.scan_for_summary_only(u64::MAX - 1, Some(10), interrupt);
+        assert_eq!(histogram.buckets.iter().sum::<u32>(), 0);
+    }
+
+    #[test]
+    fn test_read_range_combined() {
+        let slab = crate::find_flag("SLAB").unwrap().mask;
+        let mut reader = KPageReader {
+            flags: KPageFlagsReader {
+                file: entries(&[0, slab, slab]),
+                capture_pages: Some(3),
+                regions: Vec::new(),
+                counts: None,
+                filter: None,
+            },
+            // One more count than flags: the extra PFN is dropped
+            counts: KPageCountReader {
+                file: entries(&[0, 1, 500, 7]),
+            },
+        };
+
+        let pages = reader
+            .read_range_combined(1, 3, Arc::new(AtomicBool::new(false)))
+            .unwrap();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[1].pfn, 2);
+        assert!(pages[1].flags.contains(PageFlags::SLAB));
+        assert_eq!(pages[1].ref_count, 500);
+        assert_eq!(pages[1].page_info().flags, pages[1].flags);
+    }
+}
@@ -1,15 +1,52 @@
 use byteorder::{LittleEndian, ReadBytesExt};
 use clap::{Arg, Command};
 use colored::*;
-use memmap2::MmapOptions;
+use flate2::read::GzDecoder;
 use rand::Rng;
+use serde::Serialize;
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-
+use std::time::{Duration, Instant};
+
+mod codec;
+mod contiguous;
+mod doctor;
+mod filter;
+mod flags;
+mod image;
+mod kpagecount;
+mod mmap;
+mod output;
+mod pagemap;
+mod parallel;
+mod region;
+mod report;
+mod snapshot;
+mod tracker;
 mod tui;
+mod watch;
+
+pub use flags::{
+    category_mask, find_flag, flag_table, known_flags_mask, register_flag, FlagCategory, FlagDef,
+    PageClass, PageFlags, PAGE_FLAGS,
+};
+
+use contiguous::parse_flag_list;
+use filter::PageFilter;
+use kpagecount::{KPageCountReader, KPageReader};
+use linux_memory_monitor::{format_memory_kb, KsmStats, MemoryStats, MemoryStatsDiff, MemoryUtils};
+use mmap::KPageFlagsMmap;
+use output::{OutputFormat, PageWriter};
+use pagemap::{PageMapReader, ProcessPage};
+use region::Region;
+use report::{ScanReport, ThpChange, MAX_TRACKED_COMPOUNDS};
+use snapshot::KPageFlagsSnapshot;
+use tracker::FlagTracker;
+use watch::{change_marker, RangeWatcher};
 
 // Helper function to estimate total pages from /proc/meminfo
 fn get_estimated_total_pages() -> Result<u64, Box<dyn std::error::Error>> {
@@ -23,197 +60,108 @@ fn get_estimated_total_pages() -> Result<u64, Box<dyn std::error::Error>> {
             let parts: Vec<&str> = line.split_whitespace().collect();
             if parts.len() >= 2 {
                 let mem_kb: u64 = parts[1].parse()?;
-                // Convert kB to pages (assuming 4KB pages)
-                let total_pages = (mem_kb * 1024) / 4096;
+                let total_pages = (mem_kb * 1024) / MemoryUtils::get_page_size();
                 return Ok(total_pages);
             }
         }
     }
 
     // Fallback: assume 4GB of memory
-    Ok(1048576) // 4GB / 4KB = 1M pages
+    Ok((4 << 30) / MemoryUtils::get_page_size())
 }
 
-// Page flag definitions with categories
-pub const PAGE_FLAGS: &[(u64, &str, &str, FlagCategory)] = &[
-    (1 << 0, "LOCKED", "Page is locked", FlagCategory::State),
-    (1 << 1, "ERROR", "Page has error", FlagCategory::Error),
-    (
-        1 << 2,
-        "REFERENCED",
-        "Page has been referenced",
-        FlagCategory::Usage,
-    ),
-    (
-        1 << 3,
-        "UPTODATE",
-        "Page is up to date",
-        FlagCategory::State,
-    ),
-    (1 << 4, "DIRTY", "Page is dirty", FlagCategory::State),
-    (1 << 5, "LRU", "Page is on LRU list", FlagCategory::Memory),
-    (
-        1 << 6,
-        "ACTIVE",
-        "Page is on active list",
-        FlagCategory::Memory,
-    ),
-    (
-        1 << 7,
-        "SLAB",
-        "Page is slab allocated",
-        FlagCategory::Allocation,
-    ),
-    (
-        1 << 8,
-        "WRITEBACK",
-        "Page is under writeback",
-        FlagCategory::IO,
-    ),
-    (
-        1 << 9,
-        "RECLAIM",
-        "Page is being reclaimed",
-        FlagCategory::Memory,
-    ),
-    (
-        1 << 10,
-        "BUDDY",
-        "Page is free buddy page",
-        FlagCategory::Allocation,
-    ),
-    (
-        1 << 11,
-        "MMAP",
-        "Page is memory mapped",
-        FlagCategory::Usage,
-    ),
-    (1 << 12, "ANON", "Page is anonymous", FlagCategory::Usage),
-    (
-        1 << 13,
-        "SWAPCACHE",
-        "Page is in swap cache",
-        FlagCategory::Memory,
-    ),
-    (
-        1 << 14,
-        "SWAPBACKED",
-        "Page is swap backed",
-        FlagCategory::Memory,
-    ),
-    (
-        1 << 15,
-        "COMPOUND_HEAD",
-        "Compound page head",
-        FlagCategory::Structure,
-    ),
-    (
-        1 << 16,
-        "COMPOUND_TAIL",
-        "Compound page tail",
-        FlagCategory::Structure,
-    ),
-    (1 << 17, "HUGE", "Huge page", FlagCategory::Structure),
-    (
-        1 << 18,
-        "UNEVICTABLE",
-        "Page is unevictable",
-        FlagCategory::Memory,
-    ),
-    (
-        1 << 19,
-        "HWPOISON",
-        "Hardware poisoned page",
-        FlagCategory::Error,
-    ),
-    (
-        1 << 20,
-        "NOPAGE",
-        "No page frame exists",
-        FlagCategory::State,
-    ),
-    (1 << 21, "KSM", "KSM page", FlagCategory::Special),
-    (
-        1 << 22,
-        "THP",
-        "Transparent huge page",
-        FlagCategory::Structure,
-    ),
-    (1 << 23, "OFFLINE", "Page is offline", FlagCategory::State),
-    (1 << 24, "ZERO_PAGE", "Zero page", FlagCategory::Special),
-    (1 << 25, "IDLE", "Page is idle", FlagCategory::Usage),
-    (1 << 26, "PGTABLE", "Page table page", FlagCategory::Special),
-    // Additional flags that might be present
-    (
-        1 << 32,
-        "RESERVED",
-        "Reserved page (common in early memory)",
-        FlagCategory::State,
-    ),
-];
+/// Number of flags shown by each --partial-every summary line
+const PARTIAL_SUMMARY_TOP_FLAGS: usize = 6;
+
+/// Unreadable PFNs in a row after which a scan assumes the end of memory
+const MAX_CONSECUTIVE_FAILURES: u32 = 1000;
+
+/// How often scan loops poll the Ctrl-C flag
+const INTERRUPT_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Time-based interrupt polling, so Ctrl-C latency doesn't depend on how
+/// fast the scan loop is running
+struct InterruptCheck {
+    flag: Arc<AtomicBool>,
+    last_check: Instant,
+}
+
+impl InterruptCheck {
+    fn new(flag: Arc<AtomicBool>) -> Self {
+        Self {
+            flag,
+            last_check: Instant::now(),
+        }
+    }
+
+    fn triggered(&mut self) -> bool {
+        if self.last_check.elapsed() < INTERRUPT_CHECK_INTERVAL {
+            return false;
+        }
+        self.last_check = Instant::now();
+        self.flag.load(Ordering::Relaxed)
+    }
+}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum FlagCategory {
-    State,      // Page state flags
-    Memory,     // Memory management flags
-    Usage,      // Usage tracking flags
-    Allocation, // Allocation type flags
-    IO,         // I/O related flags
-    Structure,  // Page structure flags
-    Special,    // Special purpose flags
-    Error,      // Error flags
+/// Sleep for `duration` in short steps, returning false as soon as the
+/// interrupt flag is set
+async fn sleep_unless_interrupted(duration: Duration, interrupt_flag: &AtomicBool) -> bool {
+    let mut slept = Duration::ZERO;
+    while slept < duration {
+        if interrupt_flag.load(Ordering::Relaxed) {
+            return false;
+        }
+        let step = (duration - slept).min(INTERRUPT_CHECK_INTERVAL);
+        tokio::time::sleep(step).await;
+        slept += step;
+    }
+    !interrupt_flag.load(Ordering::Relaxed)
 }
 
 #[derive(Debug, Clone)]
 pub struct PageInfo {
     pfn: u64,
-    flags: u64,
+    flags: PageFlags,
 }
 
 impl PageInfo {
     fn new(pfn: u64, flags: u64) -> Self {
-        Self { pfn, flags }
+        Self {
+            pfn,
+            flags: PageFlags::from_bits(flags),
+        }
     }
 
     fn get_flag_names(&self) -> Vec<&'static str> {
-        PAGE_FLAGS
-            .iter()
-            .filter(|(flag, _, _, _)| self.flags & flag != 0)
-            .map(|(_, name, _, _)| *name)
-            .collect()
+        self.flags.iter_names().collect()
     }
 
     fn get_flag_descriptions(&self) -> Vec<(&'static str, &'static str)> {
-        PAGE_FLAGS
-            .iter()
-            .filter(|(flag, _, _, _)| self.flags & flag != 0)
-            .map(|(_, name, desc, _)| (*name, *desc))
+        self.flags
+            .iter_defs()
+            .map(|def| (def.name, def.description))
             .collect()
     }
 
-    fn get_primary_category(&self) -> Option<FlagCategory> {
-        // Return the category of the most significant flag
-        PAGE_FLAGS
-            .iter()
-            .filter(|(flag, _, _, _)| self.flags & flag != 0)
-            .map(|(_, _, _, category)| *category)
-            .next()
-    }
-
     fn get_flag_categories(&self) -> Vec<FlagCategory> {
-        let mut categories: Vec<FlagCategory> = PAGE_FLAGS
-            .iter()
-            .filter(|(flag, _, _, _)| self.flags & flag != 0)
-            .map(|(_, _, _, category)| *category)
-            .collect();
+        let mut categories: Vec<FlagCategory> =
+            self.flags.iter_defs().map(|def| def.category).collect();
         categories.sort_by_key(|c| format!("{:?}", c));
         categories.dedup();
         categories
     }
 
+    fn page_class(&self) -> PageClass {
+        PageClass::from_flags(self.flags)
+    }
+
+    /// Inactive, evictable, file-backed LRU page (see `is_reclaim_candidate`)
+    fn is_reclaimable(&self) -> bool {
+        report::is_reclaim_candidate(self.flags)
+    }
+
     fn get_unknown_flags(&self) -> Vec<u8> {
-        let known_flags: u64 = PAGE_FLAGS.iter().map(|(flag, _, _, _)| flag).sum();
-        let unknown_flags = self.flags & !known_flags;
+        let unknown_flags = self.flags.unknown().bits();
 
         let mut unknown_bits = Vec::new();
         for bit in 0..64 {
@@ -225,22 +173,130 @@ impl PageInfo {
     }
 }
 
+/// Self-describing representation of a page for JSON export, so downstream
+/// tools don't need their own copy of the flag table
+#[derive(Debug, Serialize)]
+pub struct PageRecord {
+    pfn: u64,
+    flags_raw: u64,
+    flags: Vec<&'static str>,
+    categories: Vec<FlagCategory>,
+    unknown_bits: Vec<u8>,
+}
+
+impl From<&PageInfo> for PageRecord {
+    fn from(page: &PageInfo) -> Self {
+        Self {
+            pfn: page.pfn,
+            flags_raw: page.flags.bits(),
+            flags: page.get_flag_names(),
+            categories: page.get_flag_categories(),
+            unknown_bits: page.get_unknown_flags(),
+        }
+    }
+}
+
+/// Pages the individual dump may show and how many `--nonzero-only`
+/// skipped; filtering comes before `--limit`, so the limit counts shown pages
+fn pages_to_dump(pages: &[PageInfo], nonzero_only: bool) -> (Vec<&PageInfo>, usize) {
+    let shown: Vec<&PageInfo> = pages
+        .iter()
+        .filter(|page| !nonzero_only || !page.flags.is_empty())
+        .collect();
+    let skipped = pages.len() - shown.len();
+    (shown, skipped)
+}
+
+fn write_pages_json(pages: &[&PageInfo], path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let records: Vec<PageRecord> = pages.iter().map(|page| PageRecord::from(*page)).collect();
+    let writer = std::io::BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(writer, &records)?;
+    Ok(())
+}
+
+/// Seekable source of raw 8-byte kpageflags entries
+trait FlagSource: Read + Seek + Send {}
+impl<T: Read + Seek + Send> FlagSource for T {}
+
 pub struct KPageFlagsReader {
-    file: BufReader<File>,
+    file: Box<dyn FlagSource>,
+    /// Number of entries in a capture file (procfs has no meaningful length)
+    capture_pages: Option<u64>,
+    /// Regions summary scans break flag counts down by (empty for none)
+    regions: Vec<Region>,
+    /// Read alongside flags by summary scans to histogram map counts
+    counts: Option<KPageCountReader>,
+    /// Pages scans skip unless they match (`None` keeps every page)
+    filter: Option<PageFilter>,
 }
 
 impl KPageFlagsReader {
     pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
         let file = File::open("/proc/kpageflags")?;
         Ok(Self {
-            file: BufReader::new(file),
+            file: Box::new(BufReader::new(file)),
+            capture_pages: None,
+            regions: Vec::new(),
+            counts: None,
+            filter: None,
+        })
+    }
+
+    /// Read flags from a saved copy of /proc/kpageflags, optionally gzipped,
+    /// or from a file written by --export or --save-snapshot
+    /// Gzip streams can't seek, so they are decompressed into an anonymous
+    /// temporary file; exports and snapshots keep only their own pages in
+    /// memory
+    pub fn from_capture(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut file = File::open(path)?;
+        let mut magic = [0u8; 4];
+        let magic_len = file.read(&mut magic)?;
+        let is_gzip = magic_len >= 2 && magic[..2] == [0x1f, 0x8b];
+        let is_export = magic_len == 4 && &magic == codec::EXPORT_MAGIC;
+        let is_snapshot = magic_len == 4 && &magic[..3] == snapshot::SNAPSHOT_MAGIC;
+        file.seek(SeekFrom::Start(0))?;
+
+        let (source, len): (Box<dyn FlagSource>, u64) = if is_snapshot {
+            let mut capture = codec::SparseCapture::default();
+            for page in snapshot::read_pages_binary(&mut BufReader::new(file))? {
+                capture.insert(&page)?;
+            }
+            let len = capture.byte_len();
+            (Box::new(capture), len)
+        } else if is_export {
+            let capture = codec::read_export_as_kpageflags(&mut BufReader::new(file))?;
+            let len = capture.byte_len();
+            (Box::new(capture), len)
+        } else if is_gzip {
+            let mut decompressed = tempfile::tempfile()?;
+            let len = std::io::copy(&mut GzDecoder::new(BufReader::new(file)), &mut decompressed)?;
+            decompressed.seek(SeekFrom::Start(0))?;
+            (Box::new(BufReader::new(decompressed)), len)
+        } else {
+            let len = file.metadata()?.len();
+            (Box::new(BufReader::new(file)), len)
+        };
+
+        Ok(Self {
+            file: source,
+            capture_pages: Some(len / 8),
+            regions: Vec::new(),
+            counts: None,
+            filter: None,
         })
     }
 
-    fn get_total_pages(&mut self) -> Result<u64, Box<dyn std::error::Error>> {
-        // For /proc/kpageflags, we can't reliably get the file size
-        // Instead, we'll return a flag value that indicates "read until EOF"
-        Ok(u64::MAX) // Special value indicating "read all"
+    /// Only keep pages matching `filter` in scans
+    pub fn with_filter(mut self, filter: Option<PageFilter>) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Whether scans keep a page, i.e. it passes the filter if there is one
+    fn keeps(&self, pfn: u64, flags: u64) -> bool {
+        self.filter
+            .as_ref()
+            .is_none_or(|filter| filter.matches(&PageInfo::new(pfn, flags)))
     }
 
     fn read_all_pages(
@@ -251,7 +307,6 @@ impl KPageFlagsReader {
         let mut pages = Vec::new();
         let mut pfn = start_pfn;
         let mut consecutive_failures = 0;
-        const MAX_CONSECUTIVE_FAILURES: u32 = 1000;
 
         // Get estimated total for progress reporting
         let estimated_total = get_estimated_total_pages().unwrap_or(1048576);
@@ -268,9 +323,10 @@ impl KPageFlagsReader {
             "Press Ctrl-C to stop and show summary of pages scanned so far".yellow()
         );
 
+        let mut interrupt_check = InterruptCheck::new(interrupt_flag.clone());
         loop {
-            // Check for interrupt signal every 1000 pages
-            if pages.len() % 1000 == 0 && interrupt_flag.load(Ordering::Relaxed) {
+            // Check for interrupt signal every ~100ms
+            if interrupt_check.triggered() {
                 println!(
                     "\n{}",
                     "Interrupt received! Stopping scan and showing summary..."
@@ -281,12 +337,13 @@ impl KPageFlagsReader {
             }
 
             match self.read_page_flags(pfn) {
+                Ok(Some(flags)) if !self.keeps(pfn, flags) => consecutive_failures = 0,
                 Ok(Some(flags)) => {
                     pages.push(PageInfo::new(pfn, flags));
                     consecutive_failures = 0;
 
                     // Show progress every 50,000 pages
-                    if pages.len() % 50000 == 0 {
+                    if pages.len().is_multiple_of(50000) {
                         let progress = if estimated_total > 0 {
                             format!(
                                 " ({:.1}%)",
@@ -338,7 +395,10 @@ impl KPageFlagsReader {
     }
 
     fn read_page_flags(&mut self, pfn: u64) -> Result<Option<u64>, Box<dyn std::error::Error>> {
-        let offset = pfn * 8; // Each entry is 8 bytes
+        // Each entry is 8 bytes; PFNs past the largest offset don't exist
+        let Some(offset) = pfn.checked_mul(8) else {
+            return Ok(None);
+        };
         self.file.seek(SeekFrom::Start(offset))?;
 
         match self.file.read_u64::<LittleEndian>() {
@@ -348,6 +408,107 @@ impl KPageFlagsReader {
         }
     }
 
+    /// Flags of many PFNs at once, sorted by PFN with duplicates dropped
+    ///
+    /// Runs of consecutive PFNs are read with one seek and one read each, so
+    /// this is much cheaper than a `read_page_flags` per PFN. PFNs past the
+    /// end of the data are skipped.
+    pub fn read_pfns(
+        &mut self,
+        pfns: &[u64],
+    ) -> Result<Vec<(u64, PageFlags)>, Box<dyn std::error::Error>> {
+        const MAX_RUN: usize = 4096;
+
+        let mut sorted = pfns.to_vec();
+        sorted.sort_unstable();
+        sorted.dedup();
+
+        let mut pages = Vec::with_capacity(sorted.len());
+        let mut buf = Vec::new();
+        let mut rest = sorted.as_slice();
+        while let Some(&first) = rest.first() {
+            let run = rest
+                .iter()
+                .enumerate()
+                .take(MAX_RUN)
+                .take_while(|&(i, &pfn)| pfn == first + i as u64)
+                .count();
+
+            buf.resize(run * 8, 0);
+            self.file.seek(SeekFrom::Start(first * 8))?;
+            let mut filled = 0;
+            while filled < buf.len() {
+                match self.file.read(&mut buf[filled..])? {
+                    0 => break,
+                    n => filled += n,
+                }
+            }
+
+            for (i, entry) in buf[..filled].chunks_exact(8).enumerate() {
+                pages.push((
+                    first + i as u64,
+                    PageFlags::from_bits(u64::from_le_bytes(entry.try_into().unwrap())),
+                ));
+            }
+            rest = &rest[run..];
+        }
+
+        Ok(pages)
+    }
+
+    /// Summarize only the pages process `pid` has present in RAM, found
+    /// through its pagemap instead of scanning every PFN
+    ///
+    /// Each physical page counts once, however many times it is mapped.
+    pub fn scan_process_pages(
+        &mut self,
+        pid: u32,
+        interrupt_flag: Arc<AtomicBool>,
+    ) -> Result<ScanReport, Box<dyn std::error::Error>> {
+        let pfns = pagemap::process_present_pfns(pid, interrupt_flag)?;
+        let pages = self.read_pfns(&pfns)?;
+
+        let mut report =
+            ScanReport::new(pages.first().map_or(0, |&(pfn, _)| pfn)).with_regions(&self.regions);
+        for (pfn, flags) in pages {
+            report.record(pfn, flags);
+        }
+        Ok(report)
+    }
+
+    /// Every page of `len` bytes of process `pid` from `vaddr`, with its
+    /// pagemap entry and, for pages present in RAM, its flags
+    ///
+    /// Needs root: without CAP_SYS_ADMIN pagemap hides PFNs, so no flags can
+    /// be looked up. Stops early once `interrupt_flag` is set.
+    pub fn for_process_range(
+        &mut self,
+        pid: u32,
+        vaddr: u64,
+        len: u64,
+        interrupt_flag: Arc<AtomicBool>,
+    ) -> Result<Vec<ProcessPage>, Box<dyn std::error::Error>> {
+        let mut interrupt_check = InterruptCheck::new(interrupt_flag);
+        let entries = PageMapReader::for_pid(pid)?
+            .translate_range(vaddr, len)
+            .take_while(|_| !interrupt_check.triggered())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let pfns: Vec<u64> = entries.iter().filter_map(|(_, entry)| entry.pfn).collect();
+        let flags: HashMap<u64, PageFlags> = self.read_pfns(&pfns)?.into_iter().collect();
+
+        Ok(entries
+            .into_iter()
+            .map(|(vaddr, entry)| ProcessPage {
+                vaddr,
+                entry,
+                info: entry
+                    .pfn
+                    .and_then(|pfn| flags.get(&pfn).map(|&flags| PageInfo { pfn, flags })),
+            })
+            .collect())
+    }
+
     pub fn read_range(
         &mut self,
         start_pfn: u64,
@@ -356,11 +517,11 @@ impl KPageFlagsReader {
     ) -> Result<Vec<PageInfo>, Box<dyn std::error::Error>> {
         let mut pages = Vec::new();
         let mut consecutive_failures = 0;
-        const MAX_CONSECUTIVE_FAILURES: u32 = 1000; // Stop after 1000 consecutive failures
 
-        for pfn in start_pfn..start_pfn + count {
-            // Check for interrupt signal every 1000 pages
-            if pages.len() % 1000 == 0 && interrupt_flag.load(Ordering::Relaxed) {
+        let mut interrupt_check = InterruptCheck::new(interrupt_flag);
+        for pfn in start_pfn..start_pfn.saturating_add(count) {
+            // Check for interrupt signal every ~100ms
+            if interrupt_check.triggered() {
                 println!(
                     "\n{}",
                     "Interrupt received! Stopping scan and showing summary..."
@@ -371,6 +532,7 @@ impl KPageFlagsReader {
             }
 
             match self.read_page_flags(pfn) {
+                Ok(Some(flags)) if !self.keeps(pfn, flags) => consecutive_failures = 0,
                 Ok(Some(flags)) => {
                     pages.push(PageInfo::new(pfn, flags));
                     consecutive_failures = 0;
@@ -394,6 +556,151 @@ impl KPageFlagsReader {
         Ok(pages)
     }
 
+    /// Call `visit(pfn, flags)` for every readable page in a range until it
+    /// returns false, the range ends, or Ctrl-C is pressed. No progress is
+    /// printed, so callers fully control the output.
+    fn for_each_page(
+        &mut self,
+        start_pfn: u64,
+        count: Option<u64>,
+        interrupt_flag: Arc<AtomicBool>,
+        mut visit: impl FnMut(u64, u64) -> Result<bool, Box<dyn std::error::Error>>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut consecutive_failures = 0u32;
+
+        let end_pfn = count
+            .map(|c| start_pfn.saturating_add(c))
+            .unwrap_or(u64::MAX);
+        let mut interrupt_check = InterruptCheck::new(interrupt_flag);
+        for pfn in start_pfn..end_pfn {
+            if interrupt_check.triggered() {
+                break;
+            }
+
+            match self.read_page_flags(pfn) {
+                Ok(Some(flags)) if !self.keeps(pfn, flags) => consecutive_failures = 0,
+                Ok(Some(flags)) => {
+                    consecutive_failures = 0;
+                    if !visit(pfn, flags)? {
+                        break;
+                    }
+                }
+                Ok(None) | Err(_) => {
+                    consecutive_failures += 1;
+                    if consecutive_failures > MAX_CONSECUTIVE_FAILURES {
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Call `callback` with every readable page in a range, without
+    /// collecting them, until the range ends or Ctrl-C is pressed
+    ///
+    /// Returns how many pages were passed to `callback`; pages dropped by the
+    /// reader's filter aren't counted.
+    pub fn scan_with_callback<F: FnMut(PageInfo)>(
+        &mut self,
+        start_pfn: u64,
+        count: Option<u64>,
+        interrupt_flag: Arc<AtomicBool>,
+        mut callback: F,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
+        let mut scanned = 0;
+        self.for_each_page(start_pfn, count, interrupt_flag, |pfn, flags| {
+            callback(PageInfo::new(pfn, flags));
+            scanned += 1;
+            Ok(true)
+        })?;
+        Ok(scanned)
+    }
+
+    /// Lazily read pages from `start_pfn` on, one seek and read per PFN
+    ///
+    /// Read errors are yielded and the stream carries on past them, like the
+    /// collecting scans it ends after a long run of unreadable PFNs.
+    pub fn stream_pages(
+        &mut self,
+        start_pfn: u64,
+    ) -> impl Iterator<Item = Result<PageInfo, Box<dyn std::error::Error>>> + '_ {
+        let mut pfn = start_pfn;
+        let mut consecutive_failures = 0u32;
+
+        std::iter::from_fn(move || {
+            while consecutive_failures <= MAX_CONSECUTIVE_FAILURES {
+                let current = pfn;
+                pfn = pfn.checked_add(1)?;
+                match self.read_page_flags(current) {
+                    Ok(Some(flags)) => {
+                        consecutive_failures = 0;
+                        if self.keeps(current, flags) {
+                            return Some(Ok(PageInfo::new(current, flags)));
+                        }
+                    }
+                    Ok(None) => consecutive_failures += 1,
+                    Err(e) => {
+                        consecutive_failures += 1;
+                        return Some(Err(e));
+                    }
+                }
+            }
+            None
+        })
+    }
+
+    /// Write `PFN<TAB>FLAGS` lines (decimal PFN, 16 hex digits of the raw
+    /// flag word) for a range, returning the number of lines written
+    ///
+    /// Nothing else is written to `out`, so the output can be piped into other
+    /// tools; a closed pipe just ends the dump.
+    pub fn dump_raw<W: Write>(
+        &mut self,
+        out: &mut W,
+        start_pfn: u64,
+        count: Option<u64>,
+        nonzero_only: bool,
+        interrupt_flag: Arc<AtomicBool>,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
+        let mut written = 0;
+        self.for_each_page(start_pfn, count, interrupt_flag, |pfn, flags| {
+            if nonzero_only && flags == 0 {
+                return Ok(true);
+            }
+            match writeln!(out, "{}\t{:016x}", pfn, flags) {
+                Ok(()) => {
+                    written += 1;
+                    Ok(true)
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => Ok(false),
+                Err(e) => Err(Box::new(e)),
+            }
+        })?;
+
+        match out.flush() {
+            Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => Err(Box::new(e)),
+            _ => Ok(written),
+        }
+    }
+
+    /// Number of pages in a range matching `filter`
+    pub fn count_matching(
+        &mut self,
+        filter: &PageFilter,
+        start_pfn: u64,
+        count: Option<u64>,
+        interrupt_flag: Arc<AtomicBool>,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
+        let mut matching = 0;
+        self.scan_with_callback(start_pfn, count, interrupt_flag, |page| {
+            if filter.matches(&page) {
+                matching += 1;
+            }
+        })?;
+        Ok(matching)
+    }
+
     /// Optimized summary-only scan that minimizes allocations
     /// Only stores counters, not individual PageInfo objects
     pub fn scan_for_summary_only(
@@ -402,22 +709,39 @@ impl KPageFlagsReader {
         count: Option<u64>,
         interrupt_flag: Arc<AtomicBool>,
         show_histogram: bool,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        // Pre-allocate fixed-size arrays for counters to avoid HashMap allocations
-        const MAX_FLAGS: usize = PAGE_FLAGS.len();
-        let mut flag_counts = [0u32; MAX_FLAGS];
-        let mut category_counts = [0u32; 8]; // 8 categories in FlagCategory enum
+        partial_every: Option<Duration>,
+        threshold: FlagThreshold,
+    ) -> Result<ScanReport, Box<dyn std::error::Error>> {
+        let report = self.scan_summary(start_pfn, count, interrupt_flag, partial_every)?;
+
+        // Print optimized summary using arrays instead of HashMaps
+        self.print_optimized_summary(&report, show_histogram, threshold);
+
+        Ok(report)
+    }
+
+    /// Scan a range accumulating flag counters into a ScanReport
+    /// If `partial_every` is set, a compact summary of the counters so far is
+    /// printed at that interval
+    pub fn scan_summary(
+        &mut self,
+        start_pfn: u64,
+        count: Option<u64>,
+        interrupt_flag: Arc<AtomicBool>,
+        partial_every: Option<Duration>,
+    ) -> Result<ScanReport, Box<dyn std::error::Error>> {
+        // Pre-allocate counters up front to avoid HashMap allocations
+        let mut report = ScanReport::new(start_pfn).with_regions(&self.regions);
+        if self.counts.is_some() {
+            report = report.with_map_counts();
+        }
 
-        let mut total_pages = 0u32;
-        let mut pages_with_flags = 0u32;
         let mut pfn = start_pfn;
         let mut consecutive_failures = 0u32;
-        const MAX_CONSECUTIVE_FAILURES: u32 = 1000;
 
-        let estimated_total = if count.is_none() {
-            get_estimated_total_pages().unwrap_or(1048576)
-        } else {
-            count.unwrap()
+        let estimated_total = match count {
+            Some(count) => count,
+            None => get_estimated_total_pages().unwrap_or(1048576),
         };
 
         println!(
@@ -436,15 +760,26 @@ impl KPageFlagsReader {
             );
         }
 
-        let end_pfn = count.map(|c| start_pfn + c).unwrap_or(u64::MAX);
+        let end_pfn = count
+            .map(|c| start_pfn.saturating_add(c))
+            .unwrap_or(u64::MAX);
 
+        let mut interrupt_check = InterruptCheck::new(interrupt_flag.clone());
+        let mut last_partial = Instant::now();
         loop {
             if pfn >= end_pfn {
                 break;
             }
 
-            // Check for interrupt signal every 1000 pages
-            if total_pages % 1000 == 0 && interrupt_flag.load(Ordering::Relaxed) {
+            if let Some(every) = partial_every {
+                if last_partial.elapsed() >= every {
+                    self.print_partial_summary(&report);
+                    last_partial = Instant::now();
+                }
+            }
+
+            // Check for interrupt signal every ~100ms
+            if interrupt_check.triggered() {
                 println!(
                     "\n{}",
                     "Interrupt received! Stopping scan and showing summary..."
@@ -455,24 +790,19 @@ impl KPageFlagsReader {
             }
 
             match self.read_page_flags(pfn) {
+                Ok(Some(flags)) if !self.keeps(pfn, flags) => consecutive_failures = 0,
                 Ok(Some(flags)) => {
-                    total_pages += 1;
-                    consecutive_failures = 0;
-
-                    if flags != 0 {
-                        pages_with_flags += 1;
-
-                        // Count individual flags using array indexing (faster than HashMap)
-                        for (i, (flag, _, _, category)) in PAGE_FLAGS.iter().enumerate() {
-                            if flags & flag != 0 {
-                                flag_counts[i] += 1;
-                                category_counts[*category as usize] += 1;
-                            }
+                    report.record(pfn, PageFlags::from_bits(flags));
+                    if let Some(counts) = &mut self.counts {
+                        if let Ok(Some(map_count)) = counts.read_count(pfn) {
+                            report.record_map_count(map_count);
                         }
                     }
+                    consecutive_failures = 0;
+                    let total_pages = report.total_pages;
 
                     // Show progress every 50,000 pages
-                    if total_pages % 50000 == 0 {
+                    if total_pages.is_multiple_of(50000) {
                         let progress = if estimated_total > 0 {
                             format!(
                                 " ({:.1}%)",
@@ -505,7 +835,7 @@ impl KPageFlagsReader {
             pfn += 1;
 
             // Safety check: don't read more than 100M pages (400GB of memory)
-            if total_pages > 100_000_000 {
+            if report.total_pages > 100_000_000 {
                 println!(
                     "{}",
                     "Warning: Reached safety limit of 100M pages. Stopping.".yellow()
@@ -517,34 +847,26 @@ impl KPageFlagsReader {
         let status_msg = if interrupt_flag.load(Ordering::Relaxed) {
             format!(
                 "Scan interrupted - successfully scanned {} pages",
-                total_pages
+                report.total_pages
             )
         } else {
-            format!("Successfully scanned {} total pages", total_pages)
+            format!("Successfully scanned {} total pages", report.total_pages)
         };
 
         println!("{}", status_msg.green().bold());
 
-        // Print optimized summary using arrays instead of HashMaps
-        self.print_optimized_summary(
-            total_pages,
-            pages_with_flags,
-            &flag_counts,
-            &category_counts,
-            show_histogram,
-        );
-
-        Ok(())
+        Ok(report)
     }
 
     fn print_optimized_summary(
         &self,
-        total_pages: u32,
-        pages_with_flags: u32,
-        flag_counts: &[u32],
-        category_counts: &[u32],
+        report: &ScanReport,
         show_histogram: bool,
+        threshold: FlagThreshold,
     ) {
+        let total_pages = report.total_pages;
+        let pages_with_flags = report.pages_with_flags;
+
         println!("\n{}", "=== SUMMARY ===".blue().bold());
         println!("Total pages analyzed: {}", total_pages.to_string().cyan());
         println!("Pages with flags: {}", pages_with_flags.to_string().green());
@@ -554,27 +876,25 @@ impl KPageFlagsReader {
         );
 
         // Find flags with non-zero counts and sort them
-        let mut flag_data: Vec<(usize, u32)> = flag_counts
-            .iter()
-            .enumerate()
-            .filter(|(_, &count)| count > 0)
-            .map(|(i, &count)| (i, count))
-            .collect();
-
-        if !flag_data.is_empty() {
-            flag_data.sort_by(|a, b| b.1.cmp(&a.1));
+        let (flag_data, omitted) =
+            threshold.split(report.sorted_flag_counts(), total_pages, |&(_, count)| {
+                count
+            });
 
+        if !flag_data.is_empty() || omitted > 0 {
             println!("\n{}", "Flag distribution:".blue().bold());
             for (flag_idx, count) in &flag_data {
-                let flag_name = PAGE_FLAGS[*flag_idx].1;
+                let flag_name = flag_table()[*flag_idx].name;
                 let percentage = (*count as f64 / total_pages as f64) * 100.0;
                 println!(
-                    "  {}: {} ({:.1}%)",
+                    "  {}: {} ({:.1}%) {}",
                     flag_name.green().bold(),
                     count.to_string().white(),
-                    percentage.to_string().yellow()
+                    percentage.to_string().yellow(),
+                    pages_memory(*count as u64).dimmed()
                 );
             }
+            print_omitted_flags(omitted);
 
             // Show histogram if requested
             if show_histogram {
@@ -583,7 +903,31 @@ impl KPageFlagsReader {
         }
 
         // Print category summary
-        self.print_optimized_category_summary(category_counts, total_pages);
+        self.print_optimized_category_summary(&report.category_counts, total_pages);
+    }
+
+    /// One-line summary of the top flags counted so far during a long scan
+    fn print_partial_summary(&self, report: &ScanReport) {
+        let total_pages = report.total_pages.max(1);
+        let top_flags: Vec<String> = report
+            .sorted_flag_counts()
+            .iter()
+            .take(PARTIAL_SUMMARY_TOP_FLAGS)
+            .map(|&(flag_idx, count)| {
+                format!(
+                    "{} {:.1}%",
+                    flag_table()[flag_idx].name,
+                    count as f64 / total_pages as f64 * 100.0
+                )
+            })
+            .collect();
+
+        println!(
+            "{} {} pages: {}",
+            "[partial]".blue().bold(),
+            report.total_pages.to_string().cyan(),
+            top_flags.join(", ").white()
+        );
     }
 
     fn print_optimized_histogram(&self, flag_data: &[(usize, u32)], total_pages: u32) {
@@ -600,7 +944,7 @@ impl KPageFlagsReader {
         };
 
         for (flag_idx, count) in top_flags {
-            let flag_name = PAGE_FLAGS[*flag_idx].1;
+            let flag_name = flag_table()[*flag_idx].name;
             let bar_length = (*count as f64 / max_count as f64 * histogram_width as f64) as usize;
             let percentage = (*count as f64 / total_pages as f64) * 100.0;
 
@@ -638,7 +982,7 @@ impl KPageFlagsReader {
         }
 
         if !category_data.is_empty() {
-            category_data.sort_by(|a, b| b.1.cmp(&a.1));
+            category_data.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
 
             println!("\n{}", "Flag categories:".blue().bold());
             for (category, count) in category_data {
@@ -664,8 +1008,7 @@ impl KPageFlagsReader {
         show_histogram: bool,
     ) -> Result<(), Box<dyn std::error::Error>> {
         // Pre-allocate fixed-size arrays for counters
-        const MAX_FLAGS: usize = PAGE_FLAGS.len();
-        let mut flag_counts = [0u32; MAX_FLAGS];
+        let mut flag_counts = vec![0u32; flag_table().len()];
         let mut category_counts = [0u32; 8]; // 8 categories in FlagCategory enum
 
         let mut pages_with_flags = 0u32;
@@ -694,9 +1037,10 @@ impl KPageFlagsReader {
         let mut attempts = 0u32;
         let max_attempts: u32 = sample_size * 10; // Allow up to 10x attempts to handle sparse regions
 
+        let mut interrupt_check = InterruptCheck::new(interrupt_flag.clone());
         while successful_reads < sample_size && attempts < max_attempts {
-            // Check for interrupt signal every 100 attempts
-            if attempts % 100 == 0 && interrupt_flag.load(Ordering::Relaxed) {
+            // Check for interrupt signal every ~100ms
+            if interrupt_check.triggered() {
                 println!(
                     "\n{}",
                     "Interrupt received! Stopping sampling and showing summary..."
@@ -718,16 +1062,16 @@ impl KPageFlagsReader {
                         pages_with_flags += 1;
 
                         // Count individual flags using array indexing
-                        for (i, (flag, _, _, category)) in PAGE_FLAGS.iter().enumerate() {
-                            if flags & flag != 0 {
+                        for (i, def) in flag_table().iter().enumerate() {
+                            if flags & def.mask != 0 {
                                 flag_counts[i] += 1;
-                                category_counts[*category as usize] += 1;
+                                category_counts[def.category as usize] += 1;
                             }
                         }
                     }
 
                     // Show progress every 1000 successful samples
-                    if successful_reads % 1000 == 0 {
+                    if successful_reads.is_multiple_of(1000) {
                         let progress = (successful_reads as f64 / sample_size as f64) * 100.0;
                         println!(
                             "Sampled {} pages so far ({:.1}% complete, {} attempts)",
@@ -776,6 +1120,7 @@ impl KPageFlagsReader {
         // Print sampled summary with extrapolation
         self.print_sampled_summary(
             successful_reads,
+            attempts,
             pages_with_flags,
             &flag_counts,
             &category_counts,
@@ -787,7 +1132,12 @@ impl KPageFlagsReader {
     }
 
     /// Estimate maximum PFN by checking system memory
-    fn estimate_max_pfn(&self) -> Result<u64, Box<dyn std::error::Error>> {
+    fn estimate_max_pfn(&mut self) -> Result<u64, Box<dyn std::error::Error>> {
+        // A capture's size is exact
+        if let Some(pages) = self.capture_pages {
+            return Ok(pages);
+        }
+
         // Try to get total memory from /proc/meminfo
         match get_estimated_total_pages() {
             Ok(pages) => Ok(pages),
@@ -801,7 +1151,7 @@ impl KPageFlagsReader {
     }
 
     /// Binary search to find the approximate maximum valid PFN
-    fn binary_search_max_pfn(&self) -> Result<u64, Box<dyn std::error::Error>> {
+    fn binary_search_max_pfn(&mut self) -> Result<u64, Box<dyn std::error::Error>> {
         let mut low = 0u64;
         let mut high = 100_000_000u64; // Start with 400GB assumption
         let mut last_valid = 0u64;
@@ -813,7 +1163,7 @@ impl KPageFlagsReader {
             // Test a few pages around the midpoint
             let mut valid_count = 0;
             for offset in 0..10 {
-                if let Ok(Some(_)) = self.read_page_flags_const(mid + offset) {
+                if let Ok(Some(_)) = self.read_page_flags(mid + offset) {
                     valid_count += 1;
                     last_valid = mid + offset;
                 }
@@ -830,40 +1180,39 @@ impl KPageFlagsReader {
         Ok((last_valid + 10000).max(1_000_000)) // At least 1M pages
     }
 
-    /// Read page flags without mutable self (for binary search)
-    fn read_page_flags_const(&self, pfn: u64) -> Result<Option<u64>, Box<dyn std::error::Error>> {
-        let mut file = File::open("/proc/kpageflags")?;
-        let offset = pfn * 8;
-        file.seek(SeekFrom::Start(offset))?;
-
-        match file.read_u64::<LittleEndian>() {
-            Ok(flags) => Ok(Some(flags)),
-            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(None),
-            Err(e) => Err(Box::new(e)),
-        }
-    }
-
+    #[allow(clippy::too_many_arguments)]
     fn print_sampled_summary(
         &self,
         samples_collected: u32,
+        attempts: u32,
         pages_with_flags: u32,
         flag_counts: &[u32],
         category_counts: &[u32],
-        estimated_total_pages: u64,
+        sampled_range_pages: u64,
         show_histogram: bool,
     ) {
+        // Only PFNs that could be read count as pages, so scale the sampled
+        // range by the observed hit rate
+        let extrapolation_factor = sampled_extrapolation_factor(sampled_range_pages, attempts);
+        let estimated_total_pages = (samples_collected as f64 * extrapolation_factor) as u64;
+
         println!("\n{}", "=== SAMPLED SUMMARY ===".blue().bold());
         println!(
             "Samples collected: {}",
             samples_collected.to_string().cyan()
         );
+        println!(
+            "PFN range sampled: {} ({:.1}% of attempts hit an existing page)",
+            sampled_range_pages.to_string().yellow(),
+            samples_collected as f64 / attempts.max(1) as f64 * 100.0
+        );
         println!(
             "Estimated total pages in system: {}",
             estimated_total_pages.to_string().yellow()
         );
         println!(
             "Sampling coverage: {:.3}%",
-            (samples_collected as f64 / estimated_total_pages as f64 * 100.0)
+            (samples_collected as f64 / estimated_total_pages.max(1) as f64 * 100.0)
                 .to_string()
                 .green()
         );
@@ -885,7 +1234,6 @@ impl KPageFlagsReader {
         );
 
         // Extrapolate to full system
-        let extrapolation_factor = estimated_total_pages as f64 / samples_collected as f64;
         println!("\n{}", "Extrapolated System Statistics:".blue().bold());
         println!(
             "Estimated pages with flags: {} ({:.1}%)",
@@ -906,20 +1254,21 @@ impl KPageFlagsReader {
             .collect();
 
         if !flag_data.is_empty() {
-            flag_data.sort_by(|a, b| b.1.cmp(&a.1));
+            flag_data.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
 
             println!("\n{}", "Flag distribution (sampled):".blue().bold());
             for (flag_idx, count) in &flag_data {
-                let flag_name = PAGE_FLAGS[*flag_idx].1;
+                let flag_name = flag_table()[*flag_idx].name;
                 let sample_percentage = (*count as f64 / samples_collected as f64) * 100.0;
                 let estimated_total = (*count as f64 * extrapolation_factor) as u64;
 
                 println!(
-                    "  {}: {} ({:.1}% of samples, ~{} estimated total)",
+                    "  {}: {} ({:.1}% of samples, ~{} estimated total, ~{})",
                     flag_name.green().bold(),
                     count.to_string().white(),
                     sample_percentage.to_string().yellow(),
-                    estimated_total.to_string().cyan()
+                    estimated_total.to_string().cyan(),
+                    pages_memory(estimated_total)
                 );
             }
 
@@ -956,7 +1305,7 @@ impl KPageFlagsReader {
         };
 
         for (flag_idx, count) in top_flags {
-            let flag_name = PAGE_FLAGS[*flag_idx].1;
+            let flag_name = flag_table()[*flag_idx].name;
             let bar_length = (*count as f64 / max_count as f64 * histogram_width as f64) as usize;
             let sample_percentage = (*count as f64 / samples_collected as f64) * 100.0;
             let estimated_total = (*count as f64 * extrapolation_factor) as u64;
@@ -1000,7 +1349,7 @@ impl KPageFlagsReader {
         }
 
         if !category_data.is_empty() {
-            category_data.sort_by(|a, b| b.1.cmp(&a.1));
+            category_data.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
 
             println!("\n{}", "Flag categories (sampled):".blue().bold());
             for (category, count) in category_data {
@@ -1027,7 +1376,7 @@ fn print_page_info(page: &PageInfo, verbose: bool) {
 
     println!("{} {}", pfn_str.cyan().bold(), flags_str.yellow());
 
-    if page.flags == 0 {
+    if page.flags.is_empty() {
         println!("  {}", "No flags set".dimmed());
         return;
     }
@@ -1074,19 +1423,17 @@ fn print_page_info(page: &PageInfo, verbose: bool) {
     }
 }
 
-fn print_summary(pages: &[PageInfo], show_histogram: bool) {
+fn print_summary(pages: &[PageInfo], show_histogram: bool, threshold: FlagThreshold) {
     let mut flag_counts: HashMap<&str, u32> = HashMap::new();
     let mut total_pages = 0;
     let mut pages_with_flags = 0;
 
     for page in pages {
         total_pages += 1;
-        if page.flags != 0 {
+        if !page.flags.is_empty() {
             pages_with_flags += 1;
-            for (flag, name, _, _) in PAGE_FLAGS {
-                if page.flags & flag != 0 {
-                    *flag_counts.entry(name).or_insert(0) += 1;
-                }
+            for name in page.flags.iter_names() {
+                *flag_counts.entry(name).or_insert(0) += 1;
             }
         }
     }
@@ -1103,16 +1450,20 @@ fn print_summary(pages: &[PageInfo], show_histogram: bool) {
         println!("\n{}", "Flag distribution:".blue().bold());
         let mut sorted_flags: Vec<_> = flag_counts.iter().collect();
         sorted_flags.sort_by(|a, b| b.1.cmp(a.1));
+        let (sorted_flags, omitted) =
+            threshold.split(sorted_flags, total_pages, |&(_, count)| *count);
 
         for (flag, count) in sorted_flags.iter() {
             let percentage = (**count as f64 / total_pages as f64) * 100.0;
             println!(
-                "  {}: {} ({:.1}%)",
+                "  {}: {} ({:.1}%) {}",
                 flag.green().bold(),
                 count.to_string().white(),
-                percentage.to_string().yellow()
+                percentage.to_string().yellow(),
+                pages_memory(**count as u64).dimmed()
             );
         }
+        print_omitted_flags(omitted);
 
         // Show histogram if requested
         if show_histogram {
@@ -1128,6 +1479,67 @@ fn print_summary(pages: &[PageInfo], show_histogram: bool) {
     print_category_summary(pages);
 }
 
+/// Pages each successful sample stands for when `attempts` uniform random
+/// PFNs were drawn from a range of `range_pages`
+///
+/// The range includes PFNs that don't exist; with hit rate successes /
+/// attempts, the range holds about range_pages * hit_rate real pages, spread
+/// over `successes` samples, which leaves range_pages / attempts per sample.
+fn sampled_extrapolation_factor(range_pages: u64, attempts: u32) -> f64 {
+    if attempts == 0 {
+        return 0.0;
+    }
+    range_pages as f64 / attempts as f64
+}
+
+/// Minimum share a flag needs to be listed in a summary's flag distribution
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FlagThreshold {
+    pub min_count: u32,
+    pub min_percent: f64,
+}
+
+impl FlagThreshold {
+    fn keeps(&self, count: u32, total_pages: u32) -> bool {
+        let percent = if total_pages > 0 {
+            count as f64 / total_pages as f64 * 100.0
+        } else {
+            0.0
+        };
+        count >= self.min_count && percent >= self.min_percent
+    }
+
+    /// Split sorted flag counts into those to print and how many were dropped
+    fn split<T>(
+        &self,
+        flag_data: Vec<T>,
+        total_pages: u32,
+        count: impl Fn(&T) -> u32,
+    ) -> (Vec<T>, usize) {
+        let before = flag_data.len();
+        let kept: Vec<T> = flag_data
+            .into_iter()
+            .filter(|item| self.keeps(count(item), total_pages))
+            .collect();
+        let omitted = before - kept.len();
+        (kept, omitted)
+    }
+}
+
+/// Memory covered by `pages` pages, e.g. "204,800 KB (200.0 MB)"
+fn pages_memory(pages: u64) -> String {
+    format_memory_kb(MemoryUtils::pages_to_kb(pages))
+}
+
+fn print_omitted_flags(omitted: usize) {
+    if omitted > 0 {
+        println!(
+            "{}",
+            format!("  ({} flags below threshold omitted)", omitted).dimmed()
+        );
+    }
+}
+
 fn print_histogram(sorted_flags: &[(&str, u32)], total_pages: u32) {
     println!("\n{}", "=== HISTOGRAM ===".blue().bold());
 
@@ -1194,30 +1606,26 @@ fn print_histogram(sorted_flags: &[(&str, u32)], total_pages: u32) {
 
 fn get_flag_category_char(flag_name: &str) -> &'static str {
     // Find the flag category and return appropriate character
-    for (_, name, _, category) in PAGE_FLAGS {
-        if *name == flag_name {
-            return match category {
-                FlagCategory::State => "█",      // Solid block
-                FlagCategory::Memory => "▓",     // Dark shade
-                FlagCategory::Usage => "▒",      // Medium shade
-                FlagCategory::Allocation => "░", // Light shade
-                FlagCategory::IO => "▄",         // Lower half block
-                FlagCategory::Structure => "▀",  // Upper half block
-                FlagCategory::Special => "■",    // Small solid square
-                FlagCategory::Error => "▬",      // Horizontal bar
-            };
-        }
+    if let Some(def) = find_flag(flag_name) {
+        return match def.category {
+            FlagCategory::State => "█",      // Solid block
+            FlagCategory::Memory => "▓",     // Dark shade
+            FlagCategory::Usage => "▒",      // Medium shade
+            FlagCategory::Allocation => "░", // Light shade
+            FlagCategory::IO => "▄",         // Lower half block
+            FlagCategory::Structure => "▀",  // Upper half block
+            FlagCategory::Special => "■",    // Small solid square
+            FlagCategory::Error => "▬",      // Horizontal bar
+        };
     }
     "█" // Default
 }
 
 fn get_flag_category_color(flag_name: &str) -> colored::Color {
     // Find the flag category and return appropriate color
-    for (_, name, _, category) in PAGE_FLAGS {
-        if *name == flag_name {
-            let (_, color) = get_category_symbol_and_color(*category);
-            return color;
-        }
+    if let Some(def) = find_flag(flag_name) {
+        let (_, color) = get_category_symbol_and_color(def.category);
+        return color;
     }
     colored::Color::White // Default
 }
@@ -1235,9 +1643,57 @@ pub fn get_category_symbol_and_color(category: FlagCategory) -> (char, colored::
     }
 }
 
-fn visualize_flags_grid(pages: &[PageInfo], width: usize) {
+pub fn get_class_symbol_and_color(class: PageClass) -> (char, colored::Color) {
+    match class {
+        PageClass::Anon => ('A', colored::Color::Yellow),
+        PageClass::File => ('F', colored::Color::Green),
+        PageClass::Free => ('-', colored::Color::Blue),
+        PageClass::Kernel => ('K', colored::Color::Red),
+    }
+}
+
+/// How grid cells are colored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    /// One symbol per flag category
+    Category,
+    /// Anonymous / file / free / kernel split (see `PageClass`)
+    AnonFile,
+}
+
+impl ColorMode {
+    pub fn from_name(name: &str) -> Option<ColorMode> {
+        match name {
+            "category" => Some(ColorMode::Category),
+            "anon-file" => Some(ColorMode::AnonFile),
+            _ => None,
+        }
+    }
+
+    pub fn toggled(self) -> ColorMode {
+        match self {
+            ColorMode::Category => ColorMode::AnonFile,
+            ColorMode::AnonFile => ColorMode::Category,
+        }
+    }
+}
+
+fn visualize_flags_grid(pages: &[PageInfo], width: usize, color_mode: ColorMode) {
     println!("\n{}", "=== FLAG VISUALIZATION ===".blue().bold());
 
+    if color_mode == ColorMode::AnonFile {
+        print_class_legend();
+        for (i, page) in pages.iter().enumerate() {
+            if i % width == 0 && i > 0 {
+                println!();
+            }
+            let (symbol_char, color) = get_class_symbol_and_color(page.page_class());
+            print!("{}", symbol_char.to_string().color(color));
+        }
+        println!();
+        return;
+    }
+
     // Print legend
     println!("{}", "Legend:".bold());
     println!("  {} = no flags", ".".dimmed());
@@ -1281,7 +1737,7 @@ fn visualize_flags_grid(pages: &[PageInfo], width: usize) {
             println!();
         }
 
-        let symbol = if page.flags == 0 {
+        let symbol = if page.flags.is_empty() {
             ".".dimmed()
         } else {
             let categories = page.get_flag_categories();
@@ -1300,11 +1756,35 @@ fn visualize_flags_grid(pages: &[PageInfo], width: usize) {
     println!();
 }
 
-fn print_category_summary(pages: &[PageInfo]) {
-    let mut category_counts: HashMap<FlagCategory, u32> = HashMap::new();
-
-    for page in pages {
-        for category in page.get_flag_categories() {
+fn print_class_legend() {
+    println!("{}", "Legend:".bold());
+    for (class, description) in [
+        (
+            PageClass::Anon,
+            "Anonymous (process memory, swap-backed, KSM)",
+        ),
+        (PageClass::File, "File (page cache on the LRU or mapped)"),
+        (PageClass::Free, "Free (buddy allocator)"),
+        (
+            PageClass::Kernel,
+            "Kernel (slab, page tables, reserved, no flags)",
+        ),
+    ] {
+        let (symbol_char, color) = get_class_symbol_and_color(class);
+        println!(
+            "  {} = {}",
+            symbol_char.to_string().color(color),
+            description
+        );
+    }
+    println!();
+}
+
+fn print_category_summary(pages: &[PageInfo]) {
+    let mut category_counts: HashMap<FlagCategory, u32> = HashMap::new();
+
+    for page in pages {
+        for category in page.get_flag_categories() {
             *category_counts.entry(category).or_insert(0) += 1;
         }
     }
@@ -1328,16 +1808,196 @@ fn print_category_summary(pages: &[PageInfo]) {
     }
 }
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Set up Ctrl-C handler
-    let interrupt_flag = Arc::new(AtomicBool::new(false));
-    let interrupt_flag_clone = interrupt_flag.clone();
+/// Optional report sections printed after the summary
+struct ExtraSections {
+    reclaimable: bool,
+    io_pressure: bool,
+    ksm: bool,
+    by_region: bool,
+    flags_per_page: bool,
+    count_histogram: bool,
+    /// Whether the scan is of this machine, so /proc and /sys can be compared
+    live_system: bool,
+    /// Number of largest compound allocations to list
+    largest_compounds: Option<usize>,
+}
 
-    ctrlc::set_handler(move || {
-        interrupt_flag_clone.store(true, Ordering::Relaxed);
+impl ExtraSections {
+    fn any(&self) -> bool {
+        self.reclaimable
+            || self.io_pressure
+            || self.ksm
+            || self.by_region
+            || self.flags_per_page
+            || self.count_histogram
+            || self.largest_compounds.is_some()
+    }
+}
+
+/// Current meminfo, when the scan is of this machine
+fn live_meminfo(sections: &ExtraSections) -> Option<MemoryStats> {
+    sections
+        .live_system
+        .then(|| MemoryStats::current().ok())
+        .flatten()
+}
+
+fn print_extra_sections(report: &ScanReport, sections: &ExtraSections) {
+    if sections.reclaimable {
+        report.print_reclaimable();
+    }
+    if sections.io_pressure {
+        report.print_io_pressure(live_meminfo(sections).as_ref());
+    }
+    if sections.ksm {
+        let ksm = sections
+            .live_system
+            .then(|| KsmStats::current().ok())
+            .flatten();
+        report.print_ksm(ksm.as_ref());
+    }
+    if let Some(limit) = sections.largest_compounds {
+        report.print_largest_compounds(limit);
+    }
+    if sections.by_region {
+        report.print_regions();
+    }
+    if sections.flags_per_page {
+        report.print_flags_per_page();
+    }
+    if sections.count_histogram {
+        report.print_map_counts();
+    }
+}
+
+/// Parse --vaddr-range: `START-END` in hex, as /proc/PID/maps prints ranges
+fn parse_vaddr_range(value: &str) -> Result<(u64, u64), String> {
+    let invalid = || {
+        format!(
+            "Invalid --vaddr-range '{}': expected START-END in hex",
+            value
+        )
+    };
+    let hex = |s: &str| {
+        let s = s.trim();
+        u64::from_str_radix(s.strip_prefix("0x").unwrap_or(s), 16).map_err(|_| invalid())
+    };
+
+    let (start, end) = value.split_once('-').ok_or_else(invalid)?;
+    let (start, end) = (hex(start)?, hex(end)?);
+    if end <= start {
+        return Err(format!(
+            "Invalid --vaddr-range '{}': END must be above START",
+            value
+        ));
+    }
+    Ok((start, end))
+}
+
+/// One line of --vaddr-range output
+fn print_process_page(page: &ProcessPage) {
+    let entry = &page.entry;
+    let mut markers = Vec::new();
+    if entry.is_file_page {
+        markers.push("file");
+    }
+    if entry.is_soft_dirty {
+        markers.push("soft-dirty");
+    }
+    if entry.has_exclusive {
+        markers.push("exclusive");
+    }
+    let markers = if markers.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", markers.join(", "))
+    };
+
+    let location = match (entry.swap_type, entry.swap_offset, &page.info) {
+        (Some(swap_type), Some(offset), _) => {
+            format!("swap {} offset 0x{:x}", swap_type, offset).yellow()
+        }
+        (_, _, Some(info)) => {
+            format!("PFN 0x{:x} {}", info.pfn, info.get_flag_names().join("|")).normal()
+        }
+        _ if entry.is_pfn_hidden() => "present (PFN hidden)".normal(),
+        _ if entry.is_present => "present (PFN past kpageflags)".normal(),
+        _ => "not present".dimmed(),
+    };
+    println!("0x{:012x}  {}{}", page.vaddr, location, markers.cyan());
+}
+
+/// Allowed range for --width
+const GRID_WIDTH_RANGE: std::ops::RangeInclusive<usize> = 1..=1000;
+const DEFAULT_GRID_WIDTH: usize = 80;
+
+/// Validate --width, defaulting to the terminal width when not given
+fn parse_grid_width(value: Option<&str>) -> Result<usize, String> {
+    let Some(value) = value else {
+        let columns = crossterm::terminal::size()
+            .map(|(columns, _)| columns as usize)
+            .unwrap_or(DEFAULT_GRID_WIDTH);
+        return Ok(columns.clamp(*GRID_WIDTH_RANGE.start(), *GRID_WIDTH_RANGE.end()));
+    };
+
+    let width: usize = value
+        .parse()
+        .map_err(|_| format!("Invalid --width '{}': expected a number", value))?;
+    if !GRID_WIDTH_RANGE.contains(&width) {
+        return Err(format!(
+            "Invalid --width {}: must be between {} and {}",
+            width,
+            GRID_WIDTH_RANGE.start(),
+            GRID_WIDTH_RANGE.end()
+        ));
+    }
+    Ok(width)
+}
+
+/// Parse and register a `BIT=NAME[:CATEGORY]` flag definition
+fn register_flag_definition(definition: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let (bit, rest) = definition.split_once('=').ok_or_else(|| {
+        format!(
+            "Invalid flag definition '{}': expected BIT=NAME",
+            definition
+        )
     })?;
-    let matches = Command::new("kpageflags-visualizer")
+    let (name, category) = match rest.split_once(':') {
+        Some((name, category)) => {
+            let category = FlagCategory::from_name(category)
+                .ok_or_else(|| format!("Unknown flag category '{}'", category))?;
+            (name, category)
+        }
+        None => (rest, FlagCategory::Special),
+    };
+
+    let name: &'static str = Box::leak(name.to_uppercase().into_boxed_str());
+    register_flag(bit.parse()?, name, "User-defined flag", category)?;
+    Ok(())
+}
+
+/// Modes that return before the page listing writes its output files, so
+/// they conflict with --save-snapshot, --export and --image-per-category
+/// (--diff honors --save-snapshot on its own)
+const LISTING_ONLY_CONFLICTS: [&str; 14] = [
+    "tui",
+    "raw",
+    "count-only",
+    "output-format",
+    "file-footprint",
+    "track",
+    "watch",
+    "pid",
+    "contiguous",
+    "map-counts",
+    "min-map-count",
+    "sampled",
+    "thp-interval",
+    "summary",
+];
+
+fn build_cli() -> Command {
+    Command::new("kpageflags-visualizer")
         .about("Visualize Linux kernel page flags from /proc/kpageflags")
         .arg(
             Arg::new("start")
@@ -1383,6 +2043,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .help("Show grid visualization")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("color-by")
+                .long("color-by")
+                .value_name("MODE")
+                .help("Grid coloring: flag category, or anon/file/free/kernel split")
+                .value_parser(["category", "anon-file"])
+                .default_value("category"),
+        )
         .arg(
             Arg::new("limit")
                 .short('l')
@@ -1396,8 +2064,64 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .short('w')
                 .long("width")
                 .value_name("WIDTH")
-                .help("Grid width for visualization")
-                .default_value("80"),
+                .help("Grid width for visualization, 1-1000 (default: terminal width)"),
+        )
+        .arg(
+            Arg::new("nonzero-only")
+                .long("nonzero-only")
+                .help("Only print individual pages that have flags set")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("raw")
+                .long("raw")
+                .help("Print only PFN<TAB>hex flags lines, undecoded and uncolored, for piping")
+                .conflicts_with_all(["tui", "summary", "sampled", "json", "file-footprint", "pid"])
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("count-only")
+                .long("count-only")
+                .value_name("EXPR")
+                .help("Print only the number of pages matching EXPR (flag names with ! & | and parentheses)")
+                .conflicts_with_all([
+                    "tui",
+                    "summary",
+                    "sampled",
+                    "json",
+                    "file-footprint",
+                    "raw",
+                    "pid",
+                ]),
+        )
+        .arg(
+            Arg::new("output-format")
+                .long("output-format")
+                .value_name("FORMAT")
+                .help("Page listing format: text, json (array ending in a __summary__ object), ndjson or csv")
+                .value_parser(["text", "json", "ndjson", "csv"])
+                .default_value("text")
+                .conflicts_with_all(["tui", "sampled", "count-only", "raw", "json"]),
+        )
+        .arg(
+            Arg::new("filter-flags")
+                .long("filter-flags")
+                .value_name("FLAGS")
+                .help("Only list and summarize pages having all of FLAGS (comma-separated, e.g. LRU,DIRTY)")
+                .conflicts_with_all(["tui", "sampled", "count-only", "raw", "file-footprint", "pid"]),
+        )
+        .arg(
+            Arg::new("bytes")
+                .long("bytes")
+                .help("With --count-only, also print the matching pages' size in bytes")
+                .requires("count-only")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .value_name("FILE")
+                .help("Write individual pages to FILE as JSON instead of printing them"),
         )
         .arg(
             Arg::new("histogram")
@@ -1405,18 +2129,283 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .help("Show histogram visualization in summary")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("min-count")
+                .long("min-count")
+                .value_name("N")
+                .help("Hide flags set on fewer than N pages from the summary's flag distribution")
+                .value_parser(clap::value_parser!(u32)),
+        )
+        .arg(
+            Arg::new("min-percent")
+                .long("min-percent")
+                .value_name("PERCENT")
+                .help("Hide flags set on less than PERCENT of pages from the flag distribution")
+                .value_parser(clap::value_parser!(f64)),
+        )
         .arg(
             Arg::new("tui")
                 .long("tui")
                 .help("Launch interactive TUI mode")
                 .action(clap::ArgAction::SetTrue),
         )
-        .get_matches();
+        .arg(
+            Arg::new("timeline-height")
+                .long("timeline-height")
+                .value_name("ROWS")
+                .help("Rows of the memory timeline panel in the TUI")
+                .value_parser(clap::value_parser!(u16).range(3..))
+                .default_value("8"),
+        )
+        .arg(
+            Arg::new("file-footprint")
+                .long("file-footprint")
+                .value_name("FILE")
+                .help("Show the physical pages (PFN and flags) of FILE that are in the page cache"),
+        )
+        .arg(
+            Arg::new("track")
+                .long("track")
+                .value_name("FILE")
+                .help("Watch the flags of FILE's cached pages and print each change until Ctrl-C")
+                .conflicts_with_all(["tui", "sampled", "file-footprint", "pid", "input"]),
+        )
+        .arg(
+            Arg::new("track-interval")
+                .long("track-interval")
+                .value_name("MS")
+                .help("Milliseconds between reads for --track")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .default_value("1000")
+                .requires("track"),
+        )
+        .arg(
+            Arg::new("watch")
+                .long("watch")
+                .value_name("INTERVAL_MS")
+                .help("Re-scan the range every INTERVAL_MS (default 1000) and print pages whose flags changed until Ctrl-C")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .num_args(0..=1)
+                .default_missing_value("1000")
+                .conflicts_with_all(["tui", "sampled", "file-footprint", "track", "input", "diff"]),
+        )
+        .arg(
+            Arg::new("pid")
+                .long("pid")
+                .value_name("PID")
+                .help("Summarize only the pages process PID has in RAM, found through its pagemap")
+                .value_parser(clap::value_parser!(u32))
+                .conflicts_with_all(["tui", "sampled", "file-footprint", "input"]),
+        )
+        .arg(
+            Arg::new("vaddr-range")
+                .long("vaddr-range")
+                .value_name("START-END")
+                .help("With --pid, list each page of a virtual address range (hex, as in /proc/PID/maps) with its PFN and flags")
+                .requires("pid"),
+        )
+        .arg(
+            Arg::new("contiguous")
+                .long("contiguous")
+                .value_name("FLAGS")
+                .help("List runs of physically contiguous pages having all of FLAGS (comma-separated, e.g. BUDDY; flagless buddy tail pages extend a BUDDY run)")
+                .conflicts_with_all(["tui", "sampled", "file-footprint", "pid"]),
+        )
+        .arg(
+            Arg::new("exclude-flags")
+                .long("exclude-flags")
+                .value_name("FLAGS")
+                .help("With --contiguous, pages with any of FLAGS end a run")
+                .requires("contiguous"),
+        )
+        .arg(
+            Arg::new("min-run")
+                .long("min-run")
+                .value_name("PAGES")
+                .help("With --contiguous, shortest run to list (512 is one 2MB huge page)")
+                .value_parser(clap::value_parser!(u64))
+                .default_value("1")
+                .requires("contiguous"),
+        )
+        .arg(
+            Arg::new("diff")
+                .long("diff")
+                .value_name("CAPTURE")
+                .help("Compare pages in a saved capture with a live scan (or --input) and summarize what changed")
+                .conflicts_with_all(["tui", "sampled", "file-footprint", "pid", "contiguous"]),
+        )
+        .arg(
+            Arg::new("map-counts")
+                .long("map-counts")
+                .help("Print only the histogram of pages by map count, reading /proc/kpagecount without flags")
+                .conflicts_with_all(["tui", "sampled", "file-footprint", "input", "pid"])
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("min-map-count")
+                .long("min-map-count")
+                .value_name("N")
+                .help("List pages mapped at least N times, with their flags and map count")
+                .value_parser(clap::value_parser!(u64))
+                .conflicts_with_all([
+                    "tui",
+                    "sampled",
+                    "file-footprint",
+                    "input",
+                    "pid",
+                    "map-counts",
+                ]),
+        )
+        .arg(
+            Arg::new("define-flag")
+                .long("define-flag")
+                .value_name("BIT=NAME[:CATEGORY]")
+                .help("Define an extra flag bit (e.g. vendor kernels), may be repeated")
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            Arg::new("export")
+                .long("export")
+                .value_name("FILE")
+                .help("Write scanned pages in a compact binary format readable by --input")
+                .conflicts_with_all(LISTING_ONLY_CONFLICTS)
+                .conflicts_with("diff"),
+        )
+        .arg(
+            Arg::new("input")
+                .long("input")
+                .value_name("FILE")
+                .help("Read flags from a saved copy of /proc/kpageflags (plain or gzip)"),
+        )
+        .arg(
+            Arg::new("save-snapshot")
+                .long("save-snapshot")
+                .value_name("FILE")
+                .help("Write scanned pages with the scan time, readable by --input and --diff")
+                .conflicts_with_all(LISTING_ONLY_CONFLICTS),
+        )
+        .arg(
+            Arg::new("io-pressure")
+                .long("io-pressure")
+                .help("Add a DIRTY/WRITEBACK section cross-checked against /proc/meminfo")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("by-region")
+                .long("by-region")
+                .value_name("NAME=PFN,...")
+                .help("Add per-region flag counts; regions come from /proc/zoneinfo unless given as start PFNs")
+                .default_missing_value("auto")
+                .num_args(0..=1),
+        )
+        .arg(
+            Arg::new("ksm")
+                .long("ksm")
+                .help("Add a KSM section comparing KSM-flagged pages with /sys/kernel/mm/ksm")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("largest-compound")
+                .long("largest-compound")
+                .value_name("N")
+                .help("Add a section listing the N largest compound allocations (default: 10)")
+                .value_parser(clap::value_parser!(u64).range(1..=MAX_TRACKED_COMPOUNDS as u64))
+                .default_missing_value("10")
+                .num_args(0..=1),
+        )
+        .arg(
+            Arg::new("flags-per-page")
+                .long("flags-per-page")
+                .help("Add a histogram of pages by how many known flags they have set")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("count-histogram")
+                .long("count-histogram")
+                .help("Add a histogram of pages by map count from /proc/kpagecount, and the memory in shared pages")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("reclaimable")
+                .long("reclaimable")
+                .help("Add a section counting inactive file LRU pages (prime reclaim candidates)")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("image-per-category")
+                .long("image-per-category")
+                .value_name("DIR")
+                .help("Write one monochrome PNG per flag category (laid out like --grid)")
+                .conflicts_with_all(LISTING_ONLY_CONFLICTS)
+                .conflicts_with("diff"),
+        )
+        .arg(
+            Arg::new("threads")
+                .long("threads")
+                .value_name("N")
+                .help("Scan on N threads, each with its own kpageflags descriptor (live scans; no region, compound or map count sections)")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("1")
+                .conflicts_with_all([
+                    "input",
+                    "by-region",
+                    "largest-compound",
+                    "count-histogram",
+                    "partial-every",
+                ]),
+        )
+        .arg(
+            Arg::new("partial-every")
+                .long("partial-every")
+                .value_name("SECONDS")
+                .help("With --summary, print a partial top-flags summary every N seconds"),
+        )
+        .arg(
+            Arg::new("thp-interval")
+                .long("thp-interval")
+                .value_name("SECONDS")
+                .help("Scan twice, SECONDS apart, and report huge page collapse/split"),
+        )
+        .subcommand(
+            Command::new("doctor")
+                .about("Check permissions, kernel interfaces and page size, then exit"),
+        )
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Ctrl-C, and SIGTERM/SIGHUP from service managers, stop scans early
+    // with the partial summary (ctrlc's "termination" feature). A second
+    // signal exits at once, in case a mode is stuck somewhere that doesn't
+    // poll the flag.
+    let interrupt_flag = Arc::new(AtomicBool::new(false));
+    let interrupt_flag_clone = interrupt_flag.clone();
+
+    ctrlc::set_handler(move || {
+        if interrupt_flag_clone.swap(true, Ordering::Relaxed) {
+            std::process::exit(130);
+        }
+    })?;
+    let matches = build_cli().get_matches();
+
+    if matches.subcommand_matches("doctor").is_some() {
+        if !doctor::run_doctor() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Custom flags must be registered before anything decodes flags
+    if let Some(definitions) = matches.get_many::<String>("define-flag") {
+        for definition in definitions {
+            register_flag_definition(definition)?;
+        }
+    }
 
     // Parse arguments
     let start_pfn = if let Some(start_str) = matches.get_one::<String>("start") {
-        if start_str.starts_with("0x") {
-            u64::from_str_radix(&start_str[2..], 16)?
+        if let Some(hex) = start_str.strip_prefix("0x") {
+            u64::from_str_radix(hex, 16)?
         } else {
             start_str.parse::<u64>()?
         }
@@ -1439,12 +2428,50 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let sampled_mode = matches.get_one::<String>("sampled");
     let show_grid = matches.get_flag("grid");
     let show_histogram = matches.get_flag("histogram");
+    let threshold = FlagThreshold {
+        min_count: matches.get_one::<u32>("min-count").copied().unwrap_or(0),
+        min_percent: matches
+            .get_one::<f64>("min-percent")
+            .copied()
+            .unwrap_or(0.0),
+    };
+    let nonzero_only = matches.get_flag("nonzero-only");
+    let json_path = matches.get_one::<String>("json");
+    let export_path = matches.get_one::<String>("export");
+    let snapshot_path = matches.get_one::<String>("save-snapshot");
+    let image_dir = matches.get_one::<String>("image-per-category");
+    let extra_sections = ExtraSections {
+        reclaimable: matches.get_flag("reclaimable"),
+        io_pressure: matches.get_flag("io-pressure"),
+        ksm: matches.get_flag("ksm"),
+        by_region: matches.contains_id("by-region"),
+        flags_per_page: matches.get_flag("flags-per-page"),
+        count_histogram: matches.get_flag("count-histogram"),
+        // Only a live scan can be compared with this machine's counters
+        live_system: !matches.contains_id("input"),
+        largest_compounds: matches
+            .get_one::<u64>("largest-compound")
+            .map(|&n| n as usize),
+    };
+    let threads = (*matches.get_one::<usize>("threads").unwrap()).max(1);
     let tui_mode = matches.get_flag("tui");
-    let grid_width: usize = matches.get_one::<String>("width").unwrap().parse()?;
+    let color_mode = ColorMode::from_name(matches.get_one::<String>("color-by").unwrap())
+        .expect("clap restricts --color-by values");
+    let grid_width = parse_grid_width(matches.get_one::<String>("width").map(String::as_str))?;
     let output_limit: usize = matches.get_one::<String>("limit").unwrap().parse()?;
+    let partial_every: Option<Duration> = matches
+        .get_one::<String>("partial-every")
+        .map(|s| s.parse().map(Duration::from_secs))
+        .transpose()?;
+    let thp_interval: Option<u64> = matches
+        .get_one::<String>("thp-interval")
+        .map(|s| s.parse())
+        .transpose()?;
+
+    let input_path = matches.get_one::<String>("input").map(Path::new);
 
     // Check if we have permission to read kpageflags
-    if !std::path::Path::new("/proc/kpageflags").exists() {
+    if input_path.is_none() && !std::path::Path::new("/proc/kpageflags").exists() {
         eprintln!(
             "{}",
             "Error: /proc/kpageflags not found. Make sure you're running on Linux.".red()
@@ -1455,12 +2482,429 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Launch TUI mode if requested
     if tui_mode {
         println!("{}", "Launching KPageFlags TUI...".green().bold());
-        return tui::run_tui().await;
+        let timeline_height = *matches.get_one::<u16>("timeline-height").unwrap();
+        return tui::run_tui(color_mode, timeline_height, interrupt_flag).await;
+    }
+
+    // Raw output and counts go before any banner so stdout holds nothing else
+    let count_filter = matches
+        .get_one::<String>("count-only")
+        .map(|expr| PageFilter::parse(expr))
+        .transpose()?;
+    if matches.get_flag("raw") || count_filter.is_some() {
+        let mut reader = match input_path {
+            Some(path) => KPageFlagsReader::from_capture(path)?,
+            None => KPageFlagsReader::new()?,
+        };
+        let range = if count == u64::MAX { None } else { Some(count) };
+
+        if let Some(filter) = count_filter {
+            let matching = reader.count_matching(&filter, start_pfn, range, interrupt_flag)?;
+            if matches.get_flag("bytes") {
+                println!("{}\t{}", matching, matching * MemoryUtils::get_page_size());
+            } else {
+                println!("{}", matching);
+            }
+        } else {
+            let mut out = BufWriter::new(std::io::stdout().lock());
+            reader.dump_raw(&mut out, start_pfn, range, nonzero_only, interrupt_flag)?;
+        }
+        return Ok(());
+    }
+
+    // Like --raw, machine-readable formats own stdout and stream the pages
+    let output_format =
+        OutputFormat::from_name(matches.get_one::<String>("output-format").unwrap())
+            .expect("clap restricts --output-format values");
+    if output_format != OutputFormat::Text {
+        let mut reader = match input_path {
+            Some(path) => KPageFlagsReader::from_capture(path)?,
+            None => KPageFlagsReader::new()?,
+        };
+        if let Some(list) = matches.get_one::<String>("filter-flags") {
+            reader.filter = Some(PageFilter::all_of(list)?);
+        }
+        let range = if count == u64::MAX { None } else { Some(count) };
+        let mut writer = PageWriter::new(
+            BufWriter::new(std::io::stdout().lock()),
+            output_format,
+            summary_only,
+            nonzero_only,
+            output_limit,
+        )?;
+        let mut closed = false;
+        reader.for_each_page(start_pfn, range, interrupt_flag, |pfn, flags| match writer
+            .write_page(&PageInfo::new(pfn, flags))
+        {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => {
+                closed = true;
+                Ok(false)
+            }
+            Err(e) => Err(Box::new(e)),
+        })?;
+        if !closed {
+            match writer.finish(threshold) {
+                Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => return Err(Box::new(e)),
+                _ => {}
+            }
+        }
+        return Ok(());
     }
 
     println!("{}", "KPageFlags Visualizer".blue().bold());
 
-    let mut reader = KPageFlagsReader::new()?;
+    if let Some(path) = matches.get_one::<String>("file-footprint") {
+        let path = Path::new(path);
+        let file_pages = std::fs::metadata(path)?
+            .len()
+            .div_ceil(MemoryUtils::get_page_size());
+        let pages: Vec<PageInfo> = pagemap::file_physical_footprint(path, interrupt_flag.clone())?
+            .into_iter()
+            .map(|(pfn, flags)| PageInfo::new(pfn, flags))
+            .collect();
+
+        println!(
+            "{} of {} pages of {} are in the page cache",
+            pages.len().to_string().green().bold(),
+            file_pages,
+            path.display().to_string().cyan()
+        );
+        println!("{}", "=".repeat(50).blue());
+        for page in pages.iter().take(output_limit) {
+            print_page_info(page, verbose);
+        }
+        if !pages.is_empty() {
+            print_summary(&pages, show_histogram, threshold);
+        }
+        return Ok(());
+    }
+
+    if let Some(path) = matches.get_one::<String>("track") {
+        let pfns: Vec<u64> =
+            pagemap::file_physical_footprint(Path::new(path), interrupt_flag.clone())?
+                .into_iter()
+                .map(|(pfn, _)| pfn)
+                .collect();
+        let interval = Duration::from_millis(*matches.get_one::<u64>("track-interval").unwrap());
+        println!(
+            "Tracking {} cached pages of {} every {} ms (Ctrl-C to stop)",
+            pfns.len().to_string().green().bold(),
+            path.cyan(),
+            interval.as_millis()
+        );
+        println!("{}", "=".repeat(50).blue());
+
+        let mut reader = KPageFlagsReader::new()?;
+        let mut tracker = FlagTracker::new(&pfns);
+        tracker
+            .run(&mut reader, interval, interrupt_flag, |transition| {
+                println!(
+                    "[{}] PFN 0x{:x}: {}",
+                    transition.timestamp,
+                    transition.pfn,
+                    transition.describe().yellow()
+                );
+            })
+            .await?;
+        let kept = tracker.transitions().len() as u64;
+        let mut recorded = format!("{} flag transitions recorded", tracker.transition_count());
+        if kept < tracker.transition_count() {
+            recorded.push_str(&format!(" (latest {} kept)", kept));
+        }
+        println!("{}", recorded.green().bold());
+        return Ok(());
+    }
+
+    if let Some(&interval) = matches.get_one::<u64>("watch") {
+        let range = if count == u64::MAX { None } else { Some(count) };
+        println!(
+            "Watching {} from PFN 0x{:x} every {} ms (Ctrl-C to stop)",
+            range.map_or("all pages".to_string(), |count| format!("{} pages", count)),
+            start_pfn,
+            interval
+        );
+        println!("{}", "=".repeat(50).blue());
+
+        let mut reader = KPageFlagsReader::new()?;
+        if let Some(list) = matches.get_one::<String>("filter-flags") {
+            reader.filter = Some(PageFilter::all_of(list)?);
+        }
+        let mut watcher = RangeWatcher::new(start_pfn, range);
+        let mut scans = 0;
+        watcher
+            .run(
+                &mut reader,
+                Duration::from_millis(interval),
+                interrupt_flag,
+                |diff, elapsed| {
+                    scans += 1;
+                    for (old, new) in diff.changed.iter().take(output_limit) {
+                        let (old_flags, new_flags) = (old.flags, new.flags);
+                        println!(
+                            "{} PFN 0x{:x}: {}",
+                            change_marker(old_flags, new_flags),
+                            old.pfn,
+                            tracker::describe_flag_change(old_flags, new_flags).yellow()
+                        );
+                    }
+                    println!(
+                        "{}",
+                        format!("Scan {} took {} ms", scans, elapsed.as_millis()).cyan()
+                    );
+                    println!("{}", diff.summary());
+                },
+            )
+            .await?;
+
+        let totals = watcher.cumulative_transitions();
+        println!(
+            "\n{}",
+            format!("=== TRANSITIONS OVER {} SCANS ===", scans)
+                .blue()
+                .bold()
+        );
+        if totals.is_empty() {
+            println!("{}", "No pages changed".green());
+        }
+        let max_pages = totals.first().map_or(1, |(_, pages)| *pages);
+        for (transition, pages) in totals.iter().take(15) {
+            let bar_length = (pages * 40).div_ceil(max_pages);
+            println!(
+                "{:>8} │{:<40}│ {}",
+                pages.to_string().white(),
+                "█".repeat(bar_length).green(),
+                if transition.is_empty() {
+                    "(unnamed flags)"
+                } else {
+                    transition
+                }
+                .yellow()
+            );
+        }
+        return Ok(());
+    }
+
+    if let Some(&pid) = matches.get_one::<u32>("pid") {
+        let mut reader = KPageFlagsReader::new()?;
+        if let Some(range) = matches.get_one::<String>("vaddr-range") {
+            let (start, end) = parse_vaddr_range(range)?;
+            let pages =
+                reader.for_process_range(pid, start, end - start, interrupt_flag.clone())?;
+            let present = pages.iter().filter(|page| page.entry.is_present).count();
+            let swapped = pages.iter().filter(|page| page.entry.is_swapped).count();
+            for page in &pages {
+                print_process_page(page);
+            }
+            println!("{}", "=".repeat(50).blue());
+            println!(
+                "{} pages of PID {}: {} present, {} swapped",
+                pages.len(),
+                pid.to_string().cyan(),
+                present.to_string().green().bold(),
+                swapped.to_string().yellow()
+            );
+            return Ok(());
+        }
+        let report = reader.scan_process_pages(pid, interrupt_flag.clone())?;
+        println!(
+            "{} present pages ({}) of PID {}",
+            report.total_pages.to_string().green().bold(),
+            pages_memory(report.total_pages as u64),
+            pid.to_string().cyan()
+        );
+        println!("{}", "=".repeat(50).blue());
+        reader.print_optimized_summary(&report, show_histogram, threshold);
+        report.print_reclaimable();
+        return Ok(());
+    }
+
+    if let Some(before_path) = matches.get_one::<String>("diff") {
+        let before_path = Path::new(before_path);
+        let read_snapshot =
+            |path: Option<&Path>| -> Result<KPageFlagsSnapshot, Box<dyn std::error::Error>> {
+                // Snapshots know when they were taken, other captures go
+                // by modification time
+                if let Some(path) = path.filter(|path| snapshot::is_snapshot(path).unwrap_or(false))
+                {
+                    let mut saved = KPageFlagsSnapshot::load(path)?;
+                    saved
+                        .pages
+                        .retain(|page| page.pfn >= start_pfn && page.pfn - start_pfn < count);
+                    return Ok(saved);
+                }
+                let mut reader = match path {
+                    Some(path) => KPageFlagsReader::from_capture(path)?,
+                    None => KPageFlagsReader::new()?,
+                };
+                let pages = if count == u64::MAX {
+                    reader.read_all_pages(start_pfn, interrupt_flag.clone())?
+                } else {
+                    reader.read_range(start_pfn, count, interrupt_flag.clone())?
+                };
+                Ok(match path {
+                    Some(path) => {
+                        KPageFlagsSnapshot::at(pages, std::fs::metadata(path)?.modified()?)
+                    }
+                    None => KPageFlagsSnapshot::new(pages),
+                })
+            };
+        let before = read_snapshot(Some(before_path))?;
+        let after = read_snapshot(input_path)?;
+        let diff = before.diff(&after);
+        // The next run can then diff against this one
+        if let Some(path) = snapshot_path {
+            after.save(Path::new(path))?;
+        }
+
+        println!(
+            "Changes from {} to {} over {:.1} s",
+            before_path.display().to_string().cyan(),
+            input_path
+                .map_or("now".to_string(), |path| path.display().to_string())
+                .cyan(),
+            after.timestamp.saturating_sub(before.timestamp) as f64 / 1000.0
+        );
+        println!("{}", "=".repeat(50).blue());
+        if diff.is_empty() {
+            println!("{}", "No pages changed".green());
+            return Ok(());
+        }
+        println!("{}", diff.summary());
+        for (old, new) in diff.changed.iter().take(output_limit) {
+            println!(
+                "PFN 0x{:x}: {}",
+                old.pfn,
+                tracker::describe_flag_change(old.flags, new.flags).yellow()
+            );
+        }
+        return Ok(());
+    }
+
+    if let Some(required) = matches.get_one::<String>("contiguous") {
+        let required = parse_flag_list(required)?;
+        let forbidden = matches
+            .get_one::<String>("exclude-flags")
+            .map(|list| parse_flag_list(list))
+            .transpose()?
+            .unwrap_or_default();
+        let min_run = *matches.get_one::<u64>("min-run").unwrap();
+
+        let mut reader = match input_path {
+            Some(path) => KPageFlagsReader::from_capture(path)?,
+            None => KPageFlagsReader::new()?,
+        };
+        // Streamed, so only the runs are kept in memory
+        let end_pfn = start_pfn.saturating_add(count);
+        let mut read_error = None;
+        let pages = reader
+            .stream_pages(start_pfn)
+            .filter_map(|page| page.map_err(|e| read_error = Some(e)).ok())
+            .take_while(|page| page.pfn < end_pfn && !interrupt_flag.load(Ordering::Relaxed));
+        let runs = KPageFlagsReader::find_contiguous_regions(pages, min_run, required, forbidden);
+        if let Some(e) = read_error {
+            eprintln!("{}", format!("Some pages couldn't be read: {}", e).yellow());
+        }
+        let total_bytes: u64 = runs.iter().map(|run| run.physical_size_bytes()).sum();
+
+        println!(
+            "{} runs of at least {} contiguous pages ({})",
+            runs.len().to_string().green().bold(),
+            min_run,
+            format_memory_kb(total_bytes / 1024)
+        );
+        println!("{}", "=".repeat(50).blue());
+        for run in runs.iter().take(output_limit) {
+            println!(
+                "PFN 0x{:x}-0x{:x}: {} pages ({})",
+                run.start_pfn,
+                run.end_pfn() - 1,
+                run.length,
+                format_memory_kb(run.physical_size_bytes() / 1024).cyan()
+            );
+        }
+        return Ok(());
+    }
+
+    if matches.get_flag("map-counts") {
+        let range = if count == u64::MAX { None } else { Some(count) };
+        println!(
+            "Reading map counts starting from PFN 0x{:x} (Ctrl-C to stop)",
+            start_pfn
+        );
+        println!("{}", "=".repeat(50).blue());
+        KPageCountReader::new()?.scan_for_summary_only(start_pfn, range, interrupt_flag);
+        return Ok(());
+    }
+
+    if let Some(&min_map_count) = matches.get_one::<u64>("min-map-count") {
+        let mut reader = KPageReader::new()?;
+        let pages = if count == u64::MAX {
+            reader.read_all_pages_combined(start_pfn, interrupt_flag)?
+        } else {
+            reader.read_range_combined(start_pfn, count, interrupt_flag)?
+        };
+        let shared: Vec<_> = pages
+            .iter()
+            .filter(|page| page.ref_count >= min_map_count)
+            .collect();
+
+        println!(
+            "{} of {} pages are mapped at least {} times",
+            shared.len().to_string().green().bold(),
+            pages.len(),
+            min_map_count
+        );
+        println!("{}", "=".repeat(50).blue());
+        for page in shared.iter().take(output_limit) {
+            println!(
+                "PFN 0x{:x}: {} maps [{}]",
+                page.pfn,
+                page.ref_count.to_string().cyan(),
+                page.flags.iter_names().collect::<Vec<_>>().join(", ")
+            );
+        }
+        if shared.len() > output_limit {
+            println!(
+                "{}",
+                format!("Showing first {} of {} pages", output_limit, shared.len()).yellow()
+            );
+        }
+        if !shared.is_empty() {
+            let pages: Vec<PageInfo> = shared.iter().map(|page| page.page_info()).collect();
+            print_summary(&pages, show_histogram, threshold);
+        }
+        return Ok(());
+    }
+
+    let mut reader = match input_path {
+        Some(path) => {
+            let reader = KPageFlagsReader::from_capture(path)?;
+            println!(
+                "Reading capture {} ({} pages)",
+                path.display().to_string().cyan(),
+                reader.capture_pages.unwrap_or(0)
+            );
+            reader
+        }
+        None => KPageFlagsReader::new()?,
+    };
+    if let Some(spec) = matches.get_one::<String>("by-region") {
+        reader.regions = match spec.as_str() {
+            "auto" if extra_sections.live_system => region::zone_regions(),
+            "auto" => region::default_regions(),
+            spec => region::parse_regions(spec)?,
+        };
+    }
+    if let Some(list) = matches.get_one::<String>("filter-flags") {
+        let filter = PageFilter::all_of(list)?;
+        println!("Only pages matching {}", filter.to_string().cyan());
+        reader.filter = Some(filter);
+    }
+    // Captures only hold flags, so map counts need the live kpagecount
+    if extra_sections.count_histogram && extra_sections.live_system {
+        reader.counts = KPageCountReader::new().ok();
+    }
 
     // Use sampling mode if --sampled flag is set
     if let Some(sample_str) = sampled_mode {
@@ -1476,6 +2920,37 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         return Ok(());
     }
 
+    // Compare two summary scans for huge page collapse/split
+    if let Some(interval_secs) = thp_interval {
+        let range = if count == u64::MAX { None } else { Some(count) };
+        println!(
+            "{}",
+            format!(
+                "Tracking huge page changes over {} seconds starting from PFN 0x{:x}",
+                interval_secs, start_pfn
+            )
+            .green()
+        );
+        println!("{}", "=".repeat(50).blue());
+
+        let meminfo_before = live_meminfo(&extra_sections);
+        let before = reader.scan_summary(start_pfn, range, interrupt_flag.clone(), None)?;
+        if interrupt_flag.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        if !sleep_unless_interrupted(Duration::from_secs(interval_secs), &interrupt_flag).await {
+            return Ok(());
+        }
+        let after = reader.scan_summary(start_pfn, range, interrupt_flag.clone(), None)?;
+        let meminfo_after = live_meminfo(&extra_sections);
+
+        ThpChange::between(&before, &after).print();
+        if let (Some(before), Some(after)) = (meminfo_before, meminfo_after) {
+            report::print_diff_colored(&MemoryStatsDiff::between(&before, &after));
+        }
+        return Ok(());
+    }
+
     // Use optimized summary-only scanning if --summary flag is set
     if summary_only {
         println!(
@@ -1483,31 +2958,58 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             "Using optimized summary mode (minimal memory usage)".green()
         );
 
-        if count == u64::MAX {
+        let report = if count == u64::MAX {
             println!(
                 "Analyzing ALL available pages starting from PFN 0x{:x} (summary only)",
                 start_pfn
             );
             println!("{}", "=".repeat(50).blue());
-            reader.scan_for_summary_only(
-                start_pfn,
-                None,
-                interrupt_flag.clone(),
-                show_histogram,
-            )?;
+            if threads > 1 {
+                reader.scan_for_summary_only_parallel(
+                    start_pfn,
+                    None,
+                    threads,
+                    interrupt_flag.clone(),
+                    show_histogram,
+                    threshold,
+                )?
+            } else {
+                reader.scan_for_summary_only(
+                    start_pfn,
+                    None,
+                    interrupt_flag.clone(),
+                    show_histogram,
+                    partial_every,
+                    threshold,
+                )?
+            }
         } else {
             println!(
                 "Analyzing {} pages starting from PFN 0x{:x} (summary only)",
                 count, start_pfn
             );
             println!("{}", "=".repeat(50).blue());
-            reader.scan_for_summary_only(
-                start_pfn,
-                Some(count),
-                interrupt_flag.clone(),
-                show_histogram,
-            )?;
-        }
+            if threads > 1 {
+                reader.scan_for_summary_only_parallel(
+                    start_pfn,
+                    Some(count),
+                    threads,
+                    interrupt_flag.clone(),
+                    show_histogram,
+                    threshold,
+                )?
+            } else {
+                reader.scan_for_summary_only(
+                    start_pfn,
+                    Some(count),
+                    interrupt_flag.clone(),
+                    show_histogram,
+                    partial_every,
+                    threshold,
+                )?
+            }
+        };
+        print_extra_sections(&report, &extra_sections);
 
         // Early return - no need to process individual pages
         return Ok(());
@@ -1549,7 +3051,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             );
         }
 
-        reader.read_range(start_pfn, count, interrupt_flag.clone())?
+        if threads > 1 {
+            reader.read_range_parallel(start_pfn, count, threads, interrupt_flag.clone())?
+        } else {
+            reader.read_range(start_pfn, count, interrupt_flag.clone())?
+        }
     };
 
     if pages.is_empty() {
@@ -1559,38 +3065,63 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     if !summary_only {
         // Print individual page information (limited)
-        let pages_to_show = if pages.len() > output_limit {
+        let (shown, zero_flag_pages_skipped) = pages_to_dump(&pages, nonzero_only);
+        let pages_to_show = if shown.len() > output_limit {
             if count == u64::MAX {
                 println!(
                     "{}",
                     format!(
                         "Note: Individual page output limited to first {} of {} total pages",
                         output_limit,
-                        pages.len()
+                        shown.len()
                     )
                     .yellow()
                 );
             }
             println!(
                 "{}",
-                format!("Showing first {} of {} pages:", output_limit, pages.len()).yellow()
+                format!("Showing first {} of {} pages:", output_limit, shown.len()).yellow()
             );
-            &pages[..output_limit]
+            &shown[..output_limit]
         } else {
-            &pages
+            &shown[..]
         };
 
-        for page in pages_to_show {
-            print_page_info(page, verbose);
-            println!();
+        let mut dumped_pages = Vec::new();
+        for &page in pages_to_show {
+            if json_path.is_some() {
+                dumped_pages.push(page);
+            } else {
+                print_page_info(page, verbose);
+                println!();
+            }
+        }
+
+        if let Some(path) = json_path {
+            write_pages_json(&dumped_pages, path)?;
+            println!(
+                "{}",
+                format!("Wrote {} pages to {}", dumped_pages.len(), path).green()
+            );
+        }
+
+        if nonzero_only {
+            println!(
+                "{}",
+                format!(
+                    "Skipped {} pages with no flags set",
+                    zero_flag_pages_skipped
+                )
+                .dimmed()
+            );
         }
 
-        if pages.len() > output_limit {
+        if shown.len() > output_limit {
             println!(
                 "{}",
                 format!(
                     "... and {} more pages (use --summary to see all statistics)",
-                    pages.len() - output_limit
+                    shown.len() - output_limit
                 )
                 .dimmed()
             );
@@ -1598,12 +3129,423 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     // Always show summary
-    print_summary(&pages, show_histogram);
+    print_summary(&pages, show_histogram, threshold);
+
+    if extra_sections.any() {
+        let mut report = ScanReport::new(start_pfn).with_regions(&reader.regions);
+        if reader.counts.is_some() {
+            report = report.with_map_counts();
+        }
+        for page in &pages {
+            report.record(page.pfn, page.flags);
+            if let Some(Ok(Some(map_count))) =
+                reader.counts.as_mut().map(|c| c.read_count(page.pfn))
+            {
+                report.record_map_count(map_count);
+            }
+        }
+        print_extra_sections(&report, &extra_sections);
+    }
 
     // Show grid visualization if requested
     if show_grid {
-        visualize_flags_grid(&pages, grid_width);
+        visualize_flags_grid(&pages, grid_width, color_mode);
+    }
+
+    if let Some(path) = export_path {
+        let mut writer = std::io::BufWriter::new(File::create(path)?);
+        codec::write_export(&mut writer, &pages)?;
+        println!(
+            "{}",
+            format!("Exported {} pages to {}", pages.len(), path).green()
+        );
+    }
+
+    if let Some(path) = snapshot_path {
+        snapshot::write_pages_binary(&pages, &mut BufWriter::new(File::create(path)?))?;
+        println!(
+            "{}",
+            format!("Saved snapshot of {} pages to {}", pages.len(), path).green()
+        );
+    }
+
+    if let Some(dir) = image_dir {
+        let (written, layout) = image::write_category_images(&pages, grid_width, Path::new(dir))?;
+        println!(
+            "{}",
+            format!(
+                "Wrote {} category images ({}x{} pixels, {} page(s) per pixel) to {}",
+                written.len(),
+                layout.width,
+                layout.height,
+                layout.pages_per_pixel,
+                dir
+            )
+            .green()
+        );
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_pages_to_dump_filters_before_limit() {
+        let lru = find_flag("LRU").unwrap().mask;
+        // Sparse flags: one flagged page in every ten
+        let pages: Vec<PageInfo> = (0..100)
+            .map(|pfn| PageInfo::new(pfn, if pfn % 10 == 9 { lru } else { 0 }))
+            .collect();
+
+        let (shown, skipped) = pages_to_dump(&pages, true);
+        assert_eq!(shown.len(), 10);
+        assert_eq!(skipped, 90);
+        assert!(shown.iter().all(|page| !page.flags.is_empty()));
+
+        let (shown, skipped) = pages_to_dump(&pages, false);
+        assert_eq!((shown.len(), skipped), (100, 0));
+    }
+
+    #[test]
+    fn test_grid_width_validation() {
+        assert!(parse_grid_width(Some("0")).is_err());
+        assert!(parse_grid_width(Some("1001")).is_err());
+        assert!(parse_grid_width(Some("wide")).is_err());
+        assert_eq!(parse_grid_width(Some("1")), Ok(1));
+        assert_eq!(parse_grid_width(Some("120")), Ok(120));
+
+        let default = parse_grid_width(None).unwrap();
+        assert!(GRID_WIDTH_RANGE.contains(&default));
+    }
+
+    #[test]
+    fn test_vaddr_range_parsing() {
+        assert_eq!(
+            parse_vaddr_range("7f1c2a000000-7f1c2a400000"),
+            Ok((0x7f1c2a000000, 0x7f1c2a400000))
+        );
+        assert_eq!(parse_vaddr_range("0x1000-0x3000"), Ok((0x1000, 0x3000)));
+        assert!(parse_vaddr_range("3000-1000").is_err());
+        assert!(parse_vaddr_range("1000").is_err());
+        assert!(parse_vaddr_range("xyz-1000").is_err());
+    }
+
+    #[test]
+    fn test_sampled_extrapolation_on_sparse_range() {
+        // 1000 PFNs of which only half exist; 200 attempts hit 100 pages
+        let factor = sampled_extrapolation_factor(1000, 200);
+        assert_eq!(100.0 * factor, 500.0);
+        // A flag seen on 10 of the 100 samples is on ~50 real pages
+        assert_eq!(10.0 * factor, 50.0);
+        assert_eq!(sampled_extrapolation_factor(1000, 0), 0.0);
+    }
+
+    #[test]
+    fn test_flag_threshold() {
+        let flag_data = vec![("LRU", 900), ("DIRTY", 50), ("ERROR", 2)];
+        let by_count = FlagThreshold {
+            min_count: 10,
+            ..Default::default()
+        };
+        let (kept, omitted) = by_count.split(flag_data.clone(), 1000, |&(_, c)| c);
+        assert_eq!(kept, vec![("LRU", 900), ("DIRTY", 50)]);
+        assert_eq!(omitted, 1);
+
+        let by_percent = FlagThreshold {
+            min_percent: 10.0,
+            ..Default::default()
+        };
+        let (kept, omitted) = by_percent.split(flag_data.clone(), 1000, |&(_, c)| c);
+        assert_eq!(kept, vec![("LRU", 900)]);
+        assert_eq!(omitted, 2);
+
+        let (kept, omitted) = FlagThreshold::default().split(flag_data, 1000, |&(_, c)| c);
+        assert_eq!((kept.len(), omitted), (3, 0));
+    }
+
+    #[test]
+    fn test_page_class() {
+        let bits = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| find_flag(name).unwrap().flag())
+                .fold(PageFlags::EMPTY, |acc, flag| acc | flag)
+        };
+        assert_eq!(PageClass::from_flags(bits(&["BUDDY"])), PageClass::Free);
+        assert_eq!(
+            PageClass::from_flags(bits(&["LRU", "ACTIVE", "ANON", "SWAPBACKED"])),
+            PageClass::Anon
+        );
+        assert_eq!(
+            PageClass::from_flags(bits(&["LRU", "UPTODATE", "MMAP"])),
+            PageClass::File
+        );
+        assert_eq!(PageClass::from_flags(bits(&["SLAB"])), PageClass::Kernel);
+        // Tail pages of a free buddy block carry no flags
+        assert_eq!(PageClass::from_flags(PageFlags::EMPTY), PageClass::Free);
+    }
+
+    #[test]
+    fn test_dump_raw() {
+        let data: Vec<u8> = [0x28u64, 0, 0x8000_0000_0000_0001]
+            .iter()
+            .flat_map(|flags| flags.to_le_bytes())
+            .collect();
+        let mut reader = KPageFlagsReader {
+            file: Box::new(Cursor::new(data)),
+            capture_pages: Some(3),
+            regions: Vec::new(),
+            counts: None,
+            filter: None,
+        };
+        let interrupt = Arc::new(AtomicBool::new(false));
+
+        let mut out = Vec::new();
+        let written = reader
+            .dump_raw(&mut out, 0, None, true, interrupt.clone())
+            .unwrap();
+        assert_eq!(written, 2);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "0\t0000000000000028\n2\t8000000000000001\n"
+        );
+
+        let mut out = Vec::new();
+        reader
+            .dump_raw(&mut out, 1, Some(1), false, interrupt)
+            .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "1\t0000000000000000\n");
+    }
+
+    #[test]
+    fn test_save_snapshot_rejected_where_unused() {
+        build_cli().debug_assert();
+        let parse = |args: &[&str]| {
+            build_cli().try_get_matches_from(
+                ["kpageflags-visualizer"]
+                    .iter()
+                    .chain(args)
+                    .chain(&["--save-snapshot", "x"]),
+            )
+        };
+
+        let err = parse(&["--summary"]).unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+        assert!(parse(&["--sampled", "100"]).is_err());
+        assert!(parse(&["--map-counts"]).is_err());
+        assert!(parse(&[]).is_ok());
+        assert!(parse(&["--diff", "before"]).is_ok());
+    }
+
+    #[test]
+    fn test_export_rejected_where_unused() {
+        let parse = |args: &[&str]| {
+            build_cli().try_get_matches_from(
+                ["kpageflags-visualizer"]
+                    .iter()
+                    .chain(args)
+                    .chain(&["--export", "x"]),
+            )
+        };
+
+        assert!(parse(&["--summary"]).is_err());
+        assert!(parse(&["--pid", "1"]).is_err());
+        assert!(parse(&["--diff", "before"]).is_err());
+        assert!(parse(&["--input", "capture"]).is_ok());
+
+        let image = build_cli().try_get_matches_from([
+            "kpageflags-visualizer",
+            "--summary",
+            "--image-per-category",
+            "dir",
+        ]);
+        assert!(image.is_err());
+    }
+
+    #[test]
+    fn test_from_capture_gzip() {
+        let data: Vec<u8> = (0..8u64)
+            .flat_map(|pfn| (pfn * 0x10).to_le_bytes())
+            .collect();
+        let capture = tempfile::NamedTempFile::new().unwrap();
+        let mut encoder =
+            flate2::write::GzEncoder::new(capture.as_file(), flate2::Compression::default());
+        encoder.write_all(&data).unwrap();
+        encoder.finish().unwrap();
+
+        let mut reader = KPageFlagsReader::from_capture(capture.path()).unwrap();
+        assert_eq!(reader.capture_pages, Some(8));
+        assert_eq!(reader.read_page_flags(7).unwrap(), Some(0x70));
+        assert_eq!(reader.read_page_flags(2).unwrap(), Some(0x20));
+    }
+
+    #[test]
+    fn test_ranges_near_max_pfn_do_not_overflow() {
+        let mut reader = KPageFlagsReader {
+            file: Box::new(Cursor::new(vec![0u8; 64])),
+            capture_pages: Some(8),
+            regions: Vec::new(),
+            counts: None,
+            filter: None,
+        };
+        let flag = Arc::new(AtomicBool::new(false));
+        let mut visited = 0;
+        reader
+            .for_each_page(u64::MAX - 4, Some(100), flag.clone(), |_, _| {
+                visited += 1;
+                Ok(true)
+            })
+            .unwrap();
+        assert_eq!(visited, 0);
+        assert!(reader
+            .read_range(u64::MAX - 4, 100, flag)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_read_pfns() {
+        let data: Vec<u8> = (0..8u64)
+            .flat_map(|pfn| (pfn * 0x10).to_le_bytes())
+            .collect();
+        let mut reader = KPageFlagsReader {
+            file: Box::new(Cursor::new(data)),
+            capture_pages: Some(8),
+            regions: Vec::new(),
+            counts: None,
+            filter: None,
+        };
+
+        let pages = reader.read_pfns(&[5, 1, 2, 5, 7, 3, 100]).unwrap();
+        let pages: Vec<(u64, u64)> = pages.into_iter().map(|(pfn, f)| (pfn, f.bits())).collect();
+        assert_eq!(
+            pages,
+            vec![(1, 0x10), (2, 0x20), (3, 0x30), (5, 0x50), (7, 0x70)]
+        );
+        assert!(reader.read_pfns(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_count_matching() {
+        let lru = find_flag("LRU").unwrap().mask;
+        let active = find_flag("ACTIVE").unwrap().mask;
+        let data: Vec<u8> = [lru, lru | active, 0, lru]
+            .iter()
+            .flat_map(|flags| flags.to_le_bytes())
+            .collect();
+        let mut reader = KPageFlagsReader {
+            file: Box::new(Cursor::new(data)),
+            capture_pages: Some(4),
+            regions: Vec::new(),
+            counts: None,
+            filter: None,
+        };
+        let interrupt = Arc::new(AtomicBool::new(false));
+
+        let filter = PageFilter::parse("LRU & !ACTIVE").unwrap();
+        let matching = reader.count_matching(&filter, 0, None, interrupt.clone());
+        assert_eq!(matching.unwrap(), 2);
+        let matching = reader.count_matching(&filter, 1, Some(2), interrupt);
+        assert_eq!(matching.unwrap(), 0);
+    }
+
+    #[test]
+    fn test_scan_with_callback_and_stream_pages() {
+        let lru = find_flag("LRU").unwrap().mask;
+        let data: Vec<u8> = [lru, 0, lru, lru]
+            .iter()
+            .flat_map(|flags| flags.to_le_bytes())
+            .collect();
+        let mut reader = KPageFlagsReader {
+            file: Box::new(Cursor::new(data)),
+            capture_pages: Some(4),
+            regions: Vec::new(),
+            counts: None,
+            filter: None,
+        };
+        let interrupt = Arc::new(AtomicBool::new(false));
+
+        let mut pfns = Vec::new();
+        let scanned = reader
+            .scan_with_callback(1, Some(2), interrupt.clone(), |page| pfns.push(page.pfn))
+            .unwrap();
+        assert_eq!(scanned, 2);
+        assert_eq!(pfns, [1, 2]);
+
+        let pfns: Vec<u64> = reader
+            .stream_pages(1)
+            .map(|page| page.unwrap().pfn)
+            .collect();
+        assert_eq!(pfns, [1, 2, 3]);
+
+        let mut reader = reader.with_filter(Some(PageFilter::parse("LRU").unwrap()));
+        let pfns: Vec<u64> = reader
+            .stream_pages(0)
+            .map(|page| page.unwrap().pfn)
+            .collect();
+        assert_eq!(pfns, [0, 2, 3]);
+        let scanned = reader
+            .scan_with_callback(0, None, interrupt, |_| {})
+            .unwrap();
+        assert_eq!(scanned, 3);
+    }
+
+    #[test]
+    fn test_page_flags() {
+        let flags = PageFlags::LRU | PageFlags::DIRTY | PageFlags::from_bits(1 << 60);
+        assert!(flags.contains(PageFlags::LRU | PageFlags::DIRTY));
+        assert!(!flags.contains(PageFlags::LRU | PageFlags::ACTIVE));
+        assert!(flags.intersects(PageFlags::LRU | PageFlags::ACTIVE));
+        assert!(!flags.intersects(PageFlags::ACTIVE));
+        assert_eq!(flags.iter_names().collect::<Vec<_>>(), ["DIRTY", "LRU"]);
+        assert_eq!(flags.unknown(), PageFlags::from_bits(1 << 60));
+        assert_eq!(
+            (flags & !PageFlags::DIRTY).bits(),
+            PageFlags::LRU.bits() | 1 << 60
+        );
+        assert!(PageFlags::EMPTY.is_empty());
+
+        // The named constants match the flag table
+        for (constant, name) in [
+            (PageFlags::LOCKED, "LOCKED"),
+            (PageFlags::BUDDY, "BUDDY"),
+            (PageFlags::THP, "THP"),
+            (PageFlags::PGTABLE, "PGTABLE"),
+            (PageFlags::RESERVED, "RESERVED"),
+        ] {
+            assert_eq!(find_flag(name).unwrap().flag(), constant);
+        }
+        assert_eq!(serde_json::to_string(&PageFlags::ANON).unwrap(), "4096");
+    }
+
+    #[test]
+    fn test_is_reclaimable() {
+        let page = |names: &[&str], extra: PageFlags| {
+            let flags = names
+                .iter()
+                .map(|name| find_flag(name).unwrap().flag())
+                .fold(extra, |acc, flag| acc | flag);
+            PageInfo { pfn: 0, flags }
+        };
+        const MLOCKED: PageFlags = PageFlags::MLOCKED;
+        const EMPTY: PageFlags = PageFlags::EMPTY;
+
+        assert!(page(&["LRU", "UPTODATE", "REFERENCED"], EMPTY).is_reclaimable());
+        assert!(page(&["LRU", "MMAP", "DIRTY"], EMPTY).is_reclaimable());
+
+        // Not on an LRU, active, anonymous, or pinned
+        assert!(!page(&["UPTODATE"], EMPTY).is_reclaimable());
+        assert!(!page(&["LRU", "ACTIVE"], EMPTY).is_reclaimable());
+        assert!(!page(&["LRU", "ANON", "SWAPBACKED"], EMPTY).is_reclaimable());
+        // shmem/tmpfs: swap-backed but not anon
+        assert!(!page(&["LRU", "UPTODATE", "SWAPBACKED"], EMPTY).is_reclaimable());
+        assert!(!page(&["LRU", "UNEVICTABLE"], EMPTY).is_reclaimable());
+        assert!(!page(&["LRU", "UNEVICTABLE"], MLOCKED).is_reclaimable());
+        assert!(!page(&["LRU"], MLOCKED).is_reclaimable());
+        assert!(!page(&["LRU", "RESERVED"], EMPTY).is_reclaimable());
+    }
+}
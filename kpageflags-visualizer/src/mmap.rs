@@ -0,0 +1,83 @@
+//! Memory-mapped kpageflags: one mapping up front, then each page's flags are
+//! a plain load instead of a seek and a read
+
+use crate::{get_estimated_total_pages, PageInfo};
+use memmap2::{Mmap, MmapOptions};
+use std::error::Error;
+use std::fs::File;
+use std::path::Path;
+
+pub struct KPageFlagsMmap {
+    map: Mmap,
+}
+
+impl KPageFlagsMmap {
+    /// Map /proc/kpageflags
+    ///
+    /// Only kernels whose procfs implements mmap for kpageflags allow this;
+    /// mainline returns ENODEV, so callers should fall back to
+    /// `KPageFlagsReader` when it fails or maps no pages.
+    ///
+    /// procfs reports a size of 0, so the mapping covers the PFNs estimated
+    /// from MemTotal.
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        let pages = get_estimated_total_pages()?;
+        Self::open(Path::new("/proc/kpageflags"), pages * 8)
+    }
+
+    /// Map the first `len` bytes of a file laid out like /proc/kpageflags,
+    /// e.g. the whole of an uncompressed capture
+    pub fn open(path: &Path, len: u64) -> Result<Self, Box<dyn Error>> {
+        let file = File::open(path)?;
+        // SAFETY: the mapping is read-only; a capture truncated while mapped
+        // could still raise SIGBUS, as with any file mapping
+        let map = unsafe { MmapOptions::new().len(usize::try_from(len)?).map(&file)? };
+        Ok(Self { map })
+    }
+
+    /// Number of PFNs the mapping covers
+    pub fn page_count(&self) -> u64 {
+        self.map.len() as u64 / 8
+    }
+
+    /// Flags of a page, or `None` past the end of the mapping
+    pub fn get_flags(&self, pfn: u64) -> Option<u64> {
+        let offset = usize::try_from(pfn.checked_mul(8)?).ok()?;
+        let entry = self.map.get(offset..offset.checked_add(8)?)?;
+        Some(u64::from_le_bytes(entry.try_into().unwrap()))
+    }
+
+    /// Every page from `start` up to (not including) `end`, stopping early at
+    /// the end of the mapping
+    pub fn scan_range_mmap(&self, start: u64, end: u64) -> impl Iterator<Item = PageInfo> + '_ {
+        (start..end.min(self.page_count()))
+            .filter_map(|pfn| self.get_flags(pfn).map(|flags| PageInfo::new(pfn, flags)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_mmap_capture() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        for flags in [0u64, 0x20, 0x400, 0x28] {
+            file.write_all(&flags.to_le_bytes()).unwrap();
+        }
+        file.flush().unwrap();
+
+        let map = KPageFlagsMmap::open(file.path(), 32).unwrap();
+
+        assert_eq!(map.page_count(), 4);
+        assert_eq!(map.get_flags(2), Some(0x400));
+        assert_eq!(map.get_flags(4), None);
+        assert_eq!(map.get_flags(u64::MAX), None);
+
+        let pages: Vec<PageInfo> = map.scan_range_mmap(1, 100).collect();
+        assert_eq!(pages.len(), 3);
+        assert_eq!(pages[0].pfn, 1);
+        assert_eq!(pages[2].flags.bits(), 0x28);
+    }
+}
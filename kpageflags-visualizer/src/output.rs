@@ -0,0 +1,310 @@
+//! Machine-readable page listings for --output-format: a JSON array, one
+//! JSON object per line, or CSV, each ending with summary statistics
+
+use crate::{FlagCategory, FlagThreshold, PageInfo};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// How pages are written to stdout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// The colored human-readable output
+    Text,
+    Json,
+    Ndjson,
+    Csv,
+}
+
+impl OutputFormat {
+    pub fn from_name(name: &str) -> Option<OutputFormat> {
+        match name {
+            "text" => Some(OutputFormat::Text),
+            "json" => Some(OutputFormat::Json),
+            "ndjson" => Some(OutputFormat::Ndjson),
+            "csv" => Some(OutputFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// One page as written in JSON and NDJSON output
+#[derive(Debug, Serialize)]
+struct PageRow {
+    pfn: u64,
+    flags: u64,
+    flag_names: Vec<&'static str>,
+    categories: Vec<FlagCategory>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FlagCount {
+    pub name: &'static str,
+    pub pages: u32,
+    pub percent: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CategoryCount {
+    pub category: FlagCategory,
+    pub pages: u32,
+}
+
+/// Counts over every page written, whether or not it got a row
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OutputSummary {
+    pub total_pages: u32,
+    pub pages_with_flags: u32,
+    /// Most common first, without flags below the --min-count and
+    /// --min-percent threshold
+    pub flags: Vec<FlagCount>,
+    pub categories: Vec<CategoryCount>,
+}
+
+/// The last element (JSON) or line (NDJSON) of the output
+#[derive(Serialize)]
+struct SummaryRecord<'a> {
+    #[serde(rename = "__summary__")]
+    summary: &'a OutputSummary,
+}
+
+/// Writes pages in a machine-readable format as they are scanned, then the
+/// summary in `finish`
+pub struct PageWriter<W: Write> {
+    out: W,
+    format: OutputFormat,
+    /// Only the summary, no page rows (--summary)
+    summary_only: bool,
+    nonzero_only: bool,
+    /// Most page rows to write (--limit)
+    limit: usize,
+    rows: usize,
+    total_pages: u32,
+    pages_with_flags: u32,
+    flag_counts: HashMap<&'static str, u32>,
+    category_counts: HashMap<FlagCategory, u32>,
+}
+
+impl<W: Write> PageWriter<W> {
+    /// Start the output, e.g. the CSV header; `format` must not be `Text`
+    pub fn new(
+        mut out: W,
+        format: OutputFormat,
+        summary_only: bool,
+        nonzero_only: bool,
+        limit: usize,
+    ) -> io::Result<Self> {
+        match format {
+            OutputFormat::Json => out.write_all(b"[\n")?,
+            OutputFormat::Csv if summary_only => writeln!(out, "flag,pages,percent")?,
+            OutputFormat::Csv => writeln!(out, "pfn,flags,flag_names,categories")?,
+            OutputFormat::Ndjson | OutputFormat::Text => {}
+        }
+        Ok(Self {
+            out,
+            format,
+            summary_only,
+            nonzero_only,
+            limit,
+            rows: 0,
+            total_pages: 0,
+            pages_with_flags: 0,
+            flag_counts: HashMap::new(),
+            category_counts: HashMap::new(),
+        })
+    }
+
+    /// Count a page and write its row unless rows are off or used up
+    pub fn write_page(&mut self, page: &PageInfo) -> io::Result<()> {
+        self.total_pages += 1;
+        let categories = page.get_flag_categories();
+        if !page.flags.is_empty() {
+            self.pages_with_flags += 1;
+            for name in page.flags.iter_names() {
+                *self.flag_counts.entry(name).or_default() += 1;
+            }
+            for &category in &categories {
+                *self.category_counts.entry(category).or_default() += 1;
+            }
+        }
+
+        if self.summary_only
+            || self.rows >= self.limit
+            || (self.nonzero_only && page.flags.is_empty())
+        {
+            return Ok(());
+        }
+        let row = PageRow {
+            pfn: page.pfn,
+            flags: page.flags.bits(),
+            flag_names: page.get_flag_names(),
+            categories,
+        };
+        match self.format {
+            OutputFormat::Json => {
+                if self.rows > 0 {
+                    self.out.write_all(b",\n")?;
+                }
+                self.out.write_all(b"  ")?;
+                serde_json::to_writer(&mut self.out, &row)?;
+            }
+            OutputFormat::Ndjson => {
+                serde_json::to_writer(&mut self.out, &row)?;
+                self.out.write_all(b"\n")?;
+            }
+            OutputFormat::Csv => {
+                let categories: Vec<String> =
+                    row.categories.iter().map(|c| format!("{:?}", c)).collect();
+                writeln!(
+                    self.out,
+                    "{},0x{:016x},{},{}",
+                    row.pfn,
+                    row.flags,
+                    row.flag_names.join("|"),
+                    categories.join("|")
+                )?;
+            }
+            OutputFormat::Text => {}
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    /// Summary of the pages written so far
+    pub fn summary(&self, threshold: FlagThreshold) -> OutputSummary {
+        let mut flags: Vec<(&'static str, u32)> = self
+            .flag_counts
+            .iter()
+            .map(|(&name, &n)| (name, n))
+            .collect();
+        flags.sort_by(|(a_name, a), (b_name, b)| b.cmp(a).then_with(|| a_name.cmp(b_name)));
+        let (flags, _) = threshold.split(flags, self.total_pages, |&(_, pages)| pages);
+
+        OutputSummary {
+            total_pages: self.total_pages,
+            pages_with_flags: self.pages_with_flags,
+            flags: flags
+                .into_iter()
+                .map(|(name, pages)| FlagCount {
+                    name,
+                    pages,
+                    percent: pages as f64 / self.total_pages.max(1) as f64 * 100.0,
+                })
+                .collect(),
+            categories: FlagCategory::ALL
+                .into_iter()
+                .filter_map(|category| {
+                    self.category_counts
+                        .get(&category)
+                        .map(|&pages| CategoryCount { category, pages })
+                })
+                .collect(),
+        }
+    }
+
+    /// Write the summary and close the output: a final `__summary__`
+    /// element (JSON) or line (NDJSON), or with --summary the CSV flag table
+    pub fn finish(mut self, threshold: FlagThreshold) -> io::Result<()> {
+        let summary = self.summary(threshold);
+        let record = SummaryRecord { summary: &summary };
+        match self.format {
+            OutputFormat::Json => {
+                if self.rows > 0 {
+                    self.out.write_all(b",\n")?;
+                }
+                self.out.write_all(b"  ")?;
+                serde_json::to_writer(&mut self.out, &record)?;
+                self.out.write_all(b"\n]\n")?;
+            }
+            OutputFormat::Ndjson => {
+                serde_json::to_writer(&mut self.out, &record)?;
+                self.out.write_all(b"\n")?;
+            }
+            OutputFormat::Csv if self.summary_only => {
+                for flag in &summary.flags {
+                    writeln!(self.out, "{},{},{:.3}", flag.name, flag.pages, flag.percent)?;
+                }
+            }
+            OutputFormat::Csv | OutputFormat::Text => {}
+        }
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::find_flag;
+    use serde_json::Value;
+
+    fn write(format: OutputFormat, summary_only: bool, limit: usize) -> String {
+        let lru = find_flag("LRU").unwrap().mask;
+        let dirty = find_flag("DIRTY").unwrap().mask;
+        let pages = [
+            PageInfo::new(1, lru),
+            PageInfo::new(2, 0),
+            PageInfo::new(3, lru | dirty),
+        ];
+        let mut out = Vec::new();
+        let mut writer = PageWriter::new(&mut out, format, summary_only, false, limit).unwrap();
+        for page in &pages {
+            writer.write_page(page).unwrap();
+        }
+        writer.finish(FlagThreshold::default()).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_json_output() {
+        let json: Value = serde_json::from_str(&write(OutputFormat::Json, false, 10)).unwrap();
+        let items = json.as_array().unwrap();
+        assert_eq!(items.len(), 4);
+        assert_eq!(items[2]["pfn"], 3);
+        assert_eq!(items[2]["flag_names"], serde_json::json!(["DIRTY", "LRU"]));
+        assert!(items[2]["categories"].is_array());
+
+        let summary = &items[3]["__summary__"];
+        assert_eq!(summary["total_pages"], 3);
+        assert_eq!(summary["pages_with_flags"], 2);
+        assert_eq!(summary["flags"][0]["name"], "LRU");
+        assert_eq!(summary["flags"][0]["pages"], 2);
+
+        // --summary drops the rows, --limit caps them; counts cover every page
+        let json: Value = serde_json::from_str(&write(OutputFormat::Json, true, 10)).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 1);
+        let json: Value = serde_json::from_str(&write(OutputFormat::Json, false, 1)).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), 2);
+        assert_eq!(json[1]["__summary__"]["total_pages"], 3);
+    }
+
+    #[test]
+    fn test_ndjson_and_csv_output() {
+        let ndjson = write(OutputFormat::Ndjson, false, 10);
+        let lines: Vec<Value> = ndjson
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[1]["flags"], 0);
+        assert_eq!(lines[3]["__summary__"]["total_pages"], 3);
+
+        let csv = write(OutputFormat::Csv, false, 10);
+        let mut rows = csv.lines();
+        assert_eq!(rows.next(), Some("pfn,flags,flag_names,categories"));
+        let row = rows.nth(2).unwrap();
+        assert!(row.starts_with("3,0x"), "{}", row);
+        assert!(row.contains(",DIRTY|LRU,"), "{}", row);
+        assert_eq!(rows.next(), None);
+
+        let csv = write(OutputFormat::Csv, true, 10);
+        assert_eq!(
+            csv.lines().collect::<Vec<_>>(),
+            ["flag,pages,percent", "LRU,2,66.667", "DIRTY,1,33.333"]
+        );
+        assert_eq!(
+            OutputFormat::from_name("ndjson"),
+            Some(OutputFormat::Ndjson)
+        );
+        assert_eq!(OutputFormat::from_name("xml"), None);
+    }
+}
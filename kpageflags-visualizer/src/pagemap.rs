@@ -0,0 +1,361 @@
+//! Physical pages behind a file: mincore for residency, /proc/self/pagemap
+//! for the PFN of each page, and kpageflags for its state; and the present
+//! PFNs of a whole process
+
+use crate::{InterruptCheck, KPageFlagsReader, PageInfo};
+use byteorder::{LittleEndian, ReadBytesExt};
+use linux_memory_monitor::MemoryUtils;
+use memmap2::Mmap;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+// Layout of a pagemap entry (Documentation/admin-guide/mm/pagemap.rst)
+const PAGEMAP_PRESENT: u64 = 1 << 63;
+const PAGEMAP_SWAPPED: u64 = 1 << 62;
+const PAGEMAP_FILE_OR_SHARED_ANON: u64 = 1 << 61;
+const PAGEMAP_EXCLUSIVE: u64 = 1 << 56;
+const PAGEMAP_SOFT_DIRTY: u64 = 1 << 55;
+const PAGEMAP_PFN_MASK: u64 = (1 << 55) - 1;
+const PAGEMAP_SWAP_TYPE_MASK: u64 = (1 << 5) - 1;
+
+/// Pagemap entries read per call when walking a range
+const PAGEMAP_BATCH: usize = 4096;
+
+/// One decoded pagemap word: where a virtual page currently lives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageMapEntry {
+    /// Physical page, if present and visible (PFNs read as 0 without
+    /// CAP_SYS_ADMIN, reported here as `None`)
+    pub pfn: Option<u64>,
+    pub swap_type: Option<u8>,
+    pub swap_offset: Option<u64>,
+    pub is_present: bool,
+    pub is_swapped: bool,
+    /// File-backed, or anonymous memory shared with another process
+    pub is_file_page: bool,
+    pub is_soft_dirty: bool,
+    /// Mapped by this process only
+    pub has_exclusive: bool,
+}
+
+impl PageMapEntry {
+    pub fn from_raw(raw: u64) -> Self {
+        let is_present = raw & PAGEMAP_PRESENT != 0;
+        let is_swapped = raw & PAGEMAP_SWAPPED != 0;
+        let low = raw & PAGEMAP_PFN_MASK;
+        Self {
+            pfn: Some(low).filter(|&pfn| is_present && pfn != 0),
+            swap_type: is_swapped.then_some((low & PAGEMAP_SWAP_TYPE_MASK) as u8),
+            swap_offset: is_swapped.then_some(low >> 5),
+            is_present,
+            is_swapped,
+            is_file_page: raw & PAGEMAP_FILE_OR_SHARED_ANON != 0,
+            is_soft_dirty: raw & PAGEMAP_SOFT_DIRTY != 0,
+            has_exclusive: raw & PAGEMAP_EXCLUSIVE != 0,
+        }
+    }
+
+    /// Present with its PFN hidden by missing privileges
+    pub fn is_pfn_hidden(&self) -> bool {
+        self.is_present && self.pfn.is_none()
+    }
+}
+
+/// A virtual page of a process with what pagemap and kpageflags say about it
+#[derive(Debug, Clone)]
+pub struct ProcessPage {
+    pub vaddr: u64,
+    pub entry: PageMapEntry,
+    /// Flags of the physical page, when present and its PFN is visible
+    pub info: Option<PageInfo>,
+}
+
+/// Reads a process's /proc/PID/pagemap
+pub struct PageMapReader {
+    file: File,
+}
+
+impl PageMapReader {
+    pub fn for_pid(pid: u32) -> io::Result<Self> {
+        Ok(Self {
+            file: File::open(format!("/proc/{}/pagemap", pid))?,
+        })
+    }
+
+    /// This process's own pagemap
+    pub fn for_self() -> io::Result<Self> {
+        Ok(Self {
+            file: File::open("/proc/self/pagemap")?,
+        })
+    }
+
+    /// Where the page holding `vaddr` is, or `None` if it is neither in RAM
+    /// nor in swap (never touched, or not mapped at all)
+    pub fn translate_vaddr(&mut self, vaddr: u64) -> io::Result<Option<PageMapEntry>> {
+        self.file
+            .seek(SeekFrom::Start(vaddr / MemoryUtils::get_page_size() * 8))?;
+        let entry = PageMapEntry::from_raw(self.file.read_u64::<LittleEndian>()?);
+        Ok((entry.is_present || entry.is_swapped).then_some(entry))
+    }
+
+    /// `(page address, entry)` for every page overlapping `length` bytes
+    /// from `vaddr_start`, read `PAGEMAP_BATCH` entries at a time
+    ///
+    /// Pages neither present nor swapped are included, so the iterator
+    /// yields exactly one item per page until a read fails.
+    pub fn translate_range(
+        &mut self,
+        vaddr_start: u64,
+        length: u64,
+    ) -> impl Iterator<Item = io::Result<(u64, PageMapEntry)>> + '_ {
+        let page_size = MemoryUtils::get_page_size();
+        let mut page = vaddr_start / page_size;
+        let end_page = vaddr_start.saturating_add(length).div_ceil(page_size);
+        let mut batch: Vec<u64> = Vec::new();
+        let mut batch_start = page;
+        let mut buf = vec![0u8; PAGEMAP_BATCH * 8];
+        let mut failed = false;
+
+        std::iter::from_fn(move || {
+            if failed || page >= end_page {
+                return None;
+            }
+            let index = (page - batch_start) as usize;
+            if index >= batch.len() {
+                let count = ((end_page - page) as usize).min(PAGEMAP_BATCH);
+                let bytes = &mut buf[..count * 8];
+                if let Err(e) = self
+                    .file
+                    .seek(SeekFrom::Start(page * 8))
+                    .and_then(|_| self.file.read_exact(bytes))
+                {
+                    failed = true;
+                    return Some(Err(e));
+                }
+                batch = bytes
+                    .chunks_exact(8)
+                    .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+                    .collect();
+                batch_start = page;
+            }
+
+            let raw = batch[(page - batch_start) as usize];
+            let item = (page * page_size, PageMapEntry::from_raw(raw));
+            page += 1;
+            Some(Ok(item))
+        })
+    }
+}
+
+/// Which pages of the mapping are in the page cache, without faulting any in
+fn resident_pages(map: &Mmap) -> io::Result<Vec<bool>> {
+    let mut residency = vec![0u8; map.len().div_ceil(MemoryUtils::get_page_size() as usize)];
+    // SAFETY: the address and length describe a live mapping, and the vector
+    // has one byte per page as mincore requires
+    let ret = unsafe {
+        libc::mincore(
+            map.as_ptr() as *mut libc::c_void,
+            map.len(),
+            residency.as_mut_ptr(),
+        )
+    };
+    if ret != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(residency.iter().map(|&r| r & 1 != 0).collect())
+}
+
+/// PFN and kpageflags of every page of `path` currently in the page cache,
+/// in file order
+///
+/// Pages that aren't cached (never read, evicted, or holes in a sparse file)
+/// are skipped rather than read in, so looking doesn't change the answer.
+/// Stops early with the pages found so far once `interrupt_flag` is set.
+/// Needs root: without CAP_SYS_ADMIN pagemap reports every PFN as 0.
+pub fn file_physical_footprint(
+    path: &Path,
+    interrupt_flag: Arc<AtomicBool>,
+) -> Result<Vec<(u64, u64)>, Box<dyn Error>> {
+    let file = File::open(path)?;
+    if file.metadata()?.len() == 0 {
+        // Empty files can't be mapped and have no pages
+        return Ok(Vec::new());
+    }
+
+    // SAFETY: the mapping is read-only and only read below; truncating the
+    // file concurrently could still raise SIGBUS, as with any file mapping
+    let map = unsafe { Mmap::map(&file)? };
+    let resident = resident_pages(&map)?;
+
+    let mut pagemap = PageMapReader::for_self()?;
+    let mut kpageflags = KPageFlagsReader::new()?;
+    let mut pages = Vec::new();
+    let mut interrupt_check = InterruptCheck::new(interrupt_flag);
+
+    for index in (0..resident.len()).filter(|&i| resident[i]) {
+        if interrupt_check.triggered() {
+            break;
+        }
+        let offset = index * MemoryUtils::get_page_size() as usize;
+        // The page is cached, so touching it only maps it (a minor fault)
+        std::hint::black_box(map[offset]);
+
+        let entry = pagemap.translate_vaddr(map.as_ptr() as u64 + offset as u64)?;
+        let Some(entry) = entry.filter(|entry| entry.is_present) else {
+            // Evicted between mincore and the touch
+            continue;
+        };
+        let Some(pfn) = entry.pfn else {
+            return Err("pagemap hides PFNs; run as root to resolve physical pages".into());
+        };
+
+        if let Some(flags) = kpageflags.read_page_flags(pfn)? {
+            pages.push((pfn, flags));
+        }
+    }
+
+    Ok(pages)
+}
+
+/// Address ranges of every mapping in /proc/PID/maps
+fn process_mappings(pid: u32) -> io::Result<Vec<(u64, u64)>> {
+    let maps = std::fs::read_to_string(format!("/proc/{}/maps", pid))?;
+    Ok(maps
+        .lines()
+        .filter_map(|line| {
+            let (start, end) = line.split_whitespace().next()?.split_once('-')?;
+            Some((
+                u64::from_str_radix(start, 16).ok()?,
+                u64::from_str_radix(end, 16).ok()?,
+            ))
+        })
+        .collect())
+}
+
+/// PFNs of every page of process `pid` currently present in RAM, in virtual
+/// address order
+///
+/// Pages mapped more than once (shared libraries, shared memory) appear once
+/// per mapping. Stops early with the PFNs found so far once
+/// `interrupt_flag` is set. Needs root: without CAP_SYS_ADMIN pagemap hides
+/// PFNs.
+pub fn process_present_pfns(
+    pid: u32,
+    interrupt_flag: Arc<AtomicBool>,
+) -> Result<Vec<u64>, Box<dyn Error>> {
+    let mut pagemap = PageMapReader::for_pid(pid)?;
+    let mut pfns = Vec::new();
+    let mut hidden = false;
+    let mut interrupt_check = InterruptCheck::new(interrupt_flag);
+
+    for (start, end) in process_mappings(pid)? {
+        // Some special mappings ([vsyscall]) can't be read; skip the rest of
+        // them at the first failure
+        for (_, entry) in pagemap
+            .translate_range(start, end - start)
+            .map_while(Result::ok)
+            .take_while(|_| !interrupt_check.triggered())
+        {
+            match entry.pfn {
+                Some(pfn) => pfns.push(pfn),
+                None if entry.is_pfn_hidden() => hidden = true,
+                None => {}
+            }
+        }
+    }
+
+    if pfns.is_empty() && hidden {
+        return Err("pagemap hides PFNs; run as root to resolve physical pages".into());
+    }
+    Ok(pfns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_decode_pagemap_entry() {
+        let present = PageMapEntry::from_raw(PAGEMAP_PRESENT | PAGEMAP_EXCLUSIVE | 0x1234);
+        assert_eq!(present.pfn, Some(0x1234));
+        assert!(present.is_present && present.has_exclusive);
+        assert!(!present.is_swapped && !present.is_soft_dirty && !present.is_file_page);
+        assert_eq!(present.swap_type, None);
+
+        // Swap type in bits 0-4, offset above it
+        let swapped =
+            PageMapEntry::from_raw(PAGEMAP_SWAPPED | PAGEMAP_SOFT_DIRTY | (0x99 << 5) | 3);
+        assert_eq!(swapped.pfn, None);
+        assert_eq!(swapped.swap_type, Some(3));
+        assert_eq!(swapped.swap_offset, Some(0x99));
+        assert!(swapped.is_soft_dirty);
+
+        let hidden = PageMapEntry::from_raw(PAGEMAP_PRESENT | PAGEMAP_FILE_OR_SHARED_ANON);
+        assert!(hidden.is_pfn_hidden() && hidden.is_file_page);
+        assert!(!PageMapEntry::from_raw(0).is_pfn_hidden());
+    }
+
+    #[test]
+    fn test_translate_own_range() {
+        let buf = vec![1u8; 3 * MemoryUtils::get_page_size() as usize];
+        let start = buf.as_ptr() as u64;
+
+        let mut pagemap = PageMapReader::for_self().unwrap();
+        let pages: Vec<(u64, PageMapEntry)> = pagemap
+            .translate_range(start, buf.len() as u64)
+            .collect::<io::Result<_>>()
+            .unwrap();
+        // An unaligned buffer spans one more page
+        let expected = (start + buf.len() as u64).div_ceil(MemoryUtils::get_page_size())
+            - start / MemoryUtils::get_page_size();
+        assert_eq!(pages.len() as u64, expected);
+        assert_eq!(
+            pages[0].0,
+            start / MemoryUtils::get_page_size() * MemoryUtils::get_page_size()
+        );
+        // Written to, so present even if its PFN is hidden
+        assert!(pages.iter().all(|(_, entry)| entry.is_present));
+
+        let single = pagemap.translate_vaddr(start).unwrap().unwrap();
+        assert_eq!(Some(single), pages.first().map(|&(_, entry)| entry));
+    }
+
+    #[test]
+    fn test_footprint_of_cached_file() {
+        if File::open("/proc/kpageflags").is_err() {
+            // Needs root
+            return;
+        }
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&vec![0xa5u8; 4 * MemoryUtils::get_page_size() as usize])
+            .unwrap();
+        file.flush().unwrap();
+
+        // Just written, so every page is in the page cache
+        let footprint =
+            file_physical_footprint(file.path(), Arc::new(AtomicBool::new(false))).unwrap();
+        assert_eq!(footprint.len(), 4);
+        let uptodate = crate::find_flag("UPTODATE").unwrap().mask;
+        assert!(footprint.iter().all(|&(_, flags)| flags & uptodate != 0));
+    }
+
+    #[test]
+    fn test_own_present_pfns() {
+        if File::open("/proc/kpageflags").is_err() {
+            // Needs root
+            return;
+        }
+
+        let pfns =
+            process_present_pfns(std::process::id(), Arc::new(AtomicBool::new(false))).unwrap();
+        // At least this test's code and stack are resident
+        assert!(pfns.len() > 2);
+        assert!(pfns.iter().all(|&pfn| pfn != 0));
+    }
+}
@@ -0,0 +1,243 @@
+//! Multi-threaded scans: the range is split into one chunk per thread, each
+//! read through its own kpageflags file descriptor on a Rayon pool
+
+use crate::report::{AtomicScanCounters, ScanReport};
+use crate::{get_estimated_total_pages, FlagThreshold, KPageFlagsReader, PageFlags, PageInfo};
+use colored::*;
+use rayon::prelude::*;
+use std::error::Error;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+/// Split `count` pages from `start_pfn` into `chunks` near-equal
+/// `(start, count)` ranges; a `None` count leaves the last range open-ended
+/// after an estimated `expected` pages
+fn split_range(
+    start_pfn: u64,
+    count: Option<u64>,
+    expected: u64,
+    chunks: usize,
+) -> Vec<(u64, Option<u64>)> {
+    let total = count.unwrap_or(expected);
+    let chunks = (chunks.max(1) as u64).min(total.max(1));
+    let size = total.div_ceil(chunks);
+
+    (0..chunks)
+        .map(|i| {
+            let chunk_start = start_pfn + i * size;
+            let chunk_count = size.min(total.saturating_sub(i * size));
+            if i == chunks - 1 && count.is_none() {
+                (chunk_start, None)
+            } else {
+                (chunk_start, Some(chunk_count))
+            }
+        })
+        .collect()
+}
+
+fn thread_pool(threads: usize) -> Result<rayon::ThreadPool, Box<dyn Error>> {
+    Ok(rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()?)
+}
+
+/// Read each chunk through a reader from `open`, keeping PFN order
+fn read_range_parallel_with(
+    open: impl Fn() -> Result<KPageFlagsReader, Box<dyn Error>> + Sync,
+    start_pfn: u64,
+    count: u64,
+    threads: usize,
+    interrupt_flag: Arc<AtomicBool>,
+) -> Result<Vec<PageInfo>, Box<dyn Error>> {
+    let chunks = split_range(start_pfn, Some(count), count, threads);
+    let results: Vec<Result<Vec<PageInfo>, String>> = thread_pool(threads)?.install(|| {
+        chunks
+            .par_iter()
+            .map(|&(chunk_start, chunk_count)| {
+                let mut reader = open().map_err(|e| e.to_string())?;
+                let mut pages = Vec::new();
+                reader
+                    .for_each_page(
+                        chunk_start,
+                        chunk_count,
+                        interrupt_flag.clone(),
+                        |pfn, flags| {
+                            pages.push(PageInfo::new(pfn, flags));
+                            Ok(true)
+                        },
+                    )
+                    .map_err(|e| e.to_string())?;
+                Ok(pages)
+            })
+            .collect()
+    });
+
+    let mut pages = Vec::new();
+    for chunk in results {
+        pages.extend(chunk?);
+    }
+    Ok(pages)
+}
+
+/// Count every chunk into one set of atomic counters
+fn scan_summary_parallel_with(
+    open: impl Fn() -> Result<KPageFlagsReader, Box<dyn Error>> + Sync,
+    start_pfn: u64,
+    count: Option<u64>,
+    expected: u64,
+    threads: usize,
+    interrupt_flag: Arc<AtomicBool>,
+) -> Result<ScanReport, Box<dyn Error>> {
+    let counters = AtomicScanCounters::new();
+    let chunks = split_range(start_pfn, count, expected, threads);
+    let results: Vec<Result<(), String>> = thread_pool(threads)?.install(|| {
+        chunks
+            .par_iter()
+            .map(|&(chunk_start, chunk_count)| {
+                let mut reader = open().map_err(|e| e.to_string())?;
+                reader
+                    .for_each_page(
+                        chunk_start,
+                        chunk_count,
+                        interrupt_flag.clone(),
+                        |_, flags| {
+                            counters.record(PageFlags::from_bits(flags));
+                            Ok(true)
+                        },
+                    )
+                    .map_err(|e| e.to_string())
+            })
+            .collect()
+    });
+    for result in results {
+        result?;
+    }
+
+    Ok(counters.to_report(start_pfn))
+}
+
+impl KPageFlagsReader {
+    /// `read_range` on `threads` threads, each with its own /proc/kpageflags
+    /// descriptor and a copy of this reader's filter; pages come back in PFN
+    /// order
+    pub fn read_range_parallel(
+        &self,
+        start_pfn: u64,
+        count: u64,
+        threads: usize,
+        interrupt_flag: Arc<AtomicBool>,
+    ) -> Result<Vec<PageInfo>, Box<dyn Error>> {
+        let filter = self.filter.clone();
+        read_range_parallel_with(
+            || Ok(KPageFlagsReader::new()?.with_filter(filter.clone())),
+            start_pfn,
+            count,
+            threads,
+            interrupt_flag,
+        )
+    }
+
+    /// `scan_for_summary_only` on `threads` threads counting into shared
+    /// atomic counters
+    ///
+    /// Without a count, the range is split by the estimated memory size and
+    /// the last thread reads on to the real end. The report has no compound,
+    /// region or map count data.
+    pub fn scan_for_summary_only_parallel(
+        &self,
+        start_pfn: u64,
+        count: Option<u64>,
+        threads: usize,
+        interrupt_flag: Arc<AtomicBool>,
+        show_histogram: bool,
+        threshold: FlagThreshold,
+    ) -> Result<ScanReport, Box<dyn Error>> {
+        println!(
+            "Scanning pages for summary on {} threads starting from PFN 0x{:x}...",
+            threads.to_string().cyan(),
+            start_pfn
+        );
+        let expected = get_estimated_total_pages().unwrap_or(1048576);
+        let filter = self.filter.clone();
+        let report = scan_summary_parallel_with(
+            || Ok(KPageFlagsReader::new()?.with_filter(filter.clone())),
+            start_pfn,
+            count,
+            expected,
+            threads,
+            interrupt_flag,
+        )?;
+        println!(
+            "{}",
+            format!("Successfully scanned {} total pages", report.total_pages)
+                .green()
+                .bold()
+        );
+
+        self.print_optimized_summary(&report, show_histogram, threshold);
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn open_flags(flags: Vec<u64>) -> impl Fn() -> Result<KPageFlagsReader, Box<dyn Error>> + Sync {
+        move || {
+            let data: Vec<u8> = flags.iter().flat_map(|f| f.to_le_bytes()).collect();
+            Ok(KPageFlagsReader {
+                file: Box::new(Cursor::new(data)),
+                capture_pages: Some(flags.len() as u64),
+                regions: Vec::new(),
+                counts: None,
+                filter: None,
+            })
+        }
+    }
+
+    #[test]
+    fn test_split_range() {
+        assert_eq!(
+            split_range(10, Some(10), 10, 3),
+            [(10, Some(4)), (14, Some(4)), (18, Some(2))]
+        );
+        assert_eq!(split_range(0, None, 8, 2), [(0, Some(4)), (4, None)]);
+        // Never more chunks than pages
+        assert_eq!(split_range(0, Some(2), 2, 8).len(), 2);
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let flags: Vec<u64> = (0..5000u64).map(|pfn| pfn % 7 * 0x21).collect();
+        let interrupt = Arc::new(AtomicBool::new(false));
+
+        let sequential = open_flags(flags.clone())()
+            .unwrap()
+            .read_range(100, 4000, interrupt.clone())
+            .unwrap();
+        let parallel =
+            read_range_parallel_with(open_flags(flags.clone()), 100, 4000, 4, interrupt.clone())
+                .unwrap();
+        assert_eq!(parallel.len(), 4000);
+        assert!(parallel
+            .iter()
+            .zip(&sequential)
+            .all(|(a, b)| a.pfn == b.pfn && a.flags == b.flags));
+
+        // Open-ended: the estimate is too small, the last chunk reads on
+        let report =
+            scan_summary_parallel_with(open_flags(flags.clone()), 0, None, 1000, 3, interrupt)
+                .unwrap();
+        let mut expected = ScanReport::new(0);
+        for (pfn, &f) in flags.iter().enumerate() {
+            expected.record(pfn as u64, PageFlags::from_bits(f));
+        }
+        assert_eq!(report.total_pages, 5000);
+        assert_eq!(report.pages_with_flags, expected.pages_with_flags);
+        assert_eq!(report.flag_counts, expected.flag_counts);
+        assert_eq!(report.category_counts, expected.category_counts);
+        assert_eq!(report.flags_per_page, expected.flags_per_page);
+    }
+}
@@ -0,0 +1,109 @@
+//! Coarse physical memory regions (DMA, DMA32, Normal, ...) used to break
+//! flag counts down by PFN range
+
+use linux_memory_monitor::ZoneInfo;
+
+/// A named PFN range from `start_pfn` up to the next region's start
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub name: String,
+    pub start_pfn: u64,
+}
+
+impl Region {
+    pub fn new(name: &str, start_pfn: u64) -> Self {
+        Self {
+            name: name.to_string(),
+            start_pfn,
+        }
+    }
+}
+
+/// x86-64 zone layout: DMA below 16MB, DMA32 below 4GB, Normal above
+pub fn default_regions() -> Vec<Region> {
+    vec![
+        Region::new("DMA", 0),
+        Region::new("DMA32", 0x1000),
+        Region::new("Normal", 0x100000),
+    ]
+}
+
+/// Regions matching this machine's zones from /proc/zoneinfo, falling back
+/// to `default_regions` when it can't be read
+pub fn zone_regions() -> Vec<Region> {
+    let Ok(info) = ZoneInfo::current() else {
+        return default_regions();
+    };
+    let zones = info.zones_by_pfn();
+    if zones.is_empty() {
+        return default_regions();
+    }
+
+    let multi_node = zones.iter().any(|z| z.node != zones[0].node);
+    zones
+        .iter()
+        .map(|z| {
+            let name = if multi_node {
+                format!("Node{}/{}", z.node, z.zone)
+            } else {
+                z.zone.clone()
+            };
+            Region {
+                name,
+                start_pfn: z.start_pfn,
+            }
+        })
+        .collect()
+}
+
+/// Parse `NAME=PFN[,NAME=PFN...]` where each PFN (decimal or 0x hex) is
+/// where that region starts
+pub fn parse_regions(spec: &str) -> Result<Vec<Region>, String> {
+    let mut regions = Vec::new();
+    for part in spec.split(',') {
+        let (name, pfn) = part
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid region '{}': expected NAME=PFN", part))?;
+        let pfn = pfn.trim();
+        let start_pfn = match pfn.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16),
+            None => pfn.parse(),
+        }
+        .map_err(|_| format!("Invalid start PFN '{}' for region {}", pfn, name))?;
+        regions.push(Region::new(name.trim(), start_pfn));
+    }
+
+    regions.sort_by_key(|r| r.start_pfn);
+    if let Some(pair) = regions
+        .windows(2)
+        .find(|w| w[0].start_pfn == w[1].start_pfn)
+    {
+        return Err(format!(
+            "Regions {} and {} start at the same PFN",
+            pair[0].name, pair[1].name
+        ));
+    }
+    Ok(regions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_regions() {
+        let regions = parse_regions("Normal=0x100000,DMA=0, DMA32=4096").unwrap();
+        assert_eq!(
+            regions,
+            vec![
+                Region::new("DMA", 0),
+                Region::new("DMA32", 0x1000),
+                Region::new("Normal", 0x100000),
+            ]
+        );
+
+        assert!(parse_regions("DMA").is_err());
+        assert!(parse_regions("DMA=low").is_err());
+        assert!(parse_regions("A=0,B=0x0").is_err());
+    }
+}
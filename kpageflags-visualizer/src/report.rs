@@ -0,0 +1,908 @@
+use crate::region::Region;
+use crate::{flag_table, known_flags_mask, PageFlags, PageInfo};
+use colored::*;
+use linux_memory_monitor::MemoryUtils;
+use linux_memory_monitor::{
+    format_memory_change_kb, format_memory_kb, KsmStats, MemoryStats, MemoryStatsDiff,
+};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Flags listed per region by `print_regions`
+const REGION_TOP_FLAGS: usize = 6;
+
+/// Buckets of the flags-per-page histogram: 0..=7 flags, then 8 or more
+pub const FLAG_COUNT_BUCKETS: usize = 9;
+
+/// Inclusive upper bounds of the map count histogram buckets; a final
+/// bucket collects higher counts
+const MAP_COUNT_BOUNDS: [u64; 9] = [0, 1, 2, 3, 4, 8, 16, 64, 256];
+pub const MAP_COUNT_BUCKETS: usize = MAP_COUNT_BOUNDS.len() + 1;
+
+/// Most compound allocations a scan keeps for the largest-allocations report
+pub const MAX_TRACKED_COMPOUNDS: usize = 100;
+
+/// Whether a page is one of the kernel's prime reclaim candidates: a
+/// file-backed page on the inactive LRU (what meminfo calls Inactive(file))
+///
+/// UNEVICTABLE and MLOCKED pages are pinned in memory and RESERVED pages
+/// are never handed to the allocator, so none of them can be reclaimed
+/// whatever their LRU state. SWAPBACKED pages without ANON (shmem, tmpfs)
+/// sit on the anon LRU and can only be swapped out, not dropped.
+pub fn is_reclaim_candidate(flags: PageFlags) -> bool {
+    let not_reclaimable = PageFlags::ACTIVE
+        | PageFlags::UNEVICTABLE
+        | PageFlags::ANON
+        | PageFlags::SWAPBACKED
+        | PageFlags::RESERVED
+        | PageFlags::MLOCKED;
+    flags.contains(PageFlags::LRU) && !flags.intersects(not_reclaimable)
+}
+
+/// Counters accumulated by a summary scan
+/// Only stores counters, not individual PageInfo objects
+#[derive(Debug, Clone)]
+pub struct ScanReport {
+    pub start_pfn: u64,
+    pub total_pages: u32,
+    pub pages_with_flags: u32,
+    /// Per-flag counts, indexed like flag_table()
+    pub flag_counts: Vec<u32>,
+    /// Per-category counts, indexed by FlagCategory discriminant
+    pub category_counts: [u32; 8],
+    /// Pages with HUGE or THP set
+    pub huge_backed_pages: u32,
+    /// Inactive, evictable, file-backed LRU pages (see `is_reclaim_candidate`)
+    pub reclaimable_pages: u32,
+    /// Largest COMPOUND_HEAD + COMPOUND_TAIL runs seen so far
+    pub compounds: CompoundTracker,
+    /// Per-region page and flag counts, empty unless `with_regions` was used
+    pub regions: Vec<RegionCounts>,
+    /// Pages by number of known flags set; the last bucket collects the rest
+    pub flags_per_page: [u32; FLAG_COUNT_BUCKETS],
+    /// Pages by how many times they are mapped (from /proc/kpagecount),
+    /// `None` unless `with_map_counts` was used
+    pub map_counts: Option<MapCountHistogram>,
+    /// Bits counted towards `flags_per_page`
+    known_mask: u64,
+}
+
+/// The order-independent `ScanReport` counters as atomics, so several
+/// threads can count into one set without locking
+///
+/// Compound runs, regions and map counts need pages seen in PFN order, so
+/// reports built from these counters leave them empty.
+pub struct AtomicScanCounters {
+    total_pages: AtomicU32,
+    pages_with_flags: AtomicU32,
+    flag_counts: Vec<AtomicU32>,
+    category_counts: [AtomicU32; 8],
+    huge_backed_pages: AtomicU32,
+    reclaimable_pages: AtomicU32,
+    flags_per_page: [AtomicU32; FLAG_COUNT_BUCKETS],
+    known_mask: u64,
+}
+
+impl Default for AtomicScanCounters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AtomicScanCounters {
+    pub fn new() -> Self {
+        Self {
+            total_pages: AtomicU32::new(0),
+            pages_with_flags: AtomicU32::new(0),
+            flag_counts: flag_table().iter().map(|_| AtomicU32::new(0)).collect(),
+            category_counts: Default::default(),
+            huge_backed_pages: AtomicU32::new(0),
+            reclaimable_pages: AtomicU32::new(0),
+            flags_per_page: Default::default(),
+            known_mask: known_flags_mask(),
+        }
+    }
+
+    /// Account for a single page's flags, like `ScanReport::record`
+    pub fn record(&self, flags: PageFlags) {
+        let add = |counter: &AtomicU32| counter.fetch_add(1, Ordering::Relaxed);
+        add(&self.total_pages);
+        let set = (flags.bits() & self.known_mask).count_ones() as usize;
+        add(&self.flags_per_page[set.min(FLAG_COUNT_BUCKETS - 1)]);
+
+        if flags.is_empty() {
+            return;
+        }
+        add(&self.pages_with_flags);
+        for (i, def) in flag_table().iter().enumerate() {
+            if flags.intersects(def.flag()) {
+                add(&self.flag_counts[i]);
+                add(&self.category_counts[def.category as usize]);
+            }
+        }
+
+        if flags.intersects(PageFlags::HUGE | PageFlags::THP) {
+            add(&self.huge_backed_pages);
+        }
+        if is_reclaim_candidate(flags) {
+            add(&self.reclaimable_pages);
+        }
+    }
+
+    /// The counts so far as a report starting at `start_pfn`
+    pub fn to_report(&self, start_pfn: u64) -> ScanReport {
+        let load = |counter: &AtomicU32| counter.load(Ordering::Relaxed);
+        let mut report = ScanReport::new(start_pfn);
+        report.total_pages = load(&self.total_pages);
+        report.pages_with_flags = load(&self.pages_with_flags);
+        report.flag_counts = self.flag_counts.iter().map(load).collect();
+        report.category_counts = std::array::from_fn(|i| load(&self.category_counts[i]));
+        report.huge_backed_pages = load(&self.huge_backed_pages);
+        report.reclaimable_pages = load(&self.reclaimable_pages);
+        report.flags_per_page = std::array::from_fn(|i| load(&self.flags_per_page[i]));
+        report
+    }
+}
+
+/// Pages bucketed by map count, as read from /proc/kpagecount
+#[derive(Debug, Clone, Default)]
+pub struct MapCountHistogram {
+    /// Indexed like `MAP_COUNT_BOUNDS`, plus the overflow bucket
+    pub buckets: [u32; MAP_COUNT_BUCKETS],
+    /// Pages mapped more than once (shared libraries, COW, KSM)
+    pub shared_pages: u32,
+}
+
+impl MapCountHistogram {
+    pub fn record(&mut self, map_count: u64) {
+        let bucket = MAP_COUNT_BOUNDS.partition_point(|&bound| bound < map_count);
+        self.buckets[bucket] += 1;
+        if map_count > 1 {
+            self.shared_pages += 1;
+        }
+    }
+
+    /// Label of a bucket, e.g. "0", "5-8" or "257+"
+    pub fn bucket_label(bucket: usize) -> String {
+        let low = match bucket {
+            0 => 0,
+            b => MAP_COUNT_BOUNDS[b - 1] + 1,
+        };
+        match MAP_COUNT_BOUNDS.get(bucket) {
+            None => format!("{}+", low),
+            Some(&high) if high == low => low.to_string(),
+            Some(&high) => format!("{}-{}", low, high),
+        }
+    }
+
+    pub fn shared_bytes(&self) -> u64 {
+        self.shared_pages as u64 * MemoryUtils::get_page_size()
+    }
+
+    /// Print each bucket as a bar, then the shared total
+    pub fn print(&self) {
+        let counted: u32 = self.buckets.iter().sum();
+        let max_count = self.buckets.iter().copied().max().unwrap_or(0).max(1);
+        let bar_width = 40;
+        for (bucket, &count) in self.buckets.iter().enumerate() {
+            let percentage = if counted > 0 {
+                count as f64 / counted as f64 * 100.0
+            } else {
+                0.0
+            };
+            let bar_length = (count as f64 / max_count as f64 * bar_width as f64).ceil() as usize;
+            println!(
+                "  {:>7} maps: {:>10} ({:>5.1}%) {}",
+                MapCountHistogram::bucket_label(bucket),
+                count,
+                percentage,
+                "█".repeat(bar_length).cyan()
+            );
+        }
+        println!(
+            "Shared (mapped more than once): {} pages, {}",
+            self.shared_pages.to_string().green().bold(),
+            format_memory_kb(self.shared_bytes() / 1024).green()
+        );
+    }
+}
+
+/// Page and per-flag counts for one PFN region
+#[derive(Debug, Clone)]
+pub struct RegionCounts {
+    pub region: Region,
+    pub total_pages: u32,
+    /// Per-flag counts, indexed like flag_table()
+    pub flag_counts: Vec<u32>,
+}
+
+impl ScanReport {
+    pub fn new(start_pfn: u64) -> Self {
+        Self {
+            start_pfn,
+            total_pages: 0,
+            pages_with_flags: 0,
+            flag_counts: vec![0; flag_table().len()],
+            category_counts: [0; 8],
+            huge_backed_pages: 0,
+            reclaimable_pages: 0,
+            compounds: CompoundTracker::new(MAX_TRACKED_COMPOUNDS),
+            regions: Vec::new(),
+            flags_per_page: [0; FLAG_COUNT_BUCKETS],
+            map_counts: None,
+            known_mask: known_flags_mask(),
+        }
+    }
+
+    /// Also count pages and flags per region (regions sorted by start PFN)
+    pub fn with_regions(mut self, regions: &[Region]) -> Self {
+        self.regions = regions
+            .iter()
+            .map(|region| RegionCounts {
+                region: region.clone(),
+                total_pages: 0,
+                flag_counts: vec![0; flag_table().len()],
+            })
+            .collect();
+        self
+    }
+
+    /// Also bucket pages by map count, fed through `record_map_count`
+    pub fn with_map_counts(mut self) -> Self {
+        self.map_counts = Some(MapCountHistogram::default());
+        self
+    }
+
+    /// Account for a page's map count; ignored without `with_map_counts`
+    pub fn record_map_count(&mut self, map_count: u64) {
+        if let Some(histogram) = &mut self.map_counts {
+            histogram.record(map_count);
+        }
+    }
+
+    /// Account for a single page's flags
+    pub fn record(&mut self, pfn: u64, flags: PageFlags) {
+        self.total_pages += 1;
+        self.compounds.record(pfn, flags);
+        if !self.regions.is_empty() {
+            self.record_region(pfn, flags);
+        }
+        let set = (flags.bits() & self.known_mask).count_ones() as usize;
+        self.flags_per_page[set.min(FLAG_COUNT_BUCKETS - 1)] += 1;
+
+        if flags.is_empty() {
+            return;
+        }
+        self.pages_with_flags += 1;
+
+        // Count individual flags using array indexing (faster than HashMap)
+        for (i, def) in flag_table().iter().enumerate() {
+            if flags.intersects(def.flag()) {
+                self.flag_counts[i] += 1;
+                self.category_counts[def.category as usize] += 1;
+            }
+        }
+
+        if flags.intersects(PageFlags::HUGE | PageFlags::THP) {
+            self.huge_backed_pages += 1;
+        }
+        if is_reclaim_candidate(flags) {
+            self.reclaimable_pages += 1;
+        }
+    }
+
+    /// PFNs below the first region's start count towards the first region
+    fn record_region(&mut self, pfn: u64, flags: PageFlags) {
+        let index = self
+            .regions
+            .partition_point(|r| r.region.start_pfn <= pfn)
+            .saturating_sub(1);
+        let counts = &mut self.regions[index];
+        counts.total_pages += 1;
+        for (i, def) in flag_table().iter().enumerate() {
+            if flags.intersects(def.flag()) {
+                counts.flag_counts[i] += 1;
+            }
+        }
+    }
+
+    /// Memory the kernel would reclaim first, in bytes
+    pub fn reclaimable_bytes(&self) -> u64 {
+        self.reclaimable_pages as u64 * MemoryUtils::get_page_size()
+    }
+
+    /// Print the reclaimable pages section of the summary
+    pub fn print_reclaimable(&self) {
+        println!("\n{}", "=== RECLAIMABLE PAGES ===".blue().bold());
+        println!(
+            "{}",
+            "Pages with LRU set and ACTIVE, UNEVICTABLE, MLOCKED, RESERVED, ANON clear (inactive file LRU)".dimmed()
+        );
+        let percentage = if self.total_pages > 0 {
+            self.reclaimable_pages as f64 / self.total_pages as f64 * 100.0
+        } else {
+            0.0
+        };
+        println!(
+            "Reclaimable pages: {} ({:.1} MB, {:.1}% of scanned)",
+            self.reclaimable_pages.to_string().green().bold(),
+            self.reclaimable_bytes() as f64 / (1024.0 * 1024.0),
+            percentage
+        );
+    }
+
+    /// Non-zero flag counts as (flag_table() index, count), most common first
+    pub fn sorted_flag_counts(&self) -> Vec<(usize, u32)> {
+        let mut flag_data: Vec<(usize, u32)> = self
+            .flag_counts
+            .iter()
+            .enumerate()
+            .filter(|(_, &count)| count > 0)
+            .map(|(i, &count)| (i, count))
+            .collect();
+        flag_data.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+        flag_data
+    }
+
+    /// Count of pages with the named flag set
+    pub fn flag_count(&self, name: &str) -> u32 {
+        flag_table()
+            .iter()
+            .position(|def| def.name == name)
+            .map(|i| self.flag_counts[i])
+            .unwrap_or(0)
+    }
+}
+
+impl ScanReport {
+    /// Print DIRTY/WRITEBACK page counts, cross-checked against meminfo's
+    /// Dirty/Writeback totals when `meminfo` is given
+    pub fn print_io_pressure(&self, meminfo: Option<&MemoryStats>) {
+        println!("\n{}", "=== DIRTY / WRITEBACK ===".blue().bold());
+
+        let page_kb = MemoryUtils::get_page_size() / 1024;
+        for (name, meminfo_kb) in [
+            ("DIRTY", meminfo.map(|m| m.dirty)),
+            ("WRITEBACK", meminfo.map(|m| m.writeback)),
+        ] {
+            let pages = self.flag_count(name) as u64;
+            let kb = pages * page_kb;
+            print!(
+                "  {:>9}: {} pages ({:.1} MB)",
+                name.green().bold(),
+                pages.to_string().white(),
+                kb as f64 / 1024.0
+            );
+
+            match meminfo_kb {
+                Some(meminfo_kb) => {
+                    let diff = kb as i64 - meminfo_kb as i64;
+                    println!(
+                        "  meminfo: {:.1} MB, difference: {}",
+                        meminfo_kb as f64 / 1024.0,
+                        format_signed_bytes(diff * 1024).yellow()
+                    );
+                }
+                None => println!(),
+            }
+        }
+
+        if meminfo.is_some() {
+            println!(
+                "{}",
+                "Differences are expected for partial scans, pages changing during the scan, and kernel accounting of non-LRU pages"
+                    .dimmed()
+            );
+        }
+    }
+}
+
+impl ScanReport {
+    /// Print KSM-flagged page counts alongside the ksmd counters when `ksm`
+    /// is given. Each KSM-flagged page is one merged copy, so on a full scan
+    /// the flag count should track pages_shared.
+    pub fn print_ksm(&self, ksm: Option<&KsmStats>) {
+        println!("\n{}", "=== KSM ===".blue().bold());
+
+        let flagged = self.flag_count("KSM") as u64;
+        println!(
+            "  KSM-flagged pages in scan: {} ({:.1} MB)",
+            flagged.to_string().green().bold(),
+            (flagged * MemoryUtils::get_page_size()) as f64 / (1024.0 * 1024.0)
+        );
+
+        let Some(ksm) = ksm else {
+            return;
+        };
+        if !ksm.is_enabled() && ksm.pages_shared == 0 {
+            println!(
+                "{}",
+                "KSM is disabled or not available (/sys/kernel/mm/ksm)".dimmed()
+            );
+            return;
+        }
+
+        println!(
+            "  pages_shared: {}  pages_sharing: {} ({:.1} sites per shared page)",
+            ksm.pages_shared.to_string().white(),
+            ksm.pages_sharing.to_string().white(),
+            ksm.sharing_ratio()
+        );
+        println!(
+            "  pages_unshared: {}  pages_volatile: {}  full_scans: {}",
+            ksm.pages_unshared.to_string().white(),
+            ksm.pages_volatile.to_string().white(),
+            ksm.full_scans.to_string().white()
+        );
+        println!(
+            "  Estimated memory saved: {}",
+            format_memory_kb(ksm.memory_saved_kb()).cyan().bold()
+        );
+        if !ksm.is_enabled() {
+            println!(
+                "{}",
+                "  ksmd is stopped; merged pages stay shared until written".dimmed()
+            );
+        }
+        if ksm.pages_shared > 0 {
+            println!(
+                "  Scan covers {:.1}% of pages_shared",
+                flagged as f64 / ksm.pages_shared as f64 * 100.0
+            );
+        }
+    }
+}
+
+impl ScanReport {
+    /// Print how many pages have 0, 1, 2, ... known flags set
+    pub fn print_flags_per_page(&self) {
+        println!("\n{}", "=== FLAGS PER PAGE ===".blue().bold());
+        let max_count = self
+            .flags_per_page
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let bar_width = 40;
+
+        for (set, &count) in self.flags_per_page.iter().enumerate() {
+            let label = if set == FLAG_COUNT_BUCKETS - 1 {
+                format!("{}+", set)
+            } else {
+                set.to_string()
+            };
+            let percentage = if self.total_pages > 0 {
+                count as f64 / self.total_pages as f64 * 100.0
+            } else {
+                0.0
+            };
+            let bar_length = (count as f64 / max_count as f64 * bar_width as f64).ceil() as usize;
+            println!(
+                "  {:>2} flags: {:>10} ({:>5.1}%) {}",
+                label,
+                count,
+                percentage,
+                "█".repeat(bar_length).cyan()
+            );
+        }
+    }
+
+    /// Print how many pages are mapped 0, 1, 2, ... times and how much
+    /// memory is shared
+    pub fn print_map_counts(&self) {
+        println!("\n{}", "=== MAP COUNTS ===".blue().bold());
+        let Some(histogram) = &self.map_counts else {
+            println!(
+                "  {}",
+                "/proc/kpagecount is not available (live scans as root only)".yellow()
+            );
+            return;
+        };
+
+        histogram.print();
+    }
+
+    /// Print the most common flags of each region that had pages scanned
+    pub fn print_regions(&self) {
+        println!("\n{}", "=== FLAGS BY REGION ===".blue().bold());
+
+        for (i, counts) in self.regions.iter().enumerate() {
+            if counts.total_pages == 0 {
+                continue;
+            }
+            let range = match self.regions.get(i + 1) {
+                Some(next) => format!(
+                    "PFN 0x{:x}-0x{:x}",
+                    counts.region.start_pfn,
+                    next.region.start_pfn - 1
+                ),
+                None => format!("PFN 0x{:x}+", counts.region.start_pfn),
+            };
+            println!(
+                "\n{} ({}): {} pages scanned",
+                counts.region.name.cyan().bold(),
+                range,
+                counts.total_pages.to_string().white()
+            );
+
+            let mut flag_data: Vec<(usize, u32)> = counts
+                .flag_counts
+                .iter()
+                .enumerate()
+                .filter(|(_, &count)| count > 0)
+                .map(|(i, &count)| (i, count))
+                .collect();
+            flag_data.sort_by_key(|&(_, count)| Reverse(count));
+            if flag_data.is_empty() {
+                println!("  {}", "no flags set".dimmed());
+            }
+
+            for (index, count) in flag_data.into_iter().take(REGION_TOP_FLAGS) {
+                println!(
+                    "  {:>15}: {:>8} ({:.1}%)",
+                    flag_table()[index].name.green(),
+                    count,
+                    count as f64 / counts.total_pages as f64 * 100.0
+                );
+            }
+        }
+    }
+}
+
+/// A compound allocation: a COMPOUND_HEAD page followed by contiguous
+/// COMPOUND_TAIL pages. Ordered by size first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CompoundAllocation {
+    pub pages: u64,
+    pub start_pfn: u64,
+    /// Flags of the head page
+    pub flags: PageFlags,
+}
+
+impl CompoundAllocation {
+    pub fn bytes(&self) -> u64 {
+        self.pages * MemoryUtils::get_page_size()
+    }
+
+    /// Allocation order, if the run is a power of two pages. Runs cut off by
+    /// the edge of the scanned range are not.
+    pub fn order(&self) -> Option<u32> {
+        self.pages
+            .is_power_of_two()
+            .then(|| self.pages.trailing_zeros())
+    }
+}
+
+/// Walks pages in PFN order, recognizing head/tail runs and keeping the
+/// largest `limit` of them
+#[derive(Debug, Clone)]
+pub struct CompoundTracker {
+    limit: usize,
+    current: Option<CompoundAllocation>,
+    // Min-heap so the smallest kept allocation is evicted first
+    largest: BinaryHeap<Reverse<CompoundAllocation>>,
+}
+
+impl CompoundTracker {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            current: None,
+            largest: BinaryHeap::new(),
+        }
+    }
+
+    pub fn record(&mut self, pfn: u64, flags: PageFlags) {
+        if flags.contains(PageFlags::COMPOUND_HEAD) {
+            self.close_current();
+            self.current = Some(CompoundAllocation {
+                pages: 1,
+                start_pfn: pfn,
+                flags,
+            });
+            return;
+        }
+
+        match self.current.as_mut() {
+            Some(run)
+                if flags.contains(PageFlags::COMPOUND_TAIL) && pfn == run.start_pfn + run.pages =>
+            {
+                run.pages += 1;
+            }
+            Some(_) => self.close_current(),
+            None => {}
+        }
+    }
+
+    fn close_current(&mut self) {
+        let Some(run) = self.current.take() else {
+            return;
+        };
+        // A head without tails is not a multi-page allocation
+        if run.pages < 2 || self.limit == 0 {
+            return;
+        }
+        if self.largest.len() < self.limit {
+            self.largest.push(Reverse(run));
+        } else if self.largest.peek().is_some_and(|Reverse(min)| run > *min) {
+            self.largest.pop();
+            self.largest.push(Reverse(run));
+        }
+    }
+
+    /// The largest allocations, biggest first, including a run still open at
+    /// the end of the scan
+    pub fn largest(&self) -> Vec<CompoundAllocation> {
+        let mut tracker = self.clone();
+        tracker.close_current();
+        let mut allocations: Vec<CompoundAllocation> = tracker
+            .largest
+            .into_iter()
+            .map(|Reverse(allocation)| allocation)
+            .collect();
+        allocations.sort_by_key(|a| (Reverse(a.pages), a.start_pfn));
+        allocations
+    }
+}
+
+impl ScanReport {
+    /// Print the top `limit` compound allocations by size
+    pub fn print_largest_compounds(&self, limit: usize) {
+        println!("\n{}", "=== LARGEST COMPOUND ALLOCATIONS ===".blue().bold());
+
+        let allocations = self.compounds.largest();
+        if allocations.is_empty() {
+            println!(
+                "{}",
+                "No compound pages found in the scanned range".dimmed()
+            );
+            return;
+        }
+
+        for allocation in allocations.iter().take(limit) {
+            let order = match allocation.order() {
+                Some(order) => format!("order {}", order),
+                None => "partial".to_string(),
+            };
+            let flags =
+                PageInfo::new(allocation.start_pfn, allocation.flags.bits()).get_flag_names();
+            println!(
+                "  PFN 0x{:x}: {} pages ({:.1} MB, {}) {}",
+                allocation.start_pfn,
+                allocation.pages.to_string().green().bold(),
+                allocation.bytes() as f64 / (1024.0 * 1024.0),
+                order.yellow(),
+                flags.join(" ").dimmed()
+            );
+        }
+    }
+}
+
+/// Direction of huge-page backed memory between two scans
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThpTrend {
+    /// Huge-page backed memory grew (e.g. khugepaged collapsed small pages)
+    Collapse,
+    /// Huge-page backed memory shrank (e.g. THPs were split)
+    Split,
+    Stable,
+}
+
+/// Change in huge-page related flag counts between two scans
+#[derive(Debug, Clone)]
+pub struct ThpChange {
+    pub huge_delta: i64,
+    pub thp_delta: i64,
+    pub compound_head_delta: i64,
+    pub compound_tail_delta: i64,
+    /// Net change in pages with HUGE or THP set
+    pub huge_backed_delta: i64,
+    pub trend: ThpTrend,
+}
+
+impl ThpChange {
+    /// Compare the huge-page flags of two scans of the same range
+    pub fn between(before: &ScanReport, after: &ScanReport) -> Self {
+        let delta = |name: &str| after.flag_count(name) as i64 - before.flag_count(name) as i64;
+        let huge_backed_delta = after.huge_backed_pages as i64 - before.huge_backed_pages as i64;
+
+        let trend = match huge_backed_delta {
+            d if d > 0 => ThpTrend::Collapse,
+            d if d < 0 => ThpTrend::Split,
+            _ => ThpTrend::Stable,
+        };
+
+        Self {
+            huge_delta: delta("HUGE"),
+            thp_delta: delta("THP"),
+            compound_head_delta: delta("COMPOUND_HEAD"),
+            compound_tail_delta: delta("COMPOUND_TAIL"),
+            huge_backed_delta,
+            trend,
+        }
+    }
+
+    /// Net change in huge-page backed memory in bytes
+    pub fn huge_backed_delta_bytes(&self) -> i64 {
+        self.huge_backed_delta * MemoryUtils::get_page_size() as i64
+    }
+
+    pub fn print(&self) {
+        println!("\n{}", "=== THP CHANGE ===".blue().bold());
+        for (name, delta) in [
+            ("HUGE", self.huge_delta),
+            ("THP", self.thp_delta),
+            ("COMPOUND_HEAD", self.compound_head_delta),
+            ("COMPOUND_TAIL", self.compound_tail_delta),
+        ] {
+            println!(
+                "  {:>13}: {} pages ({})",
+                name.green().bold(),
+                format!("{:+}", delta).white(),
+                format_signed_bytes(delta * MemoryUtils::get_page_size() as i64).yellow()
+            );
+        }
+
+        println!(
+            "Huge-page backed memory: {} pages ({})",
+            format!("{:+}", self.huge_backed_delta).cyan(),
+            format_signed_bytes(self.huge_backed_delta_bytes()).cyan()
+        );
+
+        match self.trend {
+            ThpTrend::Collapse => println!(
+                "{}",
+                "Suspected collapse: huge-page backed memory increased (khugepaged)"
+                    .green()
+                    .bold()
+            ),
+            ThpTrend::Split => println!(
+                "{}",
+                "Suspected split: huge-page backed memory decreased"
+                    .red()
+                    .bold()
+            ),
+            ThpTrend::Stable => println!("{}", "No change in huge-page backed memory".dimmed()),
+        }
+    }
+}
+
+/// Which direction of change is good news for a meminfo field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Favorable {
+    Higher,
+    Lower,
+    Neutral,
+}
+
+fn favorable_direction(field: &str) -> Favorable {
+    match field {
+        "MemFree" | "MemAvailable" => Favorable::Higher,
+        // Growing dirty data, anon memory, swap and unreclaimable slab all
+        // add to memory pressure
+        "Dirty" | "Writeback" | "SwapCached" | "Active(anon)" | "Inactive(anon)" | "SUnreclaim" => {
+            Favorable::Lower
+        }
+        _ => Favorable::Neutral,
+    }
+}
+
+/// Print every meminfo change, green when it moved in the favorable
+/// direction (e.g. free up, dirty down) and red when it adds pressure.
+/// Colors are dropped under NO_COLOR like all other output.
+pub fn print_diff_colored(diff: &MemoryStatsDiff) {
+    println!("\n{}", "=== MEMINFO CHANGE ===".blue().bold());
+    for (field, delta) in diff.fields() {
+        let text = format_memory_change_kb(delta);
+        let good = match favorable_direction(field) {
+            Favorable::Higher => delta > 0,
+            Favorable::Lower => delta < 0,
+            Favorable::Neutral => {
+                println!("  {:>15}: {}", field, text.white());
+                continue;
+            }
+        };
+        let text = if delta == 0 {
+            text.dimmed()
+        } else if good {
+            text.green().bold()
+        } else {
+            text.red().bold()
+        };
+        println!("  {:>15}: {}", field, text);
+    }
+}
+
+fn format_signed_bytes(bytes: i64) -> String {
+    format!("{:+.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record_run(tracker: &mut CompoundTracker, start_pfn: u64, pages: u64) {
+        tracker.record(start_pfn, PageFlags::COMPOUND_HEAD);
+        for pfn in start_pfn + 1..start_pfn + pages {
+            tracker.record(pfn, PageFlags::COMPOUND_TAIL);
+        }
+    }
+
+    #[test]
+    fn test_compound_tracker_keeps_largest_runs() {
+        let mut tracker = CompoundTracker::new(2);
+        record_run(&mut tracker, 0, 4);
+        tracker.record(4, PageFlags::EMPTY);
+        record_run(&mut tracker, 8, 512);
+        // Head with no tails is not an allocation
+        tracker.record(600, PageFlags::COMPOUND_HEAD);
+        record_run(&mut tracker, 1024, 8);
+        // Still open when the scan ends
+        record_run(&mut tracker, 2048, 3);
+
+        let largest = tracker.largest();
+        assert_eq!(largest.len(), 2);
+        assert_eq!((largest[0].start_pfn, largest[0].pages), (8, 512));
+        assert_eq!(largest[0].order(), Some(9));
+        assert_eq!((largest[1].start_pfn, largest[1].pages), (1024, 8));
+    }
+
+    #[test]
+    fn test_flags_per_page_buckets() {
+        let mut report = ScanReport::new(0);
+        report.record(0, PageFlags::EMPTY);
+        report.record(1, PageFlags::LRU | PageFlags::ACTIVE | PageFlags::ANON);
+        // Bits outside the flag table don't count
+        report.record(2, PageFlags::LRU | PageFlags::from_bits(1 << 35));
+        report.record(3, PageFlags::from_bits((1 << 12) - 1));
+
+        assert_eq!(report.flags_per_page[0], 1);
+        assert_eq!(report.flags_per_page[1], 1);
+        assert_eq!(report.flags_per_page[3], 1);
+        assert_eq!(report.flags_per_page[FLAG_COUNT_BUCKETS - 1], 1);
+        assert_eq!(
+            report.flags_per_page.iter().sum::<u32>(),
+            report.total_pages
+        );
+    }
+
+    #[test]
+    fn test_map_count_histogram() {
+        let mut report = ScanReport::new(0);
+        report.record_map_count(1);
+        assert!(report.map_counts.is_none());
+
+        let mut report = ScanReport::new(0).with_map_counts();
+        for map_count in [0, 1, 1, 2, 5, 8, 300] {
+            report.record_map_count(map_count);
+        }
+        let histogram = report.map_counts.unwrap();
+        assert_eq!(histogram.buckets, [1, 2, 1, 0, 0, 2, 0, 0, 0, 1]);
+        assert_eq!(histogram.shared_pages, 4);
+        assert_eq!(histogram.shared_bytes(), 4 * MemoryUtils::get_page_size());
+
+        let labels: Vec<String> = (0..MAP_COUNT_BUCKETS)
+            .map(MapCountHistogram::bucket_label)
+            .collect();
+        assert_eq!(
+            labels,
+            ["0", "1", "2", "3", "4", "5-8", "9-16", "17-64", "65-256", "257+"]
+        );
+    }
+
+    #[test]
+    fn test_compound_run_ends_at_gap() {
+        let mut tracker = CompoundTracker::new(10);
+        tracker.record(0, PageFlags::COMPOUND_HEAD);
+        tracker.record(1, PageFlags::COMPOUND_TAIL);
+        // A tail after a skipped PFN belongs to an unseen head
+        tracker.record(3, PageFlags::COMPOUND_TAIL);
+
+        let largest = tracker.largest();
+        assert_eq!(largest.len(), 1);
+        assert_eq!(largest[0].pages, 2);
+        assert_eq!(largest[0].order(), Some(1));
+    }
+}
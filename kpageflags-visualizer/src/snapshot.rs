@@ -0,0 +1,335 @@
+//! Scans kept with the time they were taken, and what changed between two
+//!
+//! Snapshot files are a 16-byte header (`SNAPSHOT_MAGIC`, a version byte, a
+//! u32 page count and a u64 Unix time in milliseconds) followed by 16 bytes
+//! per page: PFN then flags, all little-endian. Unlike `--export` files they
+//! are fixed width, so they are larger but trivial to read elsewhere.
+
+use crate::tracker::describe_flag_change;
+use crate::PageInfo;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Transitions listed by `PageFlagsDiff::summary`
+const TOP_TRANSITIONS: usize = 10;
+
+/// Leading bytes of a snapshot file
+pub const SNAPSHOT_MAGIC: &[u8; 3] = b"KPS";
+
+/// Snapshot format version following the magic
+const SNAPSHOT_VERSION: u8 = 1;
+
+/// Pages to reserve room for up front, so a corrupt count can't make
+/// `read_pages_binary` allocate a huge buffer before failing
+const MAX_PREALLOCATED_PAGES: usize = 1 << 20;
+
+fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+impl PageInfo {
+    /// PFN then raw flags, 8 little-endian bytes each
+    pub fn to_binary(&self) -> [u8; 16] {
+        let mut bytes = [0u8; 16];
+        bytes[..8].copy_from_slice(&self.pfn.to_le_bytes());
+        bytes[8..].copy_from_slice(&self.flags.bits().to_le_bytes());
+        bytes
+    }
+
+    pub fn from_binary(bytes: &[u8; 16]) -> Self {
+        let (pfn, flags) = bytes.split_at(8);
+        PageInfo::new(
+            u64::from_le_bytes(pfn.try_into().unwrap()),
+            u64::from_le_bytes(flags.try_into().unwrap()),
+        )
+    }
+}
+
+fn write_snapshot<W: Write>(writer: &mut W, pages: &[PageInfo], timestamp: u64) -> io::Result<()> {
+    let count = u32::try_from(pages.len()).map_err(|_| {
+        io::Error::new(io::ErrorKind::InvalidInput, "too many pages for a snapshot")
+    })?;
+    writer.write_all(SNAPSHOT_MAGIC)?;
+    writer.write_all(&[SNAPSHOT_VERSION])?;
+    writer.write_all(&count.to_le_bytes())?;
+    writer.write_all(&timestamp.to_le_bytes())?;
+    for page in pages {
+        writer.write_all(&page.to_binary())?;
+    }
+    writer.flush()
+}
+
+/// Pages and timestamp of a snapshot
+fn read_snapshot<R: Read>(reader: &mut R) -> io::Result<(Vec<PageInfo>, u64)> {
+    let mut header = [0u8; 16];
+    reader.read_exact(&mut header)?;
+    if &header[..3] != SNAPSHOT_MAGIC {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not a kpageflags snapshot",
+        ));
+    }
+    if header[3] != SNAPSHOT_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unsupported snapshot version {}", header[3]),
+        ));
+    }
+    let count = u32::from_le_bytes(header[4..8].try_into().unwrap()) as usize;
+    let timestamp = u64::from_le_bytes(header[8..].try_into().unwrap());
+
+    let mut pages = Vec::with_capacity(count.min(MAX_PREALLOCATED_PAGES));
+    let mut entry = [0u8; 16];
+    for _ in 0..count {
+        reader.read_exact(&mut entry)?;
+        pages.push(PageInfo::from_binary(&entry));
+    }
+    Ok((pages, timestamp))
+}
+
+/// Write pages as a snapshot taken now
+pub fn write_pages_binary(pages: &[PageInfo], writer: &mut impl Write) -> io::Result<()> {
+    write_snapshot(writer, pages, unix_millis(SystemTime::now()))
+}
+
+/// Read the pages of a snapshot written by `write_pages_binary` or
+/// `KPageFlagsSnapshot::save`
+pub fn read_pages_binary(reader: &mut impl Read) -> io::Result<Vec<PageInfo>> {
+    read_snapshot(reader).map(|(pages, _)| pages)
+}
+
+/// Whether the file at `path` starts with `SNAPSHOT_MAGIC`
+pub fn is_snapshot(path: &Path) -> io::Result<bool> {
+    let mut magic = Vec::new();
+    File::open(path)?.take(3).read_to_end(&mut magic)?;
+    Ok(magic == SNAPSHOT_MAGIC)
+}
+
+/// A scan result and when it was taken
+#[derive(Debug, Clone)]
+pub struct KPageFlagsSnapshot {
+    pub pages: Vec<PageInfo>,
+    /// Unix time in milliseconds
+    pub timestamp: u64,
+}
+
+impl KPageFlagsSnapshot {
+    /// Snapshot of pages scanned just now
+    pub fn new(pages: Vec<PageInfo>) -> Self {
+        Self::at(pages, SystemTime::now())
+    }
+
+    /// Snapshot of pages scanned at `time`, e.g. a capture's modification time
+    pub fn at(pages: Vec<PageInfo>, time: SystemTime) -> Self {
+        Self {
+            pages,
+            timestamp: unix_millis(time),
+        }
+    }
+
+    /// Write the snapshot, keeping its timestamp
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        write_snapshot(
+            &mut BufWriter::new(File::create(path)?),
+            &self.pages,
+            self.timestamp,
+        )
+    }
+
+    /// Read a snapshot written by `save` or `write_pages_binary`
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let (pages, timestamp) = read_snapshot(&mut BufReader::new(File::open(path)?))?;
+        Ok(Self { pages, timestamp })
+    }
+
+    /// What changed from this snapshot to `other`
+    pub fn diff(&self, other: &Self) -> PageFlagsDiff {
+        PageFlagsDiff::between(&self.pages, &other.pages)
+    }
+}
+
+/// Pages that differ between two scans
+#[derive(Debug, Clone, Default)]
+pub struct PageFlagsDiff {
+    /// Pages only in the later scan
+    pub appeared: Vec<PageInfo>,
+    /// PFNs only in the earlier scan
+    pub disappeared: Vec<u64>,
+    /// `(before, after)` for pages whose flags changed
+    pub changed: Vec<(PageInfo, PageInfo)>,
+}
+
+impl PageFlagsDiff {
+    /// Compare two scans; the inputs need not be sorted, the results are in
+    /// PFN order
+    pub fn between(before: &[PageInfo], after: &[PageInfo]) -> Self {
+        let mut before: Vec<&PageInfo> = before.iter().collect();
+        let mut after: Vec<&PageInfo> = after.iter().collect();
+        before.sort_by_key(|page| page.pfn);
+        after.sort_by_key(|page| page.pfn);
+
+        let mut diff = PageFlagsDiff::default();
+        let (mut b, mut a) = (before.iter().peekable(), after.iter().peekable());
+        loop {
+            match (b.peek(), a.peek()) {
+                (Some(old), Some(new)) => match old.pfn.cmp(&new.pfn) {
+                    Ordering::Less => diff.disappeared.push(b.next().unwrap().pfn),
+                    Ordering::Greater => diff.appeared.push((*a.next().unwrap()).clone()),
+                    Ordering::Equal => {
+                        let (old, new) = (b.next().unwrap(), a.next().unwrap());
+                        if old.flags != new.flags {
+                            diff.changed.push(((*old).clone(), (*new).clone()));
+                        }
+                    }
+                },
+                (Some(_), None) => diff.disappeared.push(b.next().unwrap().pfn),
+                (None, Some(_)) => diff.appeared.push((*a.next().unwrap()).clone()),
+                (None, None) => break,
+            }
+        }
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.appeared.is_empty() && self.disappeared.is_empty() && self.changed.is_empty()
+    }
+
+    /// Flag transitions among changed pages, e.g. "-DIRTY +WRITEBACK", with
+    /// how many pages made each, most common first
+    pub fn transition_counts(&self) -> Vec<(String, usize)> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for (old, new) in &self.changed {
+            *counts
+                .entry(describe_flag_change(old.flags, new.flags))
+                .or_default() += 1;
+        }
+        let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+        counts.sort_by(|(a_name, a), (b_name, b)| b.cmp(a).then_with(|| a_name.cmp(b_name)));
+        counts
+    }
+
+    /// Counts of each kind of difference and the most common transitions
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "{} appeared, {} disappeared, {} changed",
+            self.appeared.len(),
+            self.disappeared.len(),
+            self.changed.len()
+        );
+        for (transition, pages) in self.transition_counts().iter().take(TOP_TRANSITIONS) {
+            // Only unknown bits changed
+            let transition = if transition.is_empty() {
+                "(unnamed flags)"
+            } else {
+                transition
+            };
+            summary.push_str(&format!("\n  {:>8}  {}", pages, transition));
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::find_flag;
+
+    fn pages(entries: &[(u64, u64)]) -> Vec<PageInfo> {
+        entries
+            .iter()
+            .map(|&(pfn, flags)| PageInfo::new(pfn, flags))
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_between_snapshots() {
+        let mask = |name| find_flag(name).unwrap().mask;
+        let (dirty, writeback, lru) = (mask("DIRTY"), mask("WRITEBACK"), mask("LRU"));
+
+        let before = KPageFlagsSnapshot::new(pages(&[
+            (1, lru | dirty),
+            (2, lru | dirty),
+            (3, lru),
+            (4, lru | dirty),
+            (5, 0),
+        ]));
+        // Unsorted on purpose
+        let after = KPageFlagsSnapshot::new(pages(&[
+            (6, lru),
+            (4, lru),
+            (3, lru),
+            (2, lru | writeback),
+            (1, lru | writeback),
+        ]));
+        assert!(after.timestamp >= before.timestamp);
+
+        let diff = before.diff(&after);
+        assert_eq!(diff.disappeared, [5]);
+        assert_eq!(diff.appeared.len(), 1);
+        assert_eq!(diff.appeared[0].pfn, 6);
+        let changed: Vec<u64> = diff.changed.iter().map(|(old, _)| old.pfn).collect();
+        assert_eq!(changed, [1, 2, 4]);
+        assert_eq!(diff.changed[0].1.flags.bits(), lru | writeback);
+
+        assert_eq!(
+            diff.transition_counts(),
+            [
+                ("-DIRTY +WRITEBACK".to_string(), 2),
+                ("-DIRTY".to_string(), 1)
+            ]
+        );
+        let summary = diff.summary();
+        assert!(summary.starts_with("1 appeared, 1 disappeared, 3 changed"));
+        assert!(summary.contains("2  -DIRTY +WRITEBACK"));
+
+        assert!(before.diff(&before).is_empty());
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let page = PageInfo::new(0x1234, 0x8000_0000_0000_0028);
+        let bytes = page.to_binary();
+        assert_eq!(&bytes[..8], &0x1234u64.to_le_bytes());
+        let decoded = PageInfo::from_binary(&bytes);
+        assert_eq!((decoded.pfn, decoded.flags), (page.pfn, page.flags));
+
+        let pages = pages(&[(1, 0x28), (7, 0), (u64::MAX, u64::MAX)]);
+        let mut data = Vec::new();
+        write_pages_binary(&pages, &mut data).unwrap();
+        assert_eq!(data.len(), 16 + 3 * 16);
+        assert_eq!(&data[..3], SNAPSHOT_MAGIC);
+        let decoded = read_pages_binary(&mut data.as_slice()).unwrap();
+        let pfns: Vec<u64> = decoded.iter().map(|page| page.pfn).collect();
+        assert_eq!(pfns, [1, 7, u64::MAX]);
+        assert_eq!(decoded[2].flags.bits(), u64::MAX);
+
+        // Truncated, and another version
+        assert!(read_pages_binary(&mut &data[..data.len() - 1]).is_err());
+        data[3] = 2;
+        assert!(read_pages_binary(&mut data.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pages.snapshot");
+        let snapshot = KPageFlagsSnapshot {
+            pages: pages(&[(3, 0x10), (4, 0x20)]),
+            timestamp: 1_700_000_000_123,
+        };
+        snapshot.save(&path).unwrap();
+        assert!(is_snapshot(&path).unwrap());
+        let loaded = KPageFlagsSnapshot::load(&path).unwrap();
+        assert_eq!(loaded.timestamp, snapshot.timestamp);
+        assert!(snapshot.diff(&loaded).is_empty());
+        assert_eq!(loaded.pages.len(), 2);
+
+        std::fs::write(&path, [0u8; 8]).unwrap();
+        assert!(!is_snapshot(&path).unwrap());
+    }
+}
@@ -0,0 +1,198 @@
+//! Watch a fixed set of PFNs and record how their flags change over time
+
+use crate::{flag_table, sleep_unless_interrupted, KPageFlagsReader, PageFlags};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A page whose flags differed between two reads
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlagTransition {
+    pub pfn: u64,
+    pub old_flags: PageFlags,
+    pub new_flags: PageFlags,
+    /// Unix time of the read that saw the change, in milliseconds
+    pub timestamp: u64,
+}
+
+impl FlagTransition {
+    /// Names of known flags that were set (`+NAME`) or cleared (`-NAME`),
+    /// e.g. "-DIRTY +WRITEBACK"
+    pub fn describe(&self) -> String {
+        describe_flag_change(self.old_flags, self.new_flags)
+    }
+}
+
+/// Known flags set (`+NAME`) or cleared (`-NAME`) going from `old` to `new`
+pub fn describe_flag_change(old: PageFlags, new: PageFlags) -> String {
+    let changed = old ^ new;
+    flag_table()
+        .iter()
+        .filter(|def| changed.intersects(def.flag()))
+        .map(|def| {
+            let sign = if new.intersects(def.flag()) { '+' } else { '-' };
+            format!("{}{}", sign, def.name)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Transitions a `FlagTracker` keeps; older ones are dropped so a long
+/// run over busy pages doesn't grow without bound
+pub const MAX_RECORDED_TRANSITIONS: usize = 100_000;
+
+/// Re-reads the flags of a fixed PFN set and records each transition
+pub struct FlagTracker {
+    pfns: Vec<u64>,
+    /// Flags from the previous poll; empty before the first
+    last: HashMap<u64, PageFlags>,
+    /// The latest `MAX_RECORDED_TRANSITIONS`, oldest first
+    transitions: VecDeque<FlagTransition>,
+    /// Every transition seen, including dropped ones
+    transition_count: u64,
+}
+
+impl FlagTracker {
+    pub fn new(pfns: &[u64]) -> Self {
+        Self {
+            pfns: pfns.to_vec(),
+            last: HashMap::new(),
+            transitions: VecDeque::new(),
+            transition_count: 0,
+        }
+    }
+
+    /// Read every tracked PFN and return the transitions since the last
+    /// poll; the first poll only records a baseline
+    pub fn poll(
+        &mut self,
+        reader: &mut KPageFlagsReader,
+    ) -> Result<Vec<FlagTransition>, Box<dyn std::error::Error>> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+
+        let mut changes = Vec::new();
+        for (pfn, flags) in reader.read_pfns(&self.pfns)? {
+            if let Some(old_flags) = self.last.insert(pfn, flags) {
+                if old_flags != flags {
+                    changes.push(FlagTransition {
+                        pfn,
+                        old_flags,
+                        new_flags: flags,
+                        timestamp,
+                    });
+                }
+            }
+        }
+
+        self.transition_count += changes.len() as u64;
+        self.transitions.extend(changes.iter().cloned());
+        let excess = self
+            .transitions
+            .len()
+            .saturating_sub(MAX_RECORDED_TRANSITIONS);
+        self.transitions.drain(..excess);
+        Ok(changes)
+    }
+
+    /// Poll every `interval` until Ctrl-C, passing each transition to
+    /// `on_transition` as it is seen
+    pub async fn run(
+        &mut self,
+        reader: &mut KPageFlagsReader,
+        interval: Duration,
+        interrupt_flag: Arc<AtomicBool>,
+        mut on_transition: impl FnMut(&FlagTransition),
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        loop {
+            for transition in self.poll(reader)? {
+                on_transition(&transition);
+            }
+            // Also checks the flag for a zero interval
+            if !sleep_unless_interrupted(interval, &interrupt_flag).await {
+                return Ok(());
+            }
+        }
+    }
+
+    /// The latest `MAX_RECORDED_TRANSITIONS` transitions, oldest first
+    pub fn transitions(&self) -> &VecDeque<FlagTransition> {
+        &self.transitions
+    }
+
+    /// Number of transitions seen, including those no longer recorded
+    pub fn transition_count(&self) -> u64 {
+        self.transition_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::find_flag;
+    use std::io::Cursor;
+
+    fn reader(flags: &[u64]) -> KPageFlagsReader {
+        let data: Vec<u8> = flags.iter().flat_map(|f| f.to_le_bytes()).collect();
+        KPageFlagsReader {
+            file: Box::new(Cursor::new(data)),
+            capture_pages: Some(flags.len() as u64),
+            regions: Vec::new(),
+            counts: None,
+            filter: None,
+        }
+    }
+
+    #[test]
+    fn test_tracker_records_transitions() {
+        let mask = |name| find_flag(name).unwrap().mask;
+        let (dirty, active, lru) = (mask("DIRTY"), mask("ACTIVE"), mask("LRU"));
+
+        let mut tracker = FlagTracker::new(&[0, 2]);
+        let baseline = tracker
+            .poll(&mut reader(&[lru | active | dirty, 0, lru]))
+            .unwrap();
+        assert!(baseline.is_empty());
+
+        // PFN 0 was cleaned and aged; PFN 1 isn't tracked; PFN 2 unchanged
+        let changes = tracker.poll(&mut reader(&[lru, dirty, lru])).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].pfn, 0);
+        assert_eq!(changes[0].old_flags.bits(), lru | active | dirty);
+        assert_eq!(changes[0].new_flags.bits(), lru);
+        assert_eq!(changes[0].describe(), "-DIRTY -ACTIVE");
+        assert!(tracker.transitions().iter().eq(&changes));
+
+        let changes = tracker.poll(&mut reader(&[lru | active, 0, lru])).unwrap();
+        assert_eq!(changes[0].describe(), "+ACTIVE");
+        assert_eq!(tracker.transitions().len(), 2);
+        assert_eq!(tracker.transition_count(), 2);
+    }
+
+    #[test]
+    fn test_tracker_bounds_recorded_transitions() {
+        let mut tracker = FlagTracker::new(&[0]);
+        tracker.poll(&mut reader(&[0])).unwrap();
+        for i in 0..MAX_RECORDED_TRANSITIONS as u64 + 10 {
+            tracker.poll(&mut reader(&[i % 2 + 1])).unwrap();
+        }
+        assert_eq!(tracker.transitions().len(), MAX_RECORDED_TRANSITIONS);
+        assert_eq!(
+            tracker.transition_count(),
+            MAX_RECORDED_TRANSITIONS as u64 + 10
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_with_zero_interval_stops_on_interrupt() {
+        let mut tracker = FlagTracker::new(&[0]);
+        let flag = Arc::new(AtomicBool::new(true));
+        tracker
+            .run(&mut reader(&[0]), Duration::ZERO, flag, |_| {})
+            .await
+            .unwrap();
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{get_category_symbol_and_color, FlagCategory, KPageFlagsReader, PageInfo, PAGE_FLAGS};
+use crate::{
+    flag_table, get_category_symbol_and_color, get_class_symbol_and_color, ColorMode, FlagCategory,
+    KPageFlagsMmap, KPageFlagsReader, PageClass, PageFilter, PageInfo,
+};
 use crossterm::{
     event::{
         self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseButton,
@@ -7,33 +10,162 @@ use crossterm::{
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use linux_memory_monitor::{
+    format_memory_kb, MemorySnapshot, MemoryStats, PressureLevel, PsiResource,
+};
 use ratatui::{
-    backend::{Backend, CrosstermBackend},
+    backend::{Backend, CrosstermBackend, TestBackend},
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
+    symbols,
     text::{Line, Span, Text},
-    widgets::{Block, Borders, Gauge, Paragraph, Wrap},
+    widgets::{Axis, Block, Borders, Chart, Dataset, Gauge, GraphType, Paragraph, Wrap},
     Frame, Terminal,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::fs;
 use std::io;
+use std::path::Path;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::watch;
 use tokio::time::sleep;
 
+/// Rows of the memory timeline panel, as --timeline-height defaults to
+const DEFAULT_TIMELINE_HEIGHT: u16 = 8;
+
+/// /proc/meminfo samples kept for the timeline, one a second
+const TIMELINE_SAMPLES: usize = 120;
+
+const MEMORY_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Screen size for `e` exports when the terminal size isn't known yet
+const DEFAULT_EXPORT_SIZE: (u16, u16) = (160, 48);
+
+/// System memory as last read by the sampler task
+#[derive(Debug, Clone)]
+pub struct SystemMemory {
+    pub stats: MemoryStats,
+    /// Memory PSI `some_avg10`; `None` without /proc/pressure/memory
+    pub psi_some_avg10: Option<f64>,
+}
+
+/// Read /proc/meminfo (and memory PSI) every `MEMORY_SAMPLE_INTERVAL` on a
+/// background task until the receiver is dropped
+fn spawn_memory_sampler() -> watch::Receiver<Option<SystemMemory>> {
+    let (tx, rx) = watch::channel(None);
+    tokio::spawn(async move {
+        loop {
+            if let Ok(stats) = MemoryStats::current_async().await {
+                let psi = tokio::fs::read_to_string("/proc/pressure/memory").await;
+                let sample = SystemMemory {
+                    stats,
+                    psi_some_avg10: psi
+                        .ok()
+                        .and_then(|content| PsiResource::parse(&content).ok())
+                        .map(|psi| psi.some_avg10),
+                };
+                if tx.send(Some(sample)).is_err() {
+                    break;
+                }
+            }
+            sleep(MEMORY_SAMPLE_INTERVAL).await;
+        }
+    });
+    rx
+}
+
+/// Gauge color for a pressure level: green, yellow, orange, red
+fn pressure_color(level: PressureLevel) -> Color {
+    match level {
+        PressureLevel::Low => Color::Green,
+        PressureLevel::Medium => Color::Yellow,
+        PressureLevel::High => Color::Indexed(208),
+        PressureLevel::Critical => Color::Red,
+    }
+}
+
+/// Which series the timeline panel plots; `T` cycles through them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimelineMetric {
+    All,
+    MemAvailable,
+    PageCache,
+    InactiveFile,
+}
+
+impl TimelineMetric {
+    pub fn next(self) -> Self {
+        match self {
+            TimelineMetric::All => TimelineMetric::MemAvailable,
+            TimelineMetric::MemAvailable => TimelineMetric::PageCache,
+            TimelineMetric::PageCache => TimelineMetric::InactiveFile,
+            TimelineMetric::InactiveFile => TimelineMetric::All,
+        }
+    }
+
+    fn shows(self, metric: TimelineMetric) -> bool {
+        self == TimelineMetric::All || self == metric
+    }
+
+    /// Series name, line color and how to get it from meminfo, in KB; `All`
+    /// gives its first series
+    fn series(self) -> (&'static str, Color, fn(&MemoryStats) -> u64) {
+        match self {
+            TimelineMetric::All | TimelineMetric::MemAvailable => {
+                ("MemAvailable", Color::Green, |stats| stats.mem_available)
+            }
+            TimelineMetric::PageCache => ("Page cache", Color::Cyan, MemoryStats::page_cache_size),
+            TimelineMetric::InactiveFile => {
+                ("Inactive(file)", Color::Yellow, |stats| stats.inactive_file)
+            }
+        }
+    }
+}
+
+/// Parse a TUI search for a PFN range: `START-END` in hex, END exclusive
+fn parse_pfn_range(text: &str) -> Option<(u64, u64)> {
+    let hex = |s: &str| {
+        let s = s.trim();
+        u64::from_str_radix(s.strip_prefix("0x").unwrap_or(s), 16).ok()
+    };
+    let (start, end) = text.split_once('-')?;
+    let (start, end) = (hex(start)?, hex(end)?);
+    (end > start).then_some((start, end))
+}
+
 #[derive(Debug, Clone)]
 pub struct AppState {
     pub pages: Vec<PageInfo>,
     pub zoom_level: f64,
     pub offset_x: i64,
     pub offset_y: i64,
-    pub grid_width: usize,
-    pub grid_height: usize,
-    pub selected_page: Option<usize>,
     pub show_help: bool,
     pub show_stats: bool,
-    pub filter_category: Option<FlagCategory>,
+    /// Pages the grid shows; the number keys pick a category filter
+    pub filter: Option<PageFilter>,
+    /// Only pages in `[start, end)` are shown, set by searching for a range
+    pub pfn_range_filter: Option<(u64, u64)>,
+    /// Flag expression whose matching pages are highlighted
+    pub search: Option<PageFilter>,
+    /// Text typed after `/`; `Some` while the search box is open
+    pub search_input: Option<String>,
+    /// Why the last search couldn't be applied
+    pub search_error: Option<String>,
+    /// Hex PFN typed after `g`; `Some` while the goto box is open
+    pub goto_input: Option<String>,
+    /// Why the last goto failed, e.g. a PFN that wasn't scanned
+    pub goto_error: Option<String>,
+    /// File name typed after `e`; `Some` while the export box is open
+    pub export_input: Option<String>,
+    /// Where the last export went, or why it failed
+    pub export_message: Option<String>,
+    /// Size of the last frame drawn, which exports reuse
+    pub screen_area: Option<Rect>,
+    /// Index into flag_table() of the flag shown as a binary overlay
+    pub selected_flag: Option<usize>,
+    pub color_mode: ColorMode,
     pub last_update: Instant,
     pub total_pages_scanned: usize,
     pub scanning: bool,
@@ -43,6 +175,78 @@ pub struct AppState {
     pub selection_start: Option<(u16, u16)>,
     pub selection_end: Option<(u16, u16)>,
     pub grid_area: Option<Rect>,
+    /// Recent /proc/meminfo samples for the timeline panel, oldest first
+    pub memory_history: VecDeque<MemorySnapshot>,
+    /// Latest sample from the memory sampler, for the pressure gauge
+    pub system_memory: Option<SystemMemory>,
+    pub show_timeline: bool,
+    pub timeline_height: u16,
+    pub timeline_metric: TimelineMetric,
+}
+
+impl AppState {
+    /// Pages shown in the grid: those passing the filter and PFN range
+    pub fn visible_pages(&self) -> Vec<&PageInfo> {
+        self.pages
+            .iter()
+            .filter(|page| self.filter.as_ref().is_none_or(|f| f.matches(page)))
+            .filter(|page| {
+                self.pfn_range_filter
+                    .is_none_or(|(start, end)| (start..end).contains(&page.pfn))
+            })
+            .collect()
+    }
+
+    /// How many of the `visible` pages the current search matches, if
+    /// there is one
+    pub fn search_matches(&self, visible: &[&PageInfo]) -> Option<usize> {
+        if self.search.is_none() && self.pfn_range_filter.is_none() {
+            return None;
+        }
+        Some(match &self.search {
+            Some(search) => visible.iter().filter(|page| search.matches(page)).count(),
+            None => visible.len(),
+        })
+    }
+
+    /// Apply what was typed in the search box: a PFN range such as
+    /// `0x1000-0x2000` narrows the grid, a category name such as `io` sets
+    /// the category filter, and anything else is a flag expression such as
+    /// `DIRTY` or `LRU & !ACTIVE` whose pages are highlighted
+    pub fn apply_search(&mut self, text: &str) {
+        let text = text.trim();
+        self.search_error = None;
+        if text.is_empty() {
+            self.clear_search();
+        } else if let Some(range) = parse_pfn_range(text) {
+            self.pfn_range_filter = Some(range);
+            self.offset_x = 0;
+            self.offset_y = 0;
+        } else if let Some(category) = FlagCategory::from_name(text) {
+            self.filter = Some(PageFilter::Category(category));
+        } else {
+            match PageFilter::parse(text) {
+                Ok(filter) => self.search = Some(filter),
+                Err(e) => self.search_error = Some(e),
+            }
+        }
+    }
+
+    /// Drop the search along with the PFN range or category filter it set
+    pub fn clear_search(&mut self) {
+        self.search = None;
+        self.filter = None;
+        self.pfn_range_filter = None;
+        self.search_error = None;
+    }
+
+    /// Add a sample to the timeline, dropping the oldest past the window
+    pub fn push_memory_sample(&mut self, snapshot: MemorySnapshot) {
+        self.memory_history.push_back(snapshot);
+        while self.memory_history.len() > TIMELINE_SAMPLES {
+            self.memory_history.pop_front();
+        }
+    }
 }
 
 impl Default for AppState {
@@ -52,12 +256,20 @@ impl Default for AppState {
             zoom_level: 1.0,
             offset_x: 0,
             offset_y: 0,
-            grid_width: 80,
-            grid_height: 24,
-            selected_page: None,
             show_help: false,
             show_stats: true,
-            filter_category: None,
+            filter: None,
+            pfn_range_filter: None,
+            search: None,
+            search_input: None,
+            search_error: None,
+            goto_input: None,
+            goto_error: None,
+            export_input: None,
+            export_message: None,
+            screen_area: None,
+            selected_flag: None,
+            color_mode: ColorMode::Category,
             last_update: Instant::now(),
             total_pages_scanned: 0,
             scanning: false,
@@ -66,6 +278,11 @@ impl Default for AppState {
             selection_start: None,
             selection_end: None,
             grid_area: None,
+            memory_history: VecDeque::new(),
+            system_memory: None,
+            show_timeline: true,
+            timeline_height: DEFAULT_TIMELINE_HEIGHT,
+            timeline_metric: TimelineMetric::All,
         }
     }
 }
@@ -73,18 +290,36 @@ impl Default for AppState {
 pub struct TuiApp {
     state: AppState,
     reader: KPageFlagsReader,
+    /// Preferred over `reader` when the kernel lets kpageflags be mapped
+    mmap: Option<KPageFlagsMmap>,
     interrupt_flag: Arc<AtomicBool>,
+    /// Samples from `spawn_memory_sampler`
+    memory_rx: watch::Receiver<Option<SystemMemory>>,
 }
 
 impl TuiApp {
-    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
+    /// `interrupt_flag` is the process's signal flag: SIGTERM or SIGHUP
+    /// setting it closes the TUI
+    pub fn new(
+        color_mode: ColorMode,
+        timeline_height: u16,
+        interrupt_flag: Arc<AtomicBool>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         let reader = KPageFlagsReader::new()?;
-        let interrupt_flag = Arc::new(AtomicBool::new(false));
 
         Ok(Self {
-            state: AppState::default(),
+            state: AppState {
+                color_mode,
+                timeline_height,
+                ..AppState::default()
+            },
             reader,
+            // An empty mapping would show no pages; read instead
+            mmap: KPageFlagsMmap::new()
+                .ok()
+                .filter(|map| map.page_count() > 0),
             interrupt_flag,
+            memory_rx: spawn_memory_sampler(),
         })
     }
 
@@ -96,55 +331,84 @@ impl TuiApp {
         self.start_background_scan().await?;
 
         loop {
+            // Raw mode turns Ctrl-C into a key press, but SIGTERM and SIGHUP
+            // still arrive as signals
+            if self.interrupt_flag.load(Ordering::Relaxed) {
+                break;
+            }
             terminal.draw(|f| self.ui(f))?;
 
             if event::poll(Duration::from_millis(100))? {
                 match event::read()? {
-                    Event::Key(key) => {
-                        if key.kind == KeyEventKind::Press {
-                            match key.code {
-                                KeyCode::Char('q') => break,
-                                KeyCode::Char('h') => self.state.show_help = !self.state.show_help,
-                                KeyCode::Char('s') => {
-                                    self.state.show_stats = !self.state.show_stats
-                                }
-                                KeyCode::Char('r') => self.refresh_data().await?,
-                                KeyCode::Char('+') | KeyCode::Char('=') => self.zoom_in(),
-                                KeyCode::Char('-') => self.zoom_out(),
-                                KeyCode::Up => self.move_up(),
-                                KeyCode::Down => self.move_down(),
-                                KeyCode::Left => self.move_left(),
-                                KeyCode::Right => self.move_right(),
-                                KeyCode::Char('1') => self.set_filter(Some(FlagCategory::State)),
-                                KeyCode::Char('2') => self.set_filter(Some(FlagCategory::Memory)),
-                                KeyCode::Char('3') => self.set_filter(Some(FlagCategory::Usage)),
-                                KeyCode::Char('4') => {
-                                    self.set_filter(Some(FlagCategory::Allocation))
-                                }
-                                KeyCode::Char('5') => self.set_filter(Some(FlagCategory::IO)),
-                                KeyCode::Char('6') => {
-                                    self.set_filter(Some(FlagCategory::Structure))
-                                }
-                                KeyCode::Char('7') => self.set_filter(Some(FlagCategory::Special)),
-                                KeyCode::Char('8') => self.set_filter(Some(FlagCategory::Error)),
-                                KeyCode::Char('0') => self.set_filter(None),
-                                KeyCode::Home => self.reset_view(),
-                                KeyCode::Esc => self.cancel_selection(),
-                                _ => {}
-                            }
-                        }
+                    Event::Key(key)
+                        if key.kind == KeyEventKind::Press && self.state.search_input.is_some() =>
+                    {
+                        self.handle_search_key(key.code)
+                    }
+                    Event::Key(key)
+                        if key.kind == KeyEventKind::Press && self.state.goto_input.is_some() =>
+                    {
+                        self.handle_goto_key(key.code)
+                    }
+                    Event::Key(key)
+                        if key.kind == KeyEventKind::Press && self.state.export_input.is_some() =>
+                    {
+                        self.handle_export_key(key.code)
                     }
+                    Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                        KeyCode::Char('q') => break,
+                        KeyCode::Char('h') => self.state.show_help = !self.state.show_help,
+                        KeyCode::Char('s') => self.state.show_stats = !self.state.show_stats,
+                        KeyCode::Char('t') => self.state.show_timeline = !self.state.show_timeline,
+                        KeyCode::Char('T') => {
+                            self.state.timeline_metric = self.state.timeline_metric.next()
+                        }
+                        KeyCode::Char('r') => self.refresh_data().await?,
+                        KeyCode::Char('+') | KeyCode::Char('=') => self.zoom_in(),
+                        KeyCode::Char('-') => self.zoom_out(),
+                        KeyCode::Up => self.move_up(),
+                        KeyCode::Down => self.move_down(),
+                        KeyCode::Left => self.move_left(),
+                        KeyCode::Right => self.move_right(),
+                        KeyCode::Char('1') => self.set_filter(Some(FlagCategory::State)),
+                        KeyCode::Char('2') => self.set_filter(Some(FlagCategory::Memory)),
+                        KeyCode::Char('3') => self.set_filter(Some(FlagCategory::Usage)),
+                        KeyCode::Char('4') => self.set_filter(Some(FlagCategory::Allocation)),
+                        KeyCode::Char('5') => self.set_filter(Some(FlagCategory::IO)),
+                        KeyCode::Char('6') => self.set_filter(Some(FlagCategory::Structure)),
+                        KeyCode::Char('7') => self.set_filter(Some(FlagCategory::Special)),
+                        KeyCode::Char('8') => self.set_filter(Some(FlagCategory::Error)),
+                        KeyCode::Char('0') => self.set_filter(None),
+                        KeyCode::Char('f') => self.cycle_flag(true),
+                        KeyCode::Char('F') => self.cycle_flag(false),
+                        KeyCode::Char('c') => {
+                            self.state.color_mode = self.state.color_mode.toggled()
+                        }
+                        KeyCode::Char('/') => self.state.search_input = Some(String::new()),
+                        KeyCode::Char('e') => {
+                            self.state.export_message = None;
+                            self.state.export_input = Some(String::new());
+                        }
+                        KeyCode::Char('g') => {
+                            self.state.goto_error = None;
+                            self.state.goto_input = Some(String::new());
+                        }
+                        KeyCode::Home => self.reset_view(),
+                        KeyCode::Esc => {
+                            self.cancel_selection();
+                            self.state.clear_search();
+                        }
+                        _ => {}
+                    },
                     Event::Mouse(mouse) => {
                         self.handle_mouse_event(mouse);
                     }
+                    Event::Resize(_, _) => self.handle_resize(terminal)?,
                     _ => {}
                 }
             }
 
-            // Update scan progress if scanning
-            if self.state.scanning {
-                self.update_scan_progress().await?;
-            }
+            self.update_scan_progress().await?;
 
             sleep(Duration::from_millis(50)).await;
         }
@@ -152,14 +416,26 @@ impl TuiApp {
         Ok(())
     }
 
+    /// Pages in a PFN range, through the mapping when there is one
+    fn read_pages(
+        &mut self,
+        start_pfn: u64,
+        count: u64,
+    ) -> Result<Vec<PageInfo>, Box<dyn std::error::Error>> {
+        match &self.mmap {
+            Some(map) => Ok(map.scan_range_mmap(start_pfn, start_pfn + count).collect()),
+            None => self
+                .reader
+                .read_range(start_pfn, count, self.interrupt_flag.clone()),
+        }
+    }
+
     async fn start_background_scan(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         self.state.scanning = true;
         self.state.scan_progress = 0.0;
 
         // Start with a small sample for immediate feedback
-        let initial_pages = self
-            .reader
-            .read_range(0, 10000, self.interrupt_flag.clone())?;
+        let initial_pages = self.read_pages(0, 10000)?;
         self.state.pages = initial_pages;
         self.state.total_pages_scanned = self.state.pages.len();
         self.state.scan_progress = 0.01; // Start at 1%
@@ -167,18 +443,29 @@ impl TuiApp {
         Ok(())
     }
 
+    /// Take any new memory sample for the gauge and timeline and, while
+    /// scanning, advance the scan
     async fn update_scan_progress(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.memory_rx.has_changed().unwrap_or(false) {
+            let sample = self.memory_rx.borrow_and_update().clone();
+            if let Some(sample) = sample {
+                self.state
+                    .push_memory_sample(MemorySnapshot::from_stats(sample.stats.clone()));
+                self.state.system_memory = Some(sample);
+            }
+        }
+
+        if !self.state.scanning {
+            return Ok(());
+        }
+
         // Simulate progressive scanning
         if self.state.scan_progress < 1.0 {
             self.state.scan_progress += 0.01;
 
             // Load more pages as we progress
             if self.state.scan_progress > 0.5 && self.state.pages.len() < 50000 {
-                let more_pages = self.reader.read_range(
-                    self.state.pages.len() as u64,
-                    10000,
-                    self.interrupt_flag.clone(),
-                )?;
+                let more_pages = self.read_pages(self.state.pages.len() as u64, 10000)?;
                 self.state.pages.extend(more_pages);
                 self.state.total_pages_scanned = self.state.pages.len();
             }
@@ -194,9 +481,7 @@ impl TuiApp {
         self.state.scan_progress = 0.0;
 
         // Reload data
-        let pages = self
-            .reader
-            .read_range(0, 100000, self.interrupt_flag.clone())?;
+        let pages = self.read_pages(0, 100000)?;
         self.state.pages = pages;
         self.state.total_pages_scanned = self.state.pages.len();
         self.state.last_update = Instant::now();
@@ -230,8 +515,163 @@ impl TuiApp {
         self.state.offset_x += (10.0 / self.state.zoom_level) as i64;
     }
 
+    /// Edit the search box; Enter applies it and Esc closes it unchanged
+    fn handle_search_key(&mut self, code: KeyCode) {
+        let Some(input) = self.state.search_input.as_mut() else {
+            return;
+        };
+        match code {
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Enter => {
+                if let Some(text) = self.state.search_input.take() {
+                    self.state.apply_search(&text);
+                }
+            }
+            KeyCode::Esc => self.state.search_input = None,
+            _ => {}
+        }
+    }
+
+    /// Edit the goto box, which only takes hex digits after an optional
+    /// `0x`; Enter jumps to the PFN and Esc closes it
+    fn handle_goto_key(&mut self, code: KeyCode) {
+        let Some(input) = self.state.goto_input.as_mut() else {
+            return;
+        };
+        match code {
+            KeyCode::Char(c) if c.is_ascii_hexdigit() => input.push(c),
+            KeyCode::Char('x') if input == "0" => input.push('x'),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Enter => {
+                let input = self.state.goto_input.take().unwrap_or_default();
+                let digits = input.strip_prefix("0x").unwrap_or(&input);
+                let result = u64::from_str_radix(digits, 16)
+                    .map_err(|_| format!("'{}' is not a hex PFN", input))
+                    .and_then(|pfn| self.goto_pfn(pfn));
+                self.state.goto_error = result.err();
+            }
+            KeyCode::Esc => self.state.goto_input = None,
+            _ => {}
+        }
+    }
+
+    /// Edit the export box; Enter writes the file and Esc closes it
+    fn handle_export_key(&mut self, code: KeyCode) {
+        let Some(input) = self.state.export_input.as_mut() else {
+            return;
+        };
+        match code {
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Enter => {
+                let input = self.state.export_input.take().unwrap_or_default();
+                let path = input.trim();
+                self.state.export_message = Some(if path.is_empty() {
+                    "Export cancelled: no file name".to_string()
+                } else {
+                    match self.export_text(Path::new(path)) {
+                        Ok(()) => format!("Exported view to {}", path),
+                        Err(e) => format!("Export to {} failed: {}", path, e),
+                    }
+                });
+            }
+            KeyCode::Esc => self.state.export_input = None,
+            _ => {}
+        }
+    }
+
+    /// Draw the current view off screen and write it to `path` as plain
+    /// text, after a line with the time, page count, zoom and filter
+    ///
+    /// The view is drawn on a `TestBackend` the size of the terminal, so
+    /// the real terminal stays in raw mode and untouched.
+    pub fn export_text(&mut self, path: &Path) -> io::Result<()> {
+        let (width, height) = self
+            .state
+            .screen_area
+            .map_or(DEFAULT_EXPORT_SIZE, |area| (area.width, area.height));
+        let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+        terminal.draw(|f| self.ui(f))?;
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let mut text = format!(
+            "KPageFlags TUI export at Unix time {} - {} pages - Zoom: {:.1}x - Filter: {}\n",
+            timestamp,
+            self.state.total_pages_scanned,
+            self.state.zoom_level,
+            self.state
+                .filter
+                .as_ref()
+                .map_or("None".to_string(), |filter| filter.to_string())
+        );
+        let buffer = terminal.backend().buffer();
+        for y in 0..height {
+            let row: String = (0..width)
+                .map(|x| buffer.get(x, y).symbol.as_str())
+                .collect();
+            text.push_str(row.trim_end());
+            text.push('\n');
+        }
+        fs::write(path, text)
+    }
+
+    /// Scroll so `pfn` is in the middle of the grid at the current zoom
+    ///
+    /// Fails if `pfn` is outside the scanned pages. A PFN that was scanned
+    /// but is hidden by a filter centers the next shown page instead.
+    pub fn goto_pfn(&mut self, pfn: u64) -> Result<(), String> {
+        let pages = self.state.visible_pages();
+        let (Some(first), Some(last)) = (pages.first(), pages.last()) else {
+            return Err("No pages to go to".to_string());
+        };
+        if pfn < first.pfn || pfn > last.pfn {
+            return Err(format!(
+                "PFN 0x{:x} is outside the scanned range 0x{:x}-0x{:x}",
+                pfn, first.pfn, last.pfn
+            ));
+        }
+        let index = pages.partition_point(|page| page.pfn < pfn);
+
+        // The same geometry render_grid uses
+        let Some(area) = self.state.grid_area else {
+            return Err("The grid hasn't been drawn yet".to_string());
+        };
+        let pages_per_row = ((area.width as f64 * self.state.zoom_level) as usize).max(1);
+        let rows = ((area.height as f64 * self.state.zoom_level) as usize)
+            .max(1)
+            .min(area.height as usize);
+        let cols = pages_per_row.min(area.width as usize);
+
+        let start = index.saturating_sub(rows / 2 * pages_per_row + cols / 2);
+        self.state.offset_y = (start / pages_per_row) as i64;
+        self.state.offset_x = (start % pages_per_row) as i64;
+        Ok(())
+    }
+
     fn set_filter(&mut self, category: Option<FlagCategory>) {
-        self.state.filter_category = category;
+        self.state.filter = category.map(PageFilter::Category);
+    }
+
+    /// Step the single-flag overlay to the next (or previous) flag, passing
+    /// through "no overlay" after the last flag
+    fn cycle_flag(&mut self, forward: bool) {
+        let count = flag_table().len();
+        self.state.selected_flag = match (self.state.selected_flag, forward) {
+            (None, true) => Some(0),
+            (None, false) => count.checked_sub(1),
+            (Some(i), true) if i + 1 < count => Some(i + 1),
+            (Some(i), false) if i > 0 => Some(i - 1),
+            _ => None,
+        };
     }
 
     fn reset_view(&mut self) {
@@ -246,6 +686,16 @@ impl TuiApp {
         self.state.selection_end = None;
     }
 
+    /// Drop any selection made against the old layout and redraw at the new
+    /// size right away, so `grid_area` is current before the next mouse event
+    fn handle_resize<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
+        self.cancel_selection();
+        self.state.grid_area = None;
+        terminal.autoresize()?;
+        terminal.draw(|f| self.ui(f))?;
+        Ok(())
+    }
+
     fn handle_mouse_event(&mut self, mouse: MouseEvent) {
         if let Some(grid_area) = self.state.grid_area {
             // Check if mouse is within grid area
@@ -260,17 +710,13 @@ impl TuiApp {
                         self.state.selection_start = Some((mouse.column, mouse.row));
                         self.state.selection_end = Some((mouse.column, mouse.row));
                     }
-                    MouseEventKind::Drag(MouseButton::Left) => {
-                        if self.state.mouse_selecting {
-                            self.state.selection_end = Some((mouse.column, mouse.row));
-                        }
+                    MouseEventKind::Drag(MouseButton::Left) if self.state.mouse_selecting => {
+                        self.state.selection_end = Some((mouse.column, mouse.row));
                     }
-                    MouseEventKind::Up(MouseButton::Left) => {
-                        if self.state.mouse_selecting {
-                            self.state.selection_end = Some((mouse.column, mouse.row));
-                            self.zoom_to_selection();
-                            self.cancel_selection();
-                        }
+                    MouseEventKind::Up(MouseButton::Left) if self.state.mouse_selecting => {
+                        self.state.selection_end = Some((mouse.column, mouse.row));
+                        self.zoom_to_selection();
+                        self.cancel_selection();
                     }
                     MouseEventKind::ScrollUp => {
                         self.zoom_in();
@@ -309,14 +755,11 @@ impl TuiApp {
                 // Calculate zoom factor to fit selection to screen
                 let zoom_x = grid_area.width as f64 / selection_width;
                 let zoom_y = grid_area.height as f64 / selection_height;
-                let new_zoom = zoom_x.min(zoom_y).min(10.0).max(0.1);
+                let new_zoom = zoom_x.min(zoom_y).clamp(0.1, 10.0);
 
                 // Update zoom and center on selection
                 self.state.zoom_level = new_zoom;
 
-                // Convert grid coordinates to page coordinates
-                let pages_per_row =
-                    ((grid_area.width as f64 * self.state.zoom_level) as usize).max(1);
                 let center_x = (min_x + max_x) / 2;
                 let center_y = (min_y + max_y) / 2;
 
@@ -334,10 +777,11 @@ impl TuiApp {
     }
 
     fn ui(&mut self, f: &mut Frame) {
+        self.state.screen_area = Some(f.size());
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
-                Constraint::Length(3), // Header
+                Constraint::Length(4), // Header and pressure gauge
                 Constraint::Min(0),    // Main content
                 Constraint::Length(3), // Footer
             ])
@@ -346,19 +790,36 @@ impl TuiApp {
         // Header
         self.render_header(f, chunks[0]);
 
+        // Filtered once per frame for both the grid and the match count
+        let visible = self.state.visible_pages();
+        let mut grid_area = None;
+
         // Main content
         if self.state.show_help {
             self.render_help(f, chunks[1]);
         } else {
+            let mut content = chunks[1];
+            if self.state.show_timeline {
+                let rows = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([
+                        Constraint::Min(0),                             // Grid and stats
+                        Constraint::Length(self.state.timeline_height), // Timeline
+                    ])
+                    .split(content);
+                content = rows[0];
+                self.render_timeline(f, rows[1]);
+            }
+
             let main_chunks = Layout::default()
                 .direction(Direction::Horizontal)
                 .constraints([
                     Constraint::Percentage(70), // Grid
                     Constraint::Percentage(30), // Stats
                 ])
-                .split(chunks[1]);
+                .split(content);
 
-            self.render_grid(f, main_chunks[0]);
+            grid_area = Some(self.render_grid(f, main_chunks[0], &visible));
 
             if self.state.show_stats {
                 self.render_stats(f, main_chunks[1]);
@@ -366,11 +827,38 @@ impl TuiApp {
         }
 
         // Footer
-        self.render_footer(f, chunks[2]);
+        if let Some(input) = &self.state.search_input {
+            self.render_input(
+                f,
+                chunks[2],
+                "Search: flag expression, category or PFN range START-END (Enter apply, Esc cancel)",
+                &format!("/{}", input),
+            );
+        } else if let Some(input) = &self.state.export_input {
+            self.render_input(
+                f,
+                chunks[2],
+                "Export view as text to file (Enter save, Esc cancel)",
+                &format!("File: {}", input),
+            );
+        } else if let Some(input) = &self.state.goto_input {
+            self.render_input(
+                f,
+                chunks[2],
+                "Go to PFN, in hex (Enter go, Esc cancel)",
+                &format!("PFN: {}", input),
+            );
+        } else {
+            self.render_footer(f, chunks[2], self.state.search_matches(&visible));
+        }
+
+        if grid_area.is_some() {
+            self.state.grid_area = grid_area;
+        }
     }
 
     fn render_header(&self, f: &mut Frame, area: Rect) {
-        let title = if self.state.scanning {
+        let mut title = if self.state.scanning {
             format!(
                 "KPageFlags TUI - Scanning... ({:.1}%) - {} pages loaded",
                 self.state.scan_progress * 100.0,
@@ -382,6 +870,13 @@ impl TuiApp {
                 self.state.total_pages_scanned, self.state.zoom_level
             )
         };
+        if let Some(def) = self.state.selected_flag.and_then(|i| flag_table().get(i)) {
+            title.push_str(&format!(
+                " - Flag: {} (bit {})",
+                def.name,
+                def.mask.trailing_zeros()
+            ));
+        }
 
         let header = Paragraph::new(title)
             .style(Style::default().fg(Color::Cyan))
@@ -389,26 +884,81 @@ impl TuiApp {
             .block(Block::default().borders(Borders::ALL));
 
         f.render_widget(header, area);
+        self.render_pressure_gauge(f, area);
 
-        // Progress bar if scanning
+        // Progress bar if scanning, over the bottom border
         if self.state.scanning {
             let progress_area = Rect {
                 x: area.x + 2,
-                y: area.y + 2,
-                width: area.width - 4,
+                y: area.bottom().saturating_sub(1),
+                width: area.width.saturating_sub(4),
                 height: 1,
             };
 
             let progress = Gauge::default()
                 .block(Block::default())
                 .gauge_style(Style::default().fg(Color::Green))
-                .ratio(self.state.scan_progress.min(1.0).max(0.0));
+                .ratio(self.state.scan_progress.clamp(0.0, 1.0));
 
             f.render_widget(progress, progress_area);
         }
     }
 
-    fn render_grid(&mut self, f: &mut Frame, area: Rect) {
+    /// MemAvailable / MemTotal as a bar on the header's second line, colored
+    /// by `PressureLevel`, with memory PSI beside it where available
+    fn render_pressure_gauge(&self, f: &mut Frame, area: Rect) {
+        let Some(memory) = &self.state.system_memory else {
+            return;
+        };
+        if area.height < 4 || area.width < 6 {
+            return;
+        }
+        let line = Rect {
+            x: area.x + 2,
+            y: area.y + 2,
+            width: area.width - 4,
+            height: 1,
+        };
+        let (gauge_area, psi_area) = match memory.psi_some_avg10 {
+            Some(_) => {
+                let parts = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Min(0), Constraint::Length(22)])
+                    .split(line);
+                (parts[0], Some(parts[1]))
+            }
+            None => (line, None),
+        };
+
+        let stats = &memory.stats;
+        let ratio = if stats.mem_total > 0 {
+            (stats.mem_available as f64 / stats.mem_total as f64).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let gauge = Gauge::default()
+            .gauge_style(
+                Style::default().fg(pressure_color(PressureLevel::from_available_ratio(ratio))),
+            )
+            .ratio(ratio)
+            .label(format!(
+                "Available {:.1}% ({} of {})",
+                ratio * 100.0,
+                format_memory_kb(stats.mem_available),
+                format_memory_kb(stats.mem_total)
+            ));
+        f.render_widget(gauge, gauge_area);
+
+        if let (Some(area), Some(avg10)) = (psi_area, memory.psi_some_avg10) {
+            let psi = Paragraph::new(format!(" PSI some10: {:.2}%", avg10))
+                .style(Style::default().fg(Color::Gray));
+            f.render_widget(psi, area);
+        }
+    }
+
+    /// Draw the `visible` pages and return the grid's inner area, which
+    /// mouse handling needs
+    fn render_grid(&self, f: &mut Frame, area: Rect, filtered_pages: &[&PageInfo]) -> Rect {
         let block = Block::default()
             .title("Memory Page Grid (Click and drag to zoom)")
             .borders(Borders::ALL);
@@ -416,9 +966,6 @@ impl TuiApp {
         let inner = block.inner(area);
         f.render_widget(block, area);
 
-        // Store grid area for mouse handling
-        self.state.grid_area = Some(inner);
-
         // Calculate grid dimensions based on zoom and area
         let grid_width = ((inner.width as f64 * self.state.zoom_level) as usize).max(1);
         let grid_height = ((inner.height as f64 * self.state.zoom_level) as usize).max(1);
@@ -427,16 +974,6 @@ impl TuiApp {
         let mut lines = Vec::new();
         let pages_per_row = grid_width;
 
-        let filtered_pages: Vec<&PageInfo> = if let Some(filter_cat) = self.state.filter_category {
-            self.state
-                .pages
-                .iter()
-                .filter(|page| page.get_flag_categories().contains(&filter_cat))
-                .collect()
-        } else {
-            self.state.pages.iter().collect()
-        };
-
         let start_idx = (self.state.offset_y * pages_per_row as i64 + self.state.offset_x) as usize;
 
         for row in 0..grid_height.min(inner.height as usize) {
@@ -445,9 +982,15 @@ impl TuiApp {
             for col in 0..pages_per_row.min(inner.width as usize) {
                 let page_idx = start_idx + row * pages_per_row + col;
 
+                let mut search_hit = false;
                 let (symbol, mut color) = if page_idx < filtered_pages.len() {
                     let page = filtered_pages[page_idx];
-                    self.get_page_symbol_and_color(page)
+                    search_hit = self.state.search.as_ref().is_some_and(|s| s.matches(page));
+                    match self.state.selected_flag.and_then(|i| flag_table().get(i)) {
+                        Some(def) if page.flags.intersects(def.flag()) => ('#', Color::LightYellow),
+                        Some(_) => ('.', Color::DarkGray),
+                        None => self.get_page_symbol_and_color(page),
+                    }
                 } else {
                     ('.', Color::DarkGray)
                 };
@@ -460,6 +1003,11 @@ impl TuiApp {
                         symbol.to_string(),
                         Style::default().fg(color).bg(Color::White),
                     ));
+                } else if search_hit {
+                    spans.push(Span::styled(
+                        symbol.to_string(),
+                        Style::default().fg(Color::Black).bg(Color::LightMagenta),
+                    ));
                 } else {
                     spans.push(Span::styled(symbol.to_string(), Style::default().fg(color)));
                 }
@@ -477,6 +1025,7 @@ impl TuiApp {
         if self.state.mouse_selecting {
             self.render_selection_overlay(f, inner);
         }
+        inner
     }
 
     fn is_cell_in_selection(&self, grid_area: Rect, col: u16, row: u16) -> bool {
@@ -546,18 +1095,20 @@ impl TuiApp {
         let mut category_counts: HashMap<FlagCategory, u32> = HashMap::new();
         let mut total_pages = 0;
         let mut pages_with_flags = 0;
+        let mut reclaimable_pages = 0;
 
         for page in &self.state.pages {
             total_pages += 1;
-            if page.flags != 0 {
+            if page.is_reclaimable() {
+                reclaimable_pages += 1;
+            }
+            if !page.flags.is_empty() {
                 pages_with_flags += 1;
 
                 // Count individual flags
-                for (flag, name, _, category) in PAGE_FLAGS {
-                    if page.flags & flag != 0 {
-                        *flag_counts.entry(name).or_insert(0) += 1;
-                        *category_counts.entry(*category).or_insert(0) += 1;
-                    }
+                for def in page.flags.iter_defs() {
+                    *flag_counts.entry(def.name).or_insert(0) += 1;
+                    *category_counts.entry(def.category).or_insert(0) += 1;
                 }
             }
         }
@@ -575,6 +1126,13 @@ impl TuiApp {
                     Style::default().fg(Color::White),
                 ),
             ]),
+            Line::from(vec![
+                Span::styled("Reclaimable: ", Style::default().fg(Color::Green)),
+                Span::styled(
+                    reclaimable_pages.to_string(),
+                    Style::default().fg(Color::White),
+                ),
+            ]),
             Line::from(""),
             Line::from(Span::styled(
                 "Top Flags:",
@@ -604,6 +1162,42 @@ impl TuiApp {
             ]));
         }
 
+        if self.state.color_mode == ColorMode::AnonFile {
+            let mut class_counts: HashMap<PageClass, u32> = HashMap::new();
+            for page in &self.state.pages {
+                *class_counts.entry(page.page_class()).or_insert(0) += 1;
+            }
+
+            stats_lines.push(Line::from(""));
+            stats_lines.push(Line::from(Span::styled(
+                "Page Classes:",
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            )));
+            for class in PageClass::ALL {
+                let count = class_counts.get(&class).copied().unwrap_or(0);
+                let percentage = if total_pages > 0 {
+                    (count as f64 / total_pages as f64) * 100.0
+                } else {
+                    0.0
+                };
+
+                let (symbol, color) = get_class_symbol_and_color(class);
+                stats_lines.push(Line::from(vec![
+                    Span::styled(
+                        format!("{} ", symbol),
+                        Style::default().fg(self.ratatui_color_from_colored(color)),
+                    ),
+                    Span::styled(format!("{:?}: ", class), Style::default().fg(Color::Yellow)),
+                    Span::styled(
+                        format!("{} ({:.1}%)", count, percentage),
+                        Style::default().fg(Color::White),
+                    ),
+                ]));
+            }
+        }
+
         stats_lines.push(Line::from(""));
         stats_lines.push(Line::from(Span::styled(
             "Categories:",
@@ -646,6 +1240,93 @@ impl TuiApp {
         f.render_widget(stats_paragraph, inner);
     }
 
+    /// Chart of the sampled meminfo series over the last `TIMELINE_SAMPLES`
+    /// seconds, in MB
+    ///
+    /// The panel is too short for a chart legend, so the series are named in
+    /// the title in their line colors instead.
+    fn render_timeline(&self, f: &mut Frame, area: Rect) {
+        let shown: Vec<TimelineMetric> = [
+            TimelineMetric::MemAvailable,
+            TimelineMetric::PageCache,
+            TimelineMetric::InactiveFile,
+        ]
+        .into_iter()
+        .filter(|&metric| self.state.timeline_metric.shows(metric))
+        .collect();
+
+        let mut title = vec![Span::raw("Memory timeline:")];
+        for metric in &shown {
+            let (name, color, _) = metric.series();
+            title.push(Span::styled(
+                format!(" {}", name),
+                Style::default().fg(color),
+            ));
+        }
+        title.push(Span::raw(" (t: hide, T: metric)"));
+        let block = Block::default()
+            .title(Line::from(title))
+            .borders(Borders::ALL);
+
+        let history = &self.state.memory_history;
+        let (Some(first), Some(last)) = (history.front(), history.back()) else {
+            let waiting = Paragraph::new("Waiting for /proc/meminfo samples...")
+                .style(Style::default().fg(Color::Gray))
+                .block(block);
+            f.render_widget(waiting, area);
+            return;
+        };
+
+        let points: Vec<Vec<(f64, f64)>> = shown
+            .iter()
+            .map(|metric| {
+                let (_, _, value) = metric.series();
+                history
+                    .iter()
+                    .map(|snapshot| {
+                        (
+                            snapshot.timestamp.saturating_sub(first.timestamp) as f64 / 1000.0,
+                            value(&snapshot.stats) as f64 / 1024.0,
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let x_max = (last.timestamp.saturating_sub(first.timestamp) as f64 / 1000.0).max(1.0);
+        // Scale to MemTotal so the lines don't jump around as values change
+        let y_max = (last.stats.mem_total as f64 / 1024.0).max(1.0);
+        let datasets = shown
+            .iter()
+            .zip(&points)
+            .map(|(metric, points)| {
+                Dataset::default()
+                    .marker(symbols::Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(Style::default().fg(metric.series().1))
+                    .data(points)
+            })
+            .collect();
+
+        let chart = Chart::new(datasets)
+            .block(block)
+            .x_axis(
+                Axis::default()
+                    .title("s")
+                    .style(Style::default().fg(Color::Gray))
+                    .bounds([0.0, x_max])
+                    .labels(vec![Span::raw("0"), Span::raw(format!("{:.0}", x_max))]),
+            )
+            .y_axis(
+                Axis::default()
+                    .title("MB")
+                    .style(Style::default().fg(Color::Gray))
+                    .bounds([0.0, y_max])
+                    .labels(vec![Span::raw("0"), Span::raw(format!("{:.0}", y_max))]),
+            );
+        f.render_widget(chart, area);
+    }
+
     fn render_help(&self, f: &mut Frame, area: Rect) {
         let help_text = vec![
             Line::from(Span::styled(
@@ -670,7 +1351,12 @@ impl TuiApp {
             Line::from("Controls:"),
             Line::from("  h             - Toggle this help"),
             Line::from("  s             - Toggle statistics panel"),
+            Line::from("  t             - Toggle memory timeline panel"),
+            Line::from(
+                "  T             - Cycle timeline metric (all / available / cache / inactive file)",
+            ),
             Line::from("  r             - Refresh data"),
+            Line::from("  c             - Toggle category / anon-file coloring"),
             Line::from("  q             - Quit"),
             Line::from(""),
             Line::from("Filters (show only pages with these flag categories):"),
@@ -684,10 +1370,26 @@ impl TuiApp {
             Line::from("  8             - Error flags (ERROR, HWPOISON)"),
             Line::from("  0             - Clear filter (show all)"),
             Line::from(""),
+            Line::from("Single flag overlay (# = flag set, . = clear):"),
+            Line::from("  f / F         - Next / previous flag (cycles back to off)"),
+            Line::from(""),
+            Line::from("Search:"),
+            Line::from(
+                "  /             - Search: a flag expression (DIRTY, LRU & !ACTIVE) highlights",
+            ),
+            Line::from("                  matching pages, a category name sets the filter, and a"),
+            Line::from("                  PFN range like 0x1000-0x2000 limits the grid"),
+            Line::from("  Esc           - Clear the search and the filter it set"),
+            Line::from("  g             - Go to a PFN (hex, e.g. 0x1a3f00), centered in the grid"),
+            Line::from("  e             - Export the current view to a plain text file"),
+            Line::from(""),
             Line::from("Grid Symbols:"),
             Line::from("  S - State flags      M - Memory mgmt      U - Usage tracking"),
             Line::from("  A - Allocation       I - I/O related      T - Structure"),
             Line::from("  P - Special          E - Error flags      . - No flags"),
+            Line::from(""),
+            Line::from("Anon-file Symbols (press 'c'):"),
+            Line::from("  A - Anonymous        F - File (cache)     - - Free       K - Kernel"),
         ];
 
         let help_paragraph = Paragraph::new(Text::from(help_text))
@@ -697,12 +1399,40 @@ impl TuiApp {
         f.render_widget(help_paragraph, area);
     }
 
-    fn render_footer(&self, f: &mut Frame, area: Rect) {
-        let filter_text = if let Some(cat) = self.state.filter_category {
-            format!("Filter: {:?}", cat)
+    /// A one-line input box (`/` search, `g` goto) in place of the footer,
+    /// with the cursor after `text`
+    fn render_input(&self, f: &mut Frame, area: Rect, title: &str, text: &str) {
+        let input = Paragraph::new(text.to_string())
+            .block(Block::default().title(title).borders(Borders::ALL));
+        f.render_widget(input, area);
+        let cursor_x = area.x + 1 + text.chars().count() as u16;
+        f.set_cursor(cursor_x.min(area.right().saturating_sub(2)), area.y + 1);
+    }
+
+    fn render_footer(&self, f: &mut Frame, area: Rect, search_matches: Option<usize>) {
+        let mut filter_text = if let Some(filter) = &self.state.filter {
+            format!("Filter: {}", filter)
         } else {
             "Filter: None".to_string()
         };
+        if let Some((start, end)) = self.state.pfn_range_filter {
+            filter_text.push_str(&format!(" | PFNs 0x{:x}-0x{:x}", start, end));
+        }
+        if let Some(search) = &self.state.search {
+            filter_text.push_str(&format!(" | Search: {}", search));
+        }
+        if let Some(matches) = search_matches {
+            filter_text.push_str(&format!(" ({} matches)", matches));
+        }
+        if let Some(error) = &self.state.search_error {
+            filter_text.push_str(&format!(" | Search error: {}", error));
+        }
+        if let Some(error) = &self.state.goto_error {
+            filter_text.push_str(&format!(" | {}", error));
+        }
+        if let Some(message) = &self.state.export_message {
+            filter_text.push_str(&format!(" | {}", message));
+        }
 
         let selection_text = if self.state.mouse_selecting {
             " | Selecting..."
@@ -711,8 +1441,12 @@ impl TuiApp {
         };
 
         let footer_text = format!(
-            "Press 'h' for help | 'q' to quit | {} | Offset: ({}, {}) | Zoom: {:.1}x{}",
+            "Press 'h' for help | 'q' to quit | 'f' flag overlay | {} | Colors: {} | Offset: ({}, {}) | Zoom: {:.1}x{}",
             filter_text,
+            match self.state.color_mode {
+                ColorMode::Category => "category",
+                ColorMode::AnonFile => "anon-file",
+            },
             self.state.offset_x,
             self.state.offset_y,
             self.state.zoom_level,
@@ -728,7 +1462,12 @@ impl TuiApp {
     }
 
     fn get_page_symbol_and_color(&self, page: &PageInfo) -> (char, Color) {
-        if page.flags == 0 {
+        if self.state.color_mode == ColorMode::AnonFile {
+            let (symbol_char, colored_color) = get_class_symbol_and_color(page.page_class());
+            return (symbol_char, self.ratatui_color_from_colored(colored_color));
+        }
+
+        if page.flags.is_empty() {
             return ('.', Color::DarkGray);
         }
 
@@ -759,7 +1498,11 @@ impl TuiApp {
     }
 }
 
-pub async fn run_tui() -> Result<(), Box<dyn std::error::Error>> {
+pub async fn run_tui(
+    color_mode: ColorMode,
+    timeline_height: u16,
+    interrupt_flag: Arc<AtomicBool>,
+) -> Result<(), Box<dyn std::error::Error>> {
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -768,7 +1511,7 @@ pub async fn run_tui() -> Result<(), Box<dyn std::error::Error>> {
     let mut terminal = Terminal::new(backend)?;
 
     // Create app and run it
-    let mut app = TuiApp::new()?;
+    let mut app = TuiApp::new(color_mode, timeline_height, interrupt_flag)?;
     let res = app.run(&mut terminal).await;
 
     // Restore terminal
@@ -786,3 +1529,297 @@ pub async fn run_tui() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn test_app() -> TuiApp {
+        TuiApp {
+            state: AppState::default(),
+            reader: KPageFlagsReader {
+                file: Box::new(Cursor::new(Vec::new())),
+                capture_pages: Some(0),
+                regions: Vec::new(),
+                counts: None,
+                filter: None,
+            },
+            mmap: None,
+            interrupt_flag: Arc::new(AtomicBool::new(false)),
+            memory_rx: watch::channel(None).1,
+        }
+    }
+
+    fn press(app: &mut TuiApp, kind: MouseEventKind, column: u16, row: u16) {
+        app.handle_mouse_event(MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: crossterm::event::KeyModifiers::NONE,
+        });
+    }
+
+    #[test]
+    fn test_resize_recomputes_grid_and_cancels_selection() {
+        let mut app = test_app();
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| app.ui(f)).unwrap();
+        let before = app.state.grid_area.unwrap();
+
+        let (x, y) = (before.x + 1, before.y + 1);
+        press(&mut app, MouseEventKind::Down(MouseButton::Left), x, y);
+        assert!(app.state.mouse_selecting);
+
+        terminal.backend_mut().resize(60, 20);
+        app.handle_resize(&mut terminal).unwrap();
+        assert!(!app.state.mouse_selecting);
+        assert_eq!(app.state.selection_start, None);
+
+        let after = app.state.grid_area.unwrap();
+        assert_ne!(after, before);
+        assert!(after.right() <= 60 && after.bottom() <= 20);
+
+        // A fresh selection maps against the new bounds
+        press(
+            &mut app,
+            MouseEventKind::Down(MouseButton::Left),
+            after.x,
+            after.y,
+        );
+        assert_eq!(app.state.selection_start, Some((after.x, after.y)));
+        press(&mut app, MouseEventKind::Down(MouseButton::Left), 59, 19);
+        assert_eq!(app.state.selection_start, Some((after.x, after.y)));
+    }
+
+    #[test]
+    fn test_search() {
+        let mut app = test_app();
+        let dirty = crate::find_flag("DIRTY").unwrap().mask;
+        let lru = crate::find_flag("LRU").unwrap().mask;
+        app.state.pages = (0..0x40u64)
+            .map(|pfn| PageInfo::new(0x1000 + pfn, if pfn % 4 == 0 { lru | dirty } else { lru }))
+            .collect();
+
+        app.handle_search_key(KeyCode::Char('x'));
+        assert_eq!(app.state.search_input, None);
+        app.state.search_input = Some(String::new());
+        for c in "DIRTYY".chars() {
+            app.handle_search_key(KeyCode::Char(c));
+        }
+        app.handle_search_key(KeyCode::Backspace);
+        app.handle_search_key(KeyCode::Enter);
+        assert_eq!(app.state.search_input, None);
+        assert!(app.state.search.is_some());
+        assert_eq!(
+            app.state.search_matches(&app.state.visible_pages()),
+            Some(16)
+        );
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| app.ui(f)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let grid = app.state.grid_area.unwrap();
+        let first = buffer.get(grid.x, grid.y);
+        assert_eq!(first.bg, Color::LightMagenta);
+        assert_ne!(buffer.get(grid.x + 1, grid.y).bg, Color::LightMagenta);
+        let screen: String = buffer
+            .content()
+            .iter()
+            .map(|cell| cell.symbol.as_str())
+            .collect();
+        assert!(screen.contains("Search: DIRTY (16 matches)"));
+
+        app.state.apply_search("0x1008-0x1010");
+        assert_eq!(app.state.visible_pages().len(), 8);
+        assert_eq!(
+            app.state.search_matches(&app.state.visible_pages()),
+            Some(2)
+        );
+
+        app.state.apply_search("io");
+        assert_eq!(
+            app.state.filter,
+            Some(PageFilter::Category(FlagCategory::IO))
+        );
+
+        app.state.apply_search("NOT_A_FLAG");
+        assert!(app.state.search_error.is_some());
+
+        app.state.clear_search();
+        assert_eq!(app.state.search_matches(&app.state.visible_pages()), None);
+        assert_eq!(app.state.pfn_range_filter, None);
+        assert_eq!(app.state.filter, None);
+    }
+
+    #[test]
+    fn test_goto_pfn() {
+        let mut app = test_app();
+        app.state.pages = (0..10_000u64)
+            .map(|pfn| PageInfo::new(0x100 + pfn, 0))
+            .collect();
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| app.ui(f)).unwrap();
+        let area = app.state.grid_area.unwrap();
+        let (width, height) = (area.width as i64, area.height as i64);
+
+        // Non-hex keys are ignored, and x only after a leading 0
+        app.state.goto_input = Some(String::new());
+        for c in "0x1g3xz88".chars() {
+            app.handle_goto_key(KeyCode::Char(c));
+        }
+        assert_eq!(app.state.goto_input.as_deref(), Some("0x1388"));
+        app.handle_goto_key(KeyCode::Backspace);
+        app.handle_goto_key(KeyCode::Char('8'));
+        app.handle_goto_key(KeyCode::Enter);
+        assert_eq!(app.state.goto_input, None);
+        assert_eq!(app.state.goto_error, None);
+
+        // PFN 0x1388 is page 0x1288; the grid starts half a screen before it
+        let start = app.state.offset_y * width + app.state.offset_x;
+        assert_eq!(start, 0x1288 - (height / 2 * width + width / 2));
+
+        // Near the start the view can't scroll before page 0
+        app.goto_pfn(0x101).unwrap();
+        assert_eq!((app.state.offset_x, app.state.offset_y), (0, 0));
+
+        assert!(app.goto_pfn(0x50).is_err());
+        app.state.goto_input = Some("0x99999".to_string());
+        app.handle_goto_key(KeyCode::Enter);
+        assert!(app
+            .state
+            .goto_error
+            .unwrap()
+            .contains("outside the scanned range"));
+    }
+
+    #[tokio::test]
+    async fn test_pressure_gauge() {
+        let (tx, rx) = watch::channel(None);
+        let mut app = TuiApp {
+            memory_rx: rx,
+            ..test_app()
+        };
+        let sample = |available: u64, psi| SystemMemory {
+            stats: MemoryStats {
+                mem_total: 1000 * 1024,
+                mem_available: available * 1024,
+                ..Default::default()
+            },
+            psi_some_avg10: psi,
+        };
+
+        tx.send(Some(sample(150, Some(2.5)))).unwrap();
+        app.update_scan_progress().await.unwrap();
+        assert_eq!(app.state.memory_history.len(), 1);
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| app.ui(f)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let row: String = (0..120).map(|x| buffer.get(x, 2).symbol.clone()).collect();
+        assert!(row.contains("Available 15.0%"), "{}", row);
+        assert!(row.contains("PSI some10: 2.50%"), "{}", row);
+        // 15% available is High pressure: orange
+        assert_eq!(buffer.get(3, 2).fg, Color::Indexed(208));
+
+        // No new sample, nothing recorded
+        app.update_scan_progress().await.unwrap();
+        assert_eq!(app.state.memory_history.len(), 1);
+
+        tx.send(Some(sample(600, None))).unwrap();
+        app.update_scan_progress().await.unwrap();
+        terminal.draw(|f| app.ui(f)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let row: String = (0..120).map(|x| buffer.get(x, 2).symbol.clone()).collect();
+        assert!(!row.contains("PSI"));
+        assert_eq!(buffer.get(3, 2).fg, Color::Green);
+    }
+
+    #[test]
+    fn test_export_text() {
+        let mut app = test_app();
+        let lru = crate::find_flag("LRU").unwrap().mask;
+        app.state.pages = (0..500u64).map(|pfn| PageInfo::new(pfn, lru)).collect();
+        app.state.total_pages_scanned = 500;
+        app.state.filter = Some(PageFilter::Category(FlagCategory::Memory));
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        terminal.draw(|f| app.ui(f)).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("view.txt");
+        app.state.export_input = Some(String::new());
+        for c in path.to_str().unwrap().chars() {
+            app.handle_export_key(KeyCode::Char(c));
+        }
+        app.handle_export_key(KeyCode::Enter);
+        let text = fs::read_to_string(&path).unwrap();
+
+        assert_eq!(app.state.export_input, None);
+        assert!(app
+            .state
+            .export_message
+            .as_ref()
+            .unwrap()
+            .starts_with("Exported view"));
+        let mut lines = text.lines();
+        let header = lines.next().unwrap();
+        assert!(
+            header.contains("500 pages - Zoom: 1.0x - Filter: Memory"),
+            "{}",
+            header
+        );
+        // The terminal size is reused, box drawing kept and escapes left out
+        assert_eq!(lines.clone().count(), 30);
+        assert!(lines.clone().all(|line| line.chars().count() <= 100));
+        assert!(text.contains('┌') && text.contains("Memory Page Grid"));
+        assert!(text.contains("MMMM"));
+        assert!(!text.contains('\u{1b}'));
+    }
+
+    #[test]
+    fn test_timeline_panel() {
+        let mut app = test_app();
+        for i in 0..TIMELINE_SAMPLES as u64 + 5 {
+            let stats = MemoryStats {
+                mem_total: 1024 * 1024,
+                mem_available: 512 * 1024 - i * 1024,
+                cached: 256 * 1024,
+                ..Default::default()
+            };
+            app.state.push_memory_sample(MemorySnapshot {
+                timestamp: i * 1000,
+                ..MemorySnapshot::from_stats(stats)
+            });
+        }
+        assert_eq!(app.state.memory_history.len(), TIMELINE_SAMPLES);
+        assert_eq!(app.state.memory_history[0].timestamp, 5000);
+
+        let text = |terminal: &Terminal<TestBackend>| -> String {
+            let buffer = terminal.backend().buffer();
+            buffer
+                .content()
+                .iter()
+                .map(|cell| cell.symbol.as_str())
+                .collect()
+        };
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| app.ui(f)).unwrap();
+        let with_timeline = app.state.grid_area.unwrap();
+        let screen = text(&terminal);
+        assert!(screen.contains("Memory timeline: MemAvailable Page cache Inactive(file)"));
+
+        app.state.timeline_metric = app.state.timeline_metric.next();
+        terminal.draw(|f| app.ui(f)).unwrap();
+        let screen = text(&terminal);
+        assert!(screen.contains("Memory timeline: MemAvailable (t: hide"));
+
+        app.state.show_timeline = false;
+        terminal.draw(|f| app.ui(f)).unwrap();
+        let without_timeline = app.state.grid_area.unwrap();
+        assert_eq!(
+            without_timeline.height,
+            with_timeline.height + DEFAULT_TIMELINE_HEIGHT
+        );
+        assert!(!text(&terminal).contains("Memory timeline"));
+    }
+}
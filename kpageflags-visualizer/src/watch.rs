@@ -0,0 +1,187 @@
+//! Re-scan a PFN range on an interval and report the pages whose flags
+//! changed since the previous scan
+
+use crate::snapshot::PageFlagsDiff;
+use crate::{sleep_unless_interrupted, InterruptCheck, KPageFlagsReader, PageFlags, PageInfo};
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// `+` if a page only gained flags, `-` if it only lost some, `*` if both
+pub fn change_marker(old: PageFlags, new: PageFlags) -> char {
+    match (!old.contains(new), !new.contains(old)) {
+        (true, false) => '+',
+        (false, true) => '-',
+        _ => '*',
+    }
+}
+
+/// Keeps the previous scan of a range and the transitions seen so far
+pub struct RangeWatcher {
+    start_pfn: u64,
+    /// Pages to scan, or everything from `start_pfn` when `None`
+    count: Option<u64>,
+    /// Pages from the previous scan; `None` before the first
+    last: Option<Vec<PageInfo>>,
+    /// Pages making each transition, over every scan
+    totals: HashMap<String, usize>,
+}
+
+impl RangeWatcher {
+    pub fn new(start_pfn: u64, count: Option<u64>) -> Self {
+        Self {
+            start_pfn,
+            count,
+            last: None,
+            totals: HashMap::new(),
+        }
+    }
+
+    /// Scan the range and compare it with the previous scan; the first
+    /// scan only records a baseline and returns `None`
+    pub fn poll(
+        &mut self,
+        reader: &mut KPageFlagsReader,
+        interrupt_flag: Arc<AtomicBool>,
+    ) -> Result<Option<PageFlagsDiff>, Box<dyn std::error::Error>> {
+        let mut pages = Vec::new();
+        reader.scan_with_callback(self.start_pfn, self.count, interrupt_flag, |page| {
+            pages.push(page)
+        })?;
+        Ok(self.record(pages))
+    }
+
+    /// Scan every `interval` until Ctrl-C, passing each comparison with the
+    /// previous scan and how long the scan took to `on_scan`
+    pub async fn run(
+        &mut self,
+        reader: &mut KPageFlagsReader,
+        interval: Duration,
+        interrupt_flag: Arc<AtomicBool>,
+        mut on_scan: impl FnMut(&PageFlagsDiff, Duration),
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut interrupt_check = InterruptCheck::new(interrupt_flag.clone());
+        loop {
+            let started = Instant::now();
+            let diff = self.poll(reader, interrupt_flag.clone())?;
+            if interrupt_check.triggered() {
+                // The scan was cut short, so don't report it
+                return Ok(());
+            }
+            if let Some(diff) = diff {
+                on_scan(&diff, started.elapsed());
+            }
+
+            if !sleep_unless_interrupted(interval, &interrupt_flag).await {
+                return Ok(());
+            }
+        }
+    }
+
+    fn record(&mut self, pages: Vec<PageInfo>) -> Option<PageFlagsDiff> {
+        let diff = self
+            .last
+            .as_ref()
+            .map(|last| PageFlagsDiff::between(last, &pages));
+        if let Some(diff) = &diff {
+            for (transition, pages) in diff.transition_counts() {
+                *self.totals.entry(transition).or_default() += pages;
+            }
+        }
+        self.last = Some(pages);
+        diff
+    }
+
+    /// Transitions over every scan so far, most common first
+    pub fn cumulative_transitions(&self) -> Vec<(String, usize)> {
+        let mut totals: Vec<(String, usize)> = self
+            .totals
+            .iter()
+            .map(|(transition, &pages)| (transition.clone(), pages))
+            .collect();
+        totals.sort_by(|(a_name, a), (b_name, b)| b.cmp(a).then_with(|| a_name.cmp(b_name)));
+        totals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::find_flag;
+    use std::io::Cursor;
+
+    fn reader(flags: &[u64]) -> KPageFlagsReader {
+        let data: Vec<u8> = flags.iter().flat_map(|f| f.to_le_bytes()).collect();
+        KPageFlagsReader {
+            file: Box::new(Cursor::new(data)),
+            capture_pages: Some(flags.len() as u64),
+            regions: Vec::new(),
+            counts: None,
+            filter: None,
+        }
+    }
+
+    #[test]
+    fn test_change_marker() {
+        let (dirty, writeback, lru) = (PageFlags::DIRTY, PageFlags::WRITEBACK, PageFlags::LRU);
+        assert_eq!(change_marker(lru, lru | dirty), '+');
+        assert_eq!(change_marker(lru | dirty, lru), '-');
+        assert_eq!(change_marker(lru | dirty, lru | writeback), '*');
+    }
+
+    #[tokio::test]
+    async fn test_run_stops_once_interrupted() {
+        let flag = Arc::new(AtomicBool::new(false));
+        let mut watcher = RangeWatcher::new(0, Some(1));
+        let mut scans = 0;
+        watcher
+            .run(
+                &mut reader(&[0]),
+                Duration::from_millis(1),
+                flag.clone(),
+                |_, _| {
+                    scans += 1;
+                    flag.store(true, std::sync::atomic::Ordering::Relaxed);
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(scans, 1);
+    }
+
+    #[test]
+    fn test_range_watcher() {
+        let mask = |name| find_flag(name).unwrap().mask;
+        let (dirty, writeback, lru) = (mask("DIRTY"), mask("WRITEBACK"), mask("LRU"));
+        let flag = Arc::new(AtomicBool::new(false));
+
+        let mut watcher = RangeWatcher::new(1, Some(2));
+        let baseline = watcher
+            .poll(&mut reader(&[0, lru | dirty, lru | dirty]), flag.clone())
+            .unwrap();
+        assert!(baseline.is_none());
+
+        // PFN 0 is outside the range
+        let diff = watcher
+            .poll(
+                &mut reader(&[lru, lru | writeback, lru | dirty]),
+                flag.clone(),
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].0.pfn, 1);
+
+        watcher
+            .poll(&mut reader(&[lru, lru, lru | writeback]), flag)
+            .unwrap();
+        assert_eq!(
+            watcher.cumulative_transitions(),
+            [
+                ("-DIRTY +WRITEBACK".to_string(), 2),
+                ("-WRITEBACK".to_string(), 1)
+            ]
+        );
+    }
+}